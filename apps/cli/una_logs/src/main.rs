@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! una-logs: reads the telemetry vault, live and rolled files alike.
+//!
+//!     una-logs --level warn --target vein --since 2h
+//!     una-logs --field shard=cortex --tail 50 --follow
+
+use anyhow::{Context, Result, anyhow};
+use bandy::telemetry::query::parse_time;
+use bandy::telemetry::{LogEntry, LogQuery};
+use chrono::{DateTime, FixedOffset};
+use clap::Parser;
+use gneiss_pal::paths::UnaPaths;
+use log::Level;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(name = "una-logs")]
+#[command(about = "Query and tail the UnaOS telemetry logs")]
+struct Cli {
+    /// Log directory (defaults to the UnaOS log directory)
+    #[arg(short, long)]
+    dir: Option<PathBuf>,
+    /// Only entries at least this severe (error, warn, info, debug, trace)
+    #[arg(short, long)]
+    level: Option<Level>,
+    /// Only these targets and their children (repeatable)
+    #[arg(short, long)]
+    target: Vec<String>,
+    /// Start time: RFC 3339, `YYYY-MM-DD[ HH:MM[:SS]]`, or an age like `15m`
+    #[arg(long)]
+    since: Option<String>,
+    /// End time, in the same forms as --since
+    #[arg(long)]
+    until: Option<String>,
+    /// Require a structured field, as `key=value` (repeatable)
+    #[arg(short, long, value_parser = parse_field)]
+    field: Vec<(String, String)>,
+    /// Show only the last N matching entries
+    #[arg(short = 'n', long)]
+    tail: Option<usize>,
+    /// Keep running and print new entries as they arrive
+    #[arg(short = 'F', long)]
+    follow: bool,
+    /// Print JSON lines instead of plain text
+    #[arg(long)]
+    json: bool,
+}
+
+fn parse_field(input: &str) -> Result<(String, String), String> {
+    input
+        .split_once('=')
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .ok_or_else(|| format!("Expected key=value, got '{}'", input))
+}
+
+fn parse_bound(
+    input: Option<&str>,
+    now: DateTime<FixedOffset>,
+) -> Result<Option<DateTime<FixedOffset>>> {
+    input
+        .map(|s| parse_time(s, now).ok_or_else(|| anyhow!("Unrecognised time '{}'", s)))
+        .transpose()
+}
+
+fn print(entry: &LogEntry, json: bool) {
+    if json {
+        println!("{}", entry.to_json());
+    } else {
+        println!("{}: {}", entry.target.to_uppercase(), entry.to_plain());
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let dir = cli.dir.unwrap_or_else(UnaPaths::logs);
+    let now = chrono::Local::now().fixed_offset();
+
+    let query = LogQuery {
+        level: cli.level,
+        targets: cli.target,
+        since: parse_bound(cli.since.as_deref(), now)?,
+        until: parse_bound(cli.until.as_deref(), now)?,
+        fields: cli.field,
+    };
+
+    // The tail starts where the query stopped, so nothing falls in between.
+    let (entries, mut tail) = query
+        .run_and_tail(&dir)
+        .with_context(|| format!("Failed to read logs in {}", dir.display()))?;
+    let skip = cli.tail.map_or(0, |n| entries.len().saturating_sub(n));
+    for entry in &entries[skip..] {
+        print(entry, cli.json);
+    }
+
+    if !cli.follow {
+        return Ok(());
+    }
+    loop {
+        for entry in tail.poll()? {
+            if query.matches(&entry) {
+                print(&entry, cli.json);
+            }
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! una-paths: shows where UnaOS keeps things, and why.
+//!
+//!     una-paths
+//!     una-paths --validate
+
+use anyhow::{Result, anyhow};
+use clap::Parser;
+use gneiss_pal::paths::Layout;
+
+#[derive(Parser)]
+#[command(name = "una-paths")]
+#[command(about = "Show and check the UnaOS directory layout")]
+struct Cli {
+    /// Create missing directories and check that each one is usable
+    #[arg(long)]
+    validate: bool,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let layout = Layout::from_env().map_err(|e| anyhow!(e))?;
+
+    for location in layout.locations() {
+        println!(
+            "{:<7} {}  ({})",
+            location.kind.name(),
+            location.path.display(),
+            location.source
+        );
+    }
+    match &layout.config_file {
+        Some(file) => println!("config file: {}", file.display()),
+        None => println!("config file: none"),
+    }
+
+    if !cli.validate {
+        return Ok(());
+    }
+    let issues = layout.validate();
+    for issue in &issues {
+        let tag = if issue.is_fatal() { "ERROR" } else { "WARN" };
+        println!("{}: {}", tag, issue);
+    }
+    let fatal = issues.iter().filter(|i| i.is_fatal()).count();
+    if fatal > 0 {
+        return Err(anyhow!("{} location(s) unusable", fatal));
+    }
+    println!("ok");
+    Ok(())
+}
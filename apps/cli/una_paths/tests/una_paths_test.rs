@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Runs `una-paths` in a scrubbed environment: a temp HOME and whatever else
+/// the test sets.
+fn una_paths(home: &Path, vars: &[(&str, &Path)], args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_una-paths"))
+        .args(args)
+        .env_clear()
+        .env("HOME", home)
+        .envs(vars.iter().copied())
+        .output()
+        .unwrap()
+}
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn test_reports_layout_and_sources() {
+    let home = tempfile::tempdir().unwrap();
+    let state = tempfile::tempdir().unwrap();
+    let out = una_paths(home.path(), &[("XDG_STATE_HOME", state.path())], &[]);
+    assert!(out.status.success());
+
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let data = home.path().join(".local/share/unaos");
+    assert!(stdout.contains(&format!("data    {}  (default)", data.display())));
+    let logs = state.path().join("unaos/logs");
+    assert!(stdout.contains(&format!("logs    {}  ($XDG_STATE_HOME)", logs.display())));
+    assert!(stdout.contains("config file: none"));
+    // Without --validate nothing is created.
+    assert!(!data.exists());
+}
+
+#[test]
+fn test_validate_creates_and_fails_on_unusable() {
+    let home = tempfile::tempdir().unwrap();
+    let una = home.path().join("una");
+    let out = una_paths(home.path(), &[("UNA_HOME", &una)], &["--validate"]);
+    assert!(out.status.success());
+    assert!(String::from_utf8(out.stdout).unwrap().ends_with("ok\n"));
+    assert!(una.join("principia").is_dir());
+
+    std::fs::write(home.path().join("file"), "").unwrap();
+    let out = una_paths(
+        home.path(),
+        &[
+            ("UNA_HOME", &una),
+            ("UNA_LOG_DIR", &home.path().join("file")),
+        ],
+        &["--validate"],
+    );
+    assert!(!out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.contains("ERROR: logs"), "{}", stdout);
+}
@@ -29,7 +29,7 @@ pub async fn ignite(vault_path: PathBuf, mut synapse: Synapse) {
 
     loop {
         match rx.recv().await {
-            Ok(msg) => match msg {
+            Ok(impulse) => match impulse.msg {
                 SMessage::UserPrompt(text) => {
                     cortex.imprint("stimulus.prompt", text.as_bytes());
                 }
@@ -40,7 +40,7 @@ fn main() {
     let cortex_vault = UnaPaths::subconscious_vault();
 
     // 2. Ignite Telemetry
-    telemetry::ignite(UnaPaths::root().join("logs"));
+    telemetry::ignite(UnaPaths::logs());
     log::info!("Lumen Boot Sequence Initiated.");
 
     let (telemetry_tx, _telemetry_rx) = async_channel::unbounded::<SMessage>();
@@ -66,7 +66,10 @@ fn main() {
     // Since VeinHandler is "Pure Logic", it should run on Tokio.
     // The `handle_event` method processes events from the UI.
 
-    let vein_handler = VeinHandler::new(gui_tx, vein_storage, synapse.tx(), telemetry_tx);
+    // The model backend is chosen by UNA_LLM_PROVIDER (vertex by default). If
+    // it cannot be set up, the handler says why on the console.
+    let llm = gneiss_pal::api::provider_from_env();
+    let vein_handler = VeinHandler::new(gui_tx, vein_storage, synapse.clone(), telemetry_tx, llm);
 
     // Spawn the Brain Loop
     rt.spawn(async move {
@@ -26,7 +26,7 @@ use std::env;
 use std::rc::Rc;
 use std::path::PathBuf;
 
-use gneiss_pal::{Event, GuiUpdate};
+use gneiss_pal::{AppHandler, Event, GuiUpdate};
 use quartzite::{Backend, NativeView, NativeWindow};
 
 #[cfg(target_os = "linux")]
@@ -49,16 +49,10 @@ fn main() -> Result<()> {
     let (_tx_telemetry, rx_telemetry) = async_channel::unbounded::<bandy::SMessage>();
 
     // 2. Spawn central background task (Tokio)
+    let mut editor = tabula::TabulaHandler::new(tx_gui);
     rt.spawn(async move {
         while let Ok(event) = rx_brain.recv().await {
-            match event {
-                Event::FileSelected(path) => {
-                    println!("[UNA CORE] 🧠 Routing Impulse: {:?}", path);
-                    // Bouncing it as EditorLoad to trigger tabula
-                    let _ = tx_gui.send(GuiUpdate::EditorLoad(path.to_string_lossy().to_string())).await;
-                }
-                _ => {}
-            }
+            editor.handle_event(event);
         }
     });
 
@@ -60,7 +60,15 @@ impl Aule {
             Spline::UnaOS | Spline::Rust => ("cargo", vec!["build"]),
             Spline::Web => ("npm", vec!["run", "build"]),
             Spline::Python => ("python", vec!["setup.py", "build"]), // Or pip
-            Spline::Void => return Ok(()),                           // Nothing to build
+            Spline::Go => ("go", vec!["build", "./..."]),
+            Spline::CMake => ("cmake", vec!["--build", "build"]), // Assumes a configured build/
+            Spline::Make => ("make", vec![]),
+            Spline::Meson => ("meson", vec!["compile", "-C", "build"]),
+            Spline::Gradle => ("gradle", vec!["build"]),
+            Spline::Maven => ("mvn", vec!["package"]),
+            Spline::Zig => ("zig", vec!["build"]),
+            Spline::Nix => ("nix", vec!["build"]),
+            Spline::Void => return Ok(()), // Nothing to build
         };
 
         println!("[AULE] Forging with: {} {:?}", program, args);
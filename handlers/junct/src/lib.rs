@@ -14,20 +14,19 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use bandy::SMessage;
+use bandy::{SMessage, Synapse};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use resonance::{
     BLOCK_SIZE,
-    dsp::{Complex, FftContext},
+    dsp::{SpectrumAnalyzer, Window},
 };
-use tokio::sync::broadcast;
 
 pub struct JunctHandler {
     _stream: cpal::Stream,
 }
 
 impl JunctHandler {
-    pub fn new(bandy_tx: broadcast::Sender<SMessage>) -> anyhow::Result<Self> {
+    pub fn new(synapse: Synapse) -> anyhow::Result<Self> {
         let host = cpal::default_host();
         // If no device, we warn but don't crash the whole app?
         // Logic says "Junct ... aggregate the host OS microphone".
@@ -39,8 +38,10 @@ impl JunctHandler {
         let channels = config.channels() as usize;
         let config: cpal::StreamConfig = config.into();
 
-        let fft = FftContext::new(BLOCK_SIZE);
-        let mut buffer = vec![Complex::default(); BLOCK_SIZE];
+        // Hann-windowed, so a tone between bins does not smear across all of them.
+        let mut analyzer = SpectrumAnalyzer::new(BLOCK_SIZE, Window::Hann);
+        let mut buffer = vec![0.0; BLOCK_SIZE];
+        let mut amplitudes = vec![0.0; analyzer.bins()];
         let mut buf_idx = 0;
 
         let err_fn = |err| eprintln!("an error occurred on stream: {}", err);
@@ -52,20 +53,16 @@ impl JunctHandler {
                     let sample = if !frame.is_empty() { frame[0] } else { 0.0 };
 
                     if buf_idx < BLOCK_SIZE {
-                        buffer[buf_idx] = Complex::new(sample, 0.0);
+                        buffer[buf_idx] = sample;
                         buf_idx += 1;
                     }
 
                     if buf_idx >= BLOCK_SIZE {
-                        fft.process(&mut buffer);
+                        analyzer.amplitudes(&buffer, &mut amplitudes);
 
-                        let magnitude: Vec<f32> = buffer
-                            .iter()
-                            .take(BLOCK_SIZE / 2)
-                            .map(|c| (c.re * c.re + c.im * c.im).sqrt())
-                            .collect();
+                        let magnitude = amplitudes[..BLOCK_SIZE / 2].to_vec();
 
-                        let _ = bandy_tx.send(SMessage::Spectrum { magnitude });
+                        synapse.fire(SMessage::Spectrum { magnitude });
                         buf_idx = 0;
                     }
                 }
@@ -67,6 +67,11 @@ impl ProjectView {
             Spline::Rust => "applications-engineering-symbolic",
             Spline::Web => "network-server-symbolic",
             Spline::Python => "media-playlist-shuffle-symbolic",
+            Spline::Go => "media-skip-forward-symbolic",
+            Spline::CMake | Spline::Make | Spline::Meson => "system-run-symbolic",
+            Spline::Gradle | Spline::Maven => "package-x-generic-symbolic",
+            Spline::Zig => "weather-storm-symbolic",
+            Spline::Nix => "weather-snow-symbolic",
             Spline::Void => "folder-symbolic",
         }
     }
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! What the editor shows for a file, without the editor.
+
+use std::fs;
+use std::path::Path;
+
+/// The sourceview language id for `path`, going by its extension. `None`
+/// if it has no extension, in which case the editor keeps its language.
+pub fn language_for(path: &Path) -> Option<&'static str> {
+    let ext = path.extension().and_then(|s| s.to_str())?;
+    Some(match ext {
+        "rs" => "rust",
+        "toml" => "toml",
+        "md" => "markdown",
+        "py" => "python",
+        "js" | "ts" => "javascript",
+        "json" => "json",
+        "c" | "h" | "cpp" => "c",
+        _ => "txt",
+    })
+}
+
+/// The text of `path`, or a note saying why it could not be read.
+pub fn read_for_editor(path: &Path) -> String {
+    match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => format!("// UNAOS: FAILED TO LOAD {:?}\n// ERROR: {}", path, e),
+    }
+}
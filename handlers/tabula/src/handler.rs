@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The core side of the editor: picks files and tells the front end to load
+//! them.
+
+use async_channel::Sender;
+use gneiss_pal::{AppHandler, DashboardState, Event, GuiUpdate};
+use std::path::{Path, PathBuf};
+
+pub struct TabulaHandler {
+    gui_tx: Sender<GuiUpdate>,
+    open: Option<PathBuf>,
+}
+
+impl TabulaHandler {
+    pub fn new(gui_tx: Sender<GuiUpdate>) -> Self {
+        Self { gui_tx, open: None }
+    }
+
+    /// The file last sent to the editor.
+    pub fn open_file(&self) -> Option<&Path> {
+        self.open.as_deref()
+    }
+}
+
+impl AppHandler for TabulaHandler {
+    fn handle_event(&mut self, event: Event) {
+        if let Event::FileSelected(path) = event {
+            log::debug!("Routing {} to the editor", path.display());
+            // Bouncing it as EditorLoad to trigger tabula
+            let _ = self
+                .gui_tx
+                .try_send(GuiUpdate::EditorLoad(path.to_string_lossy().to_string()));
+            self.open = Some(path);
+        }
+    }
+
+    fn view(&self) -> DashboardState {
+        DashboardState {
+            nav_items: self
+                .open
+                .iter()
+                .filter_map(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_string())
+                .collect(),
+            ..DashboardState::default()
+        }
+    }
+}
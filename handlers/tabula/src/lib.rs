@@ -14,11 +14,20 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+pub mod document;
+mod handler;
+
+pub use handler::TabulaHandler;
+
+#[cfg(feature = "gtk")]
 use gtk4::prelude::*;
+#[cfg(feature = "gtk")]
 use gtk4::{ScrolledWindow, Widget};
+#[cfg(feature = "gtk")]
 use sourceview5::prelude::*;
+#[cfg(feature = "gtk")]
 use sourceview5::{LanguageManager, View as SourceView};
-use std::fs;
+#[cfg(feature = "gtk")]
 use std::path::Path;
 
 #[derive(Debug, Clone)]
@@ -28,11 +37,13 @@ pub enum EditorMode {
     Log,
 }
 
+#[cfg(feature = "gtk")]
 pub struct TabulaView {
     pub view: SourceView,
     container: ScrolledWindow,
 }
 
+#[cfg(feature = "gtk")]
 impl TabulaView {
     pub fn new(mode: EditorMode) -> Self {
         let view = SourceView::builder().auto_indent(true).build();
@@ -87,18 +98,8 @@ impl TabulaView {
             .unwrap();
 
         // Auto-detect language based on extension
-        if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
+        if let Some(lang_id) = document::language_for(path) {
             let lm = LanguageManager::default();
-            let lang_id = match ext {
-                "rs" => "rust",
-                "toml" => "toml",
-                "md" => "markdown",
-                "py" => "python",
-                "js" | "ts" => "javascript",
-                "json" => "json",
-                "c" | "h" | "cpp" => "c",
-                _ => "txt",
-            };
             if let Some(lang) = lm.language(lang_id) {
                 buffer.set_language(Some(&lang));
             } else {
@@ -106,12 +107,6 @@ impl TabulaView {
             }
         }
 
-        match fs::read_to_string(path) {
-            Ok(content) => buffer.set_text(&content),
-            Err(e) => buffer.set_text(&format!(
-                "// UNAOS: FAILED TO LOAD {:?}\n// ERROR: {}",
-                path, e
-            )),
-        }
+        buffer.set_text(&document::read_for_editor(path));
     }
 }
@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The pick-a-file-and-load-it flow, run headless (`--no-default-features`
+//! builds it without GTK).
+
+use gneiss_pal::headless::Headless;
+use gneiss_pal::{Event, GuiUpdate, expect_update};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tabula::TabulaHandler;
+use tabula::document::{language_for, read_for_editor};
+
+const SOON: Duration = Duration::from_secs(2);
+
+fn tabula() -> Headless<TabulaHandler> {
+    let (gui_tx, gui_rx) = async_channel::unbounded();
+    Headless::start(TabulaHandler::new(gui_tx), gui_rx)
+}
+
+#[tokio::test]
+async fn test_selected_file_is_loaded_into_the_editor() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.rs");
+    std::fs::write(&path, "fn main() {}\n").unwrap();
+
+    let ui = tabula();
+    ui.send(Event::FileSelected(path.clone()));
+    let GuiUpdate::EditorLoad(loaded) = expect_update!(ui, GuiUpdate::EditorLoad(_), SOON) else {
+        unreachable!()
+    };
+
+    // What the editor does with it.
+    let loaded = PathBuf::from(loaded);
+    assert_eq!(loaded, path);
+    assert_eq!(language_for(&loaded), Some("rust"));
+    assert_eq!(read_for_editor(&loaded), "fn main() {}\n");
+
+    assert_eq!(
+        ui.with(|t| t.open_file().map(Path::to_path_buf)).await,
+        Some(path)
+    );
+    assert_eq!(ui.view().await.nav_items, vec!["main.rs"]);
+}
+
+#[tokio::test]
+async fn test_picks_load_in_order_and_other_events_are_ignored() {
+    let ui = tabula();
+    ui.script([
+        Event::FileSelected("notes.md".into()),
+        Event::NavSelect(1),
+        Event::FileSelected("Cargo.toml".into()),
+    ]);
+
+    expect_update!(ui, GuiUpdate::EditorLoad(p) if p == "notes.md", SOON);
+    expect_update!(ui, GuiUpdate::EditorLoad(p) if p == "Cargo.toml", SOON);
+    ui.settle().await;
+    assert_eq!(ui.updates().len(), 2);
+}
+
+#[test]
+fn test_languages_and_unreadable_files() {
+    assert_eq!(language_for(Path::new("a/b.py")), Some("python"));
+    assert_eq!(language_for(Path::new("x.ts")), Some("javascript"));
+    assert_eq!(language_for(Path::new("x.weird")), Some("txt"));
+    assert_eq!(language_for(Path::new("README")), None);
+
+    let text = read_for_editor(Path::new("/nonexistent/file.rs"));
+    assert!(text.starts_with("// UNAOS: FAILED TO LOAD \"/nonexistent/file.rs\""));
+    assert!(text.contains("// ERROR: "));
+}
@@ -14,10 +14,10 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use gneiss_pal::api::{Content, Part, ResilientClient};
+use gneiss_pal::api::{Content, LlmProvider, Part};
 
 pub async fn compress_into_engram(
-    client: &mut ResilientClient,
+    llm: &dyn LlmProvider,
     user_prompt: &str,
     ai_response: &str,
 ) -> Result<String, String> {
@@ -48,6 +48,9 @@ Example Output format:
         parts: vec![Part::text(combined_text)],
     });
 
-    let (response, _) = client.generate_content(&request_contents).await?;
+    let (response, _) = llm
+        .generate(&request_contents)
+        .await
+        .map_err(|e| e.to_string())?;
     Ok(response)
 }
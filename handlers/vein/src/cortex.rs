@@ -17,11 +17,10 @@
 use unafs::io::MappedFile;
 use elessar::context::SkeletonGenerator;
 use gneiss_pal::io::MemoryMappedRegion;
-use bandy::{SMessage, MatrixEvent, SpatialNode, SpatialEdge};
+use bandy::{SMessage, MatrixEvent, SpatialNode, SpatialEdge, SpatialGraph, Synapse};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use tokio::sync::broadcast;
+use std::sync::{Arc, Mutex};
 use log::info;
 
 /// Ingests a source file into the AI Cortex's memory matrix.
@@ -57,14 +56,44 @@ pub fn ingest_for_lumen(file_path: &Path) -> Result<String, String> {
     Ok(skeleton)
 }
 
+/// The workspace map as last sent to the Matrix.
+#[derive(Default)]
+pub struct Topology {
+    sent: Option<SpatialGraph>,
+}
+
+impl Topology {
+    /// What to fire for a freshly indexed map: all of it the first time,
+    /// then only a `TopologyDiff` against the map sent before.
+    pub fn update(&mut self, nodes: Vec<SpatialNode>, edges: Vec<SpatialEdge>) -> MatrixEvent {
+        if let Some(graph) = &mut self.sent {
+            match graph.sync(nodes.clone(), edges.clone()) {
+                Ok((base, deltas)) => return MatrixEvent::TopologyDiff { base, deltas },
+                // A rejected diff leaves the graph half-applied; start over.
+                Err(e) => info!(":: CORTEX :: Resending the whole topology: {}", e),
+            }
+        }
+        self.sent = SpatialGraph::from_snapshot(nodes.clone(), edges.clone()).ok();
+        MatrixEvent::IngestTopology { nodes, edges }
+    }
+}
+
 // Update the signature to return the HashMap
-pub async fn run_indexer(root: PathBuf, tx: broadcast::Sender<SMessage>) -> HashMap<PathBuf, Arc<String>> {
-    let payload = scan_workspace(&root, &tx).await;
+pub async fn run_indexer(
+    root: PathBuf,
+    synapse: Synapse,
+    topology: Arc<Mutex<Topology>>,
+) -> HashMap<PathBuf, Arc<String>> {
+    let payload = scan_workspace(&root, &synapse, &topology).await;
     payload
 }
 
 // Rename and update return type
-async fn scan_workspace(root: &Path, tx: &broadcast::Sender<SMessage>) -> HashMap<PathBuf, Arc<String>> {
+async fn scan_workspace(
+    root: &Path,
+    synapse: &Synapse,
+    topology: &Mutex<Topology>,
+) -> HashMap<PathBuf, Arc<String>> {
     info!(":: CORTEX :: Indexing Workspace at {:?}", root);
 
     let mut indexer = elessar::context::WorkspaceIndexer::new();
@@ -75,6 +104,18 @@ async fn scan_workspace(root: &Path, tx: &broadcast::Sender<SMessage>) -> HashMa
     let mut skeleton_cache: HashMap<PathBuf, Arc<String>> = HashMap::new();
     let mut total_skeletons = 0;
 
+    // Resolved path edges only; dev-dependencies don't shape the topology.
+    for edge in indexer.graph().edges() {
+        if edge.kind != elessar::context::DependencyKind::Dev {
+            spatial_edges.push(SpatialEdge {
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                relation: "depends_on".to_string(),
+                weight: 1.0,
+            });
+        }
+    }
+
     for (crate_name, node) in &indexer.nodes {
         spatial_nodes.push(SpatialNode {
             id: crate_name.clone(),
@@ -82,16 +123,6 @@ async fn scan_workspace(root: &Path, tx: &broadcast::Sender<SMessage>) -> HashMa
             path: node.path.clone(),
         });
 
-        for dep in &node.dependencies {
-            if indexer.nodes.contains_key(dep) {
-                spatial_edges.push(SpatialEdge {
-                    from: crate_name.clone(),
-                    to: dep.clone(),
-                    relation: "depends_on".to_string(),
-                });
-            }
-        }
-
         let src_dir = node.path.join("src");
         if src_dir.exists() {
             let mut files = Vec::new();
@@ -118,15 +149,30 @@ async fn scan_workspace(root: &Path, tx: &broadcast::Sender<SMessage>) -> HashMa
                         // REMOVED: The hardcoded bandy focus
                     }
                     Err(e) => {
-                        let _ = tx.send(SMessage::Log { level: "WARN".into(), source: "Cortex".into(), content: e });
+                        synapse.fire(SMessage::Log {
+                            level: "WARN".into(),
+                            source: "Cortex".into(),
+                            content: e,
+                        });
                     }
                 }
             }
         }
     }
 
-    let _ = tx.send(SMessage::Matrix(MatrixEvent::IngestTopology { nodes: spatial_nodes, edges: spatial_edges }));
-    let _ = tx.send(SMessage::Log { level: "INFO".into(), source: "Cortex".into(), content: format!("Workspace Indexed. Generated {} AST Skeletons.", total_skeletons) });
+    let event = topology
+        .lock()
+        .unwrap()
+        .update(spatial_nodes, spatial_edges);
+    synapse.fire(SMessage::Matrix(event));
+    synapse.fire(SMessage::Log {
+        level: "INFO".into(),
+        source: "Cortex".into(),
+        content: format!(
+            "Workspace Indexed. Generated {} AST Skeletons.",
+            total_skeletons
+        ),
+    });
 
     // Return the raw cache
     skeleton_cache
@@ -21,7 +21,7 @@ pub mod storage;
 pub mod synapse;
 
 use chrono::Local;
-use gneiss_pal::api::{Content, Part, ResilientClient};
+use gneiss_pal::api::{BudgetConfig, Budgeted, Content, Delta, LlmProvider, Part, collect_stream};
 use gneiss_pal::forge::ForgeClient;
 use gneiss_pal::persistence::BrainManager;
 use gneiss_pal::{
@@ -34,10 +34,10 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 use tokio::runtime::Runtime;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::mpsc;
 
 use crate::storage::DiskManager;
-use bandy::{BandyMember, SMessage};
+use bandy::{BandyMember, SMessage, Synapse};
 
 struct State {
     mode: ViewMode,
@@ -189,7 +189,7 @@ pub struct VeinHandler {
     state: Arc<Mutex<State>>,
     tx: mpsc::UnboundedSender<String>,
     gui_tx: async_channel::Sender<GuiUpdate>,
-    bandy_tx: broadcast::Sender<SMessage>,
+    synapse: Synapse,
     telemetry_tx: async_channel::Sender<SMessage>, // <-- NEW
 }
 
@@ -197,12 +197,21 @@ impl VeinHandler {
     pub fn new(
         gui_tx: async_channel::Sender<GuiUpdate>,
         history_path: PathBuf,
-        bandy_tx: broadcast::Sender<SMessage>,
+        synapse: Synapse,
         telemetry_tx: async_channel::Sender<SMessage>, // Pure Async Channel
+        llm: Result<Arc<dyn LlmProvider>, String>,
     ) -> Self {
         let vault_path_bg = history_path.clone();
         let brain = BrainManager::new(history_path);
 
+        // Rate limits, context window and a usage ledger for this session.
+        let session = Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let llm = llm.map(|llm| -> Arc<dyn LlmProvider> {
+            Arc::new(
+                Budgeted::new(llm, BudgetConfig::from_env()).with_ledger(brain.clone(), &session),
+            )
+        });
+
         let state = Arc::new(Mutex::new(State {
             mode: ViewMode::Comms,
             nav_index: 0,
@@ -219,8 +228,9 @@ impl VeinHandler {
         let gui_tx_brain = gui_tx.clone();
         let state_bg = state.clone();
         let brain_bg = brain.clone();
-        let bandy_tx_bg = bandy_tx.clone();
+        let synapse_bg = synapse.clone();
         let telemetry_tx_bg = telemetry_tx.clone();
+        let topology_bg = Arc::new(Mutex::new(cortex::Topology::default()));
 
         thread::spawn(move || {
             // Ignite the Can-Am V8 (Tokio Runtime)
@@ -239,7 +249,7 @@ impl VeinHandler {
                 let state_indexer = state_bg.clone();
                 let telemetry_tx_indexer = telemetry_tx_bg.clone();
                 tokio::spawn(async move {
-                    let cache = cortex::run_indexer(root, bandy_tx_bg).await;
+                    let cache = cortex::run_indexer(root, synapse_bg, topology_bg).await;
 
                     let live_ctx = {
                         let mut s = state_indexer.lock().unwrap();
@@ -279,10 +289,9 @@ impl VeinHandler {
                     Err(_) => None,
                 };
 
-                let client_res = ResilientClient::new().await;
-                match client_res {
-                    Ok(mut client) => {
-                        let _ = gui_tx_brain.send(GuiUpdate::ConsoleLog(":: BRAIN :: ONLINE (PLEXUS ENABLED)\n\n".into())).await;
+                match llm {
+                    Ok(llm) => {
+                        let _ = gui_tx_brain.send(GuiUpdate::ConsoleLog(format!(":: BRAIN :: ONLINE ({}) (PLEXUS ENABLED)\n\n", llm.name()))).await;
 
                         let directive = brain_bg.get_active_directive();
                         let _ = gui_tx_brain.send(GuiUpdate::ActiveDirective(directive)).await;
@@ -294,7 +303,7 @@ impl VeinHandler {
                                 let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
                                 let disk_clone = disk.clone();
 
-                                match client.embed_content(&dir_text).await {
+                                match llm.embed(&dir_text).await {
                                     Ok(embedding) => {
                                         tokio::spawn(async move {
                                             let _ = tokio::task::spawn_blocking(move || {
@@ -334,7 +343,18 @@ impl VeinHandler {
                                         parts: parse_multimodal_text(&current_text),
                                     });
 
-                                    match client.generate_content(&context).await {
+                                    // Stream the answer to the UI as it is written; the
+                                    // final ConsoleLog below replaces the live preview.
+                                    // Usage follows as TokenUsage; calls are not run here.
+                                    let streamed = collect_stream(llm.generate_stream(&context), |delta| {
+                                        if let Delta::Text(text) = delta {
+                                            let _ = gui_tx_brain.try_send(GuiUpdate::StreamDelta(text.clone()));
+                                        }
+                                    })
+                                    .await;
+                                    let _ = gui_tx_brain.send(GuiUpdate::StreamEnd).await;
+
+                                    match streamed {
                                         Ok((response, metadata)) => {
                                             let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
                                             let display = format!("\n[UNA] [{}] :: {}\n", timestamp, response);
@@ -350,7 +370,7 @@ impl VeinHandler {
                                             }
 
                                             let safe_embed: String = response.chars().take(6000).collect();
-                                            let response_embedding = match client.embed_content(&safe_embed).await {
+                                            let response_embedding = match llm.embed(&safe_embed).await {
                                                 Ok(vec) => vec,
                                                 Err(_) => vec![],
                                             };
@@ -381,19 +401,18 @@ impl VeinHandler {
 
                                             let disk_clone_engram = disk.clone();
                                             let ai_response_clone = response.clone();
+                                            let llm_engram = llm.clone();
                                             tokio::spawn(async move {
-                                                if let Ok(mut client_clone) = ResilientClient::new().await {
-                                                    if let Ok(engram) = crate::context::compress_into_engram(&mut client_clone, &raw_user_prompt, &ai_response_clone).await {
-                                                        if let Ok(engram_embedding) = client_clone.embed_content(&engram).await {
-                                                            let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
-                                                            let _ = tokio::task::spawn_blocking(move || {
-                                                                let mut d = disk_clone_engram.lock().unwrap();
-                                                                if let Err(e) = d.save_memory("system", &engram, &timestamp, engram_embedding, "engram") {
-                                                                    eprintln!(":: PLEXUS :: Failed to save engram memory: {}", e);
-                                                                }
-                                                            }).await;
+                                                if let Ok(engram) = crate::context::compress_into_engram(llm_engram.as_ref(), &raw_user_prompt, &ai_response_clone).await
+                                                    && let Ok(engram_embedding) = llm_engram.embed(&engram).await
+                                                {
+                                                    let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
+                                                    let _ = tokio::task::spawn_blocking(move || {
+                                                        let mut d = disk_clone_engram.lock().unwrap();
+                                                        if let Err(e) = d.save_memory("system", &engram, &timestamp, engram_embedding, "engram") {
+                                                            eprintln!(":: PLEXUS :: Failed to save engram memory: {}", e);
                                                         }
-                                                    }
+                                                    }).await;
                                                 }
                                             });
                                         }
@@ -471,7 +490,7 @@ impl VeinHandler {
                                 }).await;
                             }
 
-                            let user_embedding = match client.embed_content(&user_input_text).await {
+                            let user_embedding = match llm.embed(&user_input_text).await {
                                 Ok(vec) => vec,
                                 Err(e) => {
                                     eprintln!(":: PLEXUS :: Embedding Failed: {}", e);
@@ -643,7 +662,7 @@ impl VeinHandler {
             state,
             tx: tx_to_bg,
             gui_tx,
-            bandy_tx,
+            synapse,
             telemetry_tx, // <-- NEW
         }
     }
@@ -656,9 +675,9 @@ impl VeinHandler {
 }
 
 impl BandyMember for VeinHandler {
-    fn publish(&self, _topic: &str, msg: SMessage) -> anyhow::Result<()> {
-        self.bandy_tx
-            .send(msg)
+    fn publish(&self, topic: &str, msg: SMessage) -> anyhow::Result<()> {
+        self.synapse
+            .publish(topic, msg)
             .map_err(|e| anyhow::anyhow!("Bandy Send Error: {}", e))?;
         Ok(())
     }
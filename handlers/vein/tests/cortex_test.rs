@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! What the indexer puts on the Synapse for the Matrix: the whole map once,
+//! then diffs.
+
+use bandy::graph::EdgeKey;
+use bandy::{GraphDelta, MatrixEvent, SMessage, SpatialGraph, Synapse};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use vein::cortex::{self, Topology};
+
+fn write_crate(root: &Path, name: &str, deps: &[&str]) {
+    let dir = root.join(name);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    let deps: String = deps
+        .iter()
+        .map(|d| format!("{} = {{ path = \"../{}\" }}\n", d, d))
+        .collect();
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"{}\"\nversion = \"0.1.0\"\n\n[dependencies]\n{}",
+            name, deps
+        ),
+    )
+    .unwrap();
+    fs::write(dir.join("src/lib.rs"), "pub fn f() {}\n").unwrap();
+}
+
+async fn next_topology(sub: &mut bandy::Subscription) -> MatrixEvent {
+    match sub.recv().await.unwrap().msg {
+        SMessage::Matrix(event) => event,
+        other => panic!("expected a matrix event, got {:?}", other),
+    }
+}
+
+fn depends_on(from: &str, to: &str) -> EdgeKey {
+    EdgeKey {
+        from: from.into(),
+        to: to.into(),
+        relation: "depends_on".into(),
+    }
+}
+
+#[tokio::test]
+async fn test_reindex_sends_only_the_diff() {
+    let dir = tempfile::tempdir().unwrap();
+    write_crate(dir.path(), "alpha", &["beta"]);
+    write_crate(dir.path(), "beta", &[]);
+
+    let synapse = Synapse::new();
+    let mut matrix = synapse.subscribe("matrix/#").unwrap();
+    let topology = Arc::new(Mutex::new(Topology::default()));
+
+    cortex::run_indexer(dir.path().into(), synapse.clone(), topology.clone()).await;
+    let MatrixEvent::IngestTopology { nodes, edges } = next_topology(&mut matrix).await else {
+        panic!("the first sync must carry the whole map");
+    };
+    let mut receiver = SpatialGraph::from_snapshot(nodes, edges).unwrap();
+    assert_eq!(receiver.node_count(), 2);
+    assert_eq!(receiver.edge_count(), 1);
+
+    // alpha trades beta for a new crate.
+    write_crate(dir.path(), "alpha", &["gamma"]);
+    write_crate(dir.path(), "gamma", &[]);
+    cortex::run_indexer(dir.path().into(), synapse.clone(), topology.clone()).await;
+    let MatrixEvent::TopologyDiff { base, deltas } = next_topology(&mut matrix).await else {
+        panic!("a re-index must only send a diff");
+    };
+    assert_eq!(base, 0);
+    assert_eq!(deltas.len(), 3, "{:?}", deltas);
+    assert!(deltas.contains(&GraphDelta::RemoveEdge(depends_on("alpha", "beta"))));
+    assert!(
+        deltas
+            .iter()
+            .any(|d| matches!(d, GraphDelta::AddNode(n) if n.id == "gamma"))
+    );
+    assert!(deltas.iter().any(
+        |d| matches!(d, GraphDelta::AddEdge(e) if depends_on("alpha", "gamma") == EdgeKey::of(e))
+    ));
+
+    receiver.apply_diff(base, deltas).unwrap();
+    assert_eq!(receiver.node_count(), 3);
+    assert_eq!(receiver.weight(&depends_on("alpha", "gamma")), Some(1.0));
+
+    // Nothing changed: an empty diff against the next revision.
+    cortex::run_indexer(dir.path().into(), synapse, topology).await;
+    let MatrixEvent::TopologyDiff { base, deltas } = next_topology(&mut matrix).await else {
+        panic!("an unchanged map must not be resent");
+    };
+    assert_eq!((base, deltas.len()), (1, 0));
+}
+
+#[test]
+fn test_rejected_map_falls_back_to_a_snapshot() {
+    let node = |id: &str| bandy::SpatialNode {
+        id: id.into(),
+        kind: "crate".into(),
+        path: id.into(),
+    };
+    let edge = |from: &str, to: &str| bandy::SpatialEdge {
+        from: from.into(),
+        to: to.into(),
+        relation: "depends_on".into(),
+        weight: 1.0,
+    };
+    let mut topology = Topology::default();
+    let first = topology.update(vec![node("a"), node("b")], vec![edge("a", "b")]);
+    assert!(matches!(first, MatrixEvent::IngestTopology { .. }));
+
+    // An edge to a node that is not in the map cannot be diffed.
+    let dangling = topology.update(vec![node("a")], vec![edge("a", "ghost")]);
+    assert!(matches!(dangling, MatrixEvent::IngestTopology { .. }));
+}
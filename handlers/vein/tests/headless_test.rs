@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The chat flow against a scripted model, run headless: boot, mode
+//! switch, pre-flight review, dispatch and clear.
+
+use bandy::{SMessage, Synapse};
+use gneiss_pal::api::{MockProvider, UsageMetadata};
+use gneiss_pal::headless::Headless;
+use gneiss_pal::{Event, GuiUpdate, ViewMode, WolfpackState, expect_update};
+use std::sync::Arc;
+use std::time::Duration;
+use vein::VeinHandler;
+
+const BOOT: Duration = Duration::from_secs(20);
+const SOON: Duration = Duration::from_secs(5);
+
+fn input(text: &str) -> Event {
+    Event::Input {
+        target: "console".into(),
+        text: text.into(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_chat_flow() {
+    let dir = tempfile::tempdir().unwrap();
+    let usage = UsageMetadata {
+        prompt_token_count: Some(12),
+        candidates_token_count: Some(3),
+        total_token_count: Some(15),
+    };
+    let llm = Arc::new(MockProvider::new().with_reply("Hello, Architect.", usage));
+
+    let (gui_tx, gui_rx) = async_channel::unbounded();
+    let (telemetry_tx, _telemetry_rx) = async_channel::unbounded::<SMessage>();
+    let vein = VeinHandler::new(
+        gui_tx,
+        dir.path().join("history.ufs"),
+        Synapse::new(),
+        telemetry_tx,
+        Ok(llm),
+    );
+    let ui = Headless::start(vein, gui_rx);
+
+    expect_update!(ui, GuiUpdate::ConsoleLog(l) if l.starts_with(":: BRAIN :: ONLINE"), BOOT);
+
+    // Mode switches are answered straight from the handler.
+    ui.send(input("/wolf"));
+    expect_update!(ui, GuiUpdate::ConsoleLog(l) if l.contains("Switching to Wolfpack"), SOON);
+    assert_eq!(ui.view().await.mode, ViewMode::Wolfpack);
+    ui.send(input("/comms"));
+    expect_update!(ui, GuiUpdate::ConsoleLog(l) if l.contains("Secure Comms"), SOON);
+    assert_eq!(ui.view().await.mode, ViewMode::Comms);
+
+    // A prompt is held for review, not sent.
+    ui.send(input("hello"));
+    let GuiUpdate::ReviewPayload(payload) = expect_update!(ui, GuiUpdate::ReviewPayload(_), SOON)
+    else {
+        unreachable!()
+    };
+    assert_eq!(payload.prompt, "hello");
+    assert!(
+        ui.expect_no_update(
+            |u| matches!(u, GuiUpdate::StreamDelta(_)),
+            Duration::from_millis(200)
+        )
+        .await
+        .is_ok()
+    );
+
+    // Approving it streams the scripted answer.
+    ui.send(Event::DispatchPayload(
+        serde_json::to_string(&payload).unwrap(),
+    ));
+    expect_update!(ui, GuiUpdate::StreamDelta(_), SOON);
+    expect_update!(ui, GuiUpdate::StreamEnd, SOON);
+    expect_update!(ui, GuiUpdate::ConsoleLog(l) if l.contains("[UNA]") && l.contains("Hello, Architect."), SOON);
+    expect_update!(ui, GuiUpdate::TokenUsage(12, 3, 15), SOON);
+    expect_update!(ui, GuiUpdate::SidebarStatus(WolfpackState::Idle), SOON);
+
+    ui.send(input("/clear"));
+    expect_update!(ui, GuiUpdate::ClearConsole, SOON);
+    expect_update!(ui, GuiUpdate::ConsoleLog(l) if l.contains("Reformatted"), SOON);
+    assert!(!ui.console().contains("Hello, Architect."));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_malformed_dispatch_is_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let (gui_tx, gui_rx) = async_channel::unbounded();
+    let (telemetry_tx, _telemetry_rx) = async_channel::unbounded::<SMessage>();
+    let vein = VeinHandler::new(
+        gui_tx,
+        dir.path().join("history.ufs"),
+        Synapse::new(),
+        telemetry_tx,
+        Ok(Arc::new(MockProvider::new())),
+    );
+    let ui = Headless::start(vein, gui_rx);
+    expect_update!(ui, GuiUpdate::ConsoleLog(l) if l.starts_with(":: BRAIN :: ONLINE"), BOOT);
+
+    ui.send(Event::DispatchPayload("{not json".into()));
+    expect_update!(ui, GuiUpdate::SynapseError(e) if e.contains("PreFlightPayload"), SOON);
+}
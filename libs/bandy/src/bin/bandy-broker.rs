@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! bandy-broker [SOCKET_PATH]
+//!
+//! The local switchboard. Lets vein, lumen and the una IDE share one
+//! nervous system without sharing one process.
+
+#[cfg(unix)]
+use bandy::transport::{self, Broker, BrokerConfig};
+#[cfg(unix)]
+use std::path::PathBuf;
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("[BANDY] The broker speaks Unix domain sockets only.");
+    std::process::exit(1);
+}
+
+#[cfg(unix)]
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> anyhow::Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(transport::default_socket_path);
+
+    let broker = Broker::bind(&path, BrokerConfig::default())?;
+    println!("[BANDY] Broker listening on {}", broker.path().display());
+
+    tokio::select! {
+        res = broker.run() => res?,
+        _ = tokio::signal::ctrl_c() => {
+            println!("[BANDY] Broker shutting down.");
+        }
+    }
+    Ok(())
+}
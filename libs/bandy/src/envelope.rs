@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The Envelope.
+//!
+//! Everything that leaves the process is wrapped in an `Envelope`. The
+//! `SMessage` inside is encoded as its own opaque CBOR blob, so a peer built
+//! against an older tree can still read the header, route the message, and
+//! forward a variant it has never heard of byte-for-byte.
+//!
+//! Compatibility rules:
+//! * Unknown envelope fields are ignored; missing optional ones default.
+//! * A payload that does not decode as this tree's `SMessage` is kept raw.
+//! * A bare `SMessage` with no envelope at all is read as version 0.
+
+use crate::SMessage;
+use crate::synapse::Impulse;
+use anyhow::{Context, Result};
+use serde::de::Deserializer;
+use serde::ser::{Error as _, Serializer};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The envelope layout this tree writes.
+pub const ENVELOPE_VERSION: u16 = 1;
+
+/// Returns an id unique within this process and, with overwhelming
+/// probability, across every process on the bus. Each process starts its
+/// sequence at a random offset.
+pub fn next_msg_id() -> u64 {
+    static SEQ: OnceLock<AtomicU64> = OnceLock::new();
+    SEQ.get_or_init(|| AtomicU64::new(RandomState::new().hash_one(std::process::id())))
+        .fetch_add(1, Ordering::Relaxed)
+}
+
+/// The name this process signs its envelopes with: the executable's stem.
+pub fn process_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "bandy".to_string())
+}
+
+/// The body of an envelope.
+#[derive(Debug, Clone)]
+pub enum Payload {
+    /// A message this tree understands.
+    Known(SMessage),
+    /// A message from a newer (or foreign) tree. The CBOR bytes are kept
+    /// untouched so the envelope can be forwarded without loss.
+    Unknown(Vec<u8>),
+}
+
+impl Payload {
+    fn from_bytes(bytes: Vec<u8>) -> Self {
+        match ciborium::from_reader::<SMessage, _>(bytes.as_slice()) {
+            Ok(msg) => Payload::Known(msg),
+            Err(_) => Payload::Unknown(bytes),
+        }
+    }
+}
+
+impl Serialize for Payload {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Payload::Known(msg) => {
+                let mut buf = Vec::new();
+                ciborium::into_writer(msg, &mut buf).map_err(S::Error::custom)?;
+                serializer.serialize_bytes(&buf)
+            }
+            Payload::Unknown(raw) => serializer.serialize_bytes(raw),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Payload {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = serde_bytes::ByteBuf::deserialize(deserializer)?;
+        Ok(Payload::from_bytes(bytes.into_vec()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub version: u16,
+    pub msg_id: u64,
+    /// On a reply, the `msg_id` of the request it answers.
+    #[serde(default)]
+    pub correlation_id: Option<u64>,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: i64,
+    /// Who sent it (process or shard name).
+    #[serde(default)]
+    pub source: String,
+    pub topic: String,
+    pub payload: Payload,
+}
+
+impl Envelope {
+    pub fn new(source: &str, topic: &str, msg: SMessage) -> Self {
+        Self {
+            version: ENVELOPE_VERSION,
+            msg_id: next_msg_id(),
+            correlation_id: None,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            source: source.to_string(),
+            topic: topic.to_string(),
+            payload: Payload::Known(msg),
+        }
+    }
+
+    /// Builds the answer to this envelope.
+    pub fn reply(&self, source: &str, topic: &str, msg: SMessage) -> Self {
+        Self {
+            correlation_id: Some(self.msg_id),
+            ..Self::new(source, topic, msg)
+        }
+    }
+
+    /// True if `other` answers this envelope.
+    pub fn is_answered_by(&self, other: &Envelope) -> bool {
+        other.correlation_id == Some(self.msg_id)
+    }
+
+    /// The message, if this tree understands it.
+    pub fn message(&self) -> Option<&SMessage> {
+        match &self.payload {
+            Payload::Known(msg) => Some(msg),
+            Payload::Unknown(_) => None,
+        }
+    }
+
+    /// Wraps an in-process impulse for the wire, keeping its ids.
+    pub fn from_impulse(source: &str, impulse: &Impulse) -> Self {
+        Self {
+            msg_id: impulse.id,
+            correlation_id: impulse.correlation_id,
+            ..Self::new(source, &impulse.topic, impulse.msg.clone())
+        }
+    }
+
+    /// Unwraps into an in-process impulse. Unknown payloads have no local
+    /// representation and yield `None`.
+    pub fn into_impulse(self) -> Option<Impulse> {
+        match self.payload {
+            Payload::Known(msg) => Some(Impulse {
+                topic: self.topic.into(),
+                msg,
+                id: self.msg_id,
+                correlation_id: self.correlation_id,
+                offset: None,
+            }),
+            Payload::Unknown(_) => None,
+        }
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(self, &mut buf).context("Failed to encode envelope")?;
+        Ok(buf)
+    }
+
+    /// Decodes an envelope of any version, including a bare legacy `SMessage`.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if let Ok(envelope) = ciborium::from_reader::<Envelope, _>(bytes) {
+            return Ok(envelope);
+        }
+
+        // Version 0: peers that predate the envelope sent the message alone.
+        let msg: SMessage = ciborium::from_reader(bytes)
+            .context("Bytes are neither an envelope nor an SMessage")?;
+        Ok(Self {
+            version: 0,
+            msg_id: next_msg_id(),
+            correlation_id: None,
+            timestamp: 0,
+            source: String::new(),
+            topic: msg.topic().to_string(),
+            payload: Payload::Known(msg),
+        })
+    }
+}
@@ -0,0 +1,417 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The Spatial Graph.
+//!
+//! An in-memory store for the topology the Matrix carries around: nodes are
+//! crates, structs and functions, edges are directed, weighted relations.
+//! Instead of shipping the whole map in every `IngestTopology`, a publisher
+//! keeps a `SpatialGraph`, feeds it new snapshots or deltas, and sends only
+//! the minimal `TopologyDiff` between revisions.
+
+use crate::{SpatialEdge, SpatialNode};
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, VecDeque};
+
+/// Identifies an edge: there is at most one edge per `(from, to, relation)`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct EdgeKey {
+    pub from: String,
+    pub to: String,
+    pub relation: String,
+}
+
+impl EdgeKey {
+    pub fn of(edge: &SpatialEdge) -> Self {
+        Self {
+            from: edge.from.clone(),
+            to: edge.to.clone(),
+            relation: edge.relation.clone(),
+        }
+    }
+
+    fn with_weight(&self, weight: f32) -> SpatialEdge {
+        SpatialEdge {
+            from: self.from.clone(),
+            to: self.to.clone(),
+            relation: self.relation.clone(),
+            weight,
+        }
+    }
+}
+
+/// One change to a graph.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GraphDelta {
+    /// Inserts a node, or replaces the one with the same id (keeping its edges).
+    AddNode(SpatialNode),
+    /// Removes a node and every edge touching it.
+    RemoveNode(String),
+    /// Inserts an edge, or replaces the weight of an identical one.
+    AddEdge(SpatialEdge),
+    RemoveEdge(EdgeKey),
+    UpdateWeight {
+        edge: EdgeKey,
+        weight: f32,
+    },
+}
+
+/// Which way to follow edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Outgoing,
+    Incoming,
+    Both,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SpatialGraph {
+    nodes: BTreeMap<String, SpatialNode>,
+    edges: BTreeMap<EdgeKey, f32>,
+    outgoing: HashMap<String, BTreeSet<EdgeKey>>,
+    incoming: HashMap<String, BTreeSet<EdgeKey>>,
+    revision: u64,
+}
+
+impl SpatialGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a graph from a full `IngestTopology` snapshot.
+    pub fn from_snapshot(nodes: Vec<SpatialNode>, edges: Vec<SpatialEdge>) -> Result<Self> {
+        let mut graph = Self::new();
+        for node in nodes {
+            graph.apply(GraphDelta::AddNode(node))?;
+        }
+        for edge in edges {
+            graph.apply(GraphDelta::AddEdge(edge))?;
+        }
+        graph.revision = 0;
+        Ok(graph)
+    }
+
+    /// The full state, nodes by id and edges by key.
+    pub fn snapshot(&self) -> (Vec<SpatialNode>, Vec<SpatialEdge>) {
+        let nodes = self.nodes.values().cloned().collect();
+        let edges = self
+            .edges
+            .iter()
+            .map(|(key, weight)| key.with_weight(*weight))
+            .collect();
+        (nodes, edges)
+    }
+
+    /// Counts applied batches; see `apply_diff`.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn node(&self, id: &str) -> Option<&SpatialNode> {
+        self.nodes.get(id)
+    }
+
+    pub fn weight(&self, edge: &EdgeKey) -> Option<f32> {
+        self.edges.get(edge).copied()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Applies a single delta. A rejected delta leaves the graph untouched.
+    pub fn apply(&mut self, delta: GraphDelta) -> Result<()> {
+        match delta {
+            GraphDelta::AddNode(node) => {
+                self.nodes.insert(node.id.clone(), node);
+            }
+            GraphDelta::RemoveNode(id) => {
+                if self.nodes.remove(&id).is_none() {
+                    bail!("No node '{}' to remove", id);
+                }
+                let touching: Vec<EdgeKey> = self
+                    .outgoing
+                    .remove(&id)
+                    .into_iter()
+                    .chain(self.incoming.remove(&id))
+                    .flatten()
+                    .collect();
+                for key in touching {
+                    self.unlink(&key);
+                }
+            }
+            GraphDelta::AddEdge(edge) => {
+                check_weight(edge.weight)?;
+                for end in [&edge.from, &edge.to] {
+                    if !self.nodes.contains_key(end) {
+                        bail!("Edge endpoint '{}' is not in the graph", end);
+                    }
+                }
+                let key = EdgeKey::of(&edge);
+                self.outgoing
+                    .entry(key.from.clone())
+                    .or_default()
+                    .insert(key.clone());
+                self.incoming
+                    .entry(key.to.clone())
+                    .or_default()
+                    .insert(key.clone());
+                self.edges.insert(key, edge.weight);
+            }
+            GraphDelta::RemoveEdge(key) => {
+                if !self.unlink(&key) {
+                    bail!("No edge {:?} to remove", key);
+                }
+            }
+            GraphDelta::UpdateWeight { edge, weight } => {
+                check_weight(weight)?;
+                match self.edges.get_mut(&edge) {
+                    Some(w) => *w = weight,
+                    None => bail!("No edge {:?} to reweigh", edge),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a `TopologyDiff` batch. `base` must be this graph's revision;
+    /// otherwise the receiver missed a batch and needs a fresh snapshot.
+    /// On error, deltas before the failing one stay applied.
+    pub fn apply_diff(&mut self, base: u64, deltas: Vec<GraphDelta>) -> Result<()> {
+        if base != self.revision {
+            bail!(
+                "Diff is against revision {}, graph is at {}",
+                base,
+                self.revision
+            );
+        }
+        for delta in deltas {
+            self.apply(delta)?;
+        }
+        self.revision += 1;
+        Ok(())
+    }
+
+    /// The smallest set of deltas that turns this graph into `target`.
+    ///
+    /// Edges that vanish with a removed node are not listed separately,
+    /// a changed weight is an `UpdateWeight`, and order matters: edges are
+    /// removed before nodes, and nodes added before edges.
+    pub fn diff(&self, target: &SpatialGraph) -> Vec<GraphDelta> {
+        let mut deltas = Vec::new();
+        let removed_nodes: BTreeSet<&String> = self
+            .nodes
+            .keys()
+            .filter(|id| !target.nodes.contains_key(*id))
+            .collect();
+
+        for key in self.edges.keys() {
+            let cascades = removed_nodes.contains(&key.from) || removed_nodes.contains(&key.to);
+            if !cascades && !target.edges.contains_key(key) {
+                deltas.push(GraphDelta::RemoveEdge(key.clone()));
+            }
+        }
+        for id in &removed_nodes {
+            deltas.push(GraphDelta::RemoveNode((*id).clone()));
+        }
+        for (id, node) in &target.nodes {
+            if self.nodes.get(id) != Some(node) {
+                deltas.push(GraphDelta::AddNode(node.clone()));
+            }
+        }
+        for (key, &weight) in &target.edges {
+            match self.edges.get(key) {
+                // Bitwise, so the diff round-trips exactly.
+                Some(&old) if old.to_bits() == weight.to_bits() => {}
+                Some(_) => deltas.push(GraphDelta::UpdateWeight {
+                    edge: key.clone(),
+                    weight,
+                }),
+                None => deltas.push(GraphDelta::AddEdge(key.with_weight(weight))),
+            }
+        }
+        deltas
+    }
+
+    /// Replaces the whole graph with a new snapshot and returns the
+    /// `(base, deltas)` batch that takes a receiver at the old revision there.
+    pub fn sync(
+        &mut self,
+        nodes: Vec<SpatialNode>,
+        edges: Vec<SpatialEdge>,
+    ) -> Result<(u64, Vec<GraphDelta>)> {
+        let target = Self::from_snapshot(nodes, edges)?;
+        let deltas = self.diff(&target);
+        let base = self.revision;
+        self.apply_diff(base, deltas.clone())?;
+        Ok((base, deltas))
+    }
+
+    /// Adjacent node ids, sorted and without duplicates.
+    pub fn neighbours(&self, id: &str, direction: Direction) -> Vec<&str> {
+        let mut out = BTreeSet::new();
+        if matches!(direction, Direction::Outgoing | Direction::Both) {
+            for key in self.outgoing.get(id).into_iter().flatten() {
+                out.insert(key.to.as_str());
+            }
+        }
+        if matches!(direction, Direction::Incoming | Direction::Both) {
+            for key in self.incoming.get(id).into_iter().flatten() {
+                out.insert(key.from.as_str());
+            }
+        }
+        out.into_iter().collect()
+    }
+
+    /// The cheapest path along outgoing edges, as `(cost, node ids)`.
+    /// Between two nodes, parallel edges count with their lowest weight.
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<(f64, Vec<String>)> {
+        if !self.nodes.contains_key(from) || !self.nodes.contains_key(to) {
+            return None;
+        }
+
+        let mut dist: HashMap<&str, f64> = HashMap::from([(from, 0.0)]);
+        let mut prev: HashMap<&str, &str> = HashMap::new();
+        let mut heap = BinaryHeap::from([Reverse((Cost(0.0), from))]);
+
+        while let Some(Reverse((Cost(cost), id))) = heap.pop() {
+            if id == to {
+                let mut path = vec![to.to_string()];
+                let mut at = to;
+                while let Some(&p) = prev.get(at) {
+                    path.push(p.to_string());
+                    at = p;
+                }
+                path.reverse();
+                return Some((cost, path));
+            }
+            if dist.get(id).is_some_and(|&best| cost > best) {
+                continue;
+            }
+            for key in self.outgoing.get(id).into_iter().flatten() {
+                let next = cost + self.edges[key] as f64;
+                if dist.get(key.to.as_str()).is_none_or(|&best| next < best) {
+                    dist.insert(&key.to, next);
+                    prev.insert(&key.to, id);
+                    heap.push(Reverse((Cost(next), &key.to)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Weakly connected components, each sorted, ordered by their first id.
+    pub fn components(&self) -> Vec<Vec<String>> {
+        let mut seen = BTreeSet::new();
+        let mut components = Vec::new();
+        for start in self.nodes.keys() {
+            if seen.contains(start.as_str()) {
+                continue;
+            }
+            let mut component = self.reach(start, usize::MAX);
+            seen.extend(component.iter().cloned());
+            component.sort();
+            components.push(component);
+        }
+        components
+    }
+
+    /// The subgraph within `k` hops of `id`, following edges either way.
+    /// Holds every edge between the nodes it keeps.
+    pub fn k_hop(&self, id: &str, k: usize) -> Option<SpatialGraph> {
+        if !self.nodes.contains_key(id) {
+            return None;
+        }
+        let keep: BTreeSet<String> = self.reach(id, k).into_iter().collect();
+
+        let mut sub = SpatialGraph::new();
+        for node in &keep {
+            sub.apply(GraphDelta::AddNode(self.nodes[node].clone()))
+                .ok()?;
+        }
+        for (key, &weight) in &self.edges {
+            if keep.contains(&key.from) && keep.contains(&key.to) {
+                sub.apply(GraphDelta::AddEdge(key.with_weight(weight)))
+                    .ok()?;
+            }
+        }
+        Some(sub)
+    }
+
+    /// Breadth-first over both directions, up to `depth` hops.
+    fn reach(&self, start: &str, depth: usize) -> Vec<String> {
+        let mut seen = BTreeSet::from([start]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((id, hops)) = queue.pop_front() {
+            if hops == depth {
+                continue;
+            }
+            for next in self.neighbours(id, Direction::Both) {
+                if seen.insert(next) {
+                    queue.push_back((next, hops + 1));
+                }
+            }
+        }
+        seen.into_iter().map(str::to_string).collect()
+    }
+
+    fn unlink(&mut self, key: &EdgeKey) -> bool {
+        if self.edges.remove(key).is_none() {
+            return false;
+        }
+        if let Some(set) = self.outgoing.get_mut(&key.from) {
+            set.remove(key);
+        }
+        if let Some(set) = self.incoming.get_mut(&key.to) {
+            set.remove(key);
+        }
+        true
+    }
+}
+
+fn check_weight(weight: f32) -> Result<()> {
+    if !weight.is_finite() || weight < 0.0 {
+        bail!(
+            "Edge weight must be finite and non-negative, got {}",
+            weight
+        );
+    }
+    Ok(())
+}
+
+/// A path cost that can sit in a `BinaryHeap`. Costs are never NaN.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cost(f64);
+
+impl Eq for Cost {}
+
+impl PartialOrd for Cost {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cost {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
@@ -0,0 +1,532 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The Long-Term Memory of the nervous system.
+//!
+//! An append-only log of envelopes, split into segment files. Every record
+//! gets a global offset. A late subscriber can replay from any offset (or
+//! point in time) before switching to live traffic.
+//!
+//! On disk, per segment (named after its first offset):
+//! * `<base>.seg`: records of `len: u32 BE | crc32: u32 BE | CBOR envelope`.
+//! * `<base>.idx`: one 24-byte entry per record,
+//!   `offset: u64 BE | position: u64 BE | appended_at_ms: i64 BE`.
+//!
+//! Only the newest segment is ever written. On open it is rescanned and cut
+//! back to its last intact record, and its index is rebuilt, so a crash in
+//! the middle of an append costs at most that one record.
+//!
+//! Retention is enforced on open and whenever a segment rolls. A journal
+//! that is only appended to occasionally should also call
+//! `enforce_retention` periodically; a recording `Synapse` does so from its
+//! writer thread.
+
+use crate::Envelope;
+use anyhow::{Context, Result, bail};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const RECORD_HEADER: u64 = 8;
+const INDEX_ENTRY: u64 = 24;
+
+/// Records larger than this are treated as corruption.
+pub const MAX_RECORD_LEN: u32 = 16 * 1024 * 1024;
+
+/// How much history to keep. Whole segments are dropped, oldest first, and
+/// the active segment is never dropped.
+#[derive(Debug, Clone, Default)]
+pub struct Retention {
+    /// Upper bound on the total size of all segments.
+    pub max_bytes: Option<u64>,
+    /// Drop segments whose newest record is older than this.
+    pub max_age: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+pub struct JournalConfig {
+    /// A segment is sealed and a new one started once it reaches this size.
+    pub segment_bytes: u64,
+    pub retention: Retention,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            segment_bytes: 64 * 1024 * 1024,
+            retention: Retention::default(),
+        }
+    }
+}
+
+/// Where a replay starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    /// The oldest record still retained.
+    Beginning,
+    /// A specific record. Offsets older than retention clamp to the oldest.
+    Offset(u64),
+    /// The first record appended at or after this time (Unix ms).
+    Timestamp(i64),
+    /// Nothing historical; live traffic only.
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    base: u64,
+    /// Exclusive: the offset the next record in this segment would take.
+    end: u64,
+    bytes: u64,
+    last_appended_at: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct IndexEntry {
+    offset: u64,
+    position: u64,
+    appended_at: i64,
+}
+
+impl IndexEntry {
+    fn to_bytes(self) -> [u8; INDEX_ENTRY as usize] {
+        let mut buf = [0u8; INDEX_ENTRY as usize];
+        buf[0..8].copy_from_slice(&self.offset.to_be_bytes());
+        buf[8..16].copy_from_slice(&self.position.to_be_bytes());
+        buf[16..24].copy_from_slice(&self.appended_at.to_be_bytes());
+        buf
+    }
+
+    fn from_bytes(buf: &[u8]) -> Self {
+        Self {
+            offset: u64::from_be_bytes(buf[0..8].try_into().unwrap()),
+            position: u64::from_be_bytes(buf[8..16].try_into().unwrap()),
+            appended_at: i64::from_be_bytes(buf[16..24].try_into().unwrap()),
+        }
+    }
+}
+
+pub struct Journal {
+    dir: PathBuf,
+    config: JournalConfig,
+    /// Oldest first. The last one is active.
+    segments: Vec<Segment>,
+    seg_file: File,
+    idx_file: File,
+}
+
+impl Journal {
+    /// Opens (or creates) a journal in `dir`, repairing a torn tail.
+    pub fn open(dir: impl AsRef<Path>, config: JournalConfig) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create journal at {}", dir.display()))?;
+
+        let mut bases: Vec<u64> = fs::read_dir(&dir)?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().into_string().ok()?;
+                name.strip_suffix(".seg")?.parse().ok()
+            })
+            .collect();
+        bases.sort_unstable();
+
+        let mut segments = Vec::with_capacity(bases.len().max(1));
+        if let Some((&last, sealed)) = bases.split_last() {
+            for &base in sealed {
+                segments.push(load_sealed(&dir, base)?);
+            }
+            segments.push(recover_active(&dir, last)?);
+        } else {
+            segments.push(Segment {
+                base: 0,
+                end: 0,
+                bytes: 0,
+                last_appended_at: 0,
+            });
+        }
+
+        let active = segments.last().unwrap().base;
+        let (seg_file, idx_file) = open_for_append(&dir, active)?;
+
+        let mut journal = Self {
+            dir,
+            config,
+            segments,
+            seg_file,
+            idx_file,
+        };
+        // History may have expired while nothing was running.
+        journal.enforce_retention()?;
+        Ok(journal)
+    }
+
+    /// Appends an envelope and returns its offset.
+    pub fn append(&mut self, envelope: &Envelope) -> Result<u64> {
+        let payload = envelope.encode()?;
+        if payload.len() as u64 > MAX_RECORD_LEN as u64 {
+            bail!(
+                "Envelope of {} bytes is too large to journal",
+                payload.len()
+            );
+        }
+
+        let record_len = RECORD_HEADER + payload.len() as u64;
+        let active = self.segments.last().unwrap();
+        if active.bytes > 0 && active.bytes + record_len > self.config.segment_bytes {
+            self.roll()?;
+        }
+
+        let active = self.segments.last_mut().unwrap();
+        let offset = active.end;
+        // Index timestamps never go backwards, so they can be binary searched.
+        let appended_at = chrono::Utc::now()
+            .timestamp_millis()
+            .max(active.last_appended_at);
+
+        let mut record = Vec::with_capacity(record_len as usize);
+        record.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        record.extend_from_slice(&crc32fast::hash(&payload).to_be_bytes());
+        record.extend_from_slice(&payload);
+        let entry = IndexEntry {
+            offset,
+            position: active.bytes,
+            appended_at,
+        };
+        let written = self
+            .seg_file
+            .write_all(&record)
+            .and_then(|_| self.idx_file.write_all(&entry.to_bytes()));
+        if let Err(e) = written {
+            // Never leave a half record in front of the next append.
+            let _ = self.seg_file.set_len(active.bytes);
+            let _ = self.idx_file.set_len((offset - active.base) * INDEX_ENTRY);
+            return Err(e.into());
+        }
+
+        active.end += 1;
+        active.bytes += record_len;
+        active.last_appended_at = appended_at;
+        Ok(offset)
+    }
+
+    /// Flushes everything appended so far to stable storage.
+    pub fn sync(&mut self) -> Result<()> {
+        self.seg_file.sync_data()?;
+        self.idx_file.sync_data()?;
+        Ok(())
+    }
+
+    /// The oldest retained offset.
+    pub fn first_offset(&self) -> u64 {
+        self.segments[0].base
+    }
+
+    /// The offset the next append will receive.
+    pub fn next_offset(&self) -> u64 {
+        self.segments.last().unwrap().end
+    }
+
+    /// Total size of all segment files.
+    pub fn total_bytes(&self) -> u64 {
+        self.segments.iter().map(|s| s.bytes).sum()
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Resolves a `Position` to a concrete offset in `first..=next`.
+    pub fn resolve(&self, position: Position) -> Result<u64> {
+        Ok(match position {
+            Position::Beginning => self.first_offset(),
+            Position::End => self.next_offset(),
+            Position::Offset(o) => o.clamp(self.first_offset(), self.next_offset()),
+            Position::Timestamp(ts) => self.offset_at(ts)?,
+        })
+    }
+
+    /// Reads every record from `start` up to (not including) `end`.
+    pub fn read_range(&self, start: u64, end: u64) -> Result<Vec<(u64, Envelope)>> {
+        self.replay(start, end)?.collect()
+    }
+
+    /// Streams the records from `start` up to (not including) `end`.
+    ///
+    /// The segment files are opened up front, so the replay survives
+    /// retention dropping them while it is being read.
+    pub fn replay(&self, start: u64, end: u64) -> Result<Replay> {
+        let start = start.max(self.first_offset());
+        let end = end.min(self.next_offset());
+        let mut parts = VecDeque::new();
+
+        for segment in &self.segments {
+            if segment.end <= start || segment.base >= end || segment.base == segment.end {
+                continue;
+            }
+            let from = start.max(segment.base);
+            let entry = self.index_entry(segment.base, from)?;
+
+            let mut file = File::open(seg_path(&self.dir, segment.base))?;
+            file.seek(SeekFrom::Start(entry.position))?;
+            parts.push_back((file, from..segment.end.min(end)));
+        }
+        Ok(Replay {
+            parts,
+            current: None,
+        })
+    }
+
+    /// Drops sealed segments that fall outside the retention policy.
+    pub fn enforce_retention(&mut self) -> Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let retention = self.config.retention.clone();
+
+        while self.segments.len() > 1 {
+            let oldest = &self.segments[0];
+            let too_big = retention
+                .max_bytes
+                .is_some_and(|max| self.total_bytes() > max);
+            let too_old = retention
+                .max_age
+                .is_some_and(|age| now - oldest.last_appended_at > age.as_millis() as i64);
+            if !too_big && !too_old {
+                break;
+            }
+
+            let base = oldest.base;
+            fs::remove_file(seg_path(&self.dir, base))?;
+            let _ = fs::remove_file(idx_path(&self.dir, base));
+            self.segments.remove(0);
+        }
+        Ok(())
+    }
+
+    fn roll(&mut self) -> Result<()> {
+        self.sync()?;
+        let base = self.next_offset();
+        let (seg_file, idx_file) = open_for_append(&self.dir, base)?;
+        self.seg_file = seg_file;
+        self.idx_file = idx_file;
+        self.segments.push(Segment {
+            base,
+            end: base,
+            bytes: 0,
+            last_appended_at: self.segments.last().unwrap().last_appended_at,
+        });
+        self.enforce_retention()
+    }
+
+    fn index_entry(&self, base: u64, offset: u64) -> Result<IndexEntry> {
+        let mut file = File::open(idx_path(&self.dir, base))?;
+        file.seek(SeekFrom::Start((offset - base) * INDEX_ENTRY))?;
+        let mut buf = [0u8; INDEX_ENTRY as usize];
+        file.read_exact(&mut buf)?;
+        let entry = IndexEntry::from_bytes(&buf);
+        if entry.offset != offset {
+            bail!("Journal index for segment {} is inconsistent", base);
+        }
+        Ok(entry)
+    }
+
+    /// First offset appended at or after `ts`.
+    fn offset_at(&self, ts: i64) -> Result<u64> {
+        let Some(segment) = self
+            .segments
+            .iter()
+            .find(|s| s.end > s.base && s.last_appended_at >= ts)
+        else {
+            return Ok(self.next_offset());
+        };
+
+        let (mut lo, mut hi) = (segment.base, segment.end - 1);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.index_entry(segment.base, mid)?.appended_at < ts {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        Ok(lo)
+    }
+}
+
+/// A lazy read over a range of the journal, from `Journal::replay`.
+/// Yields `(offset, envelope)` in order and stops after the first error.
+pub struct Replay {
+    parts: VecDeque<(File, Range<u64>)>,
+    current: Option<(BufReader<File>, Range<u64>)>,
+}
+
+impl Replay {
+    fn read_next(&mut self) -> Result<Option<(u64, Envelope)>> {
+        loop {
+            if let Some((reader, offsets)) = &mut self.current
+                && let Some(offset) = offsets.next()
+            {
+                let payload = read_record(reader)?
+                    .with_context(|| format!("Journal record {} is missing", offset))?;
+                return Ok(Some((offset, Envelope::decode(&payload)?)));
+            }
+            let Some((file, offsets)) = self.parts.pop_front() else {
+                return Ok(None);
+            };
+            self.current = Some((BufReader::new(file), offsets));
+        }
+    }
+}
+
+impl Iterator for Replay {
+    type Item = Result<(u64, Envelope)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.read_next();
+        if next.is_err() {
+            self.parts.clear();
+            self.current = None;
+        }
+        next.transpose()
+    }
+}
+
+fn seg_path(dir: &Path, base: u64) -> PathBuf {
+    dir.join(format!("{:020}.seg", base))
+}
+
+fn idx_path(dir: &Path, base: u64) -> PathBuf {
+    dir.join(format!("{:020}.idx", base))
+}
+
+fn open_for_append(dir: &Path, base: u64) -> Result<(File, File)> {
+    let open = |p: PathBuf| OpenOptions::new().create(true).append(true).open(p);
+    Ok((open(seg_path(dir, base))?, open(idx_path(dir, base))?))
+}
+
+/// Reads one record. `Ok(None)` means the segment ends here, cleanly or not.
+fn read_record(reader: &mut impl Read) -> Result<Option<Vec<u8>>> {
+    let mut header = [0u8; RECORD_HEADER as usize];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let len = u32::from_be_bytes(header[0..4].try_into().unwrap());
+    let crc = u32::from_be_bytes(header[4..8].try_into().unwrap());
+    if len > MAX_RECORD_LEN {
+        return Ok(None);
+    }
+
+    let mut payload = vec![0u8; len as usize];
+    match reader.read_exact(&mut payload) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    if crc32fast::hash(&payload) != crc {
+        return Ok(None);
+    }
+    Ok(Some(payload))
+}
+
+/// A sealed segment is trusted: its index says how many records it holds.
+fn load_sealed(dir: &Path, base: u64) -> Result<Segment> {
+    let bytes = fs::metadata(seg_path(dir, base))?.len();
+    let idx = fs::read(idx_path(dir, base))
+        .with_context(|| format!("Journal segment {} has no index", base))?;
+    let count = idx.len() as u64 / INDEX_ENTRY;
+    let last_appended_at = match count {
+        0 => 0,
+        n => {
+            let at = ((n - 1) * INDEX_ENTRY) as usize;
+            IndexEntry::from_bytes(&idx[at..at + INDEX_ENTRY as usize]).appended_at
+        }
+    };
+
+    Ok(Segment {
+        base,
+        end: base + count,
+        bytes,
+        last_appended_at,
+    })
+}
+
+/// Rescans the active segment, cuts off a torn tail and rebuilds its index.
+fn recover_active(dir: &Path, base: u64) -> Result<Segment> {
+    let path = seg_path(dir, base);
+    let old_index = fs::read(idx_path(dir, base)).unwrap_or_default();
+    let mut reader = BufReader::new(File::open(&path)?);
+
+    let mut entries = Vec::new();
+    let mut position = 0u64;
+    while let Some(payload) = read_record(&mut reader)? {
+        let offset = base + entries.len() as u64;
+        // Keep the original append time where the old index still has it.
+        let slot = entries.len() * INDEX_ENTRY as usize;
+        let appended_at = old_index
+            .get(slot..slot + INDEX_ENTRY as usize)
+            .map(IndexEntry::from_bytes)
+            .filter(|e| e.offset == offset && e.position == position)
+            .map(|e| e.appended_at)
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+        entries.push(IndexEntry {
+            offset,
+            position,
+            appended_at,
+        });
+        position += RECORD_HEADER + payload.len() as u64;
+    }
+
+    let file_len = fs::metadata(&path)?.len();
+    if file_len > position {
+        log::warn!(
+            "[BANDY] Journal segment {} had a torn tail; dropping {} bytes",
+            base,
+            file_len - position
+        );
+        let file = OpenOptions::new().write(true).open(&path)?;
+        file.set_len(position)?;
+        file.sync_all()?;
+    }
+
+    let mut index = Vec::with_capacity(entries.len() * INDEX_ENTRY as usize);
+    let mut last_appended_at = 0;
+    for entry in &entries {
+        // Rebuilt times must not go backwards either.
+        last_appended_at = entry.appended_at.max(last_appended_at);
+        index.extend_from_slice(
+            &IndexEntry {
+                appended_at: last_appended_at,
+                ..*entry
+            }
+            .to_bytes(),
+        );
+    }
+    let tmp = dir.join(format!("{:020}.idx.tmp", base));
+    fs::write(&tmp, &index)?;
+    fs::rename(&tmp, idx_path(dir, base))?;
+
+    Ok(Segment {
+        base,
+        end: base + entries.len() as u64,
+        bytes: position,
+        last_appended_at,
+    })
+}
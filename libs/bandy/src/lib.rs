@@ -14,13 +14,25 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+pub mod envelope;
+pub mod graph;
+pub mod journal;
+pub mod rpc;
 pub mod synapse;
 pub mod telemetry;
-
+pub mod topic;
+#[cfg(unix)]
+pub mod transport;
+
+pub use envelope::{Envelope, Payload};
+pub use graph::{GraphDelta, SpatialGraph};
+pub use journal::{Journal, JournalConfig, Position};
+pub use rpc::{Rpc, RpcError, Service};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
-pub use synapse::Synapse;
+pub use synapse::{Impulse, Subscription, Synapse};
+pub use topic::TopicFilter;
 
 /// WeightedSkeleton
 ///
@@ -109,6 +121,38 @@ pub enum SMessage {
 
     // --- MATRIX (The Spatial Cortex) ---
     Matrix(MatrixEvent),
+
+    // --- RPC (see `rpc`) ---
+    Rpc(rpc::RpcMessage),
+}
+
+impl SMessage {
+    /// The default topic a message travels on when fired without one.
+    pub fn topic(&self) -> &'static str {
+        match self {
+            SMessage::Ping => "system/ping",
+            SMessage::Kill(_) => "system/kill",
+            SMessage::Log { .. } => "system/log",
+            SMessage::EuclaseResize(..) => "euclase/resize",
+            SMessage::VugPulse => "euclase/vug/pulse",
+            SMessage::AudioChunk { .. } => "resonance/audio/chunk",
+            SMessage::Spectrum { .. } => "resonance/spectrum",
+            SMessage::UserPrompt(_) => "vein/prompt",
+            SMessage::AiToken(_) => "vein/token",
+            SMessage::AnalyzeContext { .. } => "vein/analyze",
+            SMessage::GetDiff { .. } => "vaire/diff/request",
+            SMessage::DiffPayload { .. } => "vaire/diff/payload",
+            SMessage::ContextTelemetry { .. } => "vein/telemetry",
+            SMessage::FileEvent { .. } => "unafs/file",
+            SMessage::NoOp => "midden/noop",
+            SMessage::TerminalOutput(_) => "midden/stdout",
+            SMessage::TerminalError(_) => "midden/stderr",
+            SMessage::FileSystemEvent(_) => "midden/fs",
+            SMessage::Principia(_) => "principia/command",
+            SMessage::Matrix(_) => "matrix/event",
+            SMessage::Rpc(_) => "rpc/message",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -124,6 +168,8 @@ pub enum MatrixEvent {
         nodes: Vec<SpatialNode>,
         edges: Vec<SpatialEdge>,
     },
+    /// Only what changed since revision `base` of the map (see `graph`)
+    TopologyDiff { base: u64, deltas: Vec<GraphDelta> },
     /// Vein asks Matrix to focus on a specific sector (e.g., "euclase")
     FocusSector(String),
     /// Matrix returns the raw context of that sector
@@ -132,21 +178,30 @@ pub enum MatrixEvent {
     NodeSelected(PathBuf),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SpatialNode {
     pub id: String,
     pub kind: String, // "crate", "struct", "fn"
     pub path: PathBuf,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SpatialEdge {
     pub from: String,
     pub to: String,
     pub relation: String, // "imports", "implements", "calls"
+    /// Traversal cost for path queries. Older peers sent none.
+    #[serde(default = "default_edge_weight")]
+    pub weight: f32,
+}
+
+fn default_edge_weight() -> f32 {
+    1.0
 }
 
 /// The trait that defines a "Nerve Ending" in the system.
+/// `topic` is a concrete hierarchical topic (see `topic`); wildcards are
+/// only valid in subscription filters.
 pub trait BandyMember {
     fn publish(&self, topic: &str, msg: SMessage) -> anyhow::Result<()>;
 }
@@ -0,0 +1,431 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Request and response over the Synapse.
+//!
+//! A service is anything implementing `Service`, registered under a name on
+//! an `Rpc` hub. Calls travel as `SMessage::Rpc` on `rpc/<service>/call`,
+//! answers come back on `rpc/<service>/reply`, matched by correlation id.
+//!
+//! Dropping a pending call (or letting it time out) sends a `Cancel`, and the
+//! server drops the handler's future. Handlers that must clean up should do
+//! so in `Drop`.
+//!
+//! A hub only knows the services registered on it. Clones share one
+//! registry, so keep a single hub per synapse. Callers do not consult it:
+//! the service may live on another hub, across a transport. A method the
+//! service lacks comes back from the server as `NoHandler`; a service that
+//! nobody serves surfaces as `Timeout`.
+
+use crate::synapse::{Impulse, Synapse};
+use crate::{SMessage, topic};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::{AbortHandle, JoinHandle, JoinSet};
+
+pub type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
+
+/// Why a call did not produce a result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RpcError {
+    /// The service has no such method. Sent by the serving side.
+    NoHandler { service: String, method: String },
+    /// No answer arrived in time. The call was cancelled.
+    Timeout(Duration),
+    /// The handler panicked; carries the panic message.
+    HandlerPanic(String),
+    /// The call was cancelled before the handler finished.
+    Cancelled,
+    /// Arguments or result did not (de)serialize.
+    Codec(String),
+    /// The handler ran and reported a failure.
+    Failed(String),
+    /// The synapse went away mid-call.
+    Closed,
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::NoHandler { service, method } => {
+                write!(f, "No handler for {}.{}", service, method)
+            }
+            RpcError::Timeout(after) => write!(f, "No reply within {:?}", after),
+            RpcError::HandlerPanic(msg) => write!(f, "Handler panicked: {}", msg),
+            RpcError::Cancelled => write!(f, "Call was cancelled"),
+            RpcError::Codec(msg) => write!(f, "Codec error: {}", msg),
+            RpcError::Failed(msg) => write!(f, "Call failed: {}", msg),
+            RpcError::Closed => write!(f, "Synapse severed"),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// What crosses the synapse. Cancels and answers carry the call's id as
+/// their `correlation_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RpcMessage {
+    Call {
+        service: String,
+        method: String,
+        #[serde(with = "serde_bytes")]
+        args: Vec<u8>,
+        /// How long the caller will wait; the server gives up after that too.
+        timeout_ms: u64,
+    },
+    Cancel,
+    Reply(#[serde(with = "serde_bytes")] Vec<u8>),
+    Error(RpcError),
+}
+
+/// Encodes a value for the wire: arguments on the caller side, results on
+/// the handler side.
+pub fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, RpcError> {
+    let mut buf = Vec::new();
+    ciborium::into_writer(value, &mut buf).map_err(|e| RpcError::Codec(e.to_string()))?;
+    Ok(buf)
+}
+
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, RpcError> {
+    ciborium::from_reader(bytes).map_err(|e| RpcError::Codec(e.to_string()))
+}
+
+/// One incoming call, as a handler sees it.
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub id: u64,
+    pub service: String,
+    pub method: String,
+    pub args: Vec<u8>,
+}
+
+impl Call {
+    /// Decodes the arguments.
+    pub fn args<T: DeserializeOwned>(&self) -> Result<T, RpcError> {
+        decode(&self.args)
+    }
+
+    /// The error to return for a method this service does not have.
+    pub fn no_handler(&self) -> RpcError {
+        RpcError::NoHandler {
+            service: self.service.clone(),
+            method: self.method.clone(),
+        }
+    }
+}
+
+/// A named set of methods, implemented by hand:
+///
+/// ```ignore
+/// impl Service for Math {
+///     fn call(self: Arc<Self>, call: Call) -> BoxFuture<Result<Vec<u8>, RpcError>> {
+///         Box::pin(async move {
+///             match call.method.as_str() {
+///                 "add" => {
+///                     let (a, b): (i64, i64) = call.args()?;
+///                     rpc::encode(&(a + b))
+///                 }
+///                 _ => Err(call.no_handler()),
+///             }
+///         })
+///     }
+/// }
+/// ```
+pub trait Service: Send + Sync + 'static {
+    fn call(self: Arc<Self>, call: Call) -> BoxFuture<Result<Vec<u8>, RpcError>>;
+}
+
+fn call_topic(service: &str) -> String {
+    format!("rpc/{}/call", service)
+}
+
+fn reply_topic(service: &str) -> String {
+    format!("rpc/{}/reply", service)
+}
+
+fn cancel_topic(service: &str) -> String {
+    format!("rpc/{}/cancel", service)
+}
+
+/// Keeps a service registered. Dropping it unregisters the service and
+/// abandons its in-flight calls: their handlers are dropped and no reply
+/// is sent.
+pub struct Registration {
+    rpc: Rpc,
+    name: String,
+    task: JoinHandle<()>,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.task.abort();
+        self.rpc.services.write().unwrap().remove(&self.name);
+    }
+}
+
+/// Aborts a handler when the task waiting on it goes away.
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Sends a `Cancel` unless disarmed; rides inside a pending call.
+struct CancelOnDrop<'a> {
+    synapse: &'a Synapse,
+    service: &'a str,
+    id: u64,
+    armed: bool,
+}
+
+impl Drop for CancelOnDrop<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            let mut cancel = Impulse::new(
+                &cancel_topic(self.service),
+                SMessage::Rpc(RpcMessage::Cancel),
+            );
+            cancel.correlation_id = Some(self.id);
+            self.synapse.inject(cancel);
+        }
+    }
+}
+
+/// The RPC hub for one synapse.
+#[derive(Clone)]
+pub struct Rpc {
+    synapse: Synapse,
+    services: Arc<RwLock<HashMap<String, Arc<dyn Service>>>>,
+}
+
+impl Rpc {
+    pub fn new(synapse: Synapse) -> Self {
+        Self {
+            synapse,
+            services: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn synapse(&self) -> &Synapse {
+        &self.synapse
+    }
+
+    /// Serves `service` under `name` until the returned guard is dropped.
+    /// `name` must be a single topic level. Needs a Tokio runtime.
+    pub fn register(&self, name: &str, service: Arc<dyn Service>) -> anyhow::Result<Registration> {
+        topic::validate_topic(name)?;
+        if name.contains(topic::SEPARATOR) {
+            anyhow::bail!("Service name '{}' must be a single topic level", name);
+        }
+
+        let mut services = self.services.write().unwrap();
+        if services.contains_key(name) {
+            anyhow::bail!("Service '{}' is already registered", name);
+        }
+        // Subscribe before anyone can see the name, so no call slips past.
+        let inbox = self.synapse.subscribe(&format!("rpc/{}/+", name))?;
+        services.insert(name.to_string(), service.clone());
+
+        let task = tokio::spawn(serve(
+            self.synapse.clone(),
+            name.to_string(),
+            service,
+            inbox,
+        ));
+        Ok(Registration {
+            rpc: self.clone(),
+            name: name.to_string(),
+            task,
+        })
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.services.read().unwrap().contains_key(name)
+    }
+
+    /// Calls `service.method(args)` and decodes the result.
+    pub async fn call<A: Serialize, R: DeserializeOwned>(
+        &self,
+        service: &str,
+        method: &str,
+        args: &A,
+        timeout: Duration,
+    ) -> Result<R, RpcError> {
+        let bytes = self
+            .call_raw(service, method, encode(args)?, timeout)
+            .await?;
+        decode(&bytes)
+    }
+
+    /// `call` on pre-encoded arguments, returning the encoded result.
+    /// Dropping the future cancels the call.
+    pub async fn call_raw(
+        &self,
+        service: &str,
+        method: &str,
+        args: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, RpcError> {
+        // Listen before speaking, or a fast handler beats us to it.
+        let mut replies = self
+            .synapse
+            .subscribe(&reply_topic(service))
+            .map_err(|_| RpcError::Closed)?;
+        let call = Impulse::new(
+            &call_topic(service),
+            SMessage::Rpc(RpcMessage::Call {
+                service: service.to_string(),
+                method: method.to_string(),
+                args,
+                timeout_ms: timeout.as_millis() as u64,
+            }),
+        );
+        let mut guard = CancelOnDrop {
+            synapse: &self.synapse,
+            service,
+            id: call.id,
+            armed: true,
+        };
+        self.synapse.inject(call);
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let impulse = match tokio::time::timeout_at(deadline, replies.recv()).await {
+                Err(_) => return Err(RpcError::Timeout(timeout)),
+                Ok(Err(RecvError::Lagged(n))) => {
+                    log::warn!("[BANDY] RPC caller lagged by {} impulses", n);
+                    continue;
+                }
+                Ok(Err(RecvError::Closed)) => return Err(RpcError::Closed),
+                Ok(Ok(impulse)) => impulse,
+            };
+            if impulse.correlation_id != Some(guard.id) {
+                continue;
+            }
+            match impulse.msg {
+                SMessage::Rpc(RpcMessage::Reply(bytes)) => {
+                    guard.armed = false;
+                    return Ok(bytes);
+                }
+                SMessage::Rpc(RpcMessage::Error(e)) => {
+                    guard.armed = false;
+                    return Err(e);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The serving loop of one registered service.
+async fn serve(
+    synapse: Synapse,
+    name: String,
+    service: Arc<dyn Service>,
+    mut inbox: crate::Subscription,
+) {
+    let in_flight: Arc<Mutex<HashMap<u64, AbortHandle>>> = Arc::default();
+    // Owned here so that aborting this loop aborts every call it started.
+    let mut calls = JoinSet::new();
+    loop {
+        while calls.try_join_next().is_some() {}
+        let impulse = match inbox.recv().await {
+            Ok(impulse) => impulse,
+            Err(RecvError::Lagged(n)) => {
+                log::warn!("[BANDY] RPC service '{}' lagged by {} impulses", name, n);
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        match impulse.msg {
+            SMessage::Rpc(RpcMessage::Call {
+                method,
+                args,
+                timeout_ms,
+                ..
+            }) => {
+                let call = Call {
+                    id: impulse.id,
+                    service: name.clone(),
+                    method,
+                    args,
+                };
+                let handler = tokio::spawn(service.clone().call(call));
+                in_flight
+                    .lock()
+                    .unwrap()
+                    .insert(impulse.id, handler.abort_handle());
+
+                let synapse = synapse.clone();
+                let name = name.clone();
+                let in_flight = in_flight.clone();
+                let timeout = Duration::from_millis(timeout_ms);
+                calls.spawn(async move {
+                    let abort = AbortOnDrop(handler.abort_handle());
+                    let result = match tokio::time::timeout(timeout, handler).await {
+                        Ok(Ok(result)) => result,
+                        Ok(Err(e)) if e.is_panic() => {
+                            Err(RpcError::HandlerPanic(panic_message(e.into_panic())))
+                        }
+                        Ok(Err(_)) => Err(RpcError::Cancelled),
+                        Err(_) => {
+                            // The caller has stopped waiting.
+                            abort.0.abort();
+                            Err(RpcError::Timeout(timeout))
+                        }
+                    };
+                    in_flight.lock().unwrap().remove(&impulse.id);
+
+                    let msg = match result {
+                        Ok(bytes) => RpcMessage::Reply(bytes),
+                        Err(e) => RpcMessage::Error(e),
+                    };
+                    let mut reply = Impulse::new(&reply_topic(&name), SMessage::Rpc(msg));
+                    reply.correlation_id = Some(impulse.id);
+                    synapse.inject(reply);
+                });
+            }
+            SMessage::Rpc(RpcMessage::Cancel) => {
+                let id = impulse.correlation_id.unwrap_or_default();
+                if let Some(handler) = in_flight.lock().unwrap().remove(&id) {
+                    handler.abort();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -14,38 +14,355 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::SMessage;
+use crate::envelope::{self, Envelope, next_msg_id};
+use crate::journal::{Journal, Position, Replay};
+use crate::topic::{self, TopicFilter};
+use crate::{BandyMember, SMessage};
+use anyhow::Context;
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+/// 1024 action potentials in flight. If we hit this, the system is seizing.
+pub const SYNAPSE_CAPACITY: usize = 1024;
+
+/// Envelopes waiting for the journal writer. When it falls this far behind,
+/// new impulses still go out live but are not recorded.
+pub const JOURNAL_QUEUE_CAPACITY: usize = 4096;
+
+/// How often the journal writer enforces retention on its own.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Replayed impulses read from the journal per blocking task.
+const REPLAY_BATCH: usize = 256;
+
+/// A message in flight, tagged with the topic it was published on.
+#[derive(Debug, Clone)]
+pub struct Impulse {
+    pub topic: Arc<str>,
+    pub msg: SMessage,
+    /// Unique message id (see `envelope::next_msg_id`).
+    pub id: u64,
+    /// On a reply, the `id` of the impulse being answered.
+    pub correlation_id: Option<u64>,
+    /// Position in the synapse journal, if this impulse was recorded.
+    pub offset: Option<u64>,
+}
+
+impl Impulse {
+    pub fn new(topic: &str, msg: SMessage) -> Self {
+        Self {
+            topic: Arc::from(topic),
+            msg,
+            id: next_msg_id(),
+            correlation_id: None,
+            offset: None,
+        }
+    }
+}
+
+/// The journal a synapse records into, and which topics it keeps.
+///
+/// Publishers never touch the disk: they number each recorded impulse and
+/// queue it for a writer thread, which owns the appends. Dropping the last
+/// synapse drains the queue and stops the writer.
+struct Recorder {
+    filter: TopicFilter,
+    source: String,
+    journal: Arc<Mutex<Journal>>,
+    queue: Mutex<Queue>,
+    writer: Option<JoinHandle<()>>,
+}
+
+/// Held while an impulse is numbered and sent, so journal order is channel
+/// order and `subscribe_from` can hand over from replay to live without a gap.
+struct Queue {
+    /// The offset the next queued envelope will be appended at.
+    next_offset: u64,
+    /// `None` once the recorder is shutting down.
+    commands: Option<SyncSender<Command>>,
+}
+
+enum Command {
+    Append(u64, Envelope),
+    /// Answered once everything queued before it has been appended.
+    Flush {
+        sync: bool,
+        done: mpsc::Sender<anyhow::Result<()>>,
+    },
+}
+
+impl Recorder {
+    fn start(journal: Journal, pattern: &str) -> anyhow::Result<Self> {
+        let filter = TopicFilter::new(pattern)?;
+        let next_offset = journal.next_offset();
+        let journal = Arc::new(Mutex::new(journal));
+        let (commands, rx) = mpsc::sync_channel(JOURNAL_QUEUE_CAPACITY);
+        let writer = std::thread::Builder::new()
+            .name("bandy-journal".into())
+            .spawn({
+                let journal = journal.clone();
+                move || run_writer(&journal, rx)
+            })?;
+
+        Ok(Self {
+            filter,
+            source: envelope::process_name(),
+            journal,
+            queue: Mutex::new(Queue {
+                next_offset,
+                commands: Some(commands),
+            }),
+            writer: Some(writer),
+        })
+    }
+
+    /// Opens the recorded range from `from` up to `handover`, once everything
+    /// numbered before `handover` has reached the journal. Blocks.
+    fn open_replay(&self, from: Position, handover: u64) -> anyhow::Result<Replay> {
+        flush(self.queue.lock().unwrap().commands.clone(), false)?;
+        let journal = self.journal.lock().unwrap();
+        let start = journal.resolve(from)?.min(handover);
+        journal.replay(start, handover)
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let queue = self.queue.get_mut().unwrap_or_else(|e| e.into_inner());
+        queue.commands = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Appends queued envelopes until every sender is gone, and enforces
+/// retention on a timer so a quiet bus still sheds old segments.
+fn run_writer(journal: &Mutex<Journal>, commands: mpsc::Receiver<Command>) {
+    let mut last_retention = Instant::now();
+    loop {
+        match commands.recv_timeout(RETENTION_INTERVAL) {
+            Ok(Command::Append(expected, envelope)) => {
+                let appended = journal.lock().unwrap().append(&envelope);
+                match appended {
+                    Ok(offset) if offset == expected => {}
+                    Ok(offset) => {
+                        log::error!(
+                            "[BANDY] Journal offset {} does not match {}; recording stopped",
+                            offset,
+                            expected
+                        );
+                        return;
+                    }
+                    Err(e) => {
+                        // Later offsets are already handed out; appending
+                        // past a hole would misnumber them.
+                        log::error!("[BANDY] Journal append failed; recording stopped: {}", e);
+                        return;
+                    }
+                }
+            }
+            Ok(Command::Flush { sync, done }) => {
+                let result = match sync {
+                    true => journal.lock().unwrap().sync(),
+                    false => Ok(()),
+                };
+                let _ = done.send(result);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        if last_retention.elapsed() >= RETENTION_INTERVAL {
+            last_retention = Instant::now();
+            if let Err(e) = journal.lock().unwrap().enforce_retention() {
+                log::error!("[BANDY] Journal retention failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Waits until the writer has appended everything queued before this call.
+fn flush(commands: Option<SyncSender<Command>>, sync: bool) -> anyhow::Result<()> {
+    let (done, finished) = mpsc::channel();
+    commands
+        .and_then(|c| c.send(Command::Flush { sync, done }).ok())
+        .context("The journal writer has stopped")?;
+    finished.recv().context("The journal writer has stopped")?
+}
 
 /// The connective tissue of the nervous system.
 /// Uses a broadcast channel so multiple lobes (UI, Subconscious, AI)
 /// can react to the same stimulus simultaneously.
+///
+/// Routing happens on the receiver side: every impulse travels down the
+/// single channel and each `Subscription` discards topics its filter rejects.
+///
+/// With a journal attached, impulses on recorded topics are also appended to
+/// disk, and `subscribe_from` can replay them to late subscribers.
 #[derive(Clone)]
 pub struct Synapse {
-    tx: broadcast::Sender<SMessage>,
+    tx: broadcast::Sender<Impulse>,
+    recorder: Option<Arc<Recorder>>,
 }
 
 impl Synapse {
     pub fn new() -> Self {
-        // 1024 action potentials in flight. If we hit this, the system is seizing.
-        let (tx, _) = broadcast::channel(1024);
-        Self { tx }
+        let (tx, _) = broadcast::channel(SYNAPSE_CAPACITY);
+        Self { tx, recorder: None }
+    }
+
+    /// A synapse that records every impulse matching `pattern` into `journal`.
+    pub fn with_journal(journal: Journal, pattern: &str) -> anyhow::Result<Self> {
+        let (tx, _) = broadcast::channel(SYNAPSE_CAPACITY);
+        Ok(Self {
+            tx,
+            recorder: Some(Arc::new(Recorder::start(journal, pattern)?)),
+        })
     }
 
-    /// Fires a stimulus across the nervous system.
+    /// Fires a stimulus across the nervous system on its default topic
+    /// (see `SMessage::topic`).
     pub fn fire(&self, msg: SMessage) {
         // We ignore SendError. If a tree falls in the forest...
-        let _ = self.tx.send(msg);
+        self.inject(Impulse::new(msg.topic(), msg));
+    }
+
+    /// Publishes a stimulus on an explicit topic.
+    /// Returns the number of nerve endings that were listening (before filtering).
+    pub fn publish(&self, topic: &str, msg: SMessage) -> anyhow::Result<usize> {
+        topic::validate_topic(topic)?;
+        Ok(self.inject(Impulse::new(topic, msg)))
+    }
+
+    /// Sends a fully formed impulse as-is, keeping its ids.
+    /// Used by transports relaying traffic from other processes.
+    pub fn inject(&self, mut impulse: Impulse) -> usize {
+        let Some(recorder) = &self.recorder else {
+            // Nobody listening is not a fault.
+            return self.tx.send(impulse).unwrap_or(0);
+        };
+
+        // Only queued here; the writer thread does the disk work. Numbering
+        // and sending share one lock so journal order is channel order.
+        let mut queue = recorder.queue.lock().unwrap();
+        if recorder.filter.matches(&impulse.topic)
+            && let Some(commands) = &queue.commands
+        {
+            let offset = queue.next_offset;
+            let envelope = Envelope::from_impulse(&recorder.source, &impulse);
+            match commands.try_send(Command::Append(offset, envelope)) {
+                Ok(()) => {
+                    impulse.offset = Some(offset);
+                    queue.next_offset += 1;
+                }
+                Err(TrySendError::Full(_)) => {
+                    log::warn!(
+                        "[BANDY] Journal writer is behind; not recording {}",
+                        impulse.topic
+                    )
+                }
+                Err(TrySendError::Disconnected(_)) => queue.commands = None,
+            }
+        }
+        self.tx.send(impulse).unwrap_or(0)
+    }
+
+    /// Publishes `msg` and waits for the first impulse, on any topic, whose
+    /// `correlation_id` names it.
+    pub async fn request(
+        &self,
+        topic: &str,
+        msg: SMessage,
+        timeout: Duration,
+    ) -> anyhow::Result<Impulse> {
+        topic::validate_topic(topic)?;
+        // Listen before speaking, or a fast responder beats us to it.
+        let replies = self.rx();
+        let request = Impulse::new(topic, msg);
+        let id = request.id;
+        self.inject(request);
+        replies.reply_to(id, timeout).await
+    }
+
+    /// Answers `request` on `topic`.
+    pub fn reply(&self, request: &Impulse, topic: &str, msg: SMessage) -> anyhow::Result<usize> {
+        topic::validate_topic(topic)?;
+        let mut impulse = Impulse::new(topic, msg);
+        impulse.correlation_id = Some(request.id);
+        Ok(self.inject(impulse))
+    }
+
+    /// Sprout a nerve ending that only feels topics selected by `pattern`.
+    pub fn subscribe(&self, pattern: &str) -> anyhow::Result<Subscription> {
+        Ok(Subscription {
+            filter: TopicFilter::new(pattern)?,
+            rx: self.tx.subscribe(),
+            replay: None,
+        })
+    }
+
+    /// Like `subscribe`, but first replays recorded impulses from `from`,
+    /// then continues seamlessly with live traffic.
+    ///
+    /// Nothing is read here. The journal is opened on the first `recv`, on a
+    /// blocking thread; if that fails, the error is logged and the
+    /// subscription goes straight to live traffic.
+    pub fn subscribe_from(&self, pattern: &str, from: Position) -> anyhow::Result<Subscription> {
+        let Some(recorder) = &self.recorder else {
+            anyhow::bail!("This synapse has no journal to replay from");
+        };
+        let filter = TopicFilter::new(pattern)?;
+
+        // Everything numbered before this point is replayed, the rest is live.
+        let queue = recorder.queue.lock().unwrap();
+        let rx = self.tx.subscribe();
+        let handover = queue.next_offset;
+        drop(queue);
+
+        let backlog = Backlog {
+            filter: filter.clone(),
+            source: Some((recorder.clone(), from, handover)),
+            replay: None,
+            ready: VecDeque::new(),
+        };
+        Ok(Subscription {
+            filter,
+            rx,
+            replay: Some(Replaying {
+                backlog: Arc::new(Mutex::new(backlog)),
+                reading: None,
+            }),
+        })
+    }
+
+    /// Forces recorded impulses to stable storage. A no-op without a journal.
+    pub fn sync_journal(&self) -> anyhow::Result<()> {
+        match &self.recorder {
+            Some(recorder) => flush(recorder.queue.lock().unwrap().commands.clone(), true),
+            None => Ok(()),
+        }
     }
 
     /// Direct access to the transmitter.
-    pub fn tx(&self) -> broadcast::Sender<SMessage> {
+    pub fn tx(&self) -> broadcast::Sender<Impulse> {
         self.tx.clone()
     }
 
-    /// Sprout a new nerve ending to listen to the system.
-    pub fn rx(&self) -> broadcast::Receiver<SMessage> {
-        self.tx.subscribe()
+    /// Sprout a new nerve ending to listen to the whole system.
+    pub fn rx(&self) -> Subscription {
+        self.subscribe(topic::MULTI_LEVEL)
+            .expect("The catch-all filter is always valid")
+    }
+
+    /// Number of live subscriptions. Dropping a `Subscription` unsubscribes it.
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
     }
 }
 
@@ -54,3 +371,168 @@ impl Default for Synapse {
         Self::new()
     }
 }
+
+impl BandyMember for Synapse {
+    fn publish(&self, topic: &str, msg: SMessage) -> anyhow::Result<()> {
+        Synapse::publish(self, topic, msg).map(|_| ())
+    }
+}
+
+/// A filtered nerve ending.
+///
+/// Lag is measured against the shared channel, so a slow subscriber can be
+/// overrun by traffic on topics it does not even match. When that happens
+/// `recv` reports `RecvError::Lagged(n)` with the total number of impulses
+/// that were dropped, and the next call resumes at the oldest retained one.
+pub struct Subscription {
+    filter: TopicFilter,
+    rx: broadcast::Receiver<Impulse>,
+    /// Recorded history, read lazily and drained before live traffic.
+    replay: Option<Replaying>,
+}
+
+/// A replay in progress. The backlog is shared with the blocking task that
+/// fills it, so a `recv` cancelled mid-read loses nothing.
+struct Replaying {
+    backlog: Arc<Mutex<Backlog>>,
+    reading: Option<tokio::task::JoinHandle<()>>,
+}
+
+struct Backlog {
+    filter: TopicFilter,
+    /// Where to open the replay from, until it has been opened.
+    source: Option<(Arc<Recorder>, Position, u64)>,
+    replay: Option<Replay>,
+    /// Matching impulses read but not yet handed out.
+    ready: VecDeque<Impulse>,
+}
+
+impl Backlog {
+    /// Reads up to `REPLAY_BATCH` matching impulses into `ready`, opening the
+    /// replay first if need be. Blocks on the journal.
+    fn fill(&mut self) {
+        if let Some((recorder, from, handover)) = self.source.take() {
+            match recorder.open_replay(from, handover) {
+                Ok(replay) => self.replay = Some(replay),
+                Err(e) => log::error!("[BANDY] Journal replay failed; going live: {}", e),
+            }
+        }
+        let Some(replay) = self.replay.as_mut() else {
+            return;
+        };
+        while self.ready.len() < REPLAY_BATCH {
+            match replay.next() {
+                Some(Ok((offset, env))) if self.filter.matches(&env.topic) => {
+                    if let Some(mut impulse) = env.into_impulse() {
+                        impulse.offset = Some(offset);
+                        self.ready.push_back(impulse);
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    log::error!("[BANDY] Journal replay failed; going live: {}", e);
+                    self.replay = None;
+                    break;
+                }
+                None => {
+                    self.replay = None;
+                    break;
+                }
+            }
+        }
+    }
+
+    fn is_drained(&self) -> bool {
+        self.source.is_none() && self.replay.is_none() && self.ready.is_empty()
+    }
+}
+
+impl Subscription {
+    /// Waits for the next impulse whose topic matches this subscription.
+    pub async fn recv(&mut self) -> Result<Impulse, RecvError> {
+        while let Some(replaying) = self.replay.as_mut() {
+            if let Some(impulse) = replaying.backlog.lock().unwrap().ready.pop_front() {
+                return Ok(impulse);
+            }
+            if replaying.backlog.lock().unwrap().is_drained() {
+                self.replay = None;
+                break;
+            }
+            let reading = replaying.reading.get_or_insert_with(|| {
+                let backlog = replaying.backlog.clone();
+                tokio::task::spawn_blocking(move || backlog.lock().unwrap().fill())
+            });
+            let read = reading.await;
+            replaying.reading = None;
+            if let Err(e) = read {
+                log::error!("[BANDY] Journal replay failed; going live: {}", e);
+                self.replay = None;
+            }
+        }
+        loop {
+            let impulse = self.rx.recv().await?;
+            if self.filter.matches(&impulse.topic) {
+                return Ok(impulse);
+            }
+        }
+    }
+
+    /// Non-blocking variant of `recv`. While replaying, this reads the
+    /// journal on the calling thread.
+    pub fn try_recv(&mut self) -> Result<Impulse, TryRecvError> {
+        if let Some(impulse) = self.next_replayed()? {
+            return Ok(impulse);
+        }
+        loop {
+            let impulse = self.rx.try_recv()?;
+            if self.filter.matches(&impulse.topic) {
+                return Ok(impulse);
+            }
+        }
+    }
+
+    /// The next matching impulse from the journal, until the replay runs out.
+    /// `Empty` while a `recv` cancelled mid-read still has a read in flight.
+    fn next_replayed(&mut self) -> Result<Option<Impulse>, TryRecvError> {
+        let Some(replaying) = self.replay.as_mut() else {
+            return Ok(None);
+        };
+        if let Some(reading) = &replaying.reading {
+            if !reading.is_finished() {
+                return Err(TryRecvError::Empty);
+            }
+            replaying.reading = None;
+        }
+        let mut backlog = replaying.backlog.lock().unwrap();
+        if backlog.ready.is_empty() {
+            backlog.fill();
+        }
+        if let Some(impulse) = backlog.ready.pop_front() {
+            return Ok(Some(impulse));
+        }
+        drop(backlog);
+        self.replay = None;
+        Ok(None)
+    }
+
+    /// Waits for the reply to the impulse with id `request_id`, discarding
+    /// everything else. Lag is tolerated; the reply may still be ahead of us.
+    pub async fn reply_to(mut self, request_id: u64, timeout: Duration) -> anyhow::Result<Impulse> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            match tokio::time::timeout_at(deadline, self.recv()).await {
+                Err(_) => anyhow::bail!("No reply to {:#x} within {:?}", request_id, timeout),
+                Ok(Ok(impulse)) if impulse.correlation_id == Some(request_id) => {
+                    return Ok(impulse);
+                }
+                Ok(Ok(_)) | Ok(Err(RecvError::Lagged(_))) => {}
+                Ok(Err(RecvError::Closed)) => anyhow::bail!("Synapse severed awaiting reply"),
+            }
+        }
+    }
+
+    /// The filter this subscription was created with.
+    pub fn filter(&self) -> &TopicFilter {
+        &self.filter
+    }
+}
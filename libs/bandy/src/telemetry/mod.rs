@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Telemetry.
+//!
+//! `UnaLogger` routes every `log` record to a per-target file in the log
+//! directory (`vein::cortex` lands in `vein.log`), as plain text unless
+//! `TelemetryConfig` opts into JSON lines, and echoes a plain-text copy to
+//! stdout. Files roll by size and age and the rolled copies are gzipped off
+//! the logging path; see `rolling`. `query` reads both formats back, and
+//! backs the `una-logs` tool.
+//!
+//! Structured fields ride on the `log` key-value syntax:
+//! `log::info!(files = n, root = path; "Indexed")`.
+
+pub mod query;
+pub mod rolling;
+
+pub use query::{LogEntry, LogQuery, LogTail};
+pub use rolling::{RollingFile, Rotation};
+
+use log::kv::{self, Key, VisitSource};
+use log::{Level, LevelFilter, Metadata, Record};
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// How records are written to the log files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// `[time] [LEVEL] message`.
+    #[default]
+    Plain,
+    /// One `LogEntry` per line.
+    Json,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TelemetryConfig {
+    pub format: LogFormat,
+    pub rotation: Rotation,
+}
+
+thread_local! {
+    static SPANS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks the records logged on this thread until it is dropped.
+/// Spans nest; see `span`.
+pub struct SpanGuard {
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        SPANS.with(|spans| spans.borrow_mut().pop());
+    }
+}
+
+/// Enters a named span on the current thread. Every record logged on this
+/// thread while the guard lives carries it. Thread-bound, so do not hold
+/// one across an `.await`.
+pub fn span(name: &str) -> SpanGuard {
+    SPANS.with(|spans| spans.borrow_mut().push(name.to_string()));
+    SpanGuard {
+        _not_send: std::marker::PhantomData,
+    }
+}
+
+fn current_span() -> Option<String> {
+    SPANS.with(|spans| {
+        let spans = spans.borrow();
+        (!spans.is_empty()).then(|| spans.join("/"))
+    })
+}
+
+struct FieldCollector<'a>(&'a mut Map<String, Value>);
+
+impl<'kvs> VisitSource<'kvs> for FieldCollector<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(b) = value.to_bool() {
+            Value::from(b)
+        } else if let Some(i) = value.to_i64() {
+            Value::from(i)
+        } else if let Some(u) = value.to_u64() {
+            Value::from(u)
+        } else if let Some(f) = value.to_f64() {
+            Value::from(f)
+        } else {
+            Value::from(value.to_string())
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+impl LogEntry {
+    /// Captures a `log` record, with the fields and span in effect.
+    pub fn from_record(record: &Record) -> Self {
+        let mut fields = Map::new();
+        let _ = record.key_values().visit(&mut FieldCollector(&mut fields));
+        Self {
+            timestamp: chrono::Local::now().fixed_offset(),
+            level: record.level(),
+            target: record.target().to_string(),
+            span: current_span(),
+            message: record.args().to_string(),
+            fields,
+        }
+    }
+}
+
+pub struct UnaLogger {
+    log_dir: PathBuf,
+    config: TelemetryConfig,
+    files: Mutex<HashMap<String, RollingFile>>,
+}
+
+impl UnaLogger {
+    pub fn new(log_dir: PathBuf, config: TelemetryConfig) -> Self {
+        Self {
+            log_dir,
+            config,
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl log::Log for UnaLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Debug // Unchoked from Info
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let target = record.target().split("::").next().unwrap_or("system");
+            let safe_target = target.replace(|c: char| !c.is_alphanumeric(), "_");
+
+            let entry = LogEntry::from_record(record);
+            let plain = entry.to_plain();
+            let line = match self.config.format {
+                LogFormat::Plain => format!("{}\n", plain),
+                LogFormat::Json => format!("{}\n", entry.to_json()),
+            };
+
+            // Echo to stdout for the Architect
+            println!("{}: {}", target.to_uppercase(), plain);
+
+            // Route to specific subsystem log. Do not swallow errors silently.
+            let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+            let file = files.entry(safe_target.clone()).or_insert_with(|| {
+                RollingFile::new(&self.log_dir, &safe_target, self.config.rotation.clone())
+            });
+            if let Err(e) = file.write_line(&line, SystemTime::now()) {
+                eprintln!(
+                    ">> [TELEMETRY FAULT] Failed to write to {}: {}",
+                    file.path().display(),
+                    e
+                );
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Ignites the autonomic telemetry routing system.
+pub fn ignite(log_dir: PathBuf) {
+    ignite_with(log_dir, TelemetryConfig::default());
+}
+
+/// `ignite`, with an explicit format and rotation policy.
+pub fn ignite_with(log_dir: PathBuf, config: TelemetryConfig) {
+    if !log_dir.exists()
+        && let Err(e) = fs::create_dir_all(&log_dir)
+    {
+        eprintln!(
+            ">> [CRITICAL] Failed to construct telemetry vault at {}: {}",
+            log_dir.display(),
+            e
+        );
+        return;
+    }
+
+    let logger = Box::new(UnaLogger::new(log_dir, config));
+    log::set_boxed_logger(logger)
+        .map(|()| log::set_max_level(LevelFilter::Debug)) // Unchoke the output
+        .expect("Nervous system logging failed to ignite");
+}
@@ -0,0 +1,650 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Reading the logs back.
+//!
+//! A log file may hold JSON lines, the older plain-text lines, or both (a
+//! file that predates the switch keeps growing in the new format). Lines
+//! that are neither continue the message above them.
+
+use super::rolling::{self, parse_file_name};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone};
+use log::Level;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+const PLAIN_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// One log record, in the shape of a JSON line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: DateTime<FixedOffset>,
+    pub level: Level,
+    pub target: String,
+    /// Nested spans, outermost first, joined with `/`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<String>,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+}
+
+impl LogEntry {
+    /// `[time] [LEVEL] message k=v`, the classic layout.
+    pub fn to_plain(&self) -> String {
+        let mut line = format!(
+            "[{}] [{}] ",
+            self.timestamp.format(PLAIN_TIME_FORMAT),
+            self.level
+        );
+        if let Some(span) = &self.span {
+            line.push_str(&format!("<{}> ", span));
+        }
+        line.push_str(&self.message);
+        for (key, value) in &self.fields {
+            line.push_str(&format!(" {}={}", key, field_text(value)));
+        }
+        line
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("LogEntry is always serializable")
+    }
+}
+
+fn field_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Parses one line. Plain-text lines have no target of their own, so they
+/// take `file_target`, the name of the file they came from.
+pub fn parse_line(line: &str, file_target: &str) -> Option<LogEntry> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.starts_with('{') {
+        return serde_json::from_str(line).ok();
+    }
+
+    let rest = line.strip_prefix('[')?;
+    let (time, rest) = rest.split_once("] [")?;
+    let (level, message) = rest.split_once("] ")?;
+    let naive = NaiveDateTime::parse_from_str(time, PLAIN_TIME_FORMAT).ok()?;
+    Some(LogEntry {
+        timestamp: Local.from_local_datetime(&naive).earliest()?.fixed_offset(),
+        level: level.parse().ok()?,
+        target: file_target.to_string(),
+        span: None,
+        message: message.to_string(),
+        fields: Map::new(),
+    })
+}
+
+/// Parses a run of lines, folding continuation lines into the entry above.
+/// Continuations with no entry above them are dropped.
+pub fn parse_lines(text: &str, file_target: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for line in text.lines() {
+        match parse_line(line, file_target) {
+            Some(entry) => entries.push(entry),
+            None => {
+                if let Some(last) = entries.last_mut() {
+                    last.message.push('\n');
+                    last.message.push_str(line);
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// A log file on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFile {
+    pub target: String,
+    pub path: PathBuf,
+    /// `None` for the live file, otherwise its rotation sequence.
+    pub seq: Option<u64>,
+}
+
+impl LogFile {
+    /// The whole file, decompressed if it was rolled.
+    pub fn read_to_string(&self) -> Result<String> {
+        let raw = match self.seq {
+            // Rolled files are gzipped in the background; take either form.
+            Some(seq) => {
+                let dir = self.path.parent().unwrap_or(Path::new("."));
+                rolling::read_rolled(dir, &self.target, seq)
+            }
+            None => fs::read(&self.path),
+        }
+        .with_context(|| format!("Failed to read {}", self.path.display()))?;
+        Ok(String::from_utf8_lossy(&raw).into_owned())
+    }
+
+    pub fn entries(&self) -> Result<Vec<LogEntry>> {
+        Ok(parse_lines(&self.read_to_string()?, &self.target))
+    }
+}
+
+/// Every log file in `dir`, grouped by target, oldest first within a target.
+pub fn log_files(dir: &Path) -> Result<Vec<LogFile>> {
+    let mut files: Vec<LogFile> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read log directory {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            let (target, seq) = parse_file_name(&name)?;
+            Some(LogFile {
+                target: target.to_string(),
+                path: e.path(),
+                seq,
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| {
+        (&a.target, a.seq.unwrap_or(u64::MAX)).cmp(&(&b.target, b.seq.unwrap_or(u64::MAX)))
+    });
+    // Mid-compression, a rolled file is on disk both plain and gzipped.
+    files.dedup_by(|a, b| a.target == b.target && a.seq == b.seq);
+    Ok(files)
+}
+
+/// Which entries to keep. Every condition that is set must hold.
+#[derive(Debug, Clone, Default)]
+pub struct LogQuery {
+    /// Keep entries at least this severe.
+    pub level: Option<Level>,
+    /// Keep entries whose target is, or lives under, one of these.
+    pub targets: Vec<String>,
+    pub since: Option<DateTime<FixedOffset>>,
+    pub until: Option<DateTime<FixedOffset>>,
+    /// `key=value` pairs that must all be present among the fields.
+    pub fields: Vec<(String, String)>,
+}
+
+impl LogQuery {
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if self.level.is_some_and(|level| entry.level > level) {
+            return false;
+        }
+        if !self.targets.is_empty() && !self.targets.iter().any(|t| target_under(&entry.target, t))
+        {
+            return false;
+        }
+        if self.since.is_some_and(|since| entry.timestamp < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| entry.timestamp > until) {
+            return false;
+        }
+        self.fields.iter().all(|(key, want)| {
+            entry
+                .fields
+                .get(key)
+                .is_some_and(|v| field_text(v) == *want)
+        })
+    }
+
+    /// Whether a file of `file_target` can hold anything this query keeps.
+    pub fn wants_file(&self, file_target: &str) -> bool {
+        self.targets.is_empty()
+            || self
+                .targets
+                .iter()
+                .any(|t| t.split("::").next() == Some(file_target))
+    }
+
+    /// Every matching entry in `dir`, across live and rolled files, in time order.
+    pub fn run(&self, dir: &Path) -> Result<Vec<LogEntry>> {
+        let mut entries = Vec::new();
+        for file in log_files(dir)? {
+            if self.wants_file(&file.target) {
+                entries.extend(file.entries()?.into_iter().filter(|e| self.matches(e)));
+            }
+        }
+        // Stable, so lines stamped in the same millisecond keep file order.
+        entries.sort_by_key(|e| e.timestamp);
+        Ok(entries)
+    }
+
+    /// `run`, plus a `LogTail` that picks up exactly where the read stopped,
+    /// so following the query neither drops nor repeats an entry. A
+    /// half-written last line is left for the tail.
+    pub fn run_and_tail(&self, dir: &Path) -> Result<(Vec<LogEntry>, LogTail)> {
+        let mut entries = Vec::new();
+        let mut states: HashMap<String, TailState> = HashMap::new();
+        for file in log_files(dir)? {
+            let wanted = self.wants_file(&file.target);
+            let state = states.entry(file.target.clone()).or_default();
+            let text = match file.seq {
+                Some(seq) => {
+                    state.last_seq = state.last_seq.max(seq);
+                    if !wanted {
+                        continue;
+                    }
+                    file.read_to_string()?
+                }
+                None if !wanted => {
+                    state.pos = fs::metadata(&file.path)?.len();
+                    continue;
+                }
+                None => {
+                    let raw = fs::read(&file.path)
+                        .with_context(|| format!("Failed to open {}", file.path.display()))?;
+                    let complete = raw.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+                    state.pos = complete as u64;
+                    String::from_utf8_lossy(&raw[..complete]).into_owned()
+                }
+            };
+            entries.extend(
+                parse_lines(&text, &file.target)
+                    .into_iter()
+                    .filter(|e| self.matches(e)),
+            );
+        }
+        entries.sort_by_key(|e| e.timestamp);
+        let tail = LogTail {
+            dir: dir.to_path_buf(),
+            states,
+        };
+        Ok((entries, tail))
+    }
+}
+
+fn target_under(target: &str, prefix: &str) -> bool {
+    target == prefix
+        || target
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
+/// Parses a time bound: RFC 3339, a local `YYYY-MM-DD[ HH:MM[:SS]]`, or an
+/// age such as `90s`, `15m`, `2h` or `7d` before `now`.
+pub fn parse_time(input: &str, now: DateTime<FixedOffset>) -> Option<DateTime<FixedOffset>> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(input) {
+        return Some(t);
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(input, format) {
+            return Some(Local.from_local_datetime(&naive).earliest()?.fixed_offset());
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        let naive = date.and_hms_opt(0, 0, 0)?;
+        return Some(Local.from_local_datetime(&naive).earliest()?.fixed_offset());
+    }
+
+    let unit = input.chars().last()?;
+    let amount: i64 = input[..input.len() - unit.len_utf8()].parse().ok()?;
+    let age = match unit {
+        's' => Duration::seconds(amount),
+        'm' => Duration::minutes(amount),
+        'h' => Duration::hours(amount),
+        'd' => Duration::days(amount),
+        _ => return None,
+    };
+    Some(now - age)
+}
+
+#[derive(Debug, Default)]
+struct TailState {
+    /// Bytes of the live file already seen.
+    pos: u64,
+    /// Newest rolled sequence already accounted for.
+    last_seq: u64,
+}
+
+/// Follows the live files of a log directory, across rotations.
+///
+/// If the live file rolls between polls, the unread end of it is picked up
+/// from the rolled copy before reading the new live file from the start.
+pub struct LogTail {
+    dir: PathBuf,
+    states: HashMap<String, TailState>,
+}
+
+impl LogTail {
+    /// Starts at the current end of every log in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        let mut states: HashMap<String, TailState> = HashMap::new();
+        for file in log_files(&dir)? {
+            let state = states.entry(file.target.clone()).or_default();
+            match file.seq {
+                Some(seq) => state.last_seq = state.last_seq.max(seq),
+                None => state.pos = fs::metadata(&file.path)?.len(),
+            }
+        }
+        Ok(Self { dir, states })
+    }
+
+    /// Everything appended since the last poll, in time order.
+    pub fn poll(&mut self) -> Result<Vec<LogEntry>> {
+        let mut targets: Vec<String> = log_files(&self.dir)?
+            .into_iter()
+            .map(|f| f.target)
+            .collect();
+        targets.dedup();
+
+        let mut entries = Vec::new();
+        for target in targets {
+            let state = self.states.entry(target.clone()).or_default();
+            let mut text = String::new();
+
+            let missed: Vec<u64> = rolling::rolled_seqs(&self.dir, &target)?
+                .into_iter()
+                .filter(|&seq| seq > state.last_seq)
+                .collect();
+            for seq in &missed {
+                let rolled = LogFile {
+                    target: target.clone(),
+                    path: rolling::rolled_path(&self.dir, &target, *seq),
+                    seq: Some(*seq),
+                }
+                .read_to_string()?;
+                text.push_str(rolled.get(state.pos as usize..).unwrap_or(""));
+                state.pos = 0;
+                state.last_seq = *seq;
+            }
+
+            let live = rolling::active_path(&self.dir, &target);
+            if let Ok(mut file) = File::open(&live) {
+                file.seek(SeekFrom::Start(state.pos))?;
+                let mut fresh = String::new();
+                file.read_to_string(&mut fresh)?;
+                // Leave a half-written last line for the next poll.
+                let complete = fresh.rfind('\n').map_or(0, |i| i + 1);
+                text.push_str(&fresh[..complete]);
+                state.pos += complete as u64;
+            }
+
+            entries.extend(parse_lines(&text, &target));
+        }
+        entries.sort_by_key(|e| e.timestamp);
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rolling::{RollingFile, Rotation};
+    use super::*;
+    use std::time::SystemTime;
+
+    fn at(rfc3339: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap()
+    }
+
+    fn json(ts: &str, level: &str, target: &str, message: &str, fields: &str) -> String {
+        format!(
+            r#"{{"timestamp":"{}","level":"{}","target":"{}","message":"{}","fields":{}}}"#,
+            ts, level, target, message, fields
+        )
+    }
+
+    #[test]
+    fn test_parse_mixed_plain_and_json() {
+        let text = [
+            "[2026-03-01 09:00:00.125] [INFO] Deep subconscious online",
+            "[2026-03-01 09:00:01.000] [ERROR] Stack trace follows:",
+            "   at cortex::index",
+            "   at cortex::run",
+            &json(
+                "2026-03-01T09:00:02.500+00:00",
+                "WARN",
+                "vein::cortex",
+                "Slow index",
+                r#"{"files":1200,"root":"/src"}"#,
+            ),
+            r#"{"timestamp":"2026-03-01T09:00:03+00:00","level":"DEBUG","target":"vein","span":"boot/index","message":"tick"}"#,
+        ]
+        .join("\n");
+
+        let entries = parse_lines(&text, "vein");
+        assert_eq!(entries.len(), 4);
+
+        let naive =
+            NaiveDateTime::parse_from_str("2026-03-01 09:00:00.125", PLAIN_TIME_FORMAT).unwrap();
+        assert_eq!(entries[0].timestamp.naive_local(), naive);
+        assert_eq!(entries[0].level, Level::Info);
+        assert_eq!(entries[0].target, "vein");
+        assert_eq!(entries[0].message, "Deep subconscious online");
+
+        assert_eq!(entries[1].level, Level::Error);
+        assert_eq!(
+            entries[1].message,
+            "Stack trace follows:\n   at cortex::index\n   at cortex::run"
+        );
+
+        assert_eq!(entries[2].target, "vein::cortex");
+        assert_eq!(entries[2].timestamp, at("2026-03-01T09:00:02.500Z"));
+        assert_eq!(entries[2].fields["files"], 1200);
+        assert_eq!(entries[2].fields["root"], "/src");
+
+        assert_eq!(entries[3].span.as_deref(), Some("boot/index"));
+        assert!(entries[3].fields.is_empty());
+    }
+
+    #[test]
+    fn test_unparseable_lines() {
+        assert_eq!(parse_line("", "vein"), None);
+        assert_eq!(parse_line("[not a time] [INFO] x", "vein"), None);
+        assert_eq!(
+            parse_line("[2026-03-01 09:00:00.125] [LOUD] x", "vein"),
+            None
+        );
+        assert_eq!(parse_line("{\"truncated\":", "vein"), None);
+        // An orphan continuation at the top of a file has nowhere to go.
+        assert!(parse_lines("   at nowhere\n", "vein").is_empty());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let entry = parse_line(
+            &json(
+                "2026-03-01T09:00:02.500+02:00",
+                "INFO",
+                "lumen",
+                "hello",
+                r#"{"ok":true}"#,
+            ),
+            "ignored",
+        )
+        .unwrap();
+        assert_eq!(parse_line(&entry.to_json(), "ignored").unwrap(), entry);
+        assert!(entry.to_plain().ends_with("] [INFO] hello ok=true"));
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let text = [
+            json(
+                "2026-03-01T09:00:00Z",
+                "INFO",
+                "vein",
+                "a",
+                r#"{"user":"una"}"#,
+            ),
+            json(
+                "2026-03-01T10:00:00Z",
+                "WARN",
+                "vein::cortex",
+                "b",
+                r#"{"user":"una","n":3}"#,
+            ),
+            json("2026-03-01T11:00:00Z", "ERROR", "veinous", "c", "{}"),
+            json("2026-03-01T12:00:00Z", "DEBUG", "lumen", "d", "{}"),
+        ]
+        .join("\n");
+        let entries = parse_lines(&text, "x");
+        let kept = |q: &LogQuery| -> Vec<String> {
+            entries
+                .iter()
+                .filter(|e| q.matches(e))
+                .map(|e| e.message.clone())
+                .collect()
+        };
+
+        let by_level = LogQuery {
+            level: Some(Level::Warn),
+            ..Default::default()
+        };
+        assert_eq!(kept(&by_level), ["b", "c"]);
+
+        let by_target = LogQuery {
+            targets: vec!["vein".into()],
+            ..Default::default()
+        };
+        assert_eq!(kept(&by_target), ["a", "b"]);
+        assert!(by_target.wants_file("vein"));
+        assert!(!by_target.wants_file("veinous"));
+
+        let by_time = LogQuery {
+            since: Some(at("2026-03-01T10:00:00Z")),
+            until: Some(at("2026-03-01T11:00:00Z")),
+            ..Default::default()
+        };
+        assert_eq!(kept(&by_time), ["b", "c"]);
+
+        let by_field = LogQuery {
+            fields: vec![("user".into(), "una".into()), ("n".into(), "3".into())],
+            ..Default::default()
+        };
+        assert_eq!(kept(&by_field), ["b"]);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let now = at("2026-03-01T12:00:00Z");
+        assert_eq!(parse_time("90s", now), Some(at("2026-03-01T11:58:30Z")));
+        assert_eq!(parse_time("2h", now), Some(at("2026-03-01T10:00:00Z")));
+        assert_eq!(parse_time("1d", now), Some(at("2026-02-28T12:00:00Z")));
+        assert_eq!(
+            parse_time("2026-01-02T03:04:05+01:00", now),
+            Some(at("2026-01-02T02:04:05Z"))
+        );
+        assert!(parse_time("2026-01-02 03:04", now).is_some());
+        assert!(parse_time("2026-01-02", now).is_some());
+        assert_eq!(parse_time("soon", now), None);
+        assert_eq!(parse_time("5y", now), None);
+    }
+
+    #[test]
+    fn test_query_and_tail_span_rotations() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let rotation = Rotation {
+            max_bytes: Some(150),
+            ..Rotation::never()
+        };
+        let mut vein = RollingFile::new(dir.path(), "vein", rotation);
+        let line = |i: usize| {
+            format!(
+                "{}\n",
+                json(
+                    &format!("2026-03-01T09:00:{:02}Z", i),
+                    "INFO",
+                    "vein",
+                    &i.to_string(),
+                    "{}"
+                )
+            )
+        };
+
+        for i in 0..5 {
+            vein.write_line(&line(i), now).unwrap();
+        }
+        assert!(rolling::rolled_seqs(dir.path(), "vein").unwrap().len() >= 2);
+        // An old plain-text log of another target sits alongside.
+        fs::write(
+            dir.path().join("lumen.log"),
+            "[2026-03-01 09:00:00.000] [INFO] plain\n",
+        )
+        .unwrap();
+
+        let all = LogQuery::default().run(dir.path()).unwrap();
+        let vein_msgs: Vec<&str> = all
+            .iter()
+            .filter(|e| e.target == "vein")
+            .map(|e| e.message.as_str())
+            .collect();
+        assert_eq!(vein_msgs, ["0", "1", "2", "3", "4"]);
+        assert!(all.iter().any(|e| e.message == "plain"));
+
+        let mut tail = LogTail::new(dir.path()).unwrap();
+        assert!(tail.poll().unwrap().is_empty());
+
+        // Each line forces a roll, so the tail must read the rolled copies.
+        for i in 5..9 {
+            vein.write_line(&line(i), now).unwrap();
+        }
+        let msgs: Vec<String> = tail
+            .poll()
+            .unwrap()
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(msgs, ["5", "6", "7", "8"]);
+
+        // A half-written line waits for its newline.
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(rolling::active_path(dir.path(), "vein"))
+            .unwrap();
+        std::io::Write::write_all(&mut file, line(9).trim_end().as_bytes()).unwrap();
+        assert!(tail.poll().unwrap().is_empty());
+        std::io::Write::write_all(&mut file, b"\n").unwrap();
+        assert_eq!(tail.poll().unwrap()[0].message, "9");
+    }
+
+    #[test]
+    fn test_tail_resumes_where_query_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let line = |i: usize| {
+            json(
+                &format!("2026-03-01T09:00:{:02}Z", i),
+                "INFO",
+                "vein",
+                &i.to_string(),
+                "{}",
+            )
+        };
+        let live = rolling::active_path(dir.path(), "vein");
+        // The last line is still being written when the query runs.
+        fs::write(&live, format!("{}\n{}\n{}", line(0), line(1), line(2))).unwrap();
+
+        let (entries, mut tail) = LogQuery::default().run_and_tail(dir.path()).unwrap();
+        let msgs: Vec<&str> = entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(msgs, ["0", "1"]);
+
+        let mut file = fs::OpenOptions::new().append(true).open(&live).unwrap();
+        std::io::Write::write_all(&mut file, format!("\n{}\n", line(3)).as_bytes()).unwrap();
+        let msgs: Vec<String> = tail
+            .poll()
+            .unwrap()
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(msgs, ["2", "3"]);
+    }
+}
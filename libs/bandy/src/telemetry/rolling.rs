@@ -0,0 +1,476 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Size- and age-based rotation for the per-target log files.
+//!
+//! The live file is `<target>.log`. When it rolls it is renamed to
+//! `<target>.<seq>.log`, with `seq` counting up from 1, and a background
+//! thread gzips that to `<target>.<seq>.log.gz` and deletes the oldest
+//! rolled files beyond `Rotation::keep`. Writers only ever wait for the
+//! rename.
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+/// When to roll a log file. `None` disables that trigger.
+#[derive(Debug, Clone)]
+pub struct Rotation {
+    /// Roll before a write would push the file past this size.
+    pub max_bytes: Option<u64>,
+    /// Roll once the file has been open for this long.
+    pub max_age: Option<Duration>,
+    /// How many rolled files to keep per target. `None` keeps them all.
+    pub keep: Option<usize>,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self {
+            max_bytes: Some(8 * 1024 * 1024),
+            max_age: Some(Duration::from_secs(24 * 60 * 60)),
+            // Deleting logs is for the caller to ask for.
+            keep: None,
+        }
+    }
+}
+
+impl Rotation {
+    /// Never roll, never delete: the old behaviour.
+    pub fn never() -> Self {
+        Self {
+            max_bytes: None,
+            max_age: None,
+            keep: None,
+        }
+    }
+}
+
+/// The live file of `target` in `dir`.
+pub fn active_path(dir: &Path, target: &str) -> PathBuf {
+    dir.join(format!("{}.log", target))
+}
+
+/// The `seq`th rolled file of `target` in `dir`.
+pub fn rolled_path(dir: &Path, target: &str, seq: u64) -> PathBuf {
+    dir.join(format!("{}.{}.log.gz", target, seq))
+}
+
+/// The `seq`th rolled file of `target` before it has been compressed.
+pub fn pending_path(dir: &Path, target: &str, seq: u64) -> PathBuf {
+    dir.join(format!("{}.{}.log", target, seq))
+}
+
+/// Splits a log file name into its target and, for rolled files, its sequence.
+pub fn parse_file_name(name: &str) -> Option<(&str, Option<u64>)> {
+    if let Some(stem) = name.strip_suffix(".log.gz") {
+        let (target, seq) = stem.rsplit_once('.')?;
+        return Some((target, Some(seq.parse().ok()?)));
+    }
+    let stem = name.strip_suffix(".log")?;
+    match stem.rsplit_once('.') {
+        Some((target, seq)) => Some((target, Some(seq.parse().ok()?))),
+        None => Some((stem, None)),
+    }
+}
+
+/// The text of the `seq`th rolled file of `target`, compressed or not.
+pub fn read_rolled(dir: &Path, target: &str, seq: u64) -> io::Result<Vec<u8>> {
+    let gz = rolled_path(dir, target, seq);
+    let mut raw = Vec::new();
+    match File::open(&gz) {
+        Ok(file) => {
+            GzDecoder::new(file).read_to_end(&mut raw)?;
+            return Ok(raw);
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    match File::open(pending_path(dir, target, seq)) {
+        Ok(mut file) => {
+            file.read_to_end(&mut raw)?;
+            return Ok(raw);
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    // The compressor moves the `.gz` into place before it deletes the plain
+    // copy, so it finished between the two opens.
+    GzDecoder::new(File::open(&gz)?).read_to_end(&mut raw)?;
+    Ok(raw)
+}
+
+/// Sequence numbers of the rolled files of `target`, oldest first.
+pub fn rolled_seqs(dir: &Path, target: &str) -> io::Result<Vec<u64>> {
+    let mut seqs: Vec<u64> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            match parse_file_name(&name)? {
+                (t, Some(seq)) if t == target => Some(seq),
+                _ => None,
+            }
+        })
+        .collect();
+    seqs.sort_unstable();
+    // Mid-compression, a sequence has both a plain and a gzipped file.
+    seqs.dedup();
+    Ok(seqs)
+}
+
+/// Gzips every rolled file of `target` still waiting for it, then prunes.
+fn compress_rolled(dir: &Path, target: &str, keep: Option<usize>) -> io::Result<()> {
+    let seqs = rolled_seqs(dir, target)?;
+    for &seq in &seqs {
+        let pending = pending_path(dir, target, seq);
+        let mut source = match File::open(&pending) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        // Compress beside the target name, then move into place, so a reader
+        // never sees half a gzip stream under a valid name.
+        let tmp = dir.join(format!("{}.{}.log.gz.tmp", target, seq));
+        let mut encoder = GzEncoder::new(File::create(&tmp)?, Compression::default());
+        io::copy(&mut source, &mut encoder)?;
+        encoder.finish()?.sync_all()?;
+        fs::rename(&tmp, rolled_path(dir, target, seq))?;
+        fs::remove_file(&pending)?;
+    }
+
+    if let Some(keep) = keep {
+        for &old in &seqs[..seqs.len().saturating_sub(keep)] {
+            let _ = fs::remove_file(rolled_path(dir, target, old));
+            let _ = fs::remove_file(pending_path(dir, target, old));
+        }
+    }
+    Ok(())
+}
+
+struct Live {
+    file: File,
+    bytes: u64,
+    opened_at: SystemTime,
+}
+
+/// One target's log file, opened lazily and rolled on demand.
+pub struct RollingFile {
+    dir: PathBuf,
+    target: String,
+    rotation: Rotation,
+    live: Option<Live>,
+    /// The latest compression run; each run waits for the one before it.
+    compressing: Option<JoinHandle<()>>,
+}
+
+impl RollingFile {
+    pub fn new(dir: impl Into<PathBuf>, target: &str, rotation: Rotation) -> Self {
+        Self {
+            dir: dir.into(),
+            target: target.to_string(),
+            rotation,
+            live: None,
+            compressing: None,
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        active_path(&self.dir, &self.target)
+    }
+
+    /// Appends one line (which must carry its own newline), rolling first
+    /// if the line would cross a limit. A line is never split across files.
+    pub fn write_line(&mut self, line: &str, now: SystemTime) -> io::Result<()> {
+        self.open(now)?;
+        if self.should_roll(line.len() as u64, now) {
+            self.roll()?;
+            self.open(now)?;
+        }
+
+        let live = self.live.as_mut().unwrap();
+        live.file.write_all(line.as_bytes())?;
+        live.bytes += line.len() as u64;
+        Ok(())
+    }
+
+    /// Moves the live file into the next rolled slot and hands it to a
+    /// background thread to gzip and prune. Returns the uncompressed rolled
+    /// path, or `None` if there was nothing to roll.
+    pub fn roll(&mut self) -> io::Result<Option<PathBuf>> {
+        self.live = None;
+        let active = self.path();
+        match fs::metadata(&active) {
+            Ok(meta) if meta.len() > 0 => {}
+            Ok(_) => return Ok(None),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let seq = rolled_seqs(&self.dir, &self.target)?
+            .last()
+            .map_or(1, |s| s + 1);
+        let rolled = pending_path(&self.dir, &self.target, seq);
+        fs::rename(&active, &rolled)?;
+
+        // The caller may hold the logger's lock, so the slow part runs
+        // elsewhere. Runs are chained so two never compress the same file.
+        let previous = self.compressing.take();
+        let (dir, target, keep) = (self.dir.clone(), self.target.clone(), self.rotation.keep);
+        self.compressing = Some(thread::spawn(move || {
+            if let Some(previous) = previous {
+                let _ = previous.join();
+            }
+            if let Err(e) = compress_rolled(&dir, &target, keep) {
+                eprintln!(
+                    ">> [TELEMETRY FAULT] Failed to compress rolled {} logs: {}",
+                    target, e
+                );
+            }
+        }));
+        Ok(Some(rolled))
+    }
+
+    /// Blocks until every rolled file so far is compressed and pruned.
+    pub fn settle(&mut self) {
+        if let Some(compressing) = self.compressing.take() {
+            let _ = compressing.join();
+        }
+    }
+
+    fn open(&mut self, now: SystemTime) -> io::Result<()> {
+        if self.live.is_some() {
+            return Ok(());
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path())?;
+        let meta = file.metadata()?;
+        // A file left over from an earlier run is as old as its first line.
+        let opened_at = if meta.len() > 0 {
+            meta.created().or_else(|_| meta.modified()).unwrap_or(now)
+        } else {
+            now
+        };
+        self.live = Some(Live {
+            file,
+            bytes: meta.len(),
+            opened_at,
+        });
+        Ok(())
+    }
+
+    fn should_roll(&self, incoming: u64, now: SystemTime) -> bool {
+        let Some(live) = &self.live else {
+            return false;
+        };
+        if live.bytes == 0 {
+            return false;
+        }
+        let too_big = self
+            .rotation
+            .max_bytes
+            .is_some_and(|max| live.bytes + incoming > max);
+        let too_old = self.rotation.max_age.is_some_and(|age| {
+            now.duration_since(live.opened_at)
+                .is_ok_and(|elapsed| elapsed >= age)
+        });
+        too_big || too_old
+    }
+}
+
+impl Drop for RollingFile {
+    fn drop(&mut self) {
+        self.settle();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gunzip(path: &Path) -> String {
+        let mut out = String::new();
+        GzDecoder::new(File::open(path).unwrap())
+            .read_to_string(&mut out)
+            .unwrap();
+        out
+    }
+
+    fn sized(max_bytes: u64) -> Rotation {
+        Rotation {
+            max_bytes: Some(max_bytes),
+            max_age: None,
+            keep: None,
+        }
+    }
+
+    #[test]
+    fn test_file_names() {
+        assert_eq!(parse_file_name("vein.log"), Some(("vein", None)));
+        assert_eq!(parse_file_name("vein.12.log.gz"), Some(("vein", Some(12))));
+        assert_eq!(parse_file_name("vein.3.log"), Some(("vein", Some(3))));
+        assert_eq!(parse_file_name("vein.x.log"), None);
+        assert_eq!(parse_file_name("vein.x.log.gz"), None);
+        assert_eq!(parse_file_name("vein.3.log.gz.tmp"), None);
+        assert_eq!(parse_file_name("notes.txt"), None);
+    }
+
+    #[test]
+    fn test_exact_fit_does_not_roll() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let mut log = RollingFile::new(dir.path(), "vein", sized(10));
+
+        log.write_line("12345\n", now).unwrap();
+        log.write_line("abc\n", now).unwrap();
+        // Exactly at the limit: still one file.
+        assert!(rolled_seqs(dir.path(), "vein").unwrap().is_empty());
+        assert_eq!(fs::metadata(log.path()).unwrap().len(), 10);
+
+        // One byte over: the full file rolls and the line starts a new one.
+        log.write_line("z\n", now).unwrap();
+        assert_eq!(rolled_seqs(dir.path(), "vein").unwrap(), vec![1]);
+        log.settle();
+        assert_eq!(gunzip(&rolled_path(dir.path(), "vein", 1)), "12345\nabc\n");
+        assert_eq!(fs::read_to_string(log.path()).unwrap(), "z\n");
+    }
+
+    #[test]
+    fn test_oversized_line_is_never_split() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let mut log = RollingFile::new(dir.path(), "vein", sized(4));
+
+        // Longer than the limit on an empty file: written whole, no roll.
+        log.write_line("0123456789\n", now).unwrap();
+        assert!(rolled_seqs(dir.path(), "vein").unwrap().is_empty());
+
+        log.write_line("a\n", now).unwrap();
+        log.settle();
+        assert_eq!(gunzip(&rolled_path(dir.path(), "vein", 1)), "0123456789\n");
+        assert_eq!(fs::read_to_string(log.path()).unwrap(), "a\n");
+    }
+
+    #[test]
+    fn test_age_boundary() {
+        let dir = tempfile::tempdir().unwrap();
+        let start = SystemTime::now();
+        let age = Duration::from_secs(60);
+        let rotation = Rotation {
+            max_bytes: None,
+            max_age: Some(age),
+            keep: None,
+        };
+        let mut log = RollingFile::new(dir.path(), "lumen", rotation);
+
+        log.write_line("first\n", start).unwrap();
+        log.write_line("second\n", start + age - Duration::from_millis(1))
+            .unwrap();
+        assert!(rolled_seqs(dir.path(), "lumen").unwrap().is_empty());
+
+        log.write_line("third\n", start + age).unwrap();
+        assert_eq!(rolled_seqs(dir.path(), "lumen").unwrap(), vec![1]);
+        assert_eq!(fs::read_to_string(log.path()).unwrap(), "third\n");
+
+        // The clock restarts with the new file.
+        log.write_line("fourth\n", start + age + age / 2).unwrap();
+        assert_eq!(rolled_seqs(dir.path(), "lumen").unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn test_keep_prunes_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let rotation = Rotation {
+            keep: Some(2),
+            ..sized(2)
+        };
+        let mut log = RollingFile::new(dir.path(), "aule", rotation);
+        for i in 0..5 {
+            log.write_line(&format!("{}\n", i), now).unwrap();
+        }
+
+        // Four rolls happened; only the two newest survive.
+        log.settle();
+        assert_eq!(rolled_seqs(dir.path(), "aule").unwrap(), vec![3, 4]);
+        assert_eq!(gunzip(&rolled_path(dir.path(), "aule", 4)), "3\n");
+        assert_eq!(fs::read_to_string(log.path()).unwrap(), "4\n");
+    }
+
+    #[test]
+    fn test_sequence_resumes_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        {
+            let mut log = RollingFile::new(dir.path(), "vein", sized(2));
+            log.write_line("a\n", now).unwrap();
+            log.write_line("b\n", now).unwrap();
+        }
+        let mut log = RollingFile::new(dir.path(), "vein", sized(2));
+        // The leftover live file counts towards the limit.
+        log.write_line("c\n", now).unwrap();
+        assert_eq!(rolled_seqs(dir.path(), "vein").unwrap(), vec![1, 2]);
+        log.settle();
+        assert_eq!(gunzip(&rolled_path(dir.path(), "vein", 2)), "b\n");
+
+        // Nothing to roll on an empty file.
+        log.roll().unwrap();
+        assert_eq!(log.roll().unwrap(), None);
+    }
+
+    #[test]
+    fn test_default_keeps_everything() {
+        assert_eq!(Rotation::default().keep, None);
+    }
+
+    #[test]
+    fn test_rolled_file_is_readable_before_and_after_compression() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = RollingFile::new(dir.path(), "vein", Rotation::never());
+        log.write_line("kept\n", SystemTime::now()).unwrap();
+        assert_eq!(
+            log.roll().unwrap(),
+            Some(pending_path(dir.path(), "vein", 1))
+        );
+        assert_eq!(read_rolled(dir.path(), "vein", 1).unwrap(), b"kept\n");
+
+        log.settle();
+        assert!(!pending_path(dir.path(), "vein", 1).exists());
+        assert_eq!(gunzip(&rolled_path(dir.path(), "vein", 1)), "kept\n");
+        assert_eq!(read_rolled(dir.path(), "vein", 1).unwrap(), b"kept\n");
+    }
+
+    #[test]
+    fn test_leftover_plain_roll_is_compressed_next_time() {
+        let dir = tempfile::tempdir().unwrap();
+        // A run that stopped before its compressor finished.
+        fs::write(pending_path(dir.path(), "vein", 1), "old\n").unwrap();
+
+        let mut log = RollingFile::new(dir.path(), "vein", Rotation::never());
+        log.write_line("new\n", SystemTime::now()).unwrap();
+        log.roll().unwrap();
+        log.settle();
+        assert_eq!(rolled_seqs(dir.path(), "vein").unwrap(), vec![1, 2]);
+        assert_eq!(gunzip(&rolled_path(dir.path(), "vein", 1)), "old\n");
+        assert_eq!(gunzip(&rolled_path(dir.path(), "vein", 2)), "new\n");
+    }
+}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Hierarchical topics.
+//!
+//! Topics are `/`-separated levels (e.g. `resonance/audio/chunk`).
+//! Filters follow MQTT rules: `+` matches exactly one level and `#`
+//! matches any number of trailing levels (including none). Wildcards must
+//! occupy a whole level, and `#` may only appear last.
+
+use anyhow::{Result, bail};
+
+pub const SEPARATOR: char = '/';
+pub const SINGLE_LEVEL: &str = "+";
+pub const MULTI_LEVEL: &str = "#";
+
+/// Validates a concrete topic that a message is published on.
+/// Published topics must be non-empty and may not contain wildcards.
+pub fn validate_topic(topic: &str) -> Result<()> {
+    if topic.is_empty() {
+        bail!("Topic must not be empty");
+    }
+    if topic.contains(['+', '#']) {
+        bail!("Topic '{}' must not contain wildcards", topic);
+    }
+    Ok(())
+}
+
+/// A parsed subscription pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicFilter {
+    pattern: String,
+    levels: Vec<Level>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Level {
+    Exact(String),
+    Single,
+    Multi,
+}
+
+impl TopicFilter {
+    /// Parses an MQTT-style pattern such as `matrix/+/focus` or `vein/#`.
+    pub fn new(pattern: &str) -> Result<Self> {
+        if pattern.is_empty() {
+            bail!("Topic filter must not be empty");
+        }
+
+        let raw: Vec<&str> = pattern.split(SEPARATOR).collect();
+        let mut levels = Vec::with_capacity(raw.len());
+
+        for (i, level) in raw.iter().enumerate() {
+            match *level {
+                SINGLE_LEVEL => levels.push(Level::Single),
+                MULTI_LEVEL if i == raw.len() - 1 => levels.push(Level::Multi),
+                MULTI_LEVEL => bail!("'#' must be the last level in '{}'", pattern),
+                l if l.contains(['+', '#']) => {
+                    bail!("Wildcards must occupy a whole level in '{}'", pattern)
+                }
+                l => levels.push(Level::Exact(l.to_string())),
+            }
+        }
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            levels,
+        })
+    }
+
+    /// The pattern this filter was parsed from.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns true if the concrete `topic` is selected by this filter.
+    pub fn matches(&self, topic: &str) -> bool {
+        let mut parts = topic.split(SEPARATOR);
+
+        for level in &self.levels {
+            match level {
+                Level::Multi => return true,
+                Level::Single => {
+                    if parts.next().is_none() {
+                        return false;
+                    }
+                }
+                Level::Exact(expected) => match parts.next() {
+                    Some(part) if part == expected => {}
+                    _ => return false,
+                },
+            }
+        }
+
+        // Every filter level was consumed; the topic must be too.
+        parts.next().is_none()
+    }
+}
+
+/// One-shot convenience for `TopicFilter::new(pattern)?.matches(topic)`.
+/// Invalid patterns match nothing.
+pub fn matches(pattern: &str, topic: &str) -> bool {
+    TopicFilter::new(pattern).is_ok_and(|f| f.matches(topic))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_match() {
+        assert!(matches("vein/prompt", "vein/prompt"));
+        assert!(!matches("vein/prompt", "vein/token"));
+        assert!(!matches("vein/prompt", "vein/prompt/extra"));
+        assert!(!matches("vein/prompt/extra", "vein/prompt"));
+    }
+
+    #[test]
+    fn test_single_level_wildcard() {
+        assert!(matches("matrix/+/focus", "matrix/euclase/focus"));
+        assert!(matches("+/log", "system/log"));
+        assert!(matches("+", "system"));
+        assert!(!matches("+", "system/log"));
+        assert!(!matches("matrix/+/focus", "matrix/focus"));
+        assert!(!matches("matrix/+/focus", "matrix/a/b/focus"));
+        // An empty level is still a level.
+        assert!(matches("matrix/+/focus", "matrix//focus"));
+    }
+
+    #[test]
+    fn test_multi_level_wildcard() {
+        assert!(matches("#", "system/log"));
+        assert!(matches("vein/#", "vein/prompt"));
+        assert!(matches("vein/#", "vein/a/b/c"));
+        // `#` also matches the parent level itself.
+        assert!(matches("vein/#", "vein"));
+        assert!(!matches("vein/#", "veins/prompt"));
+        assert!(matches("+/audio/#", "resonance/audio/chunk"));
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        assert!(TopicFilter::new("").is_err());
+        assert!(TopicFilter::new("vein/#/prompt").is_err());
+        assert!(TopicFilter::new("vein/pro+").is_err());
+        assert!(TopicFilter::new("vein#").is_err());
+        assert!(!matches("vein/#/prompt", "vein/x/prompt"));
+    }
+
+    #[test]
+    fn test_validate_topic() {
+        assert!(validate_topic("system/log").is_ok());
+        assert!(validate_topic("").is_err());
+        assert!(validate_topic("system/+").is_err());
+        assert!(validate_topic("system/#").is_err());
+    }
+}
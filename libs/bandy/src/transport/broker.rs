@@ -0,0 +1,218 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::{Frame, read_frame, write_frame};
+use crate::topic::TopicFilter;
+use std::collections::HashMap;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::net::unix::OwnedReadHalf;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+
+#[derive(Debug, Clone)]
+pub struct BrokerConfig {
+    /// Frames queued per client before the broker starts dropping for that
+    /// client. A stalled reader only ever loses its own traffic.
+    pub client_queue: usize,
+}
+
+impl Default for BrokerConfig {
+    fn default() -> Self {
+        Self { client_queue: 256 }
+    }
+}
+
+struct Peer {
+    filters: Vec<TopicFilter>,
+    tx: mpsc::Sender<Frame>,
+    dropped: u64,
+}
+
+type PeerMap = Arc<Mutex<HashMap<u64, Peer>>>;
+
+/// The local switchboard. Owns the socket file for its lifetime.
+pub struct Broker {
+    listener: UnixListener,
+    path: PathBuf,
+    config: BrokerConfig,
+    peers: PeerMap,
+}
+
+impl Broker {
+    /// Binds the socket, clearing a stale socket left behind by a dead
+    /// broker. Fails if another broker is still answering on `path`, or if
+    /// something other than a socket is already there.
+    pub fn bind(path: impl AsRef<Path>, config: BrokerConfig) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        match std::fs::symlink_metadata(&path) {
+            Ok(meta) if meta.file_type().is_socket() => {
+                if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        format!("A broker is already listening on {}", path.display()),
+                    ));
+                }
+                std::fs::remove_file(&path)?;
+            }
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a socket", path.display()),
+                ));
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        Ok(Self {
+            listener,
+            path,
+            config,
+            peers: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Accepts peers forever.
+    pub async fn run(self) -> io::Result<()> {
+        let mut next_id = 0u64;
+        loop {
+            let (stream, _) = self.listener.accept().await?;
+            next_id += 1;
+            log::info!("[BANDY] Peer {} connected", next_id);
+            tokio::spawn(serve_peer(
+                next_id,
+                stream,
+                self.peers.clone(),
+                self.config.client_queue,
+            ));
+        }
+    }
+}
+
+impl Drop for Broker {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+async fn serve_peer(id: u64, stream: UnixStream, peers: PeerMap, queue: usize) {
+    let (mut rd, mut wr) = stream.into_split();
+    let (tx, mut rx) = mpsc::channel::<Frame>(queue);
+
+    peers.lock().unwrap().insert(
+        id,
+        Peer {
+            filters: Vec::new(),
+            tx: tx.clone(),
+            dropped: 0,
+        },
+    );
+
+    let writer = tokio::spawn(async move {
+        while let Some(frame) = rx.recv().await {
+            if write_frame(&mut wr, &frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    if let Err(e) = read_peer(id, &mut rd, &peers, &tx).await {
+        log::warn!("[BANDY] Peer {} severed: {}", id, e);
+    }
+
+    if let Some(peer) = peers.lock().unwrap().remove(&id)
+        && peer.dropped > 0
+    {
+        log::warn!(
+            "[BANDY] Peer {} left with {} dropped frames",
+            id,
+            peer.dropped
+        );
+    }
+    log::info!("[BANDY] Peer {} disconnected", id);
+    drop(tx);
+    let _ = writer.await;
+}
+
+async fn read_peer(
+    id: u64,
+    rd: &mut OwnedReadHalf,
+    peers: &PeerMap,
+    tx: &mpsc::Sender<Frame>,
+) -> io::Result<()> {
+    while let Some(frame) = read_frame(rd).await? {
+        match frame {
+            Frame::Subscribe(pattern) => {
+                let filter = TopicFilter::new(&pattern)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+                if let Some(peer) = peers.lock().unwrap().get_mut(&id)
+                    && !peer.filters.contains(&filter)
+                {
+                    peer.filters.push(filter);
+                }
+                // Acks travel on the peer's own queue, so waiting here only
+                // ever throttles the peer that asked.
+                let _ = tx.send(Frame::Ack(pattern)).await;
+            }
+            Frame::Unsubscribe(pattern) => {
+                if let Some(peer) = peers.lock().unwrap().get_mut(&id) {
+                    peer.filters.retain(|f| f.pattern() != pattern);
+                }
+            }
+            publish @ Frame::Publish { .. } => route(peers, publish),
+            Frame::Ack(_) => {} // Only the broker acknowledges.
+        }
+    }
+    Ok(())
+}
+
+fn route(peers: &PeerMap, frame: Frame) {
+    let Frame::Publish(envelope) = &frame else {
+        return;
+    };
+    let topic = &envelope.topic;
+
+    let mut peers = peers.lock().unwrap();
+    for (id, peer) in peers.iter_mut() {
+        if !peer.filters.iter().any(|f| f.matches(topic)) {
+            continue;
+        }
+        match peer.tx.try_send(frame.clone()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                peer.dropped += 1;
+                if peer.dropped.is_power_of_two() {
+                    log::warn!(
+                        "[BANDY] Peer {} is not keeping up. {} frames dropped.",
+                        id,
+                        peer.dropped
+                    );
+                }
+            }
+            Err(TrySendError::Closed(_)) => {} // Reaped by its own task.
+        }
+    }
+}
@@ -0,0 +1,324 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::{Frame, read_frame, write_frame};
+use crate::synapse::{Impulse, Subscription};
+use crate::topic::{self, TopicFilter};
+use crate::{BandyMember, Envelope, SMessage, Synapse};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::UnixStream;
+use tokio::net::unix::OwnedReadHalf;
+use tokio::sync::{mpsc, watch};
+
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// Stamped into every outgoing envelope as `source`.
+    pub source: String,
+    /// First pause after a failed connect. Doubles on every failure.
+    pub initial_backoff: Duration,
+    /// Ceiling for the reconnect pause.
+    pub max_backoff: Duration,
+    /// Outbound frames buffered while the link is slow or down.
+    pub outbound_queue: usize,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            source: crate::envelope::process_name(),
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(5),
+            outbound_queue: 256,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LinkState {
+    connected: bool,
+    acked: BTreeSet<String>,
+}
+
+/// A handle on a remote broker.
+///
+/// Inbound impulses are republished on a local `Synapse`, so subscribers use
+/// the same `Subscription` type whether the sender lives in this process or
+/// not. Subscriptions are remembered and replayed after every reconnect.
+#[derive(Clone)]
+pub struct BrokerClient {
+    source: Arc<str>,
+    outbound: mpsc::Sender<Frame>,
+    subscriptions: Arc<Mutex<BTreeSet<String>>>,
+    inbound: Synapse,
+    state: watch::Receiver<LinkState>,
+}
+
+impl BrokerClient {
+    /// Starts the link task on the current Tokio runtime and returns at once.
+    /// The connection is established (and re-established) in the background.
+    pub fn spawn(path: impl AsRef<Path>, config: ClientConfig) -> Self {
+        let (outbound, outbound_rx) = mpsc::channel(config.outbound_queue);
+        let (state_tx, state) = watch::channel(LinkState::default());
+        let subscriptions = Arc::new(Mutex::new(BTreeSet::new()));
+        let inbound = Synapse::new();
+        let source = Arc::from(config.source.as_str());
+
+        tokio::spawn(run_link(
+            path.as_ref().to_path_buf(),
+            config,
+            outbound_rx,
+            subscriptions.clone(),
+            inbound.clone(),
+            state_tx,
+        ));
+
+        Self {
+            source,
+            outbound,
+            subscriptions,
+            inbound,
+            state,
+        }
+    }
+
+    /// Registers `pattern` with the broker and returns a local subscription
+    /// for it. Delivery starts once the broker acknowledges (see `acknowledged`).
+    pub fn subscribe(&self, pattern: &str) -> anyhow::Result<Subscription> {
+        TopicFilter::new(pattern)?;
+        let local = self.inbound.subscribe(pattern)?;
+
+        if self
+            .subscriptions
+            .lock()
+            .unwrap()
+            .insert(pattern.to_string())
+        {
+            // If the queue is full or the link is down, the reconnect replay
+            // will carry the registration instead.
+            let _ = self
+                .outbound
+                .try_send(Frame::Subscribe(pattern.to_string()));
+        }
+        Ok(local)
+    }
+
+    pub fn unsubscribe(&self, pattern: &str) {
+        if self.subscriptions.lock().unwrap().remove(pattern) {
+            let _ = self
+                .outbound
+                .try_send(Frame::Unsubscribe(pattern.to_string()));
+        }
+    }
+
+    /// Waits until the broker has confirmed `pattern` on the current connection.
+    pub async fn acknowledged(&self, pattern: &str) {
+        let mut state = self.state.clone();
+        let _ = state.wait_for(|s| s.acked.contains(pattern)).await;
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state.borrow().connected
+    }
+
+    /// Queues an impulse for the broker, waiting while the outbound queue is full.
+    pub async fn publish(&self, topic: &str, msg: SMessage) -> anyhow::Result<()> {
+        topic::validate_topic(topic)?;
+        self.send(Impulse::new(topic, msg)).await
+    }
+
+    /// Queues an impulse without waiting. Fails if the outbound queue is full.
+    pub fn try_publish(&self, topic: &str, msg: SMessage) -> anyhow::Result<()> {
+        topic::validate_topic(topic)?;
+        let envelope = Envelope::from_impulse(&self.source, &Impulse::new(topic, msg));
+        self.outbound
+            .try_send(Frame::Publish(envelope))
+            .map_err(|e| anyhow::anyhow!("Bandy link refused frame: {}", e))
+    }
+
+    /// Sends `msg` and waits for the envelope that answers it. The reply only
+    /// arrives if this client is subscribed to the topic the responder uses.
+    pub async fn request(
+        &self,
+        topic: &str,
+        msg: SMessage,
+        timeout: Duration,
+    ) -> anyhow::Result<Impulse> {
+        topic::validate_topic(topic)?;
+        let replies = self.inbound.rx();
+        let request = Impulse::new(topic, msg);
+        let id = request.id;
+        self.send(request).await?;
+        replies.reply_to(id, timeout).await
+    }
+
+    /// Answers an impulse received from the broker.
+    pub async fn reply(&self, request: &Impulse, topic: &str, msg: SMessage) -> anyhow::Result<()> {
+        topic::validate_topic(topic)?;
+        let mut impulse = Impulse::new(topic, msg);
+        impulse.correlation_id = Some(request.id);
+        self.send(impulse).await
+    }
+
+    async fn send(&self, impulse: Impulse) -> anyhow::Result<()> {
+        self.outbound
+            .send(Frame::Publish(Envelope::from_impulse(
+                &self.source,
+                &impulse,
+            )))
+            .await
+            .map_err(|_| anyhow::anyhow!("Bandy link is shut down"))
+    }
+
+    /// The local synapse that remote impulses are republished on.
+    pub fn synapse(&self) -> &Synapse {
+        &self.inbound
+    }
+}
+
+impl BandyMember for BrokerClient {
+    fn publish(&self, topic: &str, msg: SMessage) -> anyhow::Result<()> {
+        self.try_publish(topic, msg)
+    }
+}
+
+enum Ended {
+    /// Every client handle is gone. Stop for good.
+    Shutdown,
+    /// The socket died. Reconnect.
+    Lost,
+}
+
+async fn run_link(
+    path: PathBuf,
+    config: ClientConfig,
+    mut outbound: mpsc::Receiver<Frame>,
+    subscriptions: Arc<Mutex<BTreeSet<String>>>,
+    inbound: Synapse,
+    state: watch::Sender<LinkState>,
+) {
+    let mut backoff = config.initial_backoff;
+    let mut pending: Option<Frame> = None;
+
+    loop {
+        if let Ok(stream) = UnixStream::connect(&path).await {
+            backoff = config.initial_backoff;
+            state.send_modify(|s| s.connected = true);
+
+            let ended = session(
+                stream,
+                &mut outbound,
+                &mut pending,
+                &subscriptions,
+                &inbound,
+                &state,
+            )
+            .await;
+
+            state.send_modify(|s| {
+                s.connected = false;
+                s.acked.clear();
+            });
+            if let Ended::Shutdown = ended {
+                return;
+            }
+            log::warn!("[BANDY] Link to {} lost. Reconnecting.", path.display());
+        }
+
+        if outbound.is_closed() {
+            return;
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(config.max_backoff);
+    }
+}
+
+async fn session(
+    stream: UnixStream,
+    outbound: &mut mpsc::Receiver<Frame>,
+    pending: &mut Option<Frame>,
+    subscriptions: &Arc<Mutex<BTreeSet<String>>>,
+    inbound: &Synapse,
+    state: &watch::Sender<LinkState>,
+) -> Ended {
+    let (rd, mut wr) = stream.into_split();
+
+    // Replay the registrations first so nothing published after them is missed.
+    let patterns: Vec<String> = subscriptions.lock().unwrap().iter().cloned().collect();
+    for pattern in patterns {
+        if write_frame(&mut wr, &Frame::Subscribe(pattern))
+            .await
+            .is_err()
+        {
+            return Ended::Lost;
+        }
+    }
+    if let Some(frame) = pending.take()
+        && write_frame(&mut wr, &frame).await.is_err()
+    {
+        *pending = Some(frame);
+        return Ended::Lost;
+    }
+
+    let mut reader = tokio::spawn(read_link(rd, inbound.clone(), state.clone()));
+
+    let ended = loop {
+        tokio::select! {
+            _ = &mut reader => break Ended::Lost,
+            frame = outbound.recv() => match frame {
+                None => break Ended::Shutdown,
+                Some(frame) => {
+                    if write_frame(&mut wr, &frame).await.is_err() {
+                        // Hold on to it; it goes out first on the next link.
+                        *pending = Some(frame);
+                        break Ended::Lost;
+                    }
+                }
+            },
+        }
+    };
+
+    reader.abort();
+    ended
+}
+
+async fn read_link(mut rd: OwnedReadHalf, inbound: Synapse, state: watch::Sender<LinkState>) {
+    while let Ok(Some(frame)) = read_frame(&mut rd).await {
+        match frame {
+            Frame::Publish(envelope) => {
+                let (topic, source) = (envelope.topic.clone(), envelope.source.clone());
+                match envelope.into_impulse() {
+                    Some(impulse) => {
+                        inbound.inject(impulse);
+                    }
+                    None => log::debug!(
+                        "[BANDY] Skipping undecodable payload on '{}' from '{}'",
+                        topic,
+                        source
+                    ),
+                }
+            }
+            Frame::Ack(pattern) => {
+                state.send_modify(|s| {
+                    s.acked.insert(pattern);
+                });
+            }
+            Frame::Subscribe(_) | Frame::Unsubscribe(_) => {} // Not ours to handle.
+        }
+    }
+}
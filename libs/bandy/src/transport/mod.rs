@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The Long Nerve.
+//!
+//! Carries impulses between processes over a Unix domain socket.
+//! A `Broker` owns the socket and routes `Publish` frames to every peer
+//! whose subscriptions match. A `BrokerClient` bridges a remote broker into
+//! a local `Synapse`, so the rest of the process never knows the difference.
+//!
+//! Wire format: a big-endian `u32` length followed by a CBOR-encoded `Frame`.
+//! Messages travel inside an `Envelope` (see `envelope`).
+
+mod broker;
+mod client;
+
+pub use broker::{Broker, BrokerConfig};
+pub use client::{BrokerClient, ClientConfig};
+
+use crate::Envelope;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::PathBuf;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Frames larger than this are treated as corruption, not as data.
+pub const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Everything that can cross the socket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Frame {
+    /// Client → Broker: start routing topics matching this filter to me.
+    Subscribe(String),
+    /// Client → Broker: stop routing this filter to me.
+    Unsubscribe(String),
+    /// Broker → Client: the named filter is now live.
+    Ack(String),
+    /// Either direction: an enveloped message. The broker routes on the
+    /// envelope header alone, so it forwards payloads it cannot decode.
+    Publish(Envelope),
+}
+
+/// Where the broker listens when nobody says otherwise.
+/// `BANDY_SOCKET` wins, then `$XDG_RUNTIME_DIR/bandy.sock`, then the temp dir.
+pub fn default_socket_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("BANDY_SOCKET") {
+        return PathBuf::from(path);
+    }
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("bandy.sock")
+}
+
+/// Serializes a frame with its length prefix into a single buffer,
+/// so it hits the socket in one write.
+pub fn encode_frame(frame: &Frame) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; 4];
+    ciborium::into_writer(frame, &mut buf).map_err(io::Error::other)?;
+
+    let len = buf.len() - 4;
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Frame of {} bytes exceeds limit", len),
+        ));
+    }
+    buf[..4].copy_from_slice(&(len as u32).to_be_bytes());
+    Ok(buf)
+}
+
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, frame: &Frame) -> io::Result<()> {
+    let buf = encode_frame(frame)?;
+    writer.write_all(&buf).await?;
+    writer.flush().await
+}
+
+/// Reads one frame. Returns `Ok(None)` on a clean EOF between frames.
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Option<Frame>> {
+    let mut len_buf = [0u8; 4];
+    match reader.read_exact(&mut len_buf).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let len = u32::from_be_bytes(len_buf) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Frame of {} bytes exceeds limit", len),
+        ));
+    }
+
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).await?;
+    ciborium::from_reader(payload.as_slice())
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
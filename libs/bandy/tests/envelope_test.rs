@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Golden files in `tests/golden/` are frozen encodings written by earlier
+//! trees. Never regenerate them; add a new file when the format moves.
+
+use bandy::envelope::ENVELOPE_VERSION;
+use bandy::{Envelope, MatrixEvent, Payload, SMessage, Synapse};
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn golden(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("Missing golden file {}: {}", path.display(), e))
+}
+
+#[test]
+fn test_decode_v0_bare_message() {
+    let env = Envelope::decode(&golden("v0_bare_log.cbor")).unwrap();
+    assert_eq!(env.version, 0);
+    assert_eq!(env.topic, "system/log");
+    assert_eq!(env.correlation_id, None);
+    match env.message() {
+        Some(SMessage::Log {
+            level,
+            source,
+            content,
+        }) => {
+            assert_eq!(level, "INFO");
+            assert_eq!(source, "CORTEX");
+            assert_eq!(content, "Deep subconscious online");
+        }
+        other => panic!("Unexpected payload: {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_v1_envelopes() {
+    let env = Envelope::decode(&golden("v1_log.cbor")).unwrap();
+    assert_eq!(env.version, 1);
+    assert_eq!(env.msg_id, 0x5EED_0000_0000_0001);
+    assert_eq!(env.timestamp, 1_790_000_000_000);
+    assert_eq!(env.source, "lumen");
+    assert_eq!(env.topic, "system/log");
+    assert!(matches!(env.message(), Some(SMessage::Log { .. })));
+
+    let reply = Envelope::decode(&golden("v1_reply.cbor")).unwrap();
+    assert!(env.is_answered_by(&reply));
+    assert!(matches!(
+        reply.message(),
+        Some(SMessage::Matrix(MatrixEvent::SectorFocused { target, .. })) if target == "euclase"
+    ));
+}
+
+#[test]
+fn test_unknown_variant_is_preserved() {
+    let bytes = golden("v1_unknown_variant.cbor");
+    let env = Envelope::decode(&bytes).unwrap();
+
+    assert_eq!(env.topic, "euclase/holo");
+    assert_eq!(env.source, "stria");
+    assert!(env.message().is_none());
+    assert!(matches!(env.payload, Payload::Unknown(_)));
+    assert!(env.clone().into_impulse().is_none());
+
+    // Forwarding must not lose a single byte of what we did not understand.
+    assert_eq!(env.encode().unwrap(), bytes);
+}
+
+#[test]
+fn test_future_envelope_fields_are_ignored() {
+    let env = Envelope::decode(&golden("v2_extra_fields.cbor")).unwrap();
+    assert_eq!(env.version, 2);
+    assert_eq!(env.topic, "system/ping");
+    // Fields the newer writer omitted fall back to defaults.
+    assert_eq!(env.source, "");
+    assert_eq!(env.correlation_id, None);
+    assert!(matches!(env.message(), Some(SMessage::Ping)));
+}
+
+#[test]
+fn test_round_trip_and_reply() {
+    let request = Envelope::new(
+        "vein",
+        "matrix/focus",
+        SMessage::Matrix(MatrixEvent::FocusSector("bandy".into())),
+    );
+    assert_eq!(request.version, ENVELOPE_VERSION);
+
+    let decoded = Envelope::decode(&request.encode().unwrap()).unwrap();
+    assert_eq!(decoded.msg_id, request.msg_id);
+    assert_eq!(decoded.timestamp, request.timestamp);
+
+    let reply = decoded.reply("matrix", "vein/context", SMessage::Ping);
+    assert_ne!(reply.msg_id, request.msg_id);
+    assert!(request.is_answered_by(&reply));
+    assert!(!reply.is_answered_by(&request));
+
+    let impulse = reply.into_impulse().unwrap();
+    assert_eq!(impulse.correlation_id, Some(request.msg_id));
+    assert_eq!(&*impulse.topic, "vein/context");
+}
+
+#[test]
+fn test_garbage_is_rejected() {
+    assert!(Envelope::decode(&[0xff, 0x00, 0x13]).is_err());
+}
+
+#[tokio::test]
+async fn test_synapse_request_reply() {
+    let synapse = Synapse::new();
+    let mut service = synapse.subscribe("matrix/focus").unwrap();
+
+    let responder = synapse.clone();
+    tokio::spawn(async move {
+        let request = service.recv().await.unwrap();
+        // Noise with a foreign correlation id must not be mistaken for the answer.
+        let mut stray = request.clone();
+        stray.id = request.id.wrapping_add(1);
+        responder
+            .reply(&stray, "vein/context", SMessage::NoOp)
+            .unwrap();
+        responder
+            .reply(
+                &request,
+                "vein/context",
+                SMessage::AiToken("focused".into()),
+            )
+            .unwrap();
+    });
+
+    let reply = synapse
+        .request("matrix/focus", SMessage::Ping, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert!(matches!(reply.msg, SMessage::AiToken(ref t) if t == "focused"));
+
+    // Nobody answers this one.
+    let err = synapse
+        .request("void/echo", SMessage::Ping, Duration::from_millis(50))
+        .await;
+    assert!(err.is_err());
+}
@@ -0,0 +1,318 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use bandy::graph::{Direction, EdgeKey};
+use bandy::{GraphDelta, SpatialEdge, SpatialGraph, SpatialNode};
+use proptest::prelude::*;
+use std::path::PathBuf;
+
+fn node(id: &str) -> SpatialNode {
+    SpatialNode {
+        id: id.to_string(),
+        kind: "crate".to_string(),
+        path: PathBuf::from(format!("libs/{}", id)),
+    }
+}
+
+fn edge(from: &str, to: &str, weight: f32) -> SpatialEdge {
+    SpatialEdge {
+        from: from.to_string(),
+        to: to.to_string(),
+        relation: "depends_on".to_string(),
+        weight,
+    }
+}
+
+fn key(from: &str, to: &str) -> EdgeKey {
+    EdgeKey::of(&edge(from, to, 0.0))
+}
+
+/// lumen -> vein -> bandy <- elessar, lumen -> bandy (expensive), plus an island.
+fn workspace() -> SpatialGraph {
+    SpatialGraph::from_snapshot(
+        ["lumen", "vein", "bandy", "elessar", "resonance", "junct"]
+            .map(node)
+            .to_vec(),
+        vec![
+            edge("lumen", "vein", 1.0),
+            edge("vein", "bandy", 2.0),
+            edge("elessar", "bandy", 1.0),
+            edge("lumen", "bandy", 5.0),
+            edge("junct", "resonance", 1.0),
+        ],
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_neighbours() {
+    let g = workspace();
+    assert_eq!(
+        g.neighbours("lumen", Direction::Outgoing),
+        ["bandy", "vein"]
+    );
+    assert_eq!(
+        g.neighbours("bandy", Direction::Incoming),
+        ["elessar", "lumen", "vein"]
+    );
+    assert_eq!(g.neighbours("vein", Direction::Both), ["bandy", "lumen"]);
+    assert!(g.neighbours("ghost", Direction::Both).is_empty());
+}
+
+#[test]
+fn test_shortest_path_prefers_cheap_detour() {
+    let mut g = workspace();
+    let (cost, path) = g.shortest_path("lumen", "bandy").unwrap();
+    assert_eq!(cost, 3.0);
+    assert_eq!(path, ["lumen", "vein", "bandy"]);
+
+    g.apply(GraphDelta::UpdateWeight {
+        edge: key("lumen", "bandy"),
+        weight: 2.5,
+    })
+    .unwrap();
+    let (cost, path) = g.shortest_path("lumen", "bandy").unwrap();
+    assert_eq!(cost, 2.5);
+    assert_eq!(path, ["lumen", "bandy"]);
+
+    assert_eq!(g.shortest_path("bandy", "bandy").unwrap().1, ["bandy"]);
+    // Edges are directed.
+    assert!(g.shortest_path("bandy", "lumen").is_none());
+    assert!(g.shortest_path("lumen", "resonance").is_none());
+}
+
+#[test]
+fn test_components_and_k_hop() {
+    let mut g = workspace();
+    assert_eq!(
+        g.components(),
+        vec![
+            vec!["bandy", "elessar", "lumen", "vein"],
+            vec!["junct", "resonance"],
+        ]
+    );
+
+    let sub = g.k_hop("vein", 1).unwrap();
+    let (nodes, edges) = sub.snapshot();
+    let ids: Vec<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+    assert_eq!(ids, ["bandy", "lumen", "vein"]);
+    // The induced subgraph keeps the shortcut between two kept nodes.
+    assert_eq!(edges.len(), 3);
+    assert_eq!(g.k_hop("vein", 2).unwrap().node_count(), 4);
+    assert_eq!(g.k_hop("vein", 0).unwrap().node_count(), 1);
+    assert!(g.k_hop("ghost", 3).is_none());
+
+    g.apply(GraphDelta::RemoveNode("bandy".into())).unwrap();
+    assert_eq!(g.edge_count(), 2);
+    assert_eq!(g.components().len(), 3);
+}
+
+#[test]
+fn test_invalid_deltas_are_rejected() {
+    let mut g = workspace();
+    let before = g.snapshot();
+
+    assert!(
+        g.apply(GraphDelta::AddEdge(edge("lumen", "ghost", 1.0)))
+            .is_err()
+    );
+    assert!(
+        g.apply(GraphDelta::AddEdge(edge("lumen", "vein", -1.0)))
+            .is_err()
+    );
+    assert!(
+        g.apply(GraphDelta::AddEdge(edge("lumen", "vein", f32::NAN)))
+            .is_err()
+    );
+    assert!(g.apply(GraphDelta::RemoveNode("ghost".into())).is_err());
+    assert!(
+        g.apply(GraphDelta::RemoveEdge(key("bandy", "lumen")))
+            .is_err()
+    );
+    assert!(
+        g.apply(GraphDelta::UpdateWeight {
+            edge: key("bandy", "lumen"),
+            weight: 1.0
+        })
+        .is_err()
+    );
+    assert_eq!(g.snapshot(), before);
+}
+
+#[test]
+fn test_diff_is_minimal() {
+    let old = workspace();
+    let mut new = old.clone();
+    new.apply(GraphDelta::RemoveNode("junct".into())).unwrap();
+    new.apply(GraphDelta::UpdateWeight {
+        edge: key("vein", "bandy"),
+        weight: 0.5,
+    })
+    .unwrap();
+    new.apply(GraphDelta::AddNode(node("lux"))).unwrap();
+    new.apply(GraphDelta::AddEdge(edge("lux", "bandy", 1.0)))
+        .unwrap();
+
+    // The junct -> resonance edge goes with its node; nothing else is resent.
+    assert_eq!(
+        old.diff(&new),
+        vec![
+            GraphDelta::RemoveNode("junct".into()),
+            GraphDelta::AddNode(node("lux")),
+            GraphDelta::AddEdge(edge("lux", "bandy", 1.0)),
+            GraphDelta::UpdateWeight {
+                edge: key("vein", "bandy"),
+                weight: 0.5
+            },
+        ]
+    );
+    assert!(new.diff(&new).is_empty());
+}
+
+#[test]
+fn test_sync_tracks_revisions() {
+    let mut publisher = SpatialGraph::new();
+    let mut receiver = SpatialGraph::new();
+    let (nodes, edges) = workspace().snapshot();
+
+    let (base, deltas) = publisher.sync(nodes.clone(), edges).unwrap();
+    assert_eq!(base, 0);
+    receiver.apply_diff(base, deltas).unwrap();
+
+    let (base, deltas) = publisher.sync(nodes, vec![]).unwrap();
+    assert_eq!(base, 1);
+    assert_eq!(deltas.len(), 5);
+
+    // Replaying a stale batch is refused.
+    assert!(receiver.apply_diff(0, deltas.clone()).is_err());
+    receiver.apply_diff(base, deltas).unwrap();
+    assert_eq!(receiver.revision(), 2);
+    assert_eq!(receiver.snapshot(), publisher.snapshot());
+}
+
+const IDS: [&str; 6] = ["a", "b", "c", "d", "e", "f"];
+
+prop_compose! {
+    fn arb_graph()(
+        present in prop::collection::vec(any::<bool>(), IDS.len()),
+        kinds in prop::collection::vec(0..2u8, IDS.len()),
+        edges in prop::collection::vec((0..IDS.len(), 0..IDS.len(), 0..2u8, 0..8u8), 0..20),
+    ) -> SpatialGraph {
+        let nodes: Vec<SpatialNode> = IDS
+            .iter()
+            .zip(&present)
+            .zip(&kinds)
+            .filter(|((_, present), _)| **present)
+            .map(|((id, _), kind)| SpatialNode {
+                kind: if *kind == 0 { "crate".into() } else { "fn".into() },
+                ..node(id)
+            })
+            .collect();
+        let edges: Vec<SpatialEdge> = edges
+            .into_iter()
+            .filter(|(from, to, _, _)| present[*from] && present[*to])
+            .map(|(from, to, relation, weight)| SpatialEdge {
+                relation: if relation == 0 { "calls".into() } else { "imports".into() },
+                ..edge(IDS[from], IDS[to], weight as f32 / 2.0)
+            })
+            .collect();
+        SpatialGraph::from_snapshot(nodes, edges).unwrap()
+    }
+}
+
+/// Cheapest path cost by relaxing every edge until nothing improves.
+fn bellman_ford(g: &SpatialGraph, from: &str, to: &str) -> Option<f64> {
+    let (nodes, edges) = g.snapshot();
+    let mut dist: std::collections::HashMap<&str, f64> = nodes
+        .iter()
+        .map(|n| (n.id.as_str(), f64::INFINITY))
+        .collect();
+    *dist.get_mut(from)? = 0.0;
+    for _ in 0..nodes.len() {
+        for e in &edges {
+            let via = dist[e.from.as_str()] + e.weight as f64;
+            if via < dist[e.to.as_str()] {
+                dist.insert(&e.to, via);
+            }
+        }
+    }
+    dist.get(to).copied().filter(|d| d.is_finite())
+}
+
+proptest! {
+    #[test]
+    fn prop_diff_reproduces_snapshot(old in arb_graph(), new in arb_graph()) {
+        let mut patched = old.clone();
+        patched.apply_diff(old.revision(), old.diff(&new)).unwrap();
+        prop_assert_eq!(patched.snapshot(), new.snapshot());
+        prop_assert!(patched.diff(&new).is_empty());
+    }
+
+    #[test]
+    fn prop_diff_survives_the_wire(old in arb_graph(), new in arb_graph()) {
+        let mut buf = Vec::new();
+        ciborium::into_writer(&old.diff(&new), &mut buf).unwrap();
+        let deltas: Vec<GraphDelta> = ciborium::from_reader(buf.as_slice()).unwrap();
+
+        let mut patched = old.clone();
+        patched.apply_diff(0, deltas).unwrap();
+        prop_assert_eq!(patched.snapshot(), new.snapshot());
+    }
+
+    #[test]
+    fn prop_diff_touches_only_what_changed(old in arb_graph(), new in arb_graph()) {
+        let (old_nodes, old_edges) = old.snapshot();
+        let (new_nodes, new_edges) = new.snapshot();
+        let changed_nodes = old_nodes.iter().filter(|n| new.node(&n.id).is_none()).count()
+            + new_nodes.iter().filter(|n| old.node(&n.id) != Some(n)).count();
+        let changed_edges = old_edges.iter().filter(|e| new.weight(&EdgeKey::of(e)).is_none()).count()
+            + new_edges.iter().filter(|e| old.weight(&EdgeKey::of(e)) != Some(e.weight)).count();
+
+        // At most one delta per changed element; fewer when edges cascade.
+        prop_assert!(old.diff(&new).len() <= changed_nodes + changed_edges);
+    }
+
+    #[test]
+    fn prop_dijkstra_matches_brute_force(g in arb_graph(), from in 0..IDS.len(), to in 0..IDS.len()) {
+        let found = g.shortest_path(IDS[from], IDS[to]);
+        prop_assert_eq!(found.as_ref().map(|(cost, _)| *cost), bellman_ford(&g, IDS[from], IDS[to]));
+
+        if let Some((cost, path)) = found {
+            // The path is real and costs what it claims.
+            let mut total = 0.0;
+            for hop in path.windows(2) {
+                let cheapest = g
+                    .snapshot()
+                    .1
+                    .into_iter()
+                    .filter(|e| e.from == hop[0] && e.to == hop[1])
+                    .map(|e| e.weight as f64)
+                    .fold(f64::INFINITY, f64::min);
+                prop_assert!(cheapest.is_finite());
+                total += cheapest;
+            }
+            prop_assert_eq!(total, cost);
+        }
+    }
+
+    #[test]
+    fn prop_components_partition_nodes(g in arb_graph()) {
+        let mut all: Vec<String> = g.components().into_iter().flatten().collect();
+        all.sort();
+        let ids: Vec<String> = g.snapshot().0.into_iter().map(|n| n.id).collect();
+        prop_assert_eq!(all, ids);
+    }
+}
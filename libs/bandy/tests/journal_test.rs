@@ -0,0 +1,355 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use bandy::journal::Retention;
+use bandy::{Envelope, Journal, JournalConfig, Position, SMessage, Synapse};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+fn prompt(i: usize) -> Envelope {
+    Envelope::new("test", "vein/prompt", SMessage::UserPrompt(i.to_string()))
+}
+
+fn text_of(env: &Envelope) -> &str {
+    match env.message() {
+        Some(SMessage::UserPrompt(t)) => t,
+        other => panic!("Unexpected message: {:?}", other),
+    }
+}
+
+fn files_with(dir: &Path, ext: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == ext))
+        .collect();
+    files.sort();
+    files
+}
+
+fn small_segments() -> JournalConfig {
+    JournalConfig {
+        segment_bytes: 256,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_append_and_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let mut journal = Journal::open(dir.path(), JournalConfig::default()).unwrap();
+        for i in 0..5 {
+            assert_eq!(journal.append(&prompt(i)).unwrap(), i as u64);
+        }
+        journal.sync().unwrap();
+    }
+
+    let mut journal = Journal::open(dir.path(), JournalConfig::default()).unwrap();
+    assert_eq!(journal.first_offset(), 0);
+    assert_eq!(journal.next_offset(), 5);
+    assert_eq!(journal.append(&prompt(5)).unwrap(), 5);
+
+    let records = journal.read_range(2, 100).unwrap();
+    let offsets: Vec<u64> = records.iter().map(|(o, _)| *o).collect();
+    assert_eq!(offsets, vec![2, 3, 4, 5]);
+    assert_eq!(text_of(&records[0].1), "2");
+    assert_eq!(text_of(&records[3].1), "5");
+}
+
+#[test]
+fn test_rotation_spans_segments() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut journal = Journal::open(dir.path(), small_segments()).unwrap();
+    for i in 0..40 {
+        journal.append(&prompt(i)).unwrap();
+    }
+    assert!(journal.segment_count() > 2);
+    assert_eq!(files_with(dir.path(), "seg").len(), journal.segment_count());
+    drop(journal);
+
+    let journal = Journal::open(dir.path(), small_segments()).unwrap();
+    assert_eq!(journal.next_offset(), 40);
+
+    // A range that starts and ends in the middle of different segments.
+    let records = journal.read_range(7, 33).unwrap();
+    assert_eq!(records.len(), 26);
+    for (offset, env) in &records {
+        assert_eq!(text_of(env), offset.to_string());
+    }
+}
+
+#[test]
+fn test_retention_by_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = JournalConfig {
+        segment_bytes: 256,
+        retention: Retention {
+            max_bytes: Some(1024),
+            max_age: None,
+        },
+    };
+    let mut journal = Journal::open(dir.path(), config).unwrap();
+    for i in 0..100 {
+        journal.append(&prompt(i)).unwrap();
+    }
+
+    // Bounded by the limit plus the segment that is still being written.
+    assert!(journal.total_bytes() <= 1024 + 256);
+    assert!(journal.first_offset() > 0);
+    assert_eq!(
+        journal.resolve(Position::Offset(0)).unwrap(),
+        journal.first_offset()
+    );
+
+    let records = journal.read_range(0, u64::MAX).unwrap();
+    assert_eq!(records.first().unwrap().0, journal.first_offset());
+    assert_eq!(records.last().unwrap().0, 99);
+}
+
+#[test]
+fn test_retention_by_age() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = JournalConfig {
+        segment_bytes: 256,
+        retention: Retention {
+            max_bytes: None,
+            max_age: Some(Duration::from_millis(50)),
+        },
+    };
+    let mut journal = Journal::open(dir.path(), config).unwrap();
+    for i in 0..20 {
+        journal.append(&prompt(i)).unwrap();
+    }
+    let before = journal.segment_count();
+    assert!(before > 1);
+
+    std::thread::sleep(Duration::from_millis(100));
+    journal.enforce_retention().unwrap();
+
+    // Everything sealed has expired; only the active segment survives.
+    assert_eq!(journal.segment_count(), 1);
+    assert_eq!(files_with(dir.path(), "seg").len(), 1);
+    assert_eq!(journal.next_offset(), 20);
+}
+
+#[test]
+fn test_retention_on_open() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut journal = Journal::open(dir.path(), small_segments()).unwrap();
+    for i in 0..20 {
+        journal.append(&prompt(i)).unwrap();
+    }
+    assert!(journal.segment_count() > 1);
+    drop(journal);
+
+    // The history expired while nothing had the journal open.
+    std::thread::sleep(Duration::from_millis(100));
+    let config = JournalConfig {
+        retention: Retention {
+            max_bytes: None,
+            max_age: Some(Duration::from_millis(50)),
+        },
+        ..small_segments()
+    };
+    let journal = Journal::open(dir.path(), config).unwrap();
+    assert_eq!(journal.segment_count(), 1);
+    assert_eq!(files_with(dir.path(), "seg").len(), 1);
+    assert_eq!(journal.next_offset(), 20);
+}
+
+#[test]
+fn test_torn_tail_is_truncated() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut journal = Journal::open(dir.path(), JournalConfig::default()).unwrap();
+    for i in 0..3 {
+        journal.append(&prompt(i)).unwrap();
+    }
+    let intact = journal.total_bytes();
+    drop(journal);
+
+    // A crash halfway through the fourth append.
+    let seg = files_with(dir.path(), "seg").pop().unwrap();
+    let mut file = OpenOptions::new().append(true).open(&seg).unwrap();
+    file.write_all(&[0, 0, 0, 40, 0xde, 0xad]).unwrap();
+    drop(file);
+
+    let mut journal = Journal::open(dir.path(), JournalConfig::default()).unwrap();
+    assert_eq!(journal.next_offset(), 3);
+    assert_eq!(fs::metadata(&seg).unwrap().len(), intact);
+
+    assert_eq!(journal.append(&prompt(3)).unwrap(), 3);
+    let records = journal.read_range(0, u64::MAX).unwrap();
+    assert_eq!(records.len(), 4);
+    assert_eq!(text_of(&records[3].1), "3");
+}
+
+#[test]
+fn test_corrupt_record_is_truncated() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut journal = Journal::open(dir.path(), JournalConfig::default()).unwrap();
+    let mut ends = Vec::new();
+    for i in 0..4 {
+        journal.append(&prompt(i)).unwrap();
+        ends.push(journal.total_bytes());
+    }
+    drop(journal);
+
+    // Flip a byte inside the last record's payload.
+    let seg = files_with(dir.path(), "seg").pop().unwrap();
+    let mut bytes = fs::read(&seg).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    fs::write(&seg, &bytes).unwrap();
+
+    let journal = Journal::open(dir.path(), JournalConfig::default()).unwrap();
+    assert_eq!(journal.next_offset(), 3);
+    assert_eq!(fs::metadata(&seg).unwrap().len(), ends[2]);
+}
+
+#[test]
+fn test_lost_index_is_rebuilt() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut journal = Journal::open(dir.path(), JournalConfig::default()).unwrap();
+    for i in 0..6 {
+        journal.append(&prompt(i)).unwrap();
+    }
+    drop(journal);
+
+    // The index lost its last entries and half of another.
+    let idx = files_with(dir.path(), "idx").pop().unwrap();
+    let bytes = fs::read(&idx).unwrap();
+    fs::write(&idx, &bytes[..bytes.len() - 30]).unwrap();
+
+    let journal = Journal::open(dir.path(), JournalConfig::default()).unwrap();
+    assert_eq!(journal.next_offset(), 6);
+    assert_eq!(fs::metadata(&idx).unwrap().len(), bytes.len() as u64);
+    assert_eq!(text_of(&journal.read_range(5, 6).unwrap()[0].1), "5");
+}
+
+#[test]
+fn test_resolve_timestamp() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut journal = Journal::open(dir.path(), small_segments()).unwrap();
+    for i in 0..10 {
+        journal.append(&prompt(i)).unwrap();
+    }
+    std::thread::sleep(Duration::from_millis(20));
+    let cutoff = chrono::Utc::now().timestamp_millis();
+    for i in 10..20 {
+        journal.append(&prompt(i)).unwrap();
+    }
+
+    assert_eq!(journal.resolve(Position::Timestamp(cutoff)).unwrap(), 10);
+    assert_eq!(journal.resolve(Position::Timestamp(0)).unwrap(), 0);
+    assert_eq!(journal.resolve(Position::Timestamp(i64::MAX)).unwrap(), 20);
+    assert_eq!(journal.resolve(Position::End).unwrap(), 20);
+}
+
+#[tokio::test]
+async fn test_synapse_replays_then_goes_live() {
+    let dir = tempfile::tempdir().unwrap();
+    let journal = Journal::open(dir.path(), small_segments()).unwrap();
+    let synapse = Synapse::with_journal(journal, "vein/#").unwrap();
+
+    for i in 0..10 {
+        synapse
+            .publish("vein/prompt", SMessage::UserPrompt(i.to_string()))
+            .unwrap();
+        // Not recorded, and not replayed.
+        synapse.publish("system/ping", SMessage::Ping).unwrap();
+    }
+
+    let mut late = synapse
+        .subscribe_from("vein/prompt", Position::Offset(4))
+        .unwrap();
+    synapse
+        .publish("vein/prompt", SMessage::UserPrompt("live".into()))
+        .unwrap();
+
+    for i in 4..10 {
+        let impulse = late.recv().await.unwrap();
+        assert_eq!(impulse.offset, Some(i));
+        assert!(matches!(impulse.msg, SMessage::UserPrompt(ref t) if *t == i.to_string()));
+    }
+    let live = late.recv().await.unwrap();
+    assert_eq!(live.offset, Some(10));
+    assert!(matches!(live.msg, SMessage::UserPrompt(ref t) if t == "live"));
+    assert!(late.try_recv().is_err());
+
+    // Only live traffic from the end.
+    let mut tail = synapse.subscribe_from("vein/#", Position::End).unwrap();
+    assert!(tail.try_recv().is_err());
+
+    // Without a journal there is nothing to replay.
+    assert!(
+        Synapse::new()
+            .subscribe_from("#", Position::Beginning)
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_cancelled_recv_keeps_the_replay() {
+    let dir = tempfile::tempdir().unwrap();
+    let journal = Journal::open(dir.path(), small_segments()).unwrap();
+    let synapse = Synapse::with_journal(journal, "vein/#").unwrap();
+    for i in 0..600 {
+        synapse
+            .publish("vein/prompt", SMessage::UserPrompt(i.to_string()))
+            .unwrap();
+    }
+
+    let mut late = synapse
+        .subscribe_from("vein/prompt", Position::Beginning)
+        .unwrap();
+    // Give up on the first read while it is still on the blocking pool.
+    let _ = tokio::time::timeout(Duration::ZERO, late.recv()).await;
+    for i in 0..600 {
+        let impulse = late.recv().await.unwrap();
+        assert_eq!(impulse.offset, Some(i));
+    }
+    assert!(late.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_synapse_replays_from_timestamp_after_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let cutoff = {
+        let journal = Journal::open(dir.path(), JournalConfig::default()).unwrap();
+        let synapse = Synapse::with_journal(journal, "#").unwrap();
+        synapse.fire(SMessage::UserPrompt("old".into()));
+        std::thread::sleep(Duration::from_millis(20));
+        let cutoff = chrono::Utc::now().timestamp_millis();
+        synapse.fire(SMessage::UserPrompt("new".into()));
+        synapse.sync_journal().unwrap();
+        cutoff
+    };
+
+    let journal = Journal::open(dir.path(), JournalConfig::default()).unwrap();
+    let synapse = Synapse::with_journal(journal, "#").unwrap();
+    let mut sub = synapse
+        .subscribe_from("vein/#", Position::Timestamp(cutoff))
+        .unwrap();
+
+    let impulse = sub.recv().await.unwrap();
+    assert_eq!(impulse.offset, Some(1));
+    assert_eq!(&*impulse.topic, "vein/prompt");
+    assert!(matches!(impulse.msg, SMessage::UserPrompt(ref t) if t == "new"));
+    assert!(sub.try_recv().is_err());
+}
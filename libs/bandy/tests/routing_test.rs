@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use bandy::synapse::SYNAPSE_CAPACITY;
+use bandy::{BandyMember, MatrixEvent, SMessage, Synapse};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+fn prompt(text: &str) -> SMessage {
+    SMessage::UserPrompt(text.to_string())
+}
+
+fn text_of(msg: &SMessage) -> &str {
+    match msg {
+        SMessage::UserPrompt(t) => t,
+        other => panic!("Unexpected message: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_subscribe_filters_by_pattern() {
+    let synapse = Synapse::new();
+    let mut audio = synapse.subscribe("resonance/+/chunk").unwrap();
+    let mut vein = synapse.subscribe("vein/#").unwrap();
+
+    synapse.publish("vein/prompt", prompt("one")).unwrap();
+    synapse
+        .publish("resonance/mic/chunk", prompt("two"))
+        .unwrap();
+    synapse
+        .publish("vein/deep/thought", prompt("three"))
+        .unwrap();
+
+    let a = audio.recv().await.unwrap();
+    assert_eq!(&*a.topic, "resonance/mic/chunk");
+    assert_eq!(text_of(&a.msg), "two");
+    assert!(matches!(audio.try_recv(), Err(TryRecvError::Empty)));
+
+    assert_eq!(text_of(&vein.recv().await.unwrap().msg), "one");
+    assert_eq!(text_of(&vein.recv().await.unwrap().msg), "three");
+    assert!(matches!(vein.try_recv(), Err(TryRecvError::Empty)));
+}
+
+#[tokio::test]
+async fn test_fire_uses_default_topic() {
+    let synapse = Synapse::new();
+    let mut matrix = synapse.subscribe("matrix/#").unwrap();
+    let mut everything = synapse.rx();
+
+    synapse.fire(SMessage::Ping);
+    synapse.fire(SMessage::Matrix(MatrixEvent::FocusSector("euclase".into())));
+
+    let impulse = matrix.recv().await.unwrap();
+    assert_eq!(&*impulse.topic, "matrix/event");
+    assert!(matches!(
+        impulse.msg,
+        SMessage::Matrix(MatrixEvent::FocusSector(_))
+    ));
+
+    assert_eq!(&*everything.recv().await.unwrap().topic, "system/ping");
+    assert_eq!(&*everything.recv().await.unwrap().topic, "matrix/event");
+}
+
+#[test]
+fn test_bandy_member_delivers() {
+    let synapse = Synapse::new();
+    let mut sub = synapse.subscribe("system/audio/input").unwrap();
+
+    let member: &dyn BandyMember = &synapse;
+    member
+        .publish("system/audio/input", SMessage::Ping)
+        .unwrap();
+    assert!(member.publish("system/+", SMessage::Ping).is_err());
+
+    assert!(matches!(sub.try_recv().unwrap().msg, SMessage::Ping));
+}
+
+#[test]
+fn test_unsubscribe_on_drop() {
+    let synapse = Synapse::new();
+    assert_eq!(synapse.subscriber_count(), 0);
+
+    let a = synapse.subscribe("vein/#").unwrap();
+    let b = synapse.rx();
+    assert_eq!(synapse.subscriber_count(), 2);
+    assert_eq!(synapse.publish("vein/prompt", SMessage::Ping).unwrap(), 2);
+
+    drop(a);
+    assert_eq!(synapse.subscriber_count(), 1);
+
+    drop(b);
+    assert_eq!(synapse.subscriber_count(), 0);
+    // Publishing into the void is not an error.
+    assert_eq!(synapse.publish("vein/prompt", SMessage::Ping).unwrap(), 0);
+}
+
+#[test]
+fn test_lagging_receiver_skips_oldest() {
+    let synapse = Synapse::new();
+    let mut sub = synapse.subscribe("vein/prompt").unwrap();
+
+    let overflow = 10;
+    for i in 0..SYNAPSE_CAPACITY + overflow {
+        synapse
+            .publish("vein/prompt", prompt(&i.to_string()))
+            .unwrap();
+    }
+
+    match sub.try_recv() {
+        Err(TryRecvError::Lagged(n)) => assert_eq!(n as usize, overflow),
+        other => panic!("Expected lag, got {:?}", other),
+    }
+
+    // Resumes at the oldest impulse still retained by the channel.
+    let next = sub.try_recv().unwrap();
+    assert_eq!(text_of(&next.msg), overflow.to_string());
+}
+
+#[tokio::test]
+async fn test_lag_counts_unmatched_traffic() {
+    let synapse = Synapse::new();
+    let mut sub = synapse.subscribe("matrix/#").unwrap();
+
+    synapse.publish("matrix/event", prompt("early")).unwrap();
+    for _ in 0..SYNAPSE_CAPACITY {
+        synapse
+            .publish("resonance/spectrum", SMessage::Ping)
+            .unwrap();
+    }
+    synapse.publish("matrix/event", prompt("late")).unwrap();
+
+    // The noise on other topics pushed our early impulse out of the channel.
+    assert!(matches!(sub.recv().await, Err(RecvError::Lagged(2))));
+    assert_eq!(text_of(&sub.recv().await.unwrap().msg), "late");
+}
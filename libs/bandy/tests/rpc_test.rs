@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use bandy::rpc::{self, BoxFuture, Call, RpcMessage};
+use bandy::{Rpc, RpcError, SMessage, Service, Synapse};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+const PATIENT: Duration = Duration::from_secs(5);
+const BRIEF: Duration = Duration::from_millis(50);
+
+/// Counts how many handler futures were dropped before they finished.
+#[derive(Default)]
+struct Math {
+    abandoned: Arc<AtomicUsize>,
+}
+
+struct Abandoned(Arc<AtomicUsize>, bool);
+
+impl Drop for Abandoned {
+    fn drop(&mut self) {
+        if !self.1 {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl Service for Math {
+    fn call(self: Arc<Self>, call: Call) -> BoxFuture<Result<Vec<u8>, RpcError>> {
+        Box::pin(async move {
+            match call.method.as_str() {
+                "add" => {
+                    let (a, b): (i64, i64) = call.args()?;
+                    rpc::encode(&(a + b))
+                }
+                // Answers after `ms`, so calls can overlap.
+                "echo_after" => {
+                    let (value, ms): (String, u64) = call.args()?;
+                    tokio::time::sleep(Duration::from_millis(ms)).await;
+                    rpc::encode(&value)
+                }
+                "hang" => {
+                    let mut marker = Abandoned(self.abandoned.clone(), false);
+                    tokio::time::sleep(Duration::from_secs(3600)).await;
+                    marker.1 = true;
+                    rpc::encode(&())
+                }
+                "divide" => {
+                    let (a, b): (i64, i64) = call.args()?;
+                    if b == 0 {
+                        return Err(RpcError::Failed("division by zero".into()));
+                    }
+                    rpc::encode(&(a / b))
+                }
+                "explode" => panic!("core breach"),
+                _ => Err(call.no_handler()),
+            }
+        })
+    }
+}
+
+fn setup() -> (Rpc, Arc<Math>) {
+    let rpc = Rpc::new(Synapse::new());
+    let math = Arc::new(Math::default());
+    (rpc, math)
+}
+
+#[tokio::test]
+async fn test_call_round_trip() {
+    let (rpc, math) = setup();
+    let _reg = rpc.register("math", math).unwrap();
+
+    let sum: i64 = rpc.call("math", "add", &(40, 2), PATIENT).await.unwrap();
+    assert_eq!(sum, 42);
+
+    let err = rpc
+        .call::<_, i64>("math", "divide", &(1, 0), PATIENT)
+        .await
+        .unwrap_err();
+    assert_eq!(err, RpcError::Failed("division by zero".into()));
+
+    // Wrong argument shape is a codec error, not a crash.
+    let err = rpc
+        .call::<_, i64>("math", "add", &"forty-two", PATIENT)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RpcError::Codec(_)));
+}
+
+#[tokio::test]
+async fn test_no_handler() {
+    let (rpc, math) = setup();
+
+    // Nobody serves it, so nobody answers.
+    let err = rpc
+        .call::<_, i64>("math", "add", &(1, 2), BRIEF)
+        .await
+        .unwrap_err();
+    assert_eq!(err, RpcError::Timeout(BRIEF));
+
+    let reg = rpc.register("math", math.clone()).unwrap();
+    assert!(rpc.register("math", math.clone()).is_err());
+    assert!(rpc.register("bad/name", math.clone()).is_err());
+
+    let err = rpc
+        .call::<_, ()>("math", "sqrt", &4, PATIENT)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        RpcError::NoHandler {
+            service: "math".into(),
+            method: "sqrt".into()
+        }
+    );
+
+    drop(reg);
+    assert!(!rpc.is_registered("math"));
+    assert!(matches!(
+        rpc.call::<_, i64>("math", "add", &(1, 2), BRIEF).await,
+        Err(RpcError::Timeout(_))
+    ));
+}
+
+/// Forwards every impulse on `pattern` from one synapse to another, the way
+/// a transport relays traffic between processes.
+fn relay(from: &Synapse, to: &Synapse, pattern: &str) -> tokio::task::JoinHandle<()> {
+    let mut inbox = from.subscribe(pattern).unwrap();
+    let to = to.clone();
+    tokio::spawn(async move {
+        while let Ok(impulse) = inbox.recv().await {
+            to.inject(impulse);
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_call_served_by_another_hub() {
+    let client = Rpc::new(Synapse::new());
+    let server = Rpc::new(Synapse::new());
+    let _calls = relay(client.synapse(), server.synapse(), "rpc/+/call");
+    let _cancels = relay(client.synapse(), server.synapse(), "rpc/+/cancel");
+    let _replies = relay(server.synapse(), client.synapse(), "rpc/+/reply");
+
+    let _reg = server.register("math", Arc::new(Math::default())).unwrap();
+    assert!(!client.is_registered("math"));
+
+    let sum: i64 = client.call("math", "add", &(40, 2), PATIENT).await.unwrap();
+    assert_eq!(sum, 42);
+
+    // The remote server is the one that knows the method is missing.
+    let err = client
+        .call::<_, ()>("math", "sqrt", &4, PATIENT)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        RpcError::NoHandler {
+            service: "math".into(),
+            method: "sqrt".into()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_handler_panic_is_reported() {
+    let (rpc, math) = setup();
+    let _reg = rpc.register("math", math).unwrap();
+
+    let err = rpc
+        .call::<_, ()>("math", "explode", &(), PATIENT)
+        .await
+        .unwrap_err();
+    assert_eq!(err, RpcError::HandlerPanic("core breach".into()));
+
+    // The service survives its handler.
+    let sum: i64 = rpc.call("math", "add", &(1, 1), PATIENT).await.unwrap();
+    assert_eq!(sum, 2);
+}
+
+#[tokio::test]
+async fn test_timeout_cancels_the_handler() {
+    let (rpc, math) = setup();
+    let abandoned = math.abandoned.clone();
+    let _reg = rpc.register("math", math).unwrap();
+
+    let err = rpc
+        .call::<_, ()>("math", "hang", &(), Duration::from_millis(50))
+        .await
+        .unwrap_err();
+    assert_eq!(err, RpcError::Timeout(Duration::from_millis(50)));
+
+    wait_for(|| abandoned.load(Ordering::SeqCst) == 1).await;
+}
+
+#[tokio::test]
+async fn test_dropped_call_sends_cancel() {
+    let (rpc, math) = setup();
+    let abandoned = math.abandoned.clone();
+    let _reg = rpc.register("math", math).unwrap();
+    let mut cancels = rpc.synapse().subscribe("rpc/math/cancel").unwrap();
+
+    let caller = rpc.clone();
+    let pending = tokio::spawn(async move {
+        caller
+            .call::<_, ()>("math", "hang", &(), Duration::from_secs(3600))
+            .await
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    pending.abort();
+    assert!(pending.await.unwrap_err().is_cancelled());
+
+    let cancel = tokio::time::timeout(PATIENT, cancels.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(cancel.msg, SMessage::Rpc(RpcMessage::Cancel)));
+    assert!(cancel.correlation_id.is_some());
+
+    // The handler's future is dropped long before its hour is up.
+    wait_for(|| abandoned.load(Ordering::SeqCst) == 1).await;
+}
+
+#[tokio::test]
+async fn test_concurrent_calls_are_matched_to_their_callers() {
+    let (rpc, math) = setup();
+    let _reg = rpc.register("math", math).unwrap();
+
+    // Later calls finish first, so replies arrive out of order.
+    let calls = (0..16u64).map(|i| {
+        let rpc = rpc.clone();
+        tokio::spawn(async move {
+            let value = format!("call-{}", i);
+            let echoed: String = rpc
+                .call(
+                    "math",
+                    "echo_after",
+                    &(value.clone(), 160 - i * 10),
+                    PATIENT,
+                )
+                .await
+                .unwrap();
+            (value, echoed)
+        })
+    });
+    let started = tokio::time::Instant::now();
+    for call in calls.collect::<Vec<_>>() {
+        let (sent, echoed) = call.await.unwrap();
+        assert_eq!(sent, echoed);
+    }
+    // They ran side by side, not one after another.
+    assert!(started.elapsed() < Duration::from_millis(160 * 4));
+
+    // A panic and a timeout in the crowd leave the others untouched.
+    let (ok, panicked, timed_out) = tokio::join!(
+        rpc.call::<_, i64>("math", "add", &(2, 3), PATIENT),
+        rpc.call::<_, ()>("math", "explode", &(), PATIENT),
+        rpc.call::<_, ()>("math", "hang", &(), Duration::from_millis(30)),
+    );
+    assert_eq!(ok.unwrap(), 5);
+    assert!(matches!(panicked, Err(RpcError::HandlerPanic(_))));
+    assert!(matches!(timed_out, Err(RpcError::Timeout(_))));
+}
+
+#[tokio::test]
+async fn test_dropped_registration_abandons_in_flight_calls() {
+    let (rpc, math) = setup();
+    let abandoned = math.abandoned.clone();
+    let reg = rpc.register("math", math).unwrap();
+    let mut replies = rpc.synapse().subscribe("rpc/math/reply").unwrap();
+
+    let (slow, hung) = (rpc.clone(), rpc.clone());
+    let slow = tokio::spawn(async move {
+        slow.call::<_, String>("math", "echo_after", &("late", 100), PATIENT)
+            .await
+    });
+    let hung = tokio::spawn(async move {
+        hung.call::<_, ()>("math", "hang", &(), Duration::from_millis(300))
+            .await
+    });
+    tokio::time::sleep(BRIEF).await;
+    drop(reg);
+
+    // Both handlers are dropped, and nothing answers on their behalf.
+    wait_for(|| abandoned.load(Ordering::SeqCst) == 1).await;
+    assert_eq!(
+        hung.await.unwrap(),
+        Err(RpcError::Timeout(Duration::from_millis(300)))
+    );
+    // The echo was due long ago.
+    assert!(!slow.is_finished());
+    slow.abort();
+    assert!(replies.try_recv().is_err());
+}
+
+async fn wait_for(mut condition: impl FnMut() -> bool) {
+    let deadline = tokio::time::Instant::now() + PATIENT;
+    while !condition() {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "Condition never held"
+        );
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
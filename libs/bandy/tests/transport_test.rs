@@ -0,0 +1,297 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg(unix)]
+
+use bandy::transport::{
+    Broker, BrokerClient, BrokerConfig, ClientConfig, Frame, MAX_FRAME_LEN, encode_frame,
+    read_frame, write_frame,
+};
+use bandy::{Envelope, SMessage};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::time::Duration;
+use tokio::net::UnixStream;
+use tokio::time::timeout;
+
+const PATIENCE: Duration = Duration::from_secs(10);
+
+/// A broker process that dies with the test.
+struct BrokerProcess(Child);
+
+impl BrokerProcess {
+    fn spawn(path: &Path) -> Self {
+        let child = Command::new(env!("CARGO_BIN_EXE_bandy-broker"))
+            .arg(path)
+            .spawn()
+            .expect("Failed to spawn bandy-broker");
+        Self(child)
+    }
+}
+
+impl Drop for BrokerProcess {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn socket_in(dir: &tempfile::TempDir) -> PathBuf {
+    dir.path().join("bandy.sock")
+}
+
+fn fast_config() -> ClientConfig {
+    ClientConfig {
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(100),
+        ..ClientConfig::default()
+    }
+}
+
+async fn wait_until(mut cond: impl FnMut() -> bool) {
+    timeout(PATIENCE, async {
+        while !cond() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Condition never became true");
+}
+
+fn prompt_text(msg: &SMessage) -> &str {
+    match msg {
+        SMessage::UserPrompt(t) => t,
+        other => panic!("Unexpected message: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_two_clients_exchange_messages() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = socket_in(&dir);
+    let _broker = BrokerProcess::spawn(&path);
+
+    let lumen = BrokerClient::spawn(&path, fast_config());
+    let vein = BrokerClient::spawn(&path, fast_config());
+
+    let mut prompts = vein.subscribe("vein/#").unwrap();
+    let mut replies = lumen.subscribe("lumen/+").unwrap();
+    timeout(PATIENCE, vein.acknowledged("vein/#"))
+        .await
+        .unwrap();
+    timeout(PATIENCE, lumen.acknowledged("lumen/+"))
+        .await
+        .unwrap();
+
+    lumen
+        .publish("vein/prompt", SMessage::UserPrompt("hello".into()))
+        .await
+        .unwrap();
+    // Not subscribed by anyone; must not leak through.
+    lumen.publish("matrix/event", SMessage::Ping).await.unwrap();
+
+    let impulse = timeout(PATIENCE, prompts.recv()).await.unwrap().unwrap();
+    assert_eq!(&*impulse.topic, "vein/prompt");
+    assert_eq!(prompt_text(&impulse.msg), "hello");
+
+    vein.publish("lumen/reply", SMessage::AiToken("hi".into()))
+        .await
+        .unwrap();
+    let impulse = timeout(PATIENCE, replies.recv()).await.unwrap().unwrap();
+    assert!(matches!(impulse.msg, SMessage::AiToken(ref t) if t == "hi"));
+    assert!(prompts.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_client_reconnects_and_resubscribes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = socket_in(&dir);
+
+    // Clients come up before the broker exists and keep retrying.
+    let sender = BrokerClient::spawn(&path, fast_config());
+    let receiver = BrokerClient::spawn(&path, fast_config());
+    let mut sub = receiver.subscribe("system/log").unwrap();
+    assert!(!receiver.is_connected());
+
+    let broker = BrokerProcess::spawn(&path);
+    timeout(PATIENCE, receiver.acknowledged("system/log"))
+        .await
+        .unwrap();
+    sender
+        .publish("system/log", SMessage::UserPrompt("first".into()))
+        .await
+        .unwrap();
+    let impulse = timeout(PATIENCE, sub.recv()).await.unwrap().unwrap();
+    assert_eq!(prompt_text(&impulse.msg), "first");
+
+    // Kill the broker, bring up a fresh one on the same path.
+    drop(broker);
+    wait_until(|| !receiver.is_connected() && !sender.is_connected()).await;
+    let _broker = BrokerProcess::spawn(&path);
+
+    // The subscription is replayed without any help from the caller.
+    timeout(PATIENCE, receiver.acknowledged("system/log"))
+        .await
+        .unwrap();
+    wait_until(|| sender.is_connected()).await;
+
+    sender
+        .publish("system/log", SMessage::UserPrompt("second".into()))
+        .await
+        .unwrap();
+    let impulse = timeout(PATIENCE, sub.recv()).await.unwrap().unwrap();
+    assert_eq!(prompt_text(&impulse.msg), "second");
+}
+
+#[tokio::test]
+async fn test_stalled_client_does_not_block_others() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = socket_in(&dir);
+    let _broker = BrokerProcess::spawn(&path);
+
+    // A raw peer that subscribes and then never reads again.
+    wait_until(|| path.exists()).await;
+    let mut stalled = UnixStream::connect(&path).await.unwrap();
+    write_frame(&mut stalled, &Frame::Subscribe("#".into()))
+        .await
+        .unwrap();
+    assert!(matches!(
+        read_frame(&mut stalled).await.unwrap(),
+        Some(Frame::Ack(_))
+    ));
+
+    let sender = BrokerClient::spawn(&path, fast_config());
+    let healthy = BrokerClient::spawn(&path, fast_config());
+    let mut sub = healthy.subscribe("resonance/#").unwrap();
+    timeout(PATIENCE, healthy.acknowledged("resonance/#"))
+        .await
+        .unwrap();
+
+    // 600 x 16 KiB is far more than the stalled peer's socket buffer plus
+    // its broker-side queue can hold.
+    let samples = vec![0.5f32; 4096];
+    for i in 0..600u32 {
+        sender
+            .publish(
+                "resonance/audio/chunk",
+                SMessage::AudioChunk {
+                    source_id: i.to_string(),
+                    samples: samples.clone(),
+                    sample_rate: 48_000,
+                },
+            )
+            .await
+            .unwrap();
+
+        let impulse = timeout(PATIENCE, sub.recv())
+            .await
+            .expect("Healthy peer starved by a stalled one")
+            .unwrap();
+        match impulse.msg {
+            SMessage::AudioChunk { source_id, .. } => assert_eq!(source_id, i.to_string()),
+            other => panic!("Unexpected message: {:?}", other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_request_reply_across_processes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = socket_in(&dir);
+    let _broker = BrokerProcess::spawn(&path);
+
+    let matrix = BrokerClient::spawn(&path, fast_config());
+    let vein = BrokerClient::spawn(&path, fast_config());
+    let mut requests = matrix.subscribe("matrix/focus").unwrap();
+    let _replies = vein.subscribe("vein/context").unwrap();
+    timeout(PATIENCE, matrix.acknowledged("matrix/focus"))
+        .await
+        .unwrap();
+    timeout(PATIENCE, vein.acknowledged("vein/context"))
+        .await
+        .unwrap();
+
+    let responder = matrix.clone();
+    tokio::spawn(async move {
+        let request = requests.recv().await.unwrap();
+        responder
+            .reply(&request, "vein/context", SMessage::AiToken("sector".into()))
+            .await
+            .unwrap();
+    });
+
+    let reply = vein
+        .request("matrix/focus", SMessage::Ping, PATIENCE)
+        .await
+        .unwrap();
+    assert!(matches!(reply.msg, SMessage::AiToken(ref t) if t == "sector"));
+}
+
+#[tokio::test]
+async fn test_frame_codec_round_trip_and_limits() {
+    let (mut a, mut b) = UnixStream::pair().unwrap();
+
+    let frame = Frame::Publish(Envelope::new(
+        "test",
+        "vein/prompt",
+        SMessage::UserPrompt("round trip".into()),
+    ));
+    write_frame(&mut a, &frame).await.unwrap();
+    match read_frame(&mut b).await.unwrap() {
+        Some(Frame::Publish(envelope)) => {
+            assert_eq!(envelope.topic, "vein/prompt");
+            assert_eq!(envelope.source, "test");
+            assert_eq!(prompt_text(envelope.message().unwrap()), "round trip");
+        }
+        other => panic!("Unexpected frame: {:?}", other),
+    }
+
+    // A length prefix past the limit is rejected before any allocation.
+    use tokio::io::AsyncWriteExt;
+    a.write_all(&((MAX_FRAME_LEN as u32) + 1).to_be_bytes())
+        .await
+        .unwrap();
+    assert!(read_frame(&mut b).await.is_err());
+
+    // Clean EOF between frames is not an error.
+    let encoded = encode_frame(&Frame::Ack("x".into())).unwrap();
+    let (mut c, mut d) = UnixStream::pair().unwrap();
+    c.write_all(&encoded).await.unwrap();
+    drop(c);
+    assert!(matches!(
+        read_frame(&mut d).await.unwrap(),
+        Some(Frame::Ack(_))
+    ));
+    assert!(read_frame(&mut d).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_bind_only_replaces_stale_sockets() {
+    let dir = tempfile::tempdir().unwrap();
+
+    // A regular file at the path is left alone.
+    let notes = dir.path().join("notes.txt");
+    std::fs::write(&notes, "keep me").unwrap();
+    let err = Broker::bind(&notes, BrokerConfig::default()).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(std::fs::read_to_string(&notes).unwrap(), "keep me");
+
+    // A socket nobody answers on is cleared and reused.
+    let path = socket_in(&dir);
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    let broker = Broker::bind(&path, BrokerConfig::default()).unwrap();
+    assert_eq!(broker.path(), path);
+}
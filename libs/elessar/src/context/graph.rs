@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The dependency DAG between crates of one scan.
+//!
+//! Only path dependencies become edges: a registry or git dependency that
+//! shares a name with a local crate is not that crate. Dev-dependencies are
+//! kept as edges for impact queries, since tests break too, but they are
+//! left out of layering, cycle detection and the critical path because
+//! Cargo allows them to point back up the graph.
+
+use super::indexer::{CrateNode, WorkspaceIndexer};
+use super::manifest::{DependencyKind, DependencySource};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::fmt::Write as _;
+
+/// `from` depends on `to`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub kind: DependencyKind,
+}
+
+/// A dependency loop, written as the crates along it with the first repeated
+/// at the end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub path: Vec<String>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dependency cycle: {}", self.path.join(" -> "))
+    }
+}
+
+impl std::error::Error for Cycle {}
+
+pub struct CrateGraph {
+    nodes: Vec<CrateNode>,
+    index: HashMap<String, usize>,
+    edges: Vec<Edge>,
+    /// Normal and build dependencies, deduplicated and sorted by name.
+    deps: Vec<Vec<usize>>,
+    /// Every dependent, dev-dependents included.
+    dependents: Vec<Vec<usize>>,
+}
+
+impl CrateGraph {
+    pub fn new<'a>(nodes: impl IntoIterator<Item = &'a CrateNode>) -> Self {
+        let mut nodes: Vec<CrateNode> = nodes.into_iter().cloned().collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        let index: HashMap<String, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.name.clone(), i))
+            .collect();
+        let by_path: HashMap<_, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.path.clone(), i))
+            .collect();
+
+        let mut edges = Vec::new();
+        let mut deps = vec![BTreeSet::new(); nodes.len()];
+        let mut dependents = vec![BTreeSet::new(); nodes.len()];
+        for (from, node) in nodes.iter().enumerate() {
+            for dep in &node.dependencies {
+                let DependencySource::Path(path) = &dep.source else {
+                    continue;
+                };
+                let Some(&to) = by_path.get(path) else {
+                    continue;
+                };
+                let edge = Edge {
+                    from: node.name.clone(),
+                    to: nodes[to].name.clone(),
+                    kind: dep.kind,
+                };
+                if edges.contains(&edge) {
+                    // The same crate under another target.
+                    continue;
+                }
+                edges.push(edge);
+                if dep.kind != DependencyKind::Dev {
+                    deps[from].insert(to);
+                }
+                dependents[to].insert(from);
+            }
+        }
+
+        Self {
+            nodes,
+            index,
+            edges,
+            deps: deps.into_iter().map(|s| s.into_iter().collect()).collect(),
+            dependents: dependents
+                .into_iter()
+                .map(|s| s.into_iter().collect())
+                .collect(),
+        }
+    }
+
+    pub fn nodes(&self) -> &[CrateNode] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CrateNode> {
+        self.index.get(name).map(|&i| &self.nodes[i])
+    }
+
+    /// Direct normal and build dependencies of `name`.
+    pub fn dependencies(&self, name: &str) -> Vec<&str> {
+        self.names(self.index.get(name).map_or(&[][..], |&i| &self.deps[i]))
+    }
+
+    /// Crates that depend on `name` directly, in any way.
+    pub fn dependents(&self, name: &str) -> Vec<&str> {
+        self.names(
+            self.index
+                .get(name)
+                .map_or(&[][..], |&i| &self.dependents[i]),
+        )
+    }
+
+    /// Everything that has to be rebuilt or retested when `name` changes:
+    /// its dependents, transitively, sorted by name. A dev-dependent is hit
+    /// but not followed further; only its tests see the change.
+    pub fn impact(&self, name: &str) -> Vec<&str> {
+        let Some(&start) = self.index.get(name) else {
+            return Vec::new();
+        };
+        let mut hit = vec![false; self.nodes.len()];
+        let mut expanded = vec![false; self.nodes.len()];
+        expanded[start] = true;
+        let mut queue = VecDeque::from([start]);
+        while let Some(n) = queue.pop_front() {
+            for &d in &self.dependents[n] {
+                hit[d] = true;
+                if !expanded[d] && self.deps[d].contains(&n) {
+                    expanded[d] = true;
+                    queue.push_back(d);
+                }
+            }
+        }
+        hit[start] = false;
+        let hit: Vec<usize> = (0..self.nodes.len()).filter(|&i| hit[i]).collect();
+        self.names(&hit)
+    }
+
+    /// Groups crates so that each depends only on earlier layers. Layer 0
+    /// holds the crates with no local dependencies.
+    pub fn layers(&self) -> Result<Vec<Vec<&str>>, Cycle> {
+        let depth = self.depths()?;
+        let mut layers: Vec<Vec<&str>> = Vec::new();
+        for (i, &d) in depth.iter().enumerate() {
+            if layers.len() <= d {
+                layers.resize(d + 1, Vec::new());
+            }
+            layers[d].push(&self.nodes[i].name);
+        }
+        Ok(layers)
+    }
+
+    /// The first loop found, walking crates in name order.
+    pub fn find_cycle(&self) -> Option<Cycle> {
+        // 0 = unvisited, 1 = on the stack, 2 = done.
+        let mut state = vec![0u8; self.nodes.len()];
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+        for start in 0..self.nodes.len() {
+            if state[start] != 0 {
+                continue;
+            }
+            state[start] = 1;
+            stack.push((start, 0));
+            while let Some((n, next)) = stack.last_mut() {
+                let n = *n;
+                let Some(&d) = self.deps[n].get(*next) else {
+                    state[n] = 2;
+                    stack.pop();
+                    continue;
+                };
+                *next += 1;
+                match state[d] {
+                    0 => {
+                        state[d] = 1;
+                        stack.push((d, 0));
+                    }
+                    1 => {
+                        let from = stack.iter().position(|&(s, _)| s == d).unwrap();
+                        let mut path: Vec<String> = stack[from..]
+                            .iter()
+                            .map(|&(s, _)| self.nodes[s].name.clone())
+                            .collect();
+                        path.push(self.nodes[d].name.clone());
+                        return Some(Cycle { path });
+                    }
+                    _ => {}
+                }
+            }
+        }
+        None
+    }
+
+    /// The longest chain of dependencies, from the crate at its top down to
+    /// one with none. Ties go to the name that sorts first.
+    pub fn critical_path(&self) -> Result<Vec<&str>, Cycle> {
+        let depth = self.depths()?;
+        let Some(mut n) =
+            (0..self.nodes.len()).max_by(|&a, &b| depth[a].cmp(&depth[b]).then(b.cmp(&a)))
+        else {
+            return Ok(Vec::new());
+        };
+        let mut path = vec![self.nodes[n].name.as_str()];
+        while let Some(&next) = self.deps[n].iter().find(|&&d| depth[d] + 1 == depth[n]) {
+            n = next;
+            path.push(&self.nodes[n].name);
+        }
+        Ok(path)
+    }
+
+    /// Graphviz source, dev edges dashed and build edges dotted.
+    pub fn to_dot(&self) -> String {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut out = String::from("digraph workspace {\n    rankdir=LR;\n");
+        for node in &self.nodes {
+            let _ = writeln!(out, "    {};", quote(&node.name));
+        }
+        for edge in &self.edges {
+            let style = match edge.kind {
+                DependencyKind::Normal => "",
+                DependencyKind::Dev => " [style=dashed]",
+                DependencyKind::Build => " [style=dotted]",
+            };
+            let _ = writeln!(
+                out,
+                "    {} -> {}{};",
+                quote(&edge.from),
+                quote(&edge.to),
+                style
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Nodes with their layer (null when the graph has a cycle), edges, and
+    /// the cycle if there is one.
+    pub fn to_json(&self) -> Value {
+        let depth = self.depths().ok();
+        let nodes: Vec<Value> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                json!({
+                    "name": n.name,
+                    "path": n.path,
+                    "version": n.version,
+                    "layer": depth.as_ref().map(|d| d[i]),
+                })
+            })
+            .collect();
+        json!({
+            "nodes": nodes,
+            "edges": self.edges,
+            "cycle": self.find_cycle().map(|c| c.path),
+        })
+    }
+
+    /// Length of the longest dependency chain below each crate.
+    fn depths(&self) -> Result<Vec<usize>, Cycle> {
+        let mut remaining: Vec<usize> = self.deps.iter().map(Vec::len).collect();
+        let mut depth = vec![0; self.nodes.len()];
+        let mut ready: VecDeque<usize> = (0..self.nodes.len())
+            .filter(|&i| remaining[i] == 0)
+            .collect();
+        let mut placed = 0;
+        while let Some(n) = ready.pop_front() {
+            placed += 1;
+            for &d in &self.dependents[n] {
+                if !self.deps[d].contains(&n) {
+                    // A dev edge.
+                    continue;
+                }
+                depth[d] = depth[d].max(depth[n] + 1);
+                remaining[d] -= 1;
+                if remaining[d] == 0 {
+                    ready.push_back(d);
+                }
+            }
+        }
+        if placed < self.nodes.len() {
+            return Err(self.find_cycle().expect("unplaced crates form a cycle"));
+        }
+        Ok(depth)
+    }
+
+    fn names(&self, indices: &[usize]) -> Vec<&str> {
+        indices
+            .iter()
+            .map(|&i| self.nodes[i].name.as_str())
+            .collect()
+    }
+}
+
+impl WorkspaceIndexer {
+    /// The dependency graph of everything scanned so far.
+    pub fn graph(&self) -> CrateGraph {
+        CrateGraph::new(self.nodes.values())
+    }
+}
@@ -14,9 +14,13 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use super::manifest::{
+    Dependency, DependencyKind, MaybeInherited, RawDetail, RawManifest, normalize,
+};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::path::{Path, PathBuf};
-use serde::{Deserialize, Serialize};
 
 // We don't need unafs here. We just need standard paths.
 // The indexer builds the graph of crates.
@@ -26,112 +30,283 @@ use serde::{Deserialize, Serialize};
 pub struct CrateNode {
     pub name: String,
     pub path: PathBuf,
-    pub dependencies: Vec<String>,
+    pub version: Option<String>,
+    /// Root of the workspace this crate is a member of.
+    pub workspace: Option<PathBuf>,
+    pub dependencies: Vec<Dependency>,
+}
+
+impl CrateNode {
+    /// Dependencies of one kind, any target.
+    pub fn dependencies_of(&self, kind: DependencyKind) -> impl Iterator<Item = &Dependency> {
+        self.dependencies.iter().filter(move |d| d.kind == kind)
+    }
+}
+
+/// A `[workspace]` and its expanded member list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub root: PathBuf,
+    pub members: Vec<PathBuf>,
+}
+
+impl Workspace {
+    pub fn contains(&self, dir: &Path) -> bool {
+        dir == self.root || self.members.iter().any(|m| m == dir)
+    }
+}
+
+/// A manifest that could not be read, or a dependency that could not be
+/// resolved. The rest of the scan carries on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
 }
 
 /// The Workspace Indexer.
 ///
 /// It scans the workspace for `Cargo.toml` files and builds a DAG of dependencies.
+/// Members inherit `workspace = true` dependencies and package versions from
+/// the nearest enclosing workspace that lists them.
+#[derive(Default)]
 pub struct WorkspaceIndexer {
     pub nodes: HashMap<String, CrateNode>,
+    pub workspaces: Vec<Workspace>,
+    pub errors: Vec<ManifestError>,
 }
 
 impl WorkspaceIndexer {
     pub fn new() -> Self {
-        Self {
-            nodes: HashMap::new(),
-        }
+        Self::default()
     }
 
     /// Recursively scans a directory for crates.
     pub fn scan(&mut self, root: &Path) {
+        let root = normalize(root);
         let mut visited = HashSet::new();
-        self.scan_recursive(root, &mut visited);
+        let mut manifests = Vec::new();
+        Self::find_manifests(&root, &mut visited, &mut manifests);
+
+        let mut parsed = Vec::new();
+        for dir in manifests {
+            let file = dir.join("Cargo.toml");
+            let read = std::fs::read_to_string(&file)
+                .map_err(|e| e.to_string())
+                .and_then(|content| RawManifest::parse(&content));
+            match read {
+                Ok(manifest) => parsed.push((dir, manifest)),
+                Err(message) => {
+                    log::warn!(":: ELESSAR :: Skipping {:?}: {}", file, message);
+                    self.errors.push(ManifestError {
+                        path: file,
+                        message,
+                    });
+                }
+            }
+        }
+
+        let workspaces: HashMap<PathBuf, &RawManifest> = parsed
+            .iter()
+            .filter(|(_, m)| m.workspace.is_some())
+            .map(|(dir, m)| (dir.clone(), m))
+            .collect();
+        for (dir, manifest) in &workspaces {
+            let raw = manifest.workspace.as_ref().unwrap();
+            self.workspaces.push(Workspace {
+                root: dir.clone(),
+                members: expand_members(dir, &raw.members, &raw.exclude),
+            });
+        }
+        self.workspaces.sort_by(|a, b| a.root.cmp(&b.root));
+
+        for (dir, manifest) in &parsed {
+            let Some(package) = &manifest.package else {
+                continue;
+            };
+            // Cargo picks the nearest ancestor workspace; it only counts if
+            // that workspace actually lists this crate.
+            let member_of = dir
+                .ancestors()
+                .find_map(|a| self.workspaces.iter().find(|w| w.root == a))
+                .filter(|w| w.contains(dir))
+                .map(|w| w.root.clone());
+            let inherited = member_of.as_deref().map(|r| (r, workspaces[r]));
+            let node = self.build_node(dir, manifest, &package.name, inherited);
+            self.nodes.insert(node.name.clone(), node);
+        }
     }
 
-    fn scan_recursive(&mut self, dir: &Path, visited: &mut HashSet<PathBuf>) {
-        if visited.contains(dir) {
+    fn find_manifests(dir: &Path, visited: &mut HashSet<PathBuf>, out: &mut Vec<PathBuf>) {
+        if !visited.insert(dir.to_path_buf()) {
             return;
         }
-        visited.insert(dir.to_path_buf());
 
         // Check if this is a crate (has Cargo.toml)
-        let cargo_toml = dir.join("Cargo.toml");
-        if cargo_toml.exists() {
-            if let Ok(content) = std::fs::read_to_string(&cargo_toml) {
-                if let Some(node) = self.parse_cargo_toml(&content, dir) {
-                    self.nodes.insert(node.name.clone(), node);
-                }
-            }
+        if dir.join("Cargo.toml").is_file() {
+            out.push(dir.to_path_buf());
         }
 
         // Recurse into subdirectories
-        // Avoid target, .git, node_modules
+        // Avoid target, .git, node_modules, and test fixtures: their
+        // manifests are sample input, not crates of the workspace. A scan
+        // rooted inside a fixture still indexes it.
         if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_dir() {
-                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                    if name == "target" || name == ".git" || name == "node_modules" {
-                        continue;
-                    }
-                    self.scan_recursive(&path, visited);
+            let mut dirs: Vec<PathBuf> = entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect();
+            dirs.sort();
+            for path in dirs {
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                if name == "target" || name == ".git" || name == "node_modules" {
+                    continue;
+                }
+                if name == "fixtures" && dir.file_name().is_some_and(|n| n == "tests") {
+                    continue;
                 }
+                Self::find_manifests(&path, visited, out);
             }
         }
     }
 
-    fn parse_cargo_toml(&self, content: &str, path: &Path) -> Option<CrateNode> {
-        // We do a naive parse to avoid pulling in toml crate dependency if we want strict minimalism,
-        // BUT the user approved toml/parsing logic implicitly via "DAG/RAG Indexer".
-        // Wait, I didn't add `toml` crate to Cargo.toml.
-        // Can I do it with string matching? Yes, Can-Am style.
-        // Or I can add `toml` if needed.
-        // Let's try string matching for efficiency and zero deps.
+    fn build_node(
+        &mut self,
+        dir: &Path,
+        manifest: &RawManifest,
+        name: &str,
+        workspace: Option<(&Path, &RawManifest)>,
+    ) -> CrateNode {
+        let file = dir.join("Cargo.toml");
+        let shared = workspace.and_then(|(_, m)| m.workspace.as_ref());
 
-        let mut name = String::new();
-        let mut dependencies = Vec::new();
-        let mut in_package = false;
-        let mut in_deps = false;
-
-        for line in content.lines() {
-            let line = line.trim();
-            if line.starts_with("[package]") {
-                in_package = true;
-                in_deps = false;
-            } else if line.starts_with("[dependencies]") {
-                in_package = false;
-                in_deps = true;
-            } else if line.starts_with("[") {
-                in_package = false;
-                in_deps = false;
+        let version = match manifest.package.as_ref().and_then(|p| p.version.clone()) {
+            Some(MaybeInherited::Value(v)) => Some(v),
+            Some(MaybeInherited::Inherited { workspace: false }) => {
+                self.error(&file, "`version.workspace` can only be `true`");
+                None
             }
-
-            if in_package && line.starts_with("name =") {
-                if let Some(val) = line.split('=').nth(1) {
-                    name = val.trim().trim_matches('"').to_string();
+            Some(MaybeInherited::Inherited { workspace: true }) => {
+                let v = shared.and_then(|w| w.package.version.clone());
+                if v.is_none() {
+                    self.error(&file, "`version.workspace = true` but no workspace version");
                 }
+                v
+            }
+            None => None,
+        };
+
+        let mut dependencies = Vec::new();
+        for (kind, target, key, raw) in manifest.declared() {
+            let local = raw.detail();
+            if !local.workspace {
+                dependencies.push(local.resolve(key, kind, target, dir));
+                continue;
             }
+            let Some(((root, _), entry)) =
+                workspace.zip(shared.and_then(|w| w.dependencies.get(key)))
+            else {
+                self.error(
+                    &file,
+                    &format!("`{key}` inherits from a workspace that does not declare it"),
+                );
+                continue;
+            };
+            // The member may add features and make it optional; everything
+            // else comes from the workspace.
+            let mut merged: RawDetail = entry.detail();
+            merged.features.extend(local.features);
+            merged.optional = local.optional;
+            let mut dependency = merged.resolve(key, kind, target, root);
+            dependency.inherited = true;
+            dependencies.push(dependency);
+        }
 
-            if in_deps && !line.starts_with("#") && !line.is_empty() {
-                // dep = ...
-                if let Some(dep_name) = line.split('=').nth(0) {
-                     let clean = dep_name.trim();
-                     if !clean.is_empty() {
-                         dependencies.push(clean.to_string());
-                     }
+        CrateNode {
+            name: name.to_string(),
+            path: dir.to_path_buf(),
+            version,
+            workspace: workspace.map(|(root, _)| root.to_path_buf()),
+            dependencies,
+        }
+    }
+
+    fn error(&mut self, path: &Path, message: &str) {
+        log::warn!(":: ELESSAR :: {:?}: {}", path, message);
+        self.errors.push(ManifestError {
+            path: path.to_path_buf(),
+            message: message.to_string(),
+        });
+    }
+}
+
+/// Expands `members` globs (`*` and `?` within a path component) against
+/// the filesystem, then drops anything under `exclude`.
+fn expand_members(root: &Path, members: &[String], exclude: &[String]) -> Vec<PathBuf> {
+    let excluded: Vec<PathBuf> = exclude.iter().map(|e| normalize(&root.join(e))).collect();
+    let mut out = Vec::new();
+    for pattern in members {
+        let mut matches = vec![root.to_path_buf()];
+        for part in Path::new(pattern).components() {
+            let part = part.as_os_str().to_string_lossy();
+            if !part.contains(['*', '?']) {
+                matches = matches.into_iter().map(|m| m.join(&*part)).collect();
+                continue;
+            }
+            let mut next = Vec::new();
+            for base in matches {
+                let Ok(entries) = std::fs::read_dir(&base) else {
+                    continue;
+                };
+                for entry in entries.flatten() {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if entry.path().is_dir() && wildcard(&part, &name) {
+                        next.push(entry.path());
+                    }
                 }
             }
+            matches = next;
+        }
+        for m in matches {
+            let m = normalize(&m);
+            if m.join("Cargo.toml").is_file()
+                && !excluded.iter().any(|e| m.starts_with(e))
+                && !out.contains(&m)
+            {
+                out.push(m);
+            }
         }
+    }
+    out.sort();
+    out
+}
 
-        if !name.is_empty() {
-            Some(CrateNode {
-                name,
-                path: path.to_path_buf(),
-                dependencies,
-            })
+fn wildcard(pattern: &str, name: &str) -> bool {
+    let (p, n): (Vec<char>, Vec<char>) = (pattern.chars().collect(), name.chars().collect());
+    // Classic two-pointer match with backtracking to the last `*`.
+    let (mut i, mut j, mut star, mut mark) = (0, 0, None, 0);
+    while j < n.len() {
+        if i < p.len() && (p[i] == '?' || p[i] == n[j]) {
+            i += 1;
+            j += 1;
+        } else if i < p.len() && p[i] == '*' {
+            star = Some(i);
+            mark = j;
+            i += 1;
+        } else if let Some(s) = star {
+            i = s + 1;
+            mark += 1;
+            j = mark;
         } else {
-            None
+            return false;
         }
     }
+    p[i..].iter().all(|&c| c == '*')
 }
@@ -0,0 +1,273 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The parts of a `Cargo.toml` the indexer cares about.
+//!
+//! Manifests are read with `toml` into the raw shapes below, which follow
+//! Cargo's own schema closely enough to accept every form a dependency can
+//! take: a bare version string, an inline table, a `[dependencies.foo]`
+//! section, `workspace = true`, and the `target.'cfg(..)'` variants of all
+//! three dependency tables. Resolution against the workspace happens in the
+//! indexer; this module only describes what a single file says.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Which table a dependency was declared in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DependencyKind {
+    Normal,
+    Dev,
+    Build,
+}
+
+/// A pinned git checkout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GitReference {
+    Branch(String),
+    Tag(String),
+    Rev(String),
+}
+
+/// Where a dependency comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DependencySource {
+    /// crates.io, or the named alternative registry.
+    Registry(Option<String>),
+    /// A local crate. Relative paths are resolved against the manifest that
+    /// declared them (the workspace root for inherited dependencies).
+    Path(PathBuf),
+    Git {
+        url: String,
+        reference: Option<GitReference>,
+    },
+}
+
+/// One resolved dependency edge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dependency {
+    /// The name the dependent uses for it (the table key).
+    pub name: String,
+    /// The package it refers to; differs from `name` when renamed.
+    pub package: String,
+    pub kind: DependencyKind,
+    /// The version requirement, if one was given.
+    pub req: Option<String>,
+    pub source: DependencySource,
+    pub features: Vec<String>,
+    pub default_features: bool,
+    pub optional: bool,
+    /// The `cfg(..)` or triple this dependency is limited to.
+    pub target: Option<String>,
+    /// Declared with `workspace = true`.
+    pub inherited: bool,
+}
+
+/// A value that may be `{ workspace = true }` instead.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum MaybeInherited<T> {
+    Value(T),
+    Inherited { workspace: bool },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum RawDependency {
+    Version(String),
+    Detailed(RawDetail),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct RawDetail {
+    pub version: Option<String>,
+    pub path: Option<PathBuf>,
+    pub git: Option<String>,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    pub rev: Option<String>,
+    pub registry: Option<String>,
+    pub package: Option<String>,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(alias = "default_features")]
+    pub default_features: Option<bool>,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default)]
+    pub workspace: bool,
+}
+
+pub(crate) type DependencyTable = BTreeMap<String, RawDependency>;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct RawDependencyTables {
+    #[serde(default)]
+    pub dependencies: DependencyTable,
+    #[serde(default, alias = "dev_dependencies")]
+    pub dev_dependencies: DependencyTable,
+    #[serde(default, alias = "build_dependencies")]
+    pub build_dependencies: DependencyTable,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RawPackage {
+    pub name: String,
+    pub version: Option<MaybeInherited<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct RawWorkspacePackage {
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct RawWorkspace {
+    #[serde(default)]
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub package: RawWorkspacePackage,
+    #[serde(default)]
+    pub dependencies: DependencyTable,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RawManifest {
+    pub package: Option<RawPackage>,
+    pub workspace: Option<RawWorkspace>,
+    #[serde(flatten)]
+    pub tables: RawDependencyTables,
+    #[serde(default)]
+    pub target: BTreeMap<String, RawDependencyTables>,
+}
+
+impl RawManifest {
+    pub(crate) fn parse(content: &str) -> Result<Self, String> {
+        toml::from_str(content).map_err(|e| e.message().to_string())
+    }
+
+    /// Every declared dependency in a stable order: the plain tables first,
+    /// then each target's, with entries sorted by key inside a table.
+    pub(crate) fn declared(&self) -> Vec<(DependencyKind, Option<&str>, &str, &RawDependency)> {
+        let mut out = Vec::new();
+        let sections = std::iter::once((None, &self.tables)).chain(
+            self.target
+                .iter()
+                .map(|(t, tables)| (Some(t.as_str()), tables)),
+        );
+        for (target, tables) in sections {
+            for (kind, table) in [
+                (DependencyKind::Normal, &tables.dependencies),
+                (DependencyKind::Dev, &tables.dev_dependencies),
+                (DependencyKind::Build, &tables.build_dependencies),
+            ] {
+                for (name, raw) in table {
+                    out.push((kind, target, name.as_str(), raw));
+                }
+            }
+        }
+        out
+    }
+}
+
+impl RawDependency {
+    pub(crate) fn detail(&self) -> RawDetail {
+        match self {
+            RawDependency::Version(req) => RawDetail {
+                version: Some(req.clone()),
+                ..RawDetail::default()
+            },
+            RawDependency::Detailed(detail) => detail.clone(),
+        }
+    }
+}
+
+impl RawDetail {
+    /// Turns a fully specified entry into a `Dependency`. `base` is the
+    /// directory relative paths are taken from.
+    pub(crate) fn resolve(
+        self,
+        name: &str,
+        kind: DependencyKind,
+        target: Option<&str>,
+        base: &Path,
+    ) -> Dependency {
+        let source = if let Some(path) = self.path {
+            DependencySource::Path(normalize(&base.join(path)))
+        } else if let Some(url) = self.git {
+            let reference = self
+                .branch
+                .map(GitReference::Branch)
+                .or(self.tag.map(GitReference::Tag))
+                .or(self.rev.map(GitReference::Rev));
+            DependencySource::Git { url, reference }
+        } else {
+            DependencySource::Registry(self.registry)
+        };
+        Dependency {
+            name: name.to_string(),
+            package: self.package.unwrap_or_else(|| name.to_string()),
+            kind,
+            req: self.version,
+            source,
+            features: self.features,
+            default_features: self.default_features.unwrap_or(true),
+            optional: self.optional,
+            target: target.map(str::to_string),
+            inherited: false,
+        }
+    }
+}
+
+/// Removes `.` and folds `..` without touching the filesystem, so paths
+/// written differently in different manifests compare equal.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            // Only a named directory can be folded away; `..` past the
+            // root stays at the root, and a leading `..` is kept.
+            Component::ParentDir => match out.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    out.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => out.push(".."),
+            },
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_keeps_leading_parents() {
+        assert_eq!(normalize(Path::new("../../x")), PathBuf::from("../../x"));
+        assert_eq!(normalize(Path::new("a/../../b")), PathBuf::from("../b"));
+        assert_eq!(normalize(Path::new("./a/./b/../c")), PathBuf::from("a/c"));
+        assert_eq!(normalize(Path::new("/a/../../b")), PathBuf::from("/b"));
+    }
+}
@@ -14,8 +14,14 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+pub mod graph;
 pub mod indexer;
+pub mod manifest;
+pub mod packer;
 pub mod skeleton;
 
-pub use skeleton::SkeletonGenerator;
-pub use indexer::WorkspaceIndexer;
+pub use graph::{CrateGraph, Cycle, Edge};
+pub use indexer::{CrateNode, ManifestError, Workspace, WorkspaceIndexer};
+pub use manifest::{Dependency, DependencyKind, DependencySource, GitReference};
+pub use packer::{CharsPerToken, Pack, PackEntry, Packer, Representation, TokenEstimator};
+pub use skeleton::{MacroMode, SkeletonGenerator, SkeletonOptions};
@@ -0,0 +1,528 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Token-budgeted context assembly.
+//!
+//! The packer decides what an LLM gets to see of the workspace. Focus files
+//! go in whole; the rest of their crate and the crates around it in the
+//! dependency graph go in as skeletons, nearest and most recently touched
+//! first, until the budget runs out. Every decision, including what was
+//! left behind, is written to the pack's manifest.
+
+use super::graph::CrateGraph;
+use super::skeleton::{SkeletonGenerator, SkeletonOptions};
+use ignore::WalkBuilder;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Counts the tokens a piece of text will cost. Plug in the model's real
+/// tokenizer where one is available; any `Fn(&str) -> usize` will do.
+pub trait TokenEstimator {
+    fn estimate(&self, text: &str) -> usize;
+}
+
+impl<F: Fn(&str) -> usize> TokenEstimator for F {
+    fn estimate(&self, text: &str) -> usize {
+        self(text)
+    }
+}
+
+/// The usual rule of thumb: one token per so many characters, rounded up.
+#[derive(Debug, Clone, Copy)]
+pub struct CharsPerToken(pub usize);
+
+impl Default for CharsPerToken {
+    fn default() -> Self {
+        Self(4)
+    }
+}
+
+impl TokenEstimator for CharsPerToken {
+    fn estimate(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(self.0.max(1))
+    }
+}
+
+/// How much of a file made it into the pack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Representation {
+    Full,
+    Skeleton,
+    /// The first `lines` of `of`, full source or skeleton, whichever was tried last.
+    Truncated {
+        lines: usize,
+        of: usize,
+    },
+    Omitted,
+}
+
+impl Representation {
+    fn label(&self) -> &'static str {
+        match self {
+            Representation::Full => "full",
+            Representation::Skeleton => "skeleton",
+            Representation::Truncated { .. } => "truncated",
+            Representation::Omitted => "omitted",
+        }
+    }
+}
+
+/// One line of the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackEntry {
+    /// Relative to the packer's root, when the file lies under it.
+    pub path: PathBuf,
+    /// The crate the file belongs to; empty for focus files outside any crate.
+    pub crate_name: String,
+    /// Hops in the dependency graph from the nearest focus crate.
+    pub distance: usize,
+    pub representation: Representation,
+    /// Zero for omitted files.
+    pub tokens: usize,
+    pub reason: String,
+}
+
+/// The packed context and its manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Pack {
+    pub text: String,
+    pub tokens: usize,
+    pub budget: usize,
+    /// Every candidate in rank order, omitted ones included.
+    pub entries: Vec<PackEntry>,
+}
+
+impl Pack {
+    pub fn included(&self) -> impl Iterator<Item = &PackEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.representation != Representation::Omitted)
+    }
+
+    pub fn omitted(&self) -> impl Iterator<Item = &PackEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.representation == Representation::Omitted)
+    }
+
+    /// A plain-text table of what went in and why.
+    pub fn manifest(&self) -> String {
+        let mut out = format!("context pack: {} of {} tokens\n", self.tokens, self.budget);
+        for entry in &self.entries {
+            let representation = match entry.representation {
+                Representation::Truncated { lines, of } => format!("truncated {lines}/{of}"),
+                other => other.label().to_string(),
+            };
+            let tokens = match entry.representation {
+                Representation::Omitted => "-".to_string(),
+                _ => entry.tokens.to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "{:<15} {:>6}  {}  {}",
+                representation,
+                tokens,
+                entry.path.display(),
+                entry.reason
+            );
+        }
+        out
+    }
+}
+
+/// Where a crate sits relative to the focus.
+struct Placement {
+    distance: usize,
+    reason: String,
+}
+
+struct Candidate {
+    path: PathBuf,
+    crate_name: String,
+    distance: usize,
+    focus: bool,
+    recency: Option<SystemTime>,
+    reason: String,
+}
+
+/// Assembles LLM context under a token budget.
+///
+/// ```ignore
+/// let pack = Packer::new(8_000)
+///     .root(&workspace_root)
+///     .estimator(|text: &str| tokenizer.count(text))
+///     .pack(&indexer.graph(), &[focused_file]);
+/// ```
+pub struct Packer {
+    budget: usize,
+    root: Option<PathBuf>,
+    max_distance: usize,
+    skeleton: SkeletonOptions,
+    estimator: Box<dyn TokenEstimator>,
+    touched: HashMap<PathBuf, SystemTime>,
+    mtime: bool,
+}
+
+impl Packer {
+    /// A packer for `budget` tokens: four characters to the token, crates up
+    /// to two hops from the focus, public-API skeletons for the neighbours.
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            root: None,
+            max_distance: 2,
+            skeleton: SkeletonOptions {
+                keep_private: false,
+                ..SkeletonOptions::default()
+            },
+            estimator: Box::new(CharsPerToken::default()),
+            touched: HashMap::new(),
+            mtime: false,
+        }
+    }
+
+    /// Relative focus paths are resolved against `root`, and paths in the
+    /// output are written relative to it.
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Crates further than this many hops from every focus crate are left out.
+    pub fn max_distance(mut self, hops: usize) -> Self {
+        self.max_distance = hops;
+        self
+    }
+
+    pub fn skeleton_options(mut self, options: SkeletonOptions) -> Self {
+        self.skeleton = options;
+        self
+    }
+
+    pub fn estimator(mut self, estimator: impl TokenEstimator + 'static) -> Self {
+        self.estimator = Box::new(estimator);
+        self
+    }
+
+    /// Records when a file was last touched (an edit, a WAL entry, a commit).
+    /// Among files at the same distance the most recent rank first.
+    pub fn touched(mut self, path: impl Into<PathBuf>, when: SystemTime) -> Self {
+        let path = self.resolve(&path.into());
+        self.touched.insert(path, when);
+        self
+    }
+
+    /// Falls back to file modification times for files never `touched`.
+    /// Off by default, since mtimes make the output depend on the checkout.
+    pub fn recency_from_mtime(mut self, enabled: bool) -> Self {
+        self.mtime = enabled;
+        self
+    }
+
+    /// Packs `focus` and its surroundings in `graph` into the budget.
+    ///
+    /// Candidates are ranked focus files first, then by graph distance, then
+    /// by recency, then by path, so the same inputs always give the same
+    /// pack. Each is tried in the richest form that fits; the first that no
+    /// longer fits whole is cut at a line boundary, and anything after the
+    /// budget is spent is listed as omitted.
+    pub fn pack(&self, graph: &CrateGraph, focus: &[PathBuf]) -> Pack {
+        let focus: BTreeSet<PathBuf> = focus.iter().map(|p| self.resolve(p)).collect();
+        let candidates = self.rank(graph, &focus);
+
+        let mut pack = Pack {
+            text: String::new(),
+            tokens: 0,
+            budget: self.budget,
+            entries: Vec::new(),
+        };
+        for candidate in candidates {
+            let display = self.display(&candidate.path);
+            let remaining = self.budget - pack.tokens;
+            let (representation, section, reason) = match std::fs::read_to_string(&candidate.path) {
+                Ok(source) => self.fit(&candidate, &display, &source, remaining),
+                Err(e) => (
+                    Representation::Omitted,
+                    String::new(),
+                    format!("{}; unreadable: {}", candidate.reason, e),
+                ),
+            };
+            let tokens = if section.is_empty() {
+                0
+            } else {
+                self.estimator.estimate(&section)
+            };
+            pack.text.push_str(&section);
+            pack.tokens += tokens;
+            pack.entries.push(PackEntry {
+                path: display,
+                crate_name: candidate.crate_name,
+                distance: candidate.distance,
+                representation,
+                tokens,
+                reason,
+            });
+        }
+        pack
+    }
+
+    /// The richest form of one file that fits in `remaining` tokens.
+    fn fit(
+        &self,
+        candidate: &Candidate,
+        display: &Path,
+        source: &str,
+        remaining: usize,
+    ) -> (Representation, String, String) {
+        let is_rust = candidate.path.extension().is_some_and(|e| e == "rs");
+        let skeleton = if is_rust {
+            SkeletonGenerator::generate_with(source, &self.skeleton).ok()
+        } else {
+            None
+        };
+        let fits = |section: &str| self.estimator.estimate(section) <= remaining;
+
+        if candidate.focus {
+            let full = section(display, "full", source);
+            if fits(&full) {
+                return (Representation::Full, full, candidate.reason.clone());
+            }
+            if let Some(skeleton) = skeleton.as_deref().filter(|s| !s.trim().is_empty()) {
+                let section = section(display, "skeleton", skeleton);
+                if fits(&section) {
+                    let reason = format!("{}; full source over budget", candidate.reason);
+                    return (Representation::Skeleton, section, reason);
+                }
+            }
+            return self.truncate(candidate, display, source, remaining);
+        }
+
+        let Some(skeleton) = skeleton else {
+            let reason = if is_rust {
+                format!("{}; does not parse", candidate.reason)
+            } else {
+                format!("{}; not Rust", candidate.reason)
+            };
+            return (Representation::Omitted, String::new(), reason);
+        };
+        if skeleton.trim().is_empty() {
+            let reason = format!("{}; no public items", candidate.reason);
+            return (Representation::Omitted, String::new(), reason);
+        }
+        let section = section(display, "skeleton", &skeleton);
+        if fits(&section) {
+            return (Representation::Skeleton, section, candidate.reason.clone());
+        }
+        self.truncate(candidate, display, &skeleton, remaining)
+    }
+
+    /// Keeps as many leading lines of `body` as fit, or omits the file when
+    /// not even one does.
+    fn truncate(
+        &self,
+        candidate: &Candidate,
+        display: &Path,
+        body: &str,
+        remaining: usize,
+    ) -> (Representation, String, String) {
+        let lines: Vec<&str> = body.lines().collect();
+        let cut = |kept: usize| {
+            let mut text = lines[..kept].join("\n");
+            let rest = lines.len() - kept;
+            let plural = if rest == 1 { "" } else { "s" };
+            let _ = write!(text, "\n// ... {rest} more line{plural}");
+            section(display, "truncated", &text)
+        };
+        // Largest prefix that fits; the estimator is assumed to grow with the text.
+        let (mut low, mut high) = (0, lines.len());
+        while low < high {
+            let mid = (low + high).div_ceil(2);
+            if self.estimator.estimate(&cut(mid)) <= remaining {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        if low == 0 {
+            let reason = format!("{}; over budget", candidate.reason);
+            return (Representation::Omitted, String::new(), reason);
+        }
+        let representation = Representation::Truncated {
+            lines: low,
+            of: lines.len(),
+        };
+        let reason = format!("{}; truncated to fit", candidate.reason);
+        (representation, cut(low), reason)
+    }
+
+    /// Focus files, then every Rust source of the crates within reach, in
+    /// rank order.
+    fn rank(&self, graph: &CrateGraph, focus: &BTreeSet<PathBuf>) -> Vec<Candidate> {
+        let owner = |path: &Path| {
+            graph
+                .nodes()
+                .iter()
+                .filter(|n| path.starts_with(&n.path))
+                .max_by_key(|n| n.path.components().count())
+                .map(|n| n.name.clone())
+        };
+
+        let focus_crates: BTreeSet<String> = focus.iter().filter_map(|p| owner(p)).collect();
+        let placements = place(graph, &focus_crates);
+
+        let mut candidates: Vec<Candidate> = focus
+            .iter()
+            .map(|path| Candidate {
+                path: path.clone(),
+                crate_name: owner(path).unwrap_or_default(),
+                distance: 0,
+                focus: true,
+                recency: self.recency(path),
+                reason: "focus file".to_string(),
+            })
+            .collect();
+
+        for node in graph.nodes() {
+            let Some(placement) = placements.get(&node.name) else {
+                continue;
+            };
+            if placement.distance > self.max_distance {
+                continue;
+            }
+            for path in rust_sources(&node.path.join("src")) {
+                if focus.contains(&path) {
+                    continue;
+                }
+                candidates.push(Candidate {
+                    recency: self.recency(&path),
+                    path,
+                    crate_name: node.name.clone(),
+                    distance: placement.distance,
+                    focus: false,
+                    reason: placement.reason.clone(),
+                });
+            }
+        }
+
+        candidates.sort_by(|a, b| {
+            (!a.focus, a.distance, Reverse(a.recency), &a.path).cmp(&(
+                !b.focus,
+                b.distance,
+                Reverse(b.recency),
+                &b.path,
+            ))
+        });
+        candidates
+    }
+
+    fn recency(&self, path: &Path) -> Option<SystemTime> {
+        self.touched.get(path).copied().or_else(|| {
+            self.mtime
+                .then(|| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+                .flatten()
+        })
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        match &self.root {
+            Some(root) if path.is_relative() => root.join(path),
+            _ => path.to_path_buf(),
+        }
+    }
+
+    fn display(&self, path: &Path) -> PathBuf {
+        self.root
+            .as_deref()
+            .and_then(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path)
+            .to_path_buf()
+    }
+}
+
+fn section(path: &Path, label: &str, body: &str) -> String {
+    format!(
+        "--- FILE: {} ({}) ---\n{}\n\n",
+        path.display(),
+        label,
+        body.trim_end()
+    )
+}
+
+/// Breadth-first over the graph in both directions from every focus crate.
+/// Ties go to the focus crate first in name order, so reasons are stable.
+fn place(graph: &CrateGraph, focus: &BTreeSet<String>) -> HashMap<String, Placement> {
+    let mut placements = HashMap::new();
+    let mut queue = VecDeque::new();
+    for name in focus {
+        placements.insert(
+            name.clone(),
+            Placement {
+                distance: 0,
+                reason: format!("in focus crate `{name}`"),
+            },
+        );
+        queue.push_back((name.as_str(), name.as_str(), 0));
+    }
+    while let Some((name, origin, distance)) = queue.pop_front() {
+        let dependencies = graph.dependencies(name);
+        let dependents = graph.dependents(name);
+        let neighbours = dependencies
+            .iter()
+            .map(|n| (*n, true))
+            .chain(dependents.iter().map(|n| (*n, false)));
+        for (next, is_dependency) in neighbours {
+            if placements.contains_key(next) {
+                continue;
+            }
+            let hops = distance + 1;
+            let reason = if hops > 1 {
+                format!("{hops} hops from focus crate `{origin}`")
+            } else if is_dependency {
+                format!("dependency of focus crate `{origin}`")
+            } else {
+                format!("depends on focus crate `{origin}`")
+            };
+            placements.insert(
+                next.to_string(),
+                Placement {
+                    distance: hops,
+                    reason,
+                },
+            );
+            queue.push_back((next, origin, hops));
+        }
+    }
+    placements
+}
+
+/// Every `.rs` file under `dir`, `.gitignore` honoured, sorted.
+fn rust_sources(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkBuilder::new(dir)
+        .require_git(false)
+        .git_global(false)
+        .build()
+        .flatten()
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .map(|e| e.into_path())
+        .filter(|p| p.extension().is_some_and(|e| e == "rs"))
+        .collect();
+    files.sort();
+    files
+}
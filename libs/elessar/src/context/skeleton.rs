@@ -14,8 +14,51 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use syn::{parse_file, Item, ImplItem, TraitItem};
+use proc_macro2::{Group, TokenStream, TokenTree};
 use quote::quote;
+use syn::spanned::Spanned;
+use syn::visit_mut::{self, VisitMut};
+use syn::{Attribute, Block, ImplItem, Item, ItemMacro, TraitItem, Visibility, parse_file};
+
+/// What happens to `macro_rules!` definitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacroMode {
+    /// Left exactly as written.
+    Keep,
+    /// Every arm keeps its matcher; the expansions are emptied.
+    Signatures,
+    /// Removed.
+    Drop,
+}
+
+/// Knobs for `SkeletonGenerator::generate_with`.
+#[derive(Debug, Clone)]
+pub struct SkeletonOptions {
+    /// Keep `///` and `//!` comments.
+    pub keep_docs: bool,
+    /// Keep items with no visibility modifier. Anything `pub`, `pub(crate)`
+    /// or `pub(in ..)` is always kept, as are trait items and the items of
+    /// trait impls, which take their visibility from the trait.
+    pub keep_private: bool,
+    /// Bodies spanning at most this many lines are kept whole; 0 strips
+    /// every body.
+    pub max_body_lines: usize,
+    /// Reduce `#[cfg(test)]` modules to an empty `mod name {}`.
+    pub collapse_test_modules: bool,
+    pub macros: MacroMode,
+}
+
+impl Default for SkeletonOptions {
+    fn default() -> Self {
+        Self {
+            keep_docs: true,
+            keep_private: true,
+            max_body_lines: 0,
+            collapse_test_modules: true,
+            macros: MacroMode::Signatures,
+        }
+    }
+}
 
 /// The Skeleton Generator.
 ///
@@ -30,54 +73,269 @@ pub struct SkeletonGenerator;
 
 impl SkeletonGenerator {
     /// Parses a raw Rust source string and returns a minified, token-efficient
-    /// skeleton representation of the code.
+    /// skeleton representation of the code, using the default options.
     ///
     /// # Arguments
     /// * `source_code` - The raw string slice of the Rust file (provided via zero-copy mmap).
     pub fn generate(source_code: &str) -> Result<String, syn::Error> {
+        Self::generate_with(source_code, &SkeletonOptions::default())
+    }
+
+    /// As `generate`, shaped by `options`.
+    ///
+    /// The result is one top-level item per line and always parses again
+    /// with `syn::parse_file`.
+    pub fn generate_with(
+        source_code: &str,
+        options: &SkeletonOptions,
+    ) -> Result<String, syn::Error> {
         // Parse the raw string into a pure-Rust Abstract Syntax Tree (AST).
         // This is blazingly fast and requires zero C-dependencies.
         let mut ast = parse_file(source_code)?;
 
-        // Iterate mutably over every top-level item in the file.
-        for item in &mut ast.items {
-            match item {
-                // If the item is a standalone function...
-                Item::Fn(func) => {
-                    // We replace the entire `{ ... }` block with an empty block `{}`.
-                    // The signature, lifetimes, generics, and doc-comments remain intact.
-                    func.block.stmts.clear();
-                }
-                // If the item is an `impl` block (e.g., impl MyStruct { ... })
-                Item::Impl(impl_block) => {
-                    for impl_item in &mut impl_block.items {
-                        if let ImplItem::Fn(method) = impl_item {
-                            // Strip the method body.
-                            method.block.stmts.clear();
+        // Walk every item, descending into inline modules.
+        prune_items(&mut ast.items, options);
+
+        if !options.keep_docs {
+            StripDocs.visit_file_mut(&mut ast);
+        }
+
+        // Convert the modified AST back into a Rust string.
+        // quote! handles the token stream reconstruction beautifully.
+        let mut lines = Vec::with_capacity(ast.items.len() + 1);
+        let inner = &ast.attrs;
+        if !inner.is_empty() {
+            lines.push(quote!(#(#inner)*).to_string());
+        }
+        lines.extend(ast.items.iter().map(|item| quote!(#item).to_string()));
+
+        Ok(lines.join("\n"))
+    }
+}
+
+fn prune_items(items: &mut Vec<Item>, options: &SkeletonOptions) {
+    items.retain(|item| keep_item(item, options));
+
+    for item in items {
+        match item {
+            // If the item is a standalone function...
+            Item::Fn(func) => strip_body(&mut func.block, options),
+            // If the item is an `impl` block (e.g., impl MyStruct { ... })
+            Item::Impl(impl_block) => {
+                // Methods of a trait impl are as public as the trait.
+                let inherent = impl_block.trait_.is_none();
+                impl_block.items.retain(|impl_item| {
+                    options.keep_private
+                        || !inherent
+                        || match impl_item {
+                            ImplItem::Fn(f) => !private(&f.vis),
+                            ImplItem::Const(c) => !private(&c.vis),
+                            ImplItem::Type(t) => !private(&t.vis),
+                            _ => true,
                         }
+                });
+                for impl_item in &mut impl_block.items {
+                    if let ImplItem::Fn(method) = impl_item {
+                        strip_body(&mut method.block, options);
                     }
                 }
-                // If the item is a Trait definition...
-                Item::Trait(trait_block) => {
-                    for trait_item in &mut trait_block.items {
-                        if let TraitItem::Fn(trait_method) = trait_item {
-                            // Traits can have default implementations. We strip those too.
-                            if let Some(default_block) = &mut trait_method.default {
-                                default_block.stmts.clear();
-                            }
-                        }
+            }
+            // If the item is a Trait definition...
+            Item::Trait(trait_block) => {
+                for trait_item in &mut trait_block.items {
+                    // Traits can have default implementations. We strip those too.
+                    if let TraitItem::Fn(trait_method) = trait_item
+                        && let Some(default_block) = &mut trait_method.default
+                    {
+                        strip_body(default_block, options);
+                    }
+                }
+            }
+            // Inline modules are skeletons of their own.
+            Item::Mod(module) => {
+                if let Some((_, content)) = &mut module.content {
+                    if options.collapse_test_modules && is_cfg_test(&module.attrs) {
+                        content.clear();
+                    } else {
+                        prune_items(content, options);
                     }
                 }
-                // Structs, Enums, Macros, and Use statements are left untouched,
-                // as they are critical for architectural context.
-                _ => {}
             }
+            Item::Macro(mac) if is_macro_rules(mac) && options.macros == MacroMode::Signatures => {
+                mac.mac.tokens = macro_signatures(&mac.mac.tokens);
+            }
+            // Structs, Enums, and Use statements are left untouched,
+            // as they are critical for architectural context.
+            _ => {}
         }
+    }
+}
 
-        // Convert the modified AST back into a formatted Rust string.
-        // quote! handles the token stream reconstruction beautifully.
-        let skeleton = quote!(#ast).to_string();
+fn keep_item(item: &Item, options: &SkeletonOptions) -> bool {
+    if let Item::Macro(mac) = item
+        && is_macro_rules(mac)
+    {
+        if options.macros == MacroMode::Drop {
+            return false;
+        }
+        // A macro_rules! is only visible elsewhere when exported.
+        let exported = mac.attrs.iter().any(|a| a.path().is_ident("macro_export"));
+        return options.keep_private || exported;
+    }
+    if options.keep_private {
+        return true;
+    }
+    let vis = match item {
+        Item::Const(i) => &i.vis,
+        Item::Enum(i) => &i.vis,
+        Item::ExternCrate(i) => &i.vis,
+        Item::Fn(i) => &i.vis,
+        Item::Mod(i) => &i.vis,
+        Item::Static(i) => &i.vis,
+        Item::Struct(i) => &i.vis,
+        Item::Trait(i) => &i.vis,
+        Item::TraitAlias(i) => &i.vis,
+        Item::Type(i) => &i.vis,
+        Item::Union(i) => &i.vis,
+        Item::Use(i) => &i.vis,
+        // Impls, extern blocks and other macro calls have no visibility of
+        // their own.
+        _ => return true,
+    };
+    !private(vis)
+}
 
-        Ok(skeleton)
+fn private(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Inherited)
+}
+
+/// We replace the entire `{ ... }` block with an empty block `{}`, unless it
+/// is short enough to keep. The signature, lifetimes, generics, and
+/// doc-comments remain intact.
+fn strip_body(block: &mut Block, options: &SkeletonOptions) {
+    let span = block.span();
+    let lines = span.end().line.saturating_sub(span.start().line) + 1;
+    if lines > options.max_body_lines {
+        block.stmts.clear();
     }
 }
+
+fn is_cfg_test(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|a| {
+        a.path().is_ident("cfg")
+            && a.parse_args::<syn::Ident>()
+                .is_ok_and(|ident| ident == "test")
+    })
+}
+
+fn is_macro_rules(mac: &ItemMacro) -> bool {
+    mac.ident.is_some() && mac.mac.path.is_ident("macro_rules")
+}
+
+/// `(matcher) => { expansion };` becomes `(matcher) => {};`. Bodies that
+/// don't follow that shape are returned as they were.
+fn macro_signatures(tokens: &TokenStream) -> TokenStream {
+    let trees: Vec<TokenTree> = tokens.clone().into_iter().collect();
+    let mut out = Vec::with_capacity(trees.len());
+    let mut i = 0;
+    while i < trees.len() {
+        let arm = match &trees[i..] {
+            [
+                TokenTree::Group(matcher),
+                TokenTree::Punct(eq),
+                TokenTree::Punct(gt),
+                TokenTree::Group(body),
+                ..,
+            ] if eq.as_char() == '=' && gt.as_char() == '>' => [
+                TokenTree::Group(matcher.clone()),
+                TokenTree::Punct(eq.clone()),
+                TokenTree::Punct(gt.clone()),
+                TokenTree::Group(Group::new(body.delimiter(), TokenStream::new())),
+            ],
+            _ => return tokens.clone(),
+        };
+        out.extend(arm);
+        i += 4;
+        if let Some(TokenTree::Punct(semi)) = trees.get(i)
+            && semi.as_char() == ';'
+        {
+            out.push(trees[i].clone());
+            i += 1;
+        }
+    }
+    out.into_iter().collect()
+}
+
+/// Drops `#[doc]` attributes wherever docs can be written.
+struct StripDocs;
+
+fn drop_docs(attrs: &mut Vec<Attribute>) {
+    attrs.retain(|a| !a.path().is_ident("doc"));
+}
+
+impl VisitMut for StripDocs {
+    fn visit_file_mut(&mut self, node: &mut syn::File) {
+        drop_docs(&mut node.attrs);
+        visit_mut::visit_file_mut(self, node);
+    }
+
+    fn visit_item_mut(&mut self, node: &mut Item) {
+        if let Some(attrs) = item_attrs(node) {
+            drop_docs(attrs);
+        }
+        visit_mut::visit_item_mut(self, node);
+    }
+
+    fn visit_impl_item_mut(&mut self, node: &mut ImplItem) {
+        match node {
+            ImplItem::Const(i) => drop_docs(&mut i.attrs),
+            ImplItem::Fn(i) => drop_docs(&mut i.attrs),
+            ImplItem::Type(i) => drop_docs(&mut i.attrs),
+            ImplItem::Macro(i) => drop_docs(&mut i.attrs),
+            _ => {}
+        }
+        visit_mut::visit_impl_item_mut(self, node);
+    }
+
+    fn visit_trait_item_mut(&mut self, node: &mut TraitItem) {
+        match node {
+            TraitItem::Const(i) => drop_docs(&mut i.attrs),
+            TraitItem::Fn(i) => drop_docs(&mut i.attrs),
+            TraitItem::Type(i) => drop_docs(&mut i.attrs),
+            TraitItem::Macro(i) => drop_docs(&mut i.attrs),
+            _ => {}
+        }
+        visit_mut::visit_trait_item_mut(self, node);
+    }
+
+    fn visit_field_mut(&mut self, node: &mut syn::Field) {
+        drop_docs(&mut node.attrs);
+        visit_mut::visit_field_mut(self, node);
+    }
+
+    fn visit_variant_mut(&mut self, node: &mut syn::Variant) {
+        drop_docs(&mut node.attrs);
+        visit_mut::visit_variant_mut(self, node);
+    }
+}
+
+fn item_attrs(item: &mut Item) -> Option<&mut Vec<Attribute>> {
+    Some(match item {
+        Item::Const(i) => &mut i.attrs,
+        Item::Enum(i) => &mut i.attrs,
+        Item::ExternCrate(i) => &mut i.attrs,
+        Item::Fn(i) => &mut i.attrs,
+        Item::ForeignMod(i) => &mut i.attrs,
+        Item::Impl(i) => &mut i.attrs,
+        Item::Macro(i) => &mut i.attrs,
+        Item::Mod(i) => &mut i.attrs,
+        Item::Static(i) => &mut i.attrs,
+        Item::Struct(i) => &mut i.attrs,
+        Item::Trait(i) => &mut i.attrs,
+        Item::TraitAlias(i) => &mut i.attrs,
+        Item::Type(i) => &mut i.attrs,
+        Item::Union(i) => &mut i.attrs,
+        Item::Use(i) => &mut i.attrs,
+        _ => return None,
+    })
+}
@@ -0,0 +1,288 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// =====================================================================
+// CRATE: libs/elessar/src/detect.rs
+// DESCRIPTION: Spline detection. Markers, language weight, sub-projects.
+// =====================================================================
+
+//! A directory rarely has a single nature. A Rust workspace carries a
+//! Makefile, a web front end hides under `ui/`, a flake pins the toolchain.
+//! Instead of stopping at the first marker we survey the whole tree once
+//! (honouring `.gitignore`) and rank every Spline that has a claim on it.
+
+use crate::Spline;
+use ignore::WalkBuilder;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// How deep the survey descends before it stops looking.
+const MAX_DEPTH: usize = 8;
+/// A ceiling on the entries visited, source or not, so pointing Elessar at
+/// `$HOME` stays cheap.
+const MAX_ENTRIES: usize = 50_000;
+/// Share of the confidence earned by a marker file; the rest is language weight.
+const MARKER_WEIGHT: f32 = 0.5;
+/// Build output and vendored trees that are skipped even without a `.gitignore`.
+const SKIP_DIRS: &[&str] = &["target", "node_modules"];
+
+/// The files that announce a Spline in the directory that holds them.
+const MARKERS: &[(Spline, &[&str])] = &[
+    (Spline::UnaOS, &["MEMORIA.md"]),
+    (Spline::Rust, &["Cargo.toml"]),
+    (Spline::Web, &["package.json"]),
+    (
+        Spline::Python,
+        &[
+            "pyproject.toml",
+            "requirements.txt",
+            "setup.py",
+            "setup.cfg",
+            "Pipfile",
+        ],
+    ),
+    (Spline::Go, &["go.mod", "go.work"]),
+    (Spline::CMake, &["CMakeLists.txt"]),
+    (Spline::Make, &["Makefile", "makefile", "GNUmakefile"]),
+    (Spline::Meson, &["meson.build"]),
+    (
+        Spline::Gradle,
+        &[
+            "build.gradle",
+            "build.gradle.kts",
+            "settings.gradle",
+            "settings.gradle.kts",
+        ],
+    ),
+    (Spline::Maven, &["pom.xml"]),
+    (Spline::Zig, &["build.zig"]),
+    (Spline::Nix, &["flake.nix", "default.nix", "shell.nix"]),
+];
+
+/// Source languages and the Splines that build them. When a language has
+/// several candidates, its weight goes to those with a marker present, or
+/// to the first one when none is.
+const LANGUAGES: &[(&str, &[&str], &[Spline])] = &[
+    ("Rust", &["rs"], &[Spline::Rust]),
+    (
+        "JavaScript/TypeScript",
+        &[
+            "js", "mjs", "cjs", "jsx", "ts", "mts", "cts", "tsx", "vue", "svelte",
+        ],
+        &[Spline::Web],
+    ),
+    ("Python", &["py", "pyi"], &[Spline::Python]),
+    ("Go", &["go"], &[Spline::Go]),
+    (
+        "C/C++",
+        &["c", "h", "cc", "cpp", "cxx", "hh", "hpp", "hxx"],
+        &[Spline::Make, Spline::CMake, Spline::Meson],
+    ),
+    (
+        "JVM",
+        &["java", "kt", "kts", "groovy", "scala"],
+        &[Spline::Gradle, Spline::Maven],
+    ),
+    ("Zig", &["zig"], &[Spline::Zig]),
+    ("Nix", &["nix"], &[Spline::Nix]),
+];
+
+/// One entry in a ranked detection: a Spline, how sure we are of it
+/// (0.0 to 1.0) and the observations that led there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplineMatch {
+    pub spline: Spline,
+    pub confidence: f32,
+    pub evidence: Vec<String>,
+}
+
+/// A nested directory that carries markers of its own, e.g. `web/` with a
+/// `package.json` inside a Go monorepo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubProject {
+    /// Relative to the surveyed root.
+    pub path: PathBuf,
+    /// Ranked the same way as the root, over this directory's files only.
+    pub splines: Vec<SplineMatch>,
+}
+
+impl SubProject {
+    /// The most likely Spline of the sub-project.
+    pub fn spline(&self) -> Spline {
+        self.splines.first().map_or(Spline::Void, |m| m.spline)
+    }
+}
+
+struct SourceFile {
+    path: PathBuf,
+    bytes: u64,
+    language: usize,
+}
+
+/// A single walk over a directory tree, reusable for every ranking.
+pub(crate) struct Survey {
+    files: Vec<SourceFile>,
+    /// Marker files found, keyed by the directory (relative) that holds them.
+    markers: BTreeMap<PathBuf, Vec<(Spline, String)>>,
+}
+
+impl Survey {
+    pub(crate) fn scan(root: &Path) -> Self {
+        let mut survey = Survey {
+            files: Vec::new(),
+            markers: BTreeMap::new(),
+        };
+        let walker = WalkBuilder::new(root)
+            .max_depth(Some(MAX_DEPTH))
+            .require_git(false)
+            .git_global(false)
+            .filter_entry(|entry| {
+                let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                !(is_dir && entry.depth() > 0 && SKIP_DIRS.iter().any(|d| entry.file_name() == *d))
+            })
+            .build();
+
+        for entry in walker.flatten().take(MAX_ENTRIES) {
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let Ok(relative) = entry.path().strip_prefix(root) else {
+                continue;
+            };
+            let name = entry.file_name().to_string_lossy();
+            if let Some((spline, _)) = MARKERS.iter().find(|(_, files)| files.contains(&&*name)) {
+                let dir = relative.parent().unwrap_or(Path::new("")).to_path_buf();
+                survey
+                    .markers
+                    .entry(dir)
+                    .or_default()
+                    .push((*spline, name.to_string()));
+            }
+            let extension = relative.extension().and_then(|e| e.to_str()).unwrap_or("");
+            if let Some(language) = LANGUAGES
+                .iter()
+                .position(|(_, extensions, _)| extensions.contains(&extension))
+            {
+                let bytes = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                survey.files.push(SourceFile {
+                    path: relative.to_path_buf(),
+                    bytes,
+                    language,
+                });
+            }
+        }
+        survey
+    }
+
+    /// Ranks the Splines of `dir` (relative to the root; empty for the root).
+    pub(crate) fn rank(&self, dir: &Path) -> Vec<SplineMatch> {
+        let markers = self.markers.get(dir).map(Vec::as_slice).unwrap_or(&[]);
+        let mut found: HashMap<Spline, SplineMatch> = HashMap::new();
+
+        for (spline, name) in markers {
+            let entry = found.entry(*spline).or_insert_with(|| SplineMatch {
+                spline: *spline,
+                confidence: MARKER_WEIGHT,
+                evidence: Vec::new(),
+            });
+            entry.evidence.push(format!("marker: {}", name));
+        }
+
+        // Weigh the source languages under this directory by size.
+        let mut bytes = vec![0u64; LANGUAGES.len()];
+        let mut counts = vec![0usize; LANGUAGES.len()];
+        for file in self.files.iter().filter(|f| f.path.starts_with(dir)) {
+            bytes[file.language] += file.bytes;
+            counts[file.language] += 1;
+        }
+        let total: u64 = bytes.iter().sum();
+        for (language, (name, _, candidates)) in LANGUAGES.iter().enumerate() {
+            if counts[language] == 0 {
+                continue;
+            }
+            let share = if total == 0 {
+                0.0
+            } else {
+                bytes[language] as f32 / total as f32
+            };
+            let marked: Vec<Spline> = candidates
+                .iter()
+                .copied()
+                .filter(|s| markers.iter().any(|(m, _)| m == s))
+                .collect();
+            let owners = if marked.is_empty() {
+                vec![candidates[0]]
+            } else {
+                marked
+            };
+            for spline in owners {
+                let entry = found.entry(spline).or_insert_with(|| SplineMatch {
+                    spline,
+                    confidence: 0.0,
+                    evidence: Vec::new(),
+                });
+                entry.confidence += (1.0 - MARKER_WEIGHT) * share;
+                entry.evidence.push(format!(
+                    "{}: {} file{}, {:.0}% of source bytes",
+                    name,
+                    counts[language],
+                    if counts[language] == 1 { "" } else { "s" },
+                    share * 100.0
+                ));
+            }
+        }
+
+        // Our own memory core outranks everything else in the room.
+        if let Some(unaos) = found.get_mut(&Spline::UnaOS) {
+            unaos.confidence = 1.0;
+        }
+
+        let mut ranked: Vec<SplineMatch> = found
+            .into_values()
+            .filter(|m| m.confidence > 0.0)
+            .map(|mut m| {
+                m.confidence = m.confidence.min(1.0);
+                m
+            })
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.confidence
+                .total_cmp(&a.confidence)
+                .then_with(|| order(a.spline).cmp(&order(b.spline)))
+        });
+        ranked
+    }
+
+    /// Every directory below the root that carries a marker, ranked on its own.
+    pub(crate) fn subprojects(&self) -> Vec<SubProject> {
+        self.markers
+            .keys()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| SubProject {
+                path: dir.clone(),
+                splines: self.rank(dir),
+            })
+            .collect()
+    }
+}
+
+/// Tie-break by declaration order in [`MARKERS`].
+fn order(spline: Spline) -> usize {
+    MARKERS
+        .iter()
+        .position(|(s, _)| *s == spline)
+        .unwrap_or(MARKERS.len())
+}
@@ -26,11 +26,20 @@
 // Connects to the spatial indexing logic (e.g., context/indexer.rs)
 pub mod context;
 
+// Items, imports and references across the workspace's Rust sources.
+pub mod symbols;
+
+// Ranked Spline detection over the whole tree.
+mod detect;
+
+pub use detect::{SplineMatch, SubProject};
+
+use detect::Survey;
 use std::path::Path;
 
 /// Represents the fundamental nature of a workspace or directory.
 /// We call this the "Spline" - the mathematical curve that defines the project's trajectory.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum Spline {
     /// The Monolith itself. Defined by the presence of MEMORIA.md.
     UnaOS,
@@ -40,6 +49,22 @@ pub enum Spline {
     Web,
     /// A Python Project (requirements.txt / pyproject.toml).
     Python,
+    /// A Go Module (go.mod).
+    Go,
+    /// A CMake Project (CMakeLists.txt).
+    CMake,
+    /// A Make Project (Makefile).
+    Make,
+    /// A Meson Project (meson.build).
+    Meson,
+    /// A Gradle Project (build.gradle / build.gradle.kts).
+    Gradle,
+    /// A Maven Project (pom.xml).
+    Maven,
+    /// A Zig Project (build.zig).
+    Zig,
+    /// A Nix Flake or Derivation (flake.nix / default.nix).
+    Nix,
     /// Unknown territory.
     Void,
 }
@@ -47,43 +72,39 @@ pub enum Spline {
 /// The Context holds the spatial and structural awareness of our current environment.
 pub struct Context {
     pub path: std::path::PathBuf,
+    /// The strongest Spline, or `Void` when nothing claims the directory.
     pub spline: Spline,
+    /// Every Spline with a claim on the directory, most confident first.
+    pub splines: Vec<SplineMatch>,
+    /// Nested directories with markers of their own (monorepo members).
+    pub subprojects: Vec<SubProject>,
 }
 
 impl Context {
     /// Scans the given path to determine its Spline.
     /// This is the sensory input for Elessar's context awareness.
     pub fn new(path: &Path) -> Self {
-        let spline = detect_spline(path);
+        // One walk feeds both the root ranking and the sub-projects.
+        let survey = Survey::scan(path);
+        let splines = survey.rank(Path::new(""));
+        let spline = splines.first().map_or(Spline::Void, |m| m.spline);
         Self {
             path: path.to_path_buf(),
             spline,
+            splines,
+            subprojects: survey.subprojects(),
         }
     }
 }
 
-/// Interrogates the directory structure to identify the project type.
-/// Generously commented to leave no doubt about the engine's logic.
-fn detect_spline(path: &Path) -> Spline {
-    // If it contains our memory core, it is our own flesh and blood.
-    if path.join("MEMORIA.md").exists() {
-        return Spline::UnaOS;
-    }
-    // Standard Rust ecosystem detection.
-    if path.join("Cargo.toml").exists() {
-        return Spline::Rust;
-    }
-    // Standard Node/Web ecosystem detection.
-    if path.join("package.json").exists() {
-        return Spline::Web;
-    }
-    // Python environments often use either of these.
-    if path.join("requirements.txt").exists() || path.join("pyproject.toml").exists() {
-        return Spline::Python;
-    }
-
-    // If it matches nothing, it is the Void.
-    Spline::Void
+/// Interrogates the directory structure and ranks every Spline it supports.
+///
+/// Each marker file (Cargo.toml, go.mod, meson.build, ...) in `path` itself is
+/// worth half the confidence; the other half is the Spline's share of the
+/// source bytes beneath it. `.gitignore` rules are honoured, so build output
+/// and vendored trees do not tip the scales. An empty result is the Void.
+pub fn detect_splines(path: &Path) -> Vec<SplineMatch> {
+    Survey::scan(path).rank(Path::new(""))
 }
 
 #[cfg(test)]
@@ -0,0 +1,472 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! One file's worth of symbols, imports and name occurrences.
+
+use super::{Location, Symbol, SymbolKind};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::path::{Path, PathBuf};
+use syn::visit::{self, Visit};
+use syn::{ImplItem, Item, TraitItem, Type, UseTree};
+
+/// A symbol plus what resolution needs to know about it.
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+    pub symbol: Symbol,
+    /// Index into `FileIndex::modules` of the module it was declared in.
+    pub module: usize,
+    /// For methods, variants and associated items: the type or trait they
+    /// belong to, as written.
+    pub owner: Option<Vec<String>>,
+    /// `#[macro_export]`, so also visible at the crate root.
+    pub exported: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Import {
+    /// The name it binds, or `None` for a glob.
+    pub name: Option<String>,
+    pub target: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ModuleData {
+    pub path: Vec<String>,
+    pub imports: Vec<Import>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Usage {
+    /// Part of a path, resolvable.
+    Path,
+    /// A method call or a bare identifier inside a macro call; only the
+    /// name is known.
+    Textual,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Occurrence {
+    pub location: Location,
+    /// The path up to and including this name.
+    pub segments: Vec<String>,
+    pub module: usize,
+    pub usage: Usage,
+}
+
+/// An out-of-line `mod name;`.
+#[derive(Debug, Clone)]
+pub(crate) struct ModDecl {
+    pub path: Vec<String>,
+    /// Where the file may be, in the order Rust looks.
+    pub candidates: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct FileIndex {
+    pub crate_name: String,
+    /// `modules[0]` is the module the file itself is.
+    pub modules: Vec<ModuleData>,
+    pub entries: Vec<Entry>,
+    pub occurrences: Vec<Occurrence>,
+    pub decls: Vec<ModDecl>,
+}
+
+impl FileIndex {
+    pub(crate) fn module_path(&self) -> &[String] {
+        &self.modules[0].path
+    }
+}
+
+/// Parses `source`, which is the module `module` of `crate_name`.
+/// `mod_root` is true for `lib.rs`, `main.rs` and `mod.rs`, whose child
+/// modules sit beside them rather than in a directory named after them.
+pub(crate) fn collect(
+    file: &Path,
+    source: &str,
+    crate_name: &str,
+    module: Vec<String>,
+    mod_root: bool,
+) -> Result<FileIndex, String> {
+    let ast = syn::parse_file(source).map_err(|e| {
+        let at = e.span().start();
+        format!("{}:{}:{}: {}", file.display(), at.line, at.column, e)
+    })?;
+    let parent = file.parent().unwrap_or(Path::new(""));
+    let dir = if mod_root {
+        parent.to_path_buf()
+    } else {
+        parent.join(file.file_stem().unwrap_or_default())
+    };
+    let mut collector = Collector {
+        file: file.to_path_buf(),
+        index: FileIndex {
+            crate_name: crate_name.to_string(),
+            modules: vec![ModuleData {
+                path: module,
+                imports: Vec::new(),
+            }],
+            entries: Vec::new(),
+            occurrences: Vec::new(),
+            decls: Vec::new(),
+        },
+    };
+    collector.items(&ast.items, 0, &dir, parent);
+    Ok(collector.index)
+}
+
+struct Collector {
+    file: PathBuf,
+    index: FileIndex,
+}
+
+impl Collector {
+    fn location(&self, span: Span) -> Location {
+        let start = span.start();
+        Location {
+            file: self.file.clone(),
+            line: start.line,
+            column: start.column,
+        }
+    }
+
+    fn define(
+        &mut self,
+        module: usize,
+        name: String,
+        kind: SymbolKind,
+        span: Span,
+        owner: Option<Vec<String>>,
+    ) {
+        let mut path = self.index.modules[module].path.clone();
+        path.extend(owner.iter().flatten().cloned());
+        path.push(name.clone());
+        let symbol = Symbol {
+            name,
+            kind,
+            path: path.join("::"),
+            location: self.location(span),
+        };
+        self.index.entries.push(Entry {
+            symbol,
+            module,
+            owner,
+            exported: false,
+        });
+    }
+
+    /// `dir` is where `mod x;` inside this module looks for `x.rs`; `here`
+    /// is the directory `#[path]` attributes are relative to.
+    fn items(&mut self, items: &[Item], module: usize, dir: &Path, here: &Path) {
+        for item in items {
+            match item {
+                Item::Fn(i) => self.define(
+                    module,
+                    i.sig.ident.to_string(),
+                    SymbolKind::Fn,
+                    i.sig.ident.span(),
+                    None,
+                ),
+                Item::Struct(i) => self.define(
+                    module,
+                    i.ident.to_string(),
+                    SymbolKind::Struct,
+                    i.ident.span(),
+                    None,
+                ),
+                Item::Union(i) => self.define(
+                    module,
+                    i.ident.to_string(),
+                    SymbolKind::Union,
+                    i.ident.span(),
+                    None,
+                ),
+                Item::Const(i) => self.define(
+                    module,
+                    i.ident.to_string(),
+                    SymbolKind::Const,
+                    i.ident.span(),
+                    None,
+                ),
+                Item::Static(i) => self.define(
+                    module,
+                    i.ident.to_string(),
+                    SymbolKind::Static,
+                    i.ident.span(),
+                    None,
+                ),
+                Item::Type(i) => self.define(
+                    module,
+                    i.ident.to_string(),
+                    SymbolKind::TypeAlias,
+                    i.ident.span(),
+                    None,
+                ),
+                Item::Enum(i) => {
+                    self.define(
+                        module,
+                        i.ident.to_string(),
+                        SymbolKind::Enum,
+                        i.ident.span(),
+                        None,
+                    );
+                    for variant in &i.variants {
+                        let owner = Some(vec![i.ident.to_string()]);
+                        self.define(
+                            module,
+                            variant.ident.to_string(),
+                            SymbolKind::Variant,
+                            variant.ident.span(),
+                            owner,
+                        );
+                    }
+                }
+                Item::Trait(i) => {
+                    self.define(
+                        module,
+                        i.ident.to_string(),
+                        SymbolKind::Trait,
+                        i.ident.span(),
+                        None,
+                    );
+                    let owner = vec![i.ident.to_string()];
+                    for trait_item in &i.items {
+                        let (name, kind) = match trait_item {
+                            TraitItem::Fn(f) => (&f.sig.ident, SymbolKind::Method),
+                            TraitItem::Const(c) => (&c.ident, SymbolKind::Const),
+                            TraitItem::Type(t) => (&t.ident, SymbolKind::TypeAlias),
+                            _ => continue,
+                        };
+                        self.define(
+                            module,
+                            name.to_string(),
+                            kind,
+                            name.span(),
+                            Some(owner.clone()),
+                        );
+                    }
+                }
+                Item::Impl(i) => self.implementation(i, module),
+                Item::Macro(i) => {
+                    if let Some(ident) = &i.ident {
+                        self.define(
+                            module,
+                            ident.to_string(),
+                            SymbolKind::Macro,
+                            ident.span(),
+                            None,
+                        );
+                        let exported = i.attrs.iter().any(|a| a.path().is_ident("macro_export"));
+                        self.index.entries.last_mut().unwrap().exported = exported;
+                    }
+                }
+                Item::Mod(i) => {
+                    let name = i.ident.to_string();
+                    self.define(module, name.clone(), SymbolKind::Mod, i.ident.span(), None);
+                    let mut path = self.index.modules[module].path.clone();
+                    path.push(name.clone());
+                    let explicit = i.attrs.iter().find_map(|a| match &a.meta {
+                        syn::Meta::NameValue(nv) if nv.path.is_ident("path") => match &nv.value {
+                            syn::Expr::Lit(syn::ExprLit {
+                                lit: syn::Lit::Str(s),
+                                ..
+                            }) => Some(s.value()),
+                            _ => None,
+                        },
+                        _ => None,
+                    });
+                    match &i.content {
+                        Some((_, content)) => {
+                            self.index.modules.push(ModuleData {
+                                path,
+                                imports: Vec::new(),
+                            });
+                            let inner = self.index.modules.len() - 1;
+                            let inner_dir = dir.join(&name);
+                            self.items(content, inner, &inner_dir, &inner_dir);
+                        }
+                        None => {
+                            let candidates = match explicit {
+                                Some(p) => vec![here.join(p)],
+                                None => vec![
+                                    dir.join(format!("{name}.rs")),
+                                    dir.join(&name).join("mod.rs"),
+                                ],
+                            };
+                            self.index.decls.push(ModDecl { path, candidates });
+                        }
+                    }
+                    continue;
+                }
+                Item::Use(i) => {
+                    self.use_tree(&i.tree, Vec::new(), module);
+                    continue;
+                }
+                _ => {}
+            }
+            Occurrences {
+                collector: self,
+                module,
+            }
+            .visit_item(item);
+        }
+    }
+
+    fn implementation(&mut self, i: &syn::ItemImpl, module: usize) {
+        let self_ty = match &*i.self_ty {
+            Type::Path(p) => p
+                .path
+                .segments
+                .iter()
+                .map(|s| s.ident.to_string())
+                .collect(),
+            other => vec![other.to_token_stream().to_string()],
+        };
+        let name = match &i.trait_ {
+            Some((_, tr, _)) => format!(
+                "<impl {} for {}>",
+                tr.segments
+                    .last()
+                    .map(|s| s.ident.to_string())
+                    .unwrap_or_default(),
+                self_ty.join("::")
+            ),
+            None => format!("<impl {}>", self_ty.join("::")),
+        };
+        self.define(module, name, SymbolKind::Impl, i.impl_token.span, None);
+        for impl_item in &i.items {
+            let (ident, kind) = match impl_item {
+                ImplItem::Fn(f) => (&f.sig.ident, SymbolKind::Method),
+                ImplItem::Const(c) => (&c.ident, SymbolKind::Const),
+                ImplItem::Type(t) => (&t.ident, SymbolKind::TypeAlias),
+                _ => continue,
+            };
+            self.define(
+                module,
+                ident.to_string(),
+                kind,
+                ident.span(),
+                Some(self_ty.clone()),
+            );
+        }
+    }
+
+    fn use_tree(&mut self, tree: &UseTree, mut prefix: Vec<String>, module: usize) {
+        let occur = |c: &mut Self, ident: &syn::Ident, segments: Vec<String>| {
+            let location = c.location(ident.span());
+            c.index.occurrences.push(Occurrence {
+                location,
+                segments,
+                module,
+                usage: Usage::Path,
+            });
+        };
+        match tree {
+            UseTree::Path(p) => {
+                prefix.push(p.ident.to_string());
+                occur(self, &p.ident, prefix.clone());
+                self.use_tree(&p.tree, prefix, module);
+            }
+            UseTree::Name(n) => {
+                let name = n.ident.to_string();
+                // `use a::b::{self}` binds `b`.
+                let (bound, target) = if name == "self" {
+                    (prefix.last().cloned().unwrap_or_default(), prefix)
+                } else {
+                    prefix.push(name.clone());
+                    occur(self, &n.ident, prefix.clone());
+                    (name, prefix)
+                };
+                self.index.modules[module].imports.push(Import {
+                    name: Some(bound),
+                    target,
+                });
+            }
+            UseTree::Rename(r) => {
+                prefix.push(r.ident.to_string());
+                occur(self, &r.ident, prefix.clone());
+                self.index.modules[module].imports.push(Import {
+                    name: Some(r.rename.to_string()),
+                    target: prefix,
+                });
+            }
+            UseTree::Glob(_) => self.index.modules[module].imports.push(Import {
+                name: None,
+                target: prefix,
+            }),
+            UseTree::Group(g) => {
+                for tree in &g.items {
+                    self.use_tree(tree, prefix.clone(), module);
+                }
+            }
+        }
+    }
+}
+
+/// Records every name used inside one item.
+struct Occurrences<'a> {
+    collector: &'a mut Collector,
+    module: usize,
+}
+
+impl Occurrences<'_> {
+    fn push(&mut self, span: Span, segments: Vec<String>, usage: Usage) {
+        let location = self.collector.location(span);
+        self.collector.index.occurrences.push(Occurrence {
+            location,
+            segments,
+            module: self.module,
+            usage,
+        });
+    }
+
+    fn tokens(&mut self, tokens: TokenStream) {
+        for tree in tokens {
+            match tree {
+                TokenTree::Ident(ident) => {
+                    self.push(ident.span(), vec![ident.to_string()], Usage::Textual)
+                }
+                TokenTree::Group(group) => self.tokens(group.stream()),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Occurrences<'_> {
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        let mut segments = Vec::new();
+        for segment in &path.segments {
+            segments.push(segment.ident.to_string());
+            self.push(segment.ident.span(), segments.clone(), Usage::Path);
+        }
+        visit::visit_path(self, path);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        self.push(
+            call.method.span(),
+            vec![call.method.to_string()],
+            Usage::Textual,
+        );
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        self.visit_path(&mac.path);
+        self.tokens(mac.tokens.clone());
+    }
+}
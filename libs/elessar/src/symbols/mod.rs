@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A workspace-wide index of Rust items, for go-to-definition and
+//! find-references.
+//!
+//! Each crate found by `WorkspaceIndexer` is read from its `src/lib.rs` (or
+//! `src/main.rs`) down through its `mod` declarations. Every item gets a
+//! `Symbol` with its module path and position; every name used in a path,
+//! `use`, method call or macro call is kept as an occurrence. Names are
+//! resolved lazily at query time through `use` imports, re-exports and
+//! globs, which keeps `update_file` down to re-reading one file.
+//!
+//! Resolution covers what can be done without types: paths, imports and
+//! associated items reached through a type's path. Method calls and names
+//! inside macro calls are matched by name only and reported as such.
+//! Visibility is not checked.
+
+mod collect;
+mod resolve;
+
+use crate::context::WorkspaceIndexer;
+use collect::{FileIndex, ModDecl, Usage};
+use resolve::Resolver;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SymbolKind {
+    Fn,
+    /// A function in an impl or trait.
+    Method,
+    Struct,
+    Enum,
+    Variant,
+    Union,
+    Trait,
+    Impl,
+    Const,
+    Static,
+    TypeAlias,
+    Macro,
+    Mod,
+}
+
+/// A position in a file: 1-based line, 0-based column in characters.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Location {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Where it is defined, e.g. `gneiss_pal::paths::UnaPaths::logs`. Impls
+    /// are named `<impl Trait for Type>`.
+    pub path: String,
+    /// The defining name (the `impl` keyword for impls).
+    pub location: Location,
+}
+
+/// A use of a symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reference {
+    pub location: Location,
+    /// False when only the name matched: method calls and macro arguments.
+    pub resolved: bool,
+}
+
+/// Something a path can lead to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Target {
+    Module(Vec<String>),
+    /// A file and an index into its entries.
+    Symbol(PathBuf, usize),
+}
+
+#[derive(Default)]
+pub struct SymbolIndex {
+    /// Ordered by path, so lookups that scan every file answer the same
+    /// way each run.
+    files: BTreeMap<PathBuf, FileIndex>,
+    /// Crate name (with `-` as `_`) to its root file.
+    crates: HashMap<String, PathBuf>,
+    /// Module path to the file it lives in and its index there.
+    modules: HashMap<Vec<String>, (PathBuf, usize)>,
+    /// Files that failed to parse, with the error. Their last good index,
+    /// if any, stays in use.
+    pub errors: HashMap<PathBuf, String>,
+}
+
+impl SymbolIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes every crate the indexer found that has a `src/lib.rs` or
+    /// `src/main.rs`.
+    pub fn build(workspace: &WorkspaceIndexer) -> Self {
+        let mut index = Self::new();
+        let mut nodes: Vec<_> = workspace.nodes.values().collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        for node in nodes {
+            index.add_crate(&node.name, &node.path);
+        }
+        index
+    }
+
+    /// Indexes one crate rooted at `dir`.
+    pub fn add_crate(&mut self, name: &str, dir: &Path) {
+        let Some(root) = ["src/lib.rs", "src/main.rs"]
+            .iter()
+            .map(|f| dir.join(f))
+            .find(|f| f.is_file())
+        else {
+            return;
+        };
+        let name = name.replace('-', "_");
+        self.crates.insert(name.clone(), root.clone());
+        self.index_tree(&root, &name, vec![name.clone()], true);
+        self.rebuild_modules();
+    }
+
+    /// Re-reads `file` after it changed, was created or was deleted. New or
+    /// removed `mod` declarations bring their files in or drop them.
+    /// Returns false if the file is not part of any indexed crate.
+    pub fn update_file(&mut self, file: &Path) -> bool {
+        let (crate_name, module, mod_root) = if let Some(known) = self.files.get(file) {
+            let mod_root = self.crates.values().any(|r| r == file)
+                || file.file_name().is_some_and(|n| n == "mod.rs");
+            (
+                known.crate_name.clone(),
+                known.module_path().to_vec(),
+                mod_root,
+            )
+        } else if let Some((crate_name, decl)) = self.declaring(file) {
+            (
+                crate_name,
+                decl.path,
+                file.file_name().is_some_and(|n| n == "mod.rs"),
+            )
+        } else {
+            return false;
+        };
+
+        let old_children = self.children(file);
+        if file.is_file() {
+            self.index_one(file, &crate_name, module, mod_root);
+        } else {
+            self.files.remove(file);
+            self.errors.remove(file);
+        }
+        let new_children = self.children(file);
+
+        for gone in old_children.iter().filter(|c| !new_children.contains(c)) {
+            self.remove_tree(gone);
+        }
+        for (child, module) in self.child_modules(file) {
+            if !self.files.contains_key(&child) {
+                let mod_root = child.file_name().is_some_and(|n| n == "mod.rs");
+                self.index_tree(&child, &crate_name, module, mod_root);
+            }
+        }
+        self.rebuild_modules();
+        true
+    }
+
+    /// Every symbol, by file and then by position in the file.
+    pub fn symbols(&self) -> impl Iterator<Item = &Symbol> {
+        self.files
+            .values()
+            .flat_map(|f| f.entries.iter().map(|e| &e.symbol))
+    }
+
+    /// The symbol defined at exactly `path`.
+    pub fn find(&self, path: &str) -> Option<&Symbol> {
+        self.symbols().find(|s| s.path == path)
+    }
+
+    pub fn named(&self, name: &str) -> Vec<&Symbol> {
+        let mut out: Vec<_> = self.symbols().filter(|s| s.name == name).collect();
+        out.sort_by(|a, b| a.path.cmp(&b.path));
+        out
+    }
+
+    /// What `path` (`crate::a::B`, as it could be written at the root of
+    /// `crate`) refers to, following re-exports.
+    pub fn resolve(&self, path: &str) -> Option<&Symbol> {
+        let segments: Vec<String> = path.split("::").map(str::to_string).collect();
+        let target = self.resolve_path(&segments[..1], &segments)?;
+        self.symbol_of(&target)
+    }
+
+    /// Go to definition: the symbol the name at `line`/`column` of `file`
+    /// refers to, or the symbol defined there.
+    pub fn definition_at(&self, file: &Path, line: usize, column: usize) -> Option<&Symbol> {
+        let index = self.files.get(file)?;
+        let covers = |l: &Location, name: &str| {
+            l.line == line && (l.column..l.column + name.chars().count()).contains(&column)
+        };
+        if let Some(entry) = index
+            .entries
+            .iter()
+            .find(|e| covers(&e.symbol.location, &e.symbol.name))
+        {
+            return Some(&entry.symbol);
+        }
+        let occurrence = index
+            .occurrences
+            .iter()
+            .find(|o| covers(&o.location, o.segments.last().unwrap()))?;
+        match occurrence.usage {
+            Usage::Path => {
+                let module = &index.modules[occurrence.module].path;
+                let target = self.resolve_path(module, &occurrence.segments)?;
+                self.symbol_of(&target)
+            }
+            // Without types, a method call is only unambiguous if there is
+            // one method by that name.
+            Usage::Textual => {
+                let name = occurrence.segments.last().unwrap();
+                let mut candidates = self.symbols().filter(|s| &s.name == name);
+                let first = candidates.next()?;
+                candidates.next().is_none().then_some(first)
+            }
+        }
+    }
+
+    /// Find references: every place the symbol at `path` is named, sorted by
+    /// file and position. Method calls and macro arguments with the same
+    /// name are included as unresolved.
+    pub fn references(&self, path: &str) -> Vec<Reference> {
+        let Some(target) = self.target_of(path) else {
+            return Vec::new();
+        };
+        let symbol = self.symbol_of(&target).unwrap();
+        let textual = matches!(
+            symbol.kind,
+            SymbolKind::Fn | SymbolKind::Method | SymbolKind::Macro
+        );
+        // One resolver for the whole scan: most occurrences share modules.
+        let mut resolver = Resolver::new(self);
+        let mut out = Vec::new();
+        for index in self.files.values() {
+            for occurrence in &index.occurrences {
+                let resolved = match occurrence.usage {
+                    Usage::Path => {
+                        let module = &index.modules[occurrence.module].path;
+                        resolver.path(module, &occurrence.segments).as_ref() == Some(&target)
+                    }
+                    Usage::Textual => false,
+                };
+                let by_name = occurrence.usage == Usage::Textual
+                    && textual
+                    && occurrence.segments[0] == symbol.name;
+                if resolved || by_name {
+                    out.push(Reference {
+                        location: occurrence.location.clone(),
+                        resolved,
+                    });
+                }
+            }
+        }
+        out.sort_by(|a, b| {
+            (&a.location.file, a.location.line, a.location.column).cmp(&(
+                &b.location.file,
+                b.location.line,
+                b.location.column,
+            ))
+        });
+        out
+    }
+
+    fn target_of(&self, path: &str) -> Option<Target> {
+        self.files.iter().find_map(|(file, index)| {
+            let i = index.entries.iter().position(|e| e.symbol.path == path)?;
+            Some(match index.entries[i].symbol.kind {
+                SymbolKind::Mod => Target::Module(path.split("::").map(str::to_string).collect()),
+                _ => Target::Symbol(file.clone(), i),
+            })
+        })
+    }
+
+    fn symbol_of(&self, target: &Target) -> Option<&Symbol> {
+        match target {
+            Target::Symbol(file, i) => Some(&self.files[file].entries[*i].symbol),
+            Target::Module(path) => {
+                let joined = path.join("::");
+                self.symbols()
+                    .find(|s| s.kind == SymbolKind::Mod && s.path == joined)
+            }
+        }
+    }
+
+    fn index_one(&mut self, file: &Path, crate_name: &str, module: Vec<String>, mod_root: bool) {
+        let read = std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file.display(), e));
+        match read.and_then(|source| collect::collect(file, &source, crate_name, module, mod_root))
+        {
+            Ok(index) => {
+                self.files.insert(file.to_path_buf(), index);
+                self.errors.remove(file);
+            }
+            Err(e) => {
+                log::warn!(":: ELESSAR :: {}", e);
+                self.errors.insert(file.to_path_buf(), e);
+            }
+        }
+    }
+
+    fn index_tree(&mut self, file: &Path, crate_name: &str, module: Vec<String>, mod_root: bool) {
+        self.index_one(file, crate_name, module, mod_root);
+        for (child, module) in self.child_modules(file) {
+            if !self.files.contains_key(&child) {
+                let mod_root = child.file_name().is_some_and(|n| n == "mod.rs");
+                self.index_tree(&child, crate_name, module, mod_root);
+            }
+        }
+    }
+
+    fn remove_tree(&mut self, file: &Path) {
+        for child in self.children(file) {
+            self.remove_tree(&child);
+        }
+        self.files.remove(file);
+        self.errors.remove(file);
+    }
+
+    /// The files `file`'s `mod` declarations point at, with their module
+    /// paths.
+    fn child_modules(&self, file: &Path) -> Vec<(PathBuf, Vec<String>)> {
+        let Some(index) = self.files.get(file) else {
+            return Vec::new();
+        };
+        index
+            .decls
+            .iter()
+            .filter_map(|d| {
+                let found = d.candidates.iter().find(|c| c.is_file())?;
+                Some((found.clone(), d.path.clone()))
+            })
+            .collect()
+    }
+
+    fn children(&self, file: &Path) -> Vec<PathBuf> {
+        self.child_modules(file)
+            .into_iter()
+            .map(|(f, _)| f)
+            .collect()
+    }
+
+    /// The indexed file whose `mod` declaration names `file`.
+    fn declaring(&self, file: &Path) -> Option<(String, ModDecl)> {
+        self.files.values().find_map(|index| {
+            let decl = index
+                .decls
+                .iter()
+                .find(|d| d.candidates.iter().any(|c| c == file))?;
+            Some((index.crate_name.clone(), decl.clone()))
+        })
+    }
+
+    fn rebuild_modules(&mut self) {
+        self.modules.clear();
+        for (file, index) in &self.files {
+            for (i, module) in index.modules.iter().enumerate() {
+                self.modules.insert(module.path.clone(), (file.clone(), i));
+            }
+        }
+    }
+}
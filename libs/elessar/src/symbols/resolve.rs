@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Path resolution over the indexed modules.
+
+use super::{SymbolIndex, SymbolKind, Target};
+use std::collections::{HashMap, HashSet};
+
+type Key = (Vec<String>, String);
+
+/// One query's worth of resolution. Lookups already answered are
+/// remembered, and a lookup that comes back round to itself (a glob whose
+/// path starts with a name the glob might provide) fails instead of looping.
+/// An answer reached through such a cut depends on where the cycle was
+/// entered, so it is not remembered.
+pub(crate) struct Resolver<'a> {
+    index: &'a SymbolIndex,
+    active: HashSet<Key>,
+    done: HashMap<Key, Option<Target>>,
+    /// Cycles cut so far.
+    cuts: usize,
+}
+
+impl<'a> Resolver<'a> {
+    pub(crate) fn new(index: &'a SymbolIndex) -> Self {
+        Self {
+            index,
+            active: HashSet::new(),
+            done: HashMap::new(),
+            cuts: 0,
+        }
+    }
+
+    /// Resolves `segments` as written inside `module`.
+    pub(crate) fn path(&mut self, module: &[String], segments: &[String]) -> Option<Target> {
+        let (first, rest) = segments.split_first()?;
+        let mut current = match first.as_str() {
+            "crate" => Target::Module(module[..1].to_vec()),
+            "self" => Target::Module(module.to_vec()),
+            "super" => Target::Module(parent(module)),
+            _ => self.lookup(module, first).or_else(|| {
+                self.index
+                    .crates
+                    .contains_key(first.as_str())
+                    .then(|| Target::Module(vec![first.clone()]))
+            })?,
+        };
+        for segment in rest {
+            current = match current {
+                Target::Module(path) if segment == "super" => Target::Module(parent(&path)),
+                Target::Module(path) => self.lookup(&path, segment)?,
+                Target::Symbol(..) => self.member(&current, segment)?,
+            };
+        }
+        Some(current)
+    }
+
+    /// `name` as seen from inside `module`: its own items, then what it
+    /// imports by name, then what its globs bring in.
+    fn lookup(&mut self, module: &[String], name: &str) -> Option<Target> {
+        let key = (module.to_vec(), name.to_string());
+        if let Some(known) = self.done.get(&key) {
+            return known.clone();
+        }
+        if !self.active.insert(key.clone()) {
+            self.cuts += 1;
+            return None;
+        }
+        let cuts = self.cuts;
+        let found = self.lookup_uncached(module, name);
+        self.active.remove(&key);
+        if self.cuts == cuts {
+            self.done.insert(key, found.clone());
+        }
+        found
+    }
+
+    fn lookup_uncached(&mut self, module: &[String], name: &str) -> Option<Target> {
+        let index = self.index;
+        let (file, i) = index.modules.get(module)?;
+        let file_index = &index.files[file];
+
+        let own = file_index.entries.iter().position(|e| {
+            e.module == *i
+                && e.owner.is_none()
+                && e.symbol.name == name
+                && e.symbol.kind != SymbolKind::Impl
+        });
+        if let Some(found) = own {
+            return Some(match file_index.entries[found].symbol.kind {
+                SymbolKind::Mod => {
+                    let mut path = module.to_vec();
+                    path.push(name.to_string());
+                    Target::Module(path)
+                }
+                _ => Target::Symbol(file.clone(), found),
+            });
+        }
+
+        // `#[macro_export]` macros live at the crate root wherever they are
+        // written.
+        if module.len() == 1 {
+            let exported = index
+                .files
+                .iter()
+                .filter(|(_, f)| f.crate_name == module[0])
+                .find_map(|(other, f)| {
+                    let found = f
+                        .entries
+                        .iter()
+                        .position(|e| e.exported && e.symbol.name == name)?;
+                    Some(Target::Symbol(other.clone(), found))
+                });
+            if exported.is_some() {
+                return exported;
+            }
+        }
+
+        let imports = &file_index.modules[*i].imports;
+        for import in imports.iter().filter(|im| im.name.as_deref() == Some(name)) {
+            if let Some(target) = self.path(module, &import.target) {
+                return Some(target);
+            }
+        }
+        for glob in imports.iter().filter(|im| im.name.is_none()) {
+            let found = match self.path(module, &glob.target) {
+                Some(Target::Module(path)) => self.lookup(&path, name),
+                // `use Enum::*` brings in its variants.
+                Some(owner @ Target::Symbol(..)) => self.member(&owner, name),
+                None => None,
+            };
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+
+    /// An associated item or variant named `name` whose owner resolves to
+    /// `owner`.
+    fn member(&mut self, owner: &Target, name: &str) -> Option<Target> {
+        let index = self.index;
+        for (file, file_index) in &index.files {
+            for (i, entry) in file_index.entries.iter().enumerate() {
+                let Some(written) = &entry.owner else {
+                    continue;
+                };
+                if entry.symbol.name != name {
+                    continue;
+                }
+                let module = &file_index.modules[entry.module].path;
+                if self.path(module, written).as_ref() == Some(owner) {
+                    return Some(Target::Symbol(file.clone(), i));
+                }
+            }
+        }
+        None
+    }
+}
+
+fn parent(module: &[String]) -> Vec<String> {
+    module[..module.len().saturating_sub(1).max(1)].to_vec()
+}
+
+impl SymbolIndex {
+    pub(crate) fn resolve_path(&self, module: &[String], segments: &[String]) -> Option<Target> {
+        Resolver::new(self).path(module, segments)
+    }
+}
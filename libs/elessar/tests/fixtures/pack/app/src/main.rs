@@ -0,0 +1,8 @@
+use pack_core::math::Vec2;
+use pack_render::canvas::Canvas;
+use pack_render::{Dot, Draw};
+
+fn main() {
+    let mut canvas = Canvas::new(8, 8);
+    Dot(Vec2::new(3.0, 4.0)).draw(&mut canvas);
+}
@@ -0,0 +1,10 @@
+//! Shared maths for the fixture.
+
+pub mod math;
+
+/// Clamps `value` into `0.0..=1.0`.
+pub fn unit(value: f32) -> f32 {
+    value.clamp(0.0, 1.0)
+}
+
+fn unused() {}
@@ -0,0 +1,25 @@
+/// A grid of pixels.
+pub struct Canvas {
+    width: usize,
+    pixels: Vec<bool>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            pixels: vec![false; width * height],
+        }
+    }
+
+    /// Sets one pixel; out-of-range points are ignored.
+    pub fn plot(&mut self, x: usize, y: usize) {
+        if let Some(p) = self.pixels.get_mut(y * self.width + x) {
+            *p = true;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pixels.fill(false);
+    }
+}
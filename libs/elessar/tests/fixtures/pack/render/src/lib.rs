@@ -0,0 +1,18 @@
+//! Draws things made of core vectors.
+
+pub mod canvas;
+
+use pack_core::math::Vec2;
+
+/// Something that can be drawn.
+pub trait Draw {
+    fn draw(&self, canvas: &mut canvas::Canvas);
+}
+
+pub struct Dot(pub Vec2);
+
+impl Draw for Dot {
+    fn draw(&self, canvas: &mut canvas::Canvas) {
+        canvas.plot(self.0.x as usize, self.0.y as usize);
+    }
+}
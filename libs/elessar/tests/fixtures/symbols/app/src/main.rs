@@ -0,0 +1,18 @@
+use fixture_shapes::geometry::Kind::*;
+use fixture_shapes::geometry::solid::Cube;
+use fixture_shapes::prelude::*;
+use fixture_shapes::{Square, square};
+
+fn main() {
+    let s = Square::new(2.0);
+    let a = s.area();
+    let h = helper();
+    let q = Sq::new(1.0);
+    let c = Cube { face: s };
+    let k = Flat;
+    let m = square!(3.0);
+    let u = fixture_shapes::geometry::UNIT;
+    let n = fixture_shapes::COUNT;
+    println!("{a} {h} {} {} {} {u} {n}", q.side, c.volume(), m.side);
+    let _ = k;
+}
@@ -0,0 +1,32 @@
+pub mod solid;
+
+pub trait Shape {
+    fn area(&self) -> f64;
+
+    fn name(&self) -> &str {
+        "shape"
+    }
+}
+
+pub struct Square {
+    pub side: f64,
+}
+
+impl Square {
+    pub fn new(side: f64) -> Self {
+        Square { side }
+    }
+}
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+}
+
+pub const UNIT: f64 = 1.0;
+
+pub enum Kind {
+    Flat,
+    Solid,
+}
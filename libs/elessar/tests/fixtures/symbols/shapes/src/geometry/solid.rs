@@ -0,0 +1,11 @@
+use super::{Shape, Square};
+
+pub struct Cube {
+    pub face: Square,
+}
+
+impl Cube {
+    pub fn volume(&self) -> f64 {
+        self.face.area() * self.face.side
+    }
+}
@@ -0,0 +1,5 @@
+pub fn helper() -> u32 {
+    4
+}
+
+pub static COUNT: u32 = 0;
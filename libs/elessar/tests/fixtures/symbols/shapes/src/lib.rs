@@ -0,0 +1,18 @@
+pub mod geometry;
+mod internal;
+
+pub use geometry::Square;
+pub use internal::*;
+
+/// Everything an app needs, under one glob.
+pub mod prelude {
+    pub use crate::geometry::{Shape, Square as Sq};
+    pub use crate::internal::helper;
+}
+
+#[macro_export]
+macro_rules! square {
+    ($side:expr) => {
+        $crate::geometry::Square::new($side)
+    };
+}
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use elessar::context::{
+    CrateGraph, CrateNode, Dependency, DependencyKind, DependencySource, WorkspaceIndexer,
+};
+use std::path::{Path, PathBuf};
+
+fn path_of(name: &str) -> PathBuf {
+    Path::new("/ws").join(name)
+}
+
+fn dep(name: &str, kind: DependencyKind) -> Dependency {
+    Dependency {
+        name: name.to_string(),
+        package: name.to_string(),
+        kind,
+        req: None,
+        source: DependencySource::Path(path_of(name)),
+        features: Vec::new(),
+        default_features: true,
+        optional: false,
+        target: None,
+        inherited: false,
+    }
+}
+
+/// `spec` is `crate: dep dep ..`, with `dev:`/`build:` prefixes on deps.
+fn workspace(spec: &[&str]) -> Vec<CrateNode> {
+    spec.iter()
+        .map(|line| {
+            let (name, deps) = line.split_once(':').unwrap_or((line, ""));
+            let dependencies = deps
+                .split_whitespace()
+                .map(|d| match d.split_once(':') {
+                    Some(("dev", d)) => dep(d, DependencyKind::Dev),
+                    Some(("build", d)) => dep(d, DependencyKind::Build),
+                    _ => dep(d, DependencyKind::Normal),
+                })
+                .collect();
+            CrateNode {
+                name: name.to_string(),
+                path: path_of(name),
+                version: Some("0.1.0".into()),
+                workspace: Some(PathBuf::from("/ws")),
+                dependencies,
+            }
+        })
+        .collect()
+}
+
+// app -> vein -> gneiss -> (none); app -> quartz -> gneiss; tool -> gneiss;
+// gneiss has a dev edge back to app, which Cargo allows.
+fn trinity() -> CrateGraph {
+    CrateGraph::new(&workspace(&[
+        "app: vein quartz",
+        "vein: gneiss build:codegen",
+        "quartz: gneiss",
+        "gneiss: dev:app",
+        "codegen:",
+        "tool: gneiss",
+    ]))
+}
+
+#[test]
+fn test_layers() {
+    let graph = trinity();
+    assert_eq!(
+        graph.layers().unwrap(),
+        vec![
+            vec!["codegen", "gneiss"],
+            vec!["quartz", "tool", "vein"],
+            vec!["app"],
+        ]
+    );
+    assert_eq!(graph.find_cycle(), None);
+}
+
+#[test]
+fn test_direct_and_reverse_dependencies() {
+    let graph = trinity();
+    assert_eq!(graph.dependencies("vein"), ["codegen", "gneiss"]);
+    // Dev edges don't count for ordering, but still make a dependent.
+    assert!(graph.dependencies("gneiss").is_empty());
+    assert_eq!(graph.dependents("app"), ["gneiss"]);
+    assert_eq!(graph.dependents("gneiss"), ["quartz", "tool", "vein"]);
+    assert!(graph.dependents("nope").is_empty());
+}
+
+#[test]
+fn test_impact() {
+    let graph = trinity();
+    assert_eq!(graph.impact("quartz"), ["app", "gneiss"]);
+    assert_eq!(graph.impact("tool"), Vec::<&str>::new());
+    assert_eq!(graph.impact("gneiss"), ["app", "quartz", "tool", "vein"]);
+    // `gneiss` dev-depends on `app`, so its tests see the change, but
+    // nothing that depends on `gneiss` does.
+    assert_eq!(graph.impact("codegen"), ["app", "gneiss", "vein"]);
+}
+
+#[test]
+fn test_critical_path() {
+    assert_eq!(
+        trinity().critical_path().unwrap(),
+        ["app", "quartz", "gneiss"]
+    );
+    let chain = CrateGraph::new(&workspace(&["a: b", "b: c", "c: d", "d:", "x: d"]));
+    assert_eq!(chain.critical_path().unwrap(), ["a", "b", "c", "d"]);
+    assert!(CrateGraph::new(&[]).critical_path().unwrap().is_empty());
+}
+
+#[test]
+fn test_cycle_is_reported_with_its_path() {
+    let graph = CrateGraph::new(&workspace(&[
+        "app: core",
+        "core: util",
+        "util: store",
+        "store: core",
+        "leaf:",
+    ]));
+    let cycle = graph.find_cycle().unwrap();
+    assert_eq!(cycle.path, ["core", "util", "store", "core"]);
+    assert_eq!(
+        cycle.to_string(),
+        "dependency cycle: core -> util -> store -> core"
+    );
+    assert_eq!(graph.layers().unwrap_err(), cycle);
+    assert_eq!(graph.critical_path().unwrap_err(), cycle);
+    // Impact still works on a cyclic graph.
+    assert_eq!(graph.impact("util"), ["app", "core", "store"]);
+
+    let json = graph.to_json();
+    assert_eq!(
+        json["cycle"],
+        serde_json::json!(["core", "util", "store", "core"])
+    );
+    assert!(json["nodes"][0]["layer"].is_null());
+}
+
+#[test]
+fn test_self_dependency_is_a_cycle() {
+    let graph = CrateGraph::new(&workspace(&["ouro: ouro"]));
+    assert_eq!(graph.find_cycle().unwrap().path, ["ouro", "ouro"]);
+}
+
+#[test]
+fn test_only_local_path_dependencies_become_edges() {
+    let mut nodes = workspace(&["app: core", "core:"]);
+    let mut registry = dep("core", DependencyKind::Normal);
+    registry.source = DependencySource::Registry(None);
+    let mut elsewhere = dep("other", DependencyKind::Normal);
+    elsewhere.source = DependencySource::Path("/elsewhere/other".into());
+    // The same crate again under a target: one edge.
+    let mut targeted = dep("core", DependencyKind::Normal);
+    targeted.target = Some("cfg(unix)".into());
+    nodes[1].dependencies = vec![registry, elsewhere];
+    nodes[0].dependencies.push(targeted);
+
+    let graph = CrateGraph::new(&nodes);
+    assert_eq!(graph.edges().len(), 1);
+    assert!(graph.dependencies("core").is_empty());
+}
+
+#[test]
+fn test_dot_export() {
+    let graph = CrateGraph::new(&workspace(&["a: b dev:c build:d", "b:", "c:", "d:"]));
+    assert_eq!(
+        graph.to_dot(),
+        "digraph workspace {\n    rankdir=LR;\n    \"a\";\n    \"b\";\n    \"c\";\n    \"d\";\n    \
+         \"a\" -> \"b\";\n    \"a\" -> \"c\" [style=dashed];\n    \"a\" -> \"d\" [style=dotted];\n}\n"
+    );
+}
+
+#[test]
+fn test_json_export() {
+    let json = trinity().to_json();
+    assert!(json["cycle"].is_null());
+    let nodes = json["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 6);
+    assert_eq!(nodes[0]["name"], "app");
+    assert_eq!(nodes[0]["layer"], 2);
+    assert_eq!(nodes[0]["path"], "/ws/app");
+    let edges = json["edges"].as_array().unwrap();
+    assert_eq!(edges.len(), 7);
+    assert!(edges.contains(&serde_json::json!({"from": "gneiss", "to": "app", "kind": "Dev"})));
+}
+
+#[test]
+fn test_graph_of_a_scanned_workspace() {
+    let mut indexer = WorkspaceIndexer::new();
+    indexer.scan(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/workspaces/inherit"));
+    let graph = indexer.graph();
+    assert_eq!(
+        graph.layers().unwrap(),
+        vec![
+            vec!["fixture-core", "fixture-skip"],
+            vec!["fixture-app", "fixture-gen"]
+        ]
+    );
+    assert_eq!(graph.impact("fixture-core"), ["fixture-app", "fixture-gen"]);
+}
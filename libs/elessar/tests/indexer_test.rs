@@ -0,0 +1,252 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use elessar::context::{
+    CrateNode, Dependency, DependencyKind, DependencySource, GitReference, WorkspaceIndexer,
+};
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/workspaces")
+        .join(name)
+}
+
+fn index(name: &str) -> WorkspaceIndexer {
+    let mut indexer = WorkspaceIndexer::new();
+    indexer.scan(&fixture(name));
+    indexer
+}
+
+fn dep<'a>(node: &'a CrateNode, name: &str, kind: DependencyKind) -> &'a Dependency {
+    node.dependencies
+        .iter()
+        .find(|d| d.name == name && d.kind == kind)
+        .unwrap_or_else(|| panic!("{} has no {:?} dependency `{}`", node.name, kind, name))
+}
+
+#[test]
+fn test_inline_tables_sections_and_kinds() {
+    let indexer = index("forms");
+    assert!(indexer.errors.is_empty(), "{:?}", indexer.errors);
+    assert!(indexer.workspaces.is_empty());
+    let forms = &indexer.nodes["fixture-forms"];
+    assert_eq!(forms.version.as_deref(), Some("0.1.0"));
+    assert_eq!(forms.workspace, None);
+
+    let log = dep(forms, "log", DependencyKind::Normal);
+    assert_eq!(log.req.as_deref(), Some("0.4"));
+    assert_eq!(log.source, DependencySource::Registry(None));
+    assert!(log.default_features && !log.optional && log.target.is_none());
+
+    let serde = dep(forms, "serde", DependencyKind::Normal);
+    assert_eq!(serde.features, ["derive", "rc"]);
+    assert!(!serde.default_features);
+
+    // A `[dependencies.regex]` section, with the underscore spelling.
+    let regex = dep(forms, "regex", DependencyKind::Normal);
+    assert_eq!(regex.req.as_deref(), Some("1.10"));
+    assert_eq!(regex.features, ["std"]);
+    assert!(!regex.default_features);
+
+    assert_eq!(forms.dependencies_of(DependencyKind::Dev).count(), 2);
+    assert_eq!(forms.dependencies_of(DependencyKind::Build).count(), 2);
+    assert_eq!(forms.dependencies.len(), 14);
+}
+
+#[test]
+fn test_renames_sources_and_targets() {
+    let indexer = index("forms");
+    let forms = &indexer.nodes["fixture-forms"];
+
+    let json = dep(forms, "json", DependencyKind::Normal);
+    assert_eq!(json.package, "serde_json");
+
+    let local = dep(forms, "local", DependencyKind::Normal);
+    assert!(local.optional);
+    assert_eq!(
+        local.source,
+        DependencySource::Path(fixture("rooted/plugin"))
+    );
+
+    let git = |name| dep(forms, name, DependencyKind::Normal).source.clone();
+    assert_eq!(
+        git("pinned-branch"),
+        DependencySource::Git {
+            url: "https://example.org/a.git".into(),
+            reference: Some(GitReference::Branch("main".into())),
+        }
+    );
+    assert!(matches!(
+        git("pinned-tag"),
+        DependencySource::Git { reference: Some(GitReference::Tag(t)), .. } if t == "v1.0"
+    ));
+    assert!(matches!(
+        git("pinned-rev"),
+        DependencySource::Git { reference: Some(GitReference::Rev(r)), .. } if r == "abc123"
+    ));
+    assert_eq!(
+        git("private"),
+        DependencySource::Registry(Some("internal".into()))
+    );
+
+    assert_eq!(
+        dep(forms, "libc", DependencyKind::Normal).target.as_deref(),
+        Some("cfg(unix)")
+    );
+    let winapi = dep(forms, "winapi", DependencyKind::Dev);
+    assert_eq!(winapi.target.as_deref(), Some("cfg(windows)"));
+    assert_eq!(winapi.features, ["winuser"]);
+    assert_eq!(
+        dep(forms, "pkg-config", DependencyKind::Build)
+            .target
+            .as_deref(),
+        Some("x86_64-unknown-linux-gnu")
+    );
+}
+
+#[test]
+fn test_workspace_members_globs_and_exclude() {
+    let indexer = index("inherit");
+    assert_eq!(indexer.workspaces.len(), 1);
+    let ws = &indexer.workspaces[0];
+    assert_eq!(ws.root, fixture("inherit"));
+    assert_eq!(
+        ws.members,
+        [
+            fixture("inherit/crates/app"),
+            fixture("inherit/crates/core"),
+            fixture("inherit/tools/gen"),
+        ]
+    );
+
+    for member in ["fixture-app", "fixture-core", "fixture-gen"] {
+        assert_eq!(
+            indexer.nodes[member].workspace.as_deref(),
+            Some(ws.root.as_path())
+        );
+    }
+    // Still indexed, just not a member.
+    assert_eq!(indexer.nodes["fixture-skip"].workspace, None);
+}
+
+#[test]
+fn test_inherited_dependencies_and_versions() {
+    let indexer = index("inherit");
+    let core = &indexer.nodes["fixture-core"];
+    assert_eq!(core.version.as_deref(), Some("1.2.3"));
+    assert_eq!(
+        indexer.nodes["fixture-gen"].version.as_deref(),
+        Some("1.2.3")
+    );
+
+    let serde = dep(core, "serde", DependencyKind::Normal);
+    assert!(serde.inherited);
+    assert_eq!(serde.req.as_deref(), Some("1.0"));
+    assert_eq!(serde.features, ["derive"]);
+
+    let app = &indexer.nodes["fixture-app"];
+    assert_eq!(app.version.as_deref(), Some("0.9.0"));
+    // Member features add to the workspace's; `optional` is the member's.
+    let serde = dep(app, "serde", DependencyKind::Normal);
+    assert_eq!(serde.features, ["derive", "rc"]);
+    assert!(serde.optional);
+    // Paths in `[workspace.dependencies]` are relative to the workspace root.
+    assert_eq!(
+        dep(app, "fixture-core", DependencyKind::Normal).source,
+        DependencySource::Path(fixture("inherit/crates/core"))
+    );
+    let tokio = dep(app, "tokio", DependencyKind::Dev);
+    assert_eq!(tokio.req.as_deref(), Some("1.49"));
+    assert_eq!(tokio.features, ["macros"]);
+
+    // And relative to the member when it names the path itself.
+    assert_eq!(
+        dep(
+            &indexer.nodes["fixture-gen"],
+            "fixture-core",
+            DependencyKind::Build
+        )
+        .source,
+        DependencySource::Path(fixture("inherit/crates/core"))
+    );
+}
+
+#[test]
+fn test_unresolvable_inheritance_is_reported() {
+    let indexer = index("inherit");
+    let mut messages: Vec<_> = indexer
+        .errors
+        .iter()
+        .map(|e| (e.path.clone(), e.message.clone()))
+        .collect();
+    messages.sort();
+    assert_eq!(messages.len(), 2, "{:?}", messages);
+    assert_eq!(messages[0].0, fixture("inherit/crates/app/Cargo.toml"));
+    assert!(messages[0].1.contains("`missing`"));
+    assert_eq!(messages[1].0, fixture("inherit/crates/skip/Cargo.toml"));
+    assert!(messages[1].1.contains("`serde`"));
+
+    assert!(
+        !indexer.nodes["fixture-app"]
+            .dependencies
+            .iter()
+            .any(|d| d.name == "missing")
+    );
+    assert!(indexer.nodes["fixture-skip"].dependencies.is_empty());
+}
+
+#[test]
+fn test_root_package_is_its_own_member() {
+    let indexer = index("rooted");
+    assert!(indexer.errors.is_empty(), "{:?}", indexer.errors);
+    let root = &indexer.nodes["fixture-rooted"];
+    assert_eq!(root.workspace.as_deref(), Some(fixture("rooted").as_path()));
+    assert!(dep(root, "anyhow", DependencyKind::Normal).inherited);
+    let plugin = &indexer.nodes["fixture-plugin"];
+    assert_eq!(
+        dep(plugin, "anyhow", DependencyKind::Normal).req.as_deref(),
+        Some("1.0")
+    );
+    assert_eq!(
+        dep(root, "fixture-plugin", DependencyKind::Normal).source,
+        DependencySource::Path(fixture("rooted/plugin"))
+    );
+}
+
+#[test]
+fn test_broken_manifest_does_not_stop_the_scan() {
+    let indexer = index("broken");
+    assert_eq!(indexer.errors.len(), 1);
+    assert_eq!(indexer.errors[0].path, fixture("broken/bad/Cargo.toml"));
+    assert_eq!(indexer.nodes.len(), 1);
+    assert!(indexer.nodes.contains_key("fixture-good"));
+}
+
+#[test]
+fn test_fixtures_are_skipped_from_above() {
+    let mut indexer = WorkspaceIndexer::new();
+    indexer.scan(Path::new(env!("CARGO_MANIFEST_DIR")));
+    assert!(indexer.nodes.contains_key("elessar"));
+    assert!(
+        indexer
+            .nodes
+            .keys()
+            .all(|name| !name.starts_with("fixture-")),
+        "{:?}",
+        indexer.nodes.keys().collect::<Vec<_>>()
+    );
+}
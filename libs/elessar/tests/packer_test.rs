@@ -0,0 +1,208 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use elessar::context::{CrateGraph, Pack, Packer, Representation, WorkspaceIndexer};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pack")
+}
+
+fn graph(root: &Path) -> CrateGraph {
+    let mut indexer = WorkspaceIndexer::new();
+    indexer.scan(root);
+    assert!(indexer.errors.is_empty(), "{:?}", indexer.errors);
+    indexer.graph()
+}
+
+fn pack(budget: usize) -> Pack {
+    let root = fixture();
+    Packer::new(budget)
+        .root(&root)
+        .pack(&graph(&root), &[PathBuf::from("render/src/lib.rs")])
+}
+
+/// Compares manifest and text against `tests/snapshots/<name>.pack`. Run with
+/// `ELESSAR_BLESS=1` to rewrite the snapshots after an intended change.
+fn golden(name: &str, pack: &Pack) {
+    let out = format!("{}\n{}", pack.manifest(), pack.text);
+    let file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.pack"));
+    if std::env::var_os("ELESSAR_BLESS").is_some() {
+        std::fs::write(&file, &out).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&file)
+        .unwrap_or_else(|_| panic!("no snapshot {file:?}; run with ELESSAR_BLESS=1"));
+    assert!(
+        out == expected,
+        "pack {name} changed; run with ELESSAR_BLESS=1 if intended\n--- got\n{out}"
+    );
+}
+
+fn summary(pack: &Pack) -> Vec<(String, Representation)> {
+    pack.entries
+        .iter()
+        .map(|e| (e.path.to_string_lossy().into_owned(), e.representation))
+        .collect()
+}
+
+#[test]
+fn test_golden_generous_budget() {
+    let pack = pack(10_000);
+    assert_eq!(
+        summary(&pack),
+        vec![
+            ("render/src/lib.rs".to_string(), Representation::Full),
+            ("render/src/canvas.rs".to_string(), Representation::Skeleton),
+            ("app/src/main.rs".to_string(), Representation::Omitted),
+            ("core/src/lib.rs".to_string(), Representation::Skeleton),
+            ("core/src/math.rs".to_string(), Representation::Skeleton),
+        ]
+    );
+    // The unrelated crate is never a candidate.
+    assert!(pack.entries.iter().all(|e| e.crate_name != "pack-tools"));
+    golden("pack_generous", &pack);
+}
+
+#[test]
+fn test_golden_tight_budget_truncates_deterministically() {
+    let pack = pack(280);
+    assert!(pack.tokens <= 280);
+    assert_eq!(pack.entries[0].representation, Representation::Full);
+    assert!(
+        pack.entries
+            .iter()
+            .any(|e| matches!(e.representation, Representation::Truncated { .. }))
+    );
+    assert!(pack.omitted().count() > 0);
+    golden("pack_tight", &pack);
+    assert_eq!(pack, self::pack(280));
+}
+
+#[test]
+fn test_golden_focus_over_budget_falls_back_to_skeleton() {
+    let pack = pack(90);
+    assert!(pack.tokens <= 90);
+    assert_eq!(pack.entries[0].representation, Representation::Skeleton);
+    assert_eq!(
+        pack.entries[0].reason,
+        "focus file; full source over budget"
+    );
+    golden("pack_starved", &pack);
+}
+
+#[test]
+fn test_zero_budget_omits_everything() {
+    let pack = pack(0);
+    assert_eq!(pack.tokens, 0);
+    assert!(pack.text.is_empty());
+    assert_eq!(pack.included().count(), 0);
+    assert_eq!(pack.omitted().count(), pack.entries.len());
+}
+
+#[test]
+fn test_pluggable_estimator() {
+    let root = fixture();
+    let graph = graph(&root);
+    let focus = [PathBuf::from("render/src/lib.rs")];
+    let words = |text: &str| text.split_whitespace().count();
+
+    let by_words = Packer::new(10_000)
+        .root(&root)
+        .estimator(words)
+        .pack(&graph, &focus);
+    for entry in by_words.included() {
+        let start = by_words
+            .text
+            .find(&format!("--- FILE: {} ", entry.path.display()))
+            .unwrap();
+        let end = by_words.text[start + 1..]
+            .find("--- FILE: ")
+            .map_or(by_words.text.len(), |i| start + 1 + i);
+        assert_eq!(entry.tokens, words(&by_words.text[start..end]));
+    }
+    assert_eq!(by_words.tokens, words(&by_words.text));
+    assert!(by_words.tokens < pack(10_000).tokens);
+}
+
+#[test]
+fn test_recency_orders_files_at_equal_distance() {
+    let root = fixture();
+    let graph = graph(&root);
+    let focus = [PathBuf::from("render/src/canvas.rs")];
+    let epoch = SystemTime::UNIX_EPOCH;
+
+    let pack = Packer::new(10_000)
+        .root(&root)
+        .touched("core/src/math.rs", epoch + Duration::from_secs(20))
+        .touched("core/src/lib.rs", epoch + Duration::from_secs(10))
+        .pack(&graph, &focus);
+    let order: Vec<String> = pack
+        .entries
+        .iter()
+        .map(|e| e.path.to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            "render/src/canvas.rs",
+            "render/src/lib.rs",
+            "core/src/math.rs",
+            "core/src/lib.rs",
+            "app/src/main.rs",
+        ]
+    );
+    assert_eq!(
+        pack.entries[2].reason,
+        "dependency of focus crate `pack-render`"
+    );
+    // A binary has nothing to show its neighbours.
+    assert_eq!(
+        pack.entries[4].reason,
+        "depends on focus crate `pack-render`; no public items"
+    );
+}
+
+#[test]
+fn test_max_distance_limits_reach() {
+    let root = fixture();
+    let graph = graph(&root);
+    let pack = Packer::new(10_000)
+        .root(&root)
+        .max_distance(1)
+        .pack(&graph, &[PathBuf::from("core/src/math.rs")]);
+    let crates: Vec<&str> = pack.entries.iter().map(|e| e.crate_name.as_str()).collect();
+    // app depends on core directly; render is one hop too.
+    assert_eq!(
+        crates,
+        vec![
+            "pack-core",
+            "pack-core",
+            "pack-app",
+            "pack-render",
+            "pack-render"
+        ]
+    );
+
+    let pack = Packer::new(10_000)
+        .root(&root)
+        .max_distance(0)
+        .pack(&graph, &[PathBuf::from("core/src/math.rs")]);
+    assert!(pack.entries.iter().all(|e| e.crate_name == "pack-core"));
+}
@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use elessar::context::{MacroMode, SkeletonGenerator, SkeletonOptions};
+use std::path::Path;
+
+const SOURCE: &str = r#"
+//! Crate docs.
+
+/// A public function.
+pub fn public() -> u32 {
+    let x = 1;
+    x + 1
+}
+
+fn private_helper() {}
+
+pub fn one_liner() -> u32 { 7 }
+
+/// Exported.
+#[macro_export]
+macro_rules! shout {
+    ($e:expr) => { println!("{}!", $e) };
+    () => { println!("!") };
+}
+
+macro_rules! local {
+    ($x:ident) => { let $x = 0; };
+}
+
+pub mod outer {
+    /// Inside a module.
+    pub struct Thing {
+        /// A field.
+        pub value: u32,
+        secret: u32,
+    }
+
+    impl Thing {
+        pub fn get(&self) -> u32 {
+            self.value + self.secret
+        }
+
+        fn hidden(&self) {
+            println!("hidden");
+        }
+    }
+
+    impl Default for Thing {
+        fn default() -> Self {
+            Thing { value: 0, secret: 0 }
+        }
+    }
+
+    mod inner {
+        pub trait Shape {
+            /// Area.
+            fn area(&self) -> f32 {
+                let unit = 1.0;
+                unit * 2.0
+            }
+            fn sides(&self) -> u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn it_works() {
+        assert_eq!(2 + 2, 4);
+    }
+}
+"#;
+
+fn skeleton(options: &SkeletonOptions) -> String {
+    let out = SkeletonGenerator::generate_with(SOURCE, options).unwrap();
+    // Whatever the options, the result is still Rust.
+    syn::parse_file(&out).unwrap_or_else(|e| panic!("{e}\n{out}"));
+    out
+}
+
+fn squash(s: &str) -> String {
+    s.split_whitespace().collect()
+}
+
+fn has(out: &str, needle: &str) -> bool {
+    squash(out).contains(&squash(needle))
+}
+
+#[test]
+fn test_defaults_recurse_into_modules() {
+    let out = skeleton(&SkeletonOptions::default());
+    assert!(has(&out, "pub fn public () -> u32 { }"));
+    assert!(has(&out, "pub fn get (& self) -> u32 { }"));
+    assert!(has(&out, "fn default () -> Self { }"));
+    assert!(has(&out, "fn area (& self) -> f32 { }"));
+    assert!(has(&out, "fn sides (& self) -> u32 ;"));
+    assert!(!out.contains("println"));
+    assert!(!out.contains("unit"));
+    // Docs and private items stay by default.
+    assert!(out.contains("Crate docs."));
+    assert!(out.contains("A field."));
+    assert!(has(&out, "fn private_helper () { }"));
+    assert!(has(&out, "fn hidden (& self) { }"));
+    // One top-level item per line.
+    assert_eq!(out.lines().count(), 8);
+}
+
+#[test]
+fn test_test_modules_collapse() {
+    let out = skeleton(&SkeletonOptions::default());
+    assert!(has(&out, "# [cfg (test)] mod tests { }"));
+    assert!(!out.contains("it_works"));
+
+    let out = skeleton(&SkeletonOptions {
+        collapse_test_modules: false,
+        ..SkeletonOptions::default()
+    });
+    assert!(has(&out, "fn it_works () { }"));
+}
+
+#[test]
+fn test_docs_can_be_dropped() {
+    let out = skeleton(&SkeletonOptions {
+        keep_docs: false,
+        ..SkeletonOptions::default()
+    });
+    for doc in [
+        "Crate docs.",
+        "A public function.",
+        "Exported.",
+        "Inside a module.",
+        "A field.",
+        "Area.",
+    ] {
+        assert!(!out.contains(doc), "{doc} survived");
+    }
+    assert!(out.contains("macro_export"));
+}
+
+#[test]
+fn test_private_items_can_be_dropped() {
+    let out = skeleton(&SkeletonOptions {
+        keep_private: false,
+        ..SkeletonOptions::default()
+    });
+    assert!(!out.contains("private_helper"));
+    assert!(!out.contains("hidden"));
+    assert!(!out.contains("macro_rules ! local"));
+    assert!(!out.contains("mod inner"));
+    assert!(!out.contains("mod tests"));
+    // Public, exported and trait-impl items stay.
+    assert!(out.contains("pub fn get"));
+    assert!(out.contains("fn default"));
+    assert!(out.contains("macro_rules ! shout"));
+}
+
+#[test]
+fn test_short_bodies_are_kept() {
+    let out = skeleton(&SkeletonOptions {
+        max_body_lines: 1,
+        ..SkeletonOptions::default()
+    });
+    assert!(has(&out, "pub fn one_liner () -> u32 { 7 }"));
+    assert!(has(&out, "pub fn public () -> u32 { }"));
+
+    let out = skeleton(&SkeletonOptions {
+        max_body_lines: 4,
+        ..SkeletonOptions::default()
+    });
+    assert!(has(&out, "x + 1"));
+    assert!(has(&out, "unit * 2.0"));
+    assert!(has(&out, "fn hidden (& self) { println ! (\"hidden\") ; }"));
+}
+
+#[test]
+fn test_macro_modes() {
+    let out = skeleton(&SkeletonOptions::default());
+    assert!(has(
+        &out,
+        "macro_rules ! shout { ($ e : expr) => { } ; () => { } ; }"
+    ));
+
+    let out = skeleton(&SkeletonOptions {
+        macros: MacroMode::Keep,
+        ..SkeletonOptions::default()
+    });
+    assert!(out.contains("println ! (\"{}!\" , $ e)"));
+
+    let out = skeleton(&SkeletonOptions {
+        macros: MacroMode::Drop,
+        ..SkeletonOptions::default()
+    });
+    assert!(!out.contains("macro_rules"));
+}
+
+/// Compares against `tests/snapshots/<name>.skel`. Run with
+/// `ELESSAR_BLESS=1` to rewrite the snapshots after an intended change.
+fn snapshot(name: &str, source: &str, options: &SkeletonOptions) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let code = std::fs::read_to_string(root.join(source)).unwrap();
+    let out = SkeletonGenerator::generate_with(&code, options).unwrap() + "\n";
+    syn::parse_file(&out).unwrap();
+
+    let file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.skel"));
+    if std::env::var_os("ELESSAR_BLESS").is_some() {
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, &out).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&file)
+        .unwrap_or_else(|_| panic!("no snapshot {file:?}; run with ELESSAR_BLESS=1"));
+    assert!(
+        out == expected,
+        "skeleton of {source} changed; run with ELESSAR_BLESS=1 if intended\n--- got\n{out}"
+    );
+}
+
+#[test]
+fn test_snapshot_euclase_vec3() {
+    snapshot(
+        "euclase_vec3",
+        "libs/euclase/src/vec3.rs",
+        &SkeletonOptions::default(),
+    );
+}
+
+#[test]
+fn test_snapshot_bandy_topic_public_api() {
+    snapshot(
+        "bandy_topic",
+        "libs/bandy/src/topic.rs",
+        &SkeletonOptions {
+            keep_docs: false,
+            keep_private: false,
+            ..SkeletonOptions::default()
+        },
+    );
+}
+
+#[test]
+fn test_snapshot_gneiss_pal_headless() {
+    snapshot(
+        "gneiss_pal_headless",
+        "libs/gneiss_pal/src/headless.rs",
+        &SkeletonOptions::default(),
+    );
+}
+
+#[test]
+fn test_snapshot_tabula_document_short_bodies() {
+    snapshot(
+        "tabula_document",
+        "handlers/tabula/src/document.rs",
+        &SkeletonOptions {
+            max_body_lines: 8,
+            collapse_test_modules: false,
+            ..SkeletonOptions::default()
+        },
+    );
+}
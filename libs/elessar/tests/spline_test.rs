@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use elessar::{Context, Spline, detect_splines};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Lays out `files` (relative path, contents) under a fresh temp directory.
+fn tree(files: &[(&str, &str)]) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (path, contents) in files {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    dir
+}
+
+fn ranked(root: &Path) -> Vec<Spline> {
+    detect_splines(root).into_iter().map(|m| m.spline).collect()
+}
+
+#[test]
+fn test_each_marker_is_recognised() {
+    let cases: &[(Spline, &str, &str)] = &[
+        (Spline::Rust, "Cargo.toml", "src/main.rs"),
+        (Spline::Web, "package.json", "src/index.ts"),
+        (Spline::Python, "pyproject.toml", "app/main.py"),
+        (Spline::Go, "go.mod", "cmd/main.go"),
+        (Spline::CMake, "CMakeLists.txt", "src/main.c"),
+        (Spline::Make, "Makefile", "main.c"),
+        (Spline::Meson, "meson.build", "src/main.cpp"),
+        (Spline::Gradle, "build.gradle.kts", "src/Main.kt"),
+        (Spline::Maven, "pom.xml", "src/Main.java"),
+        (Spline::Zig, "build.zig", "src/main.zig"),
+        (Spline::Nix, "flake.nix", "modules/host.nix"),
+    ];
+    for (spline, marker, source) in cases {
+        let dir = tree(&[(marker, ""), (source, "source text")]);
+        let matches = detect_splines(dir.path());
+        assert_eq!(matches.len(), 1, "{:?}: {:?}", spline, matches);
+        assert_eq!(matches[0].spline, *spline);
+        assert_eq!(matches[0].confidence, 1.0);
+        assert!(
+            matches[0].evidence.contains(&format!("marker: {}", marker)),
+            "{:?}",
+            matches[0].evidence
+        );
+        assert_eq!(Context::new(dir.path()).spline, *spline);
+    }
+}
+
+#[test]
+fn test_polyglot_ranking_follows_language_weight() {
+    let dir = tree(&[
+        ("Cargo.toml", ""),
+        ("Makefile", ""),
+        ("src/lib.rs", &"x".repeat(300)),
+        ("scripts/gen.py", &"x".repeat(100)),
+    ]);
+    let matches = detect_splines(dir.path());
+    assert_eq!(
+        ranked(dir.path()),
+        vec![Spline::Rust, Spline::Make, Spline::Python]
+    );
+    // Marker (0.5) + three quarters of the source bytes (0.375).
+    assert!((matches[0].confidence - 0.875).abs() < 1e-6);
+    assert_eq!(matches[1].confidence, 0.5);
+    assert!((matches[2].confidence - 0.125).abs() < 1e-6);
+    assert_eq!(
+        matches[2].evidence,
+        vec!["Python: 1 file, 25% of source bytes".to_string()]
+    );
+}
+
+#[test]
+fn test_languages_without_markers_still_rank() {
+    let dir = tree(&[
+        ("a.go", &"x".repeat(100)),
+        ("b.go", &"x".repeat(100)),
+        ("native/lib.c", &"x".repeat(200)),
+        ("README.md", &"x".repeat(10_000)),
+    ]);
+    let matches = detect_splines(dir.path());
+    // C with no build file falls to Make; documentation carries no weight.
+    // Equal confidence ties break in declaration order.
+    assert_eq!(ranked(dir.path()), vec![Spline::Go, Spline::Make]);
+    assert_eq!(matches[0].confidence, 0.25);
+    assert_eq!(matches[1].confidence, 0.25);
+
+    let empty = tempfile::tempdir().unwrap();
+    assert!(detect_splines(empty.path()).is_empty());
+    assert_eq!(Context::new(empty.path()).spline, Spline::Void);
+}
+
+#[test]
+fn test_gitignore_is_honoured() {
+    let dir = tree(&[
+        ("go.mod", ""),
+        ("main.go", &"x".repeat(100)),
+        (".gitignore", "generated/\n*.py\n!keep.py\n"),
+        ("generated/huge.rs", &"x".repeat(100_000)),
+        ("tools/skip.py", &"x".repeat(100_000)),
+        ("tools/keep.py", &"x".repeat(100)),
+        // Build output is skipped even where nothing ignores it.
+        ("web/node_modules/dep/index.js", &"x".repeat(100_000)),
+    ]);
+    let matches = detect_splines(dir.path());
+    assert_eq!(ranked(dir.path()), vec![Spline::Go, Spline::Python]);
+    assert_eq!(matches[0].confidence, 0.75);
+    assert_eq!(matches[1].confidence, 0.25);
+}
+
+#[test]
+fn test_monorepo_sub_projects() {
+    let dir = tree(&[
+        ("flake.nix", &"x".repeat(50)),
+        ("services/api/go.mod", ""),
+        ("services/api/main.go", &"x".repeat(400)),
+        ("web/package.json", ""),
+        ("web/src/app.tsx", &"x".repeat(400)),
+        ("web/src/legacy.js", &"x".repeat(100)),
+        ("tools/lint/Cargo.toml", ""),
+        ("tools/lint/src/main.rs", &"x".repeat(50)),
+        (".gitignore", "vendor/\n"),
+        ("vendor/lib/CMakeLists.txt", ""),
+    ]);
+    let ctx = Context::new(dir.path());
+    assert_eq!(ctx.spline, Spline::Nix);
+    assert_eq!(
+        ctx.splines.iter().map(|m| m.spline).collect::<Vec<_>>(),
+        vec![Spline::Nix, Spline::Web, Spline::Go, Spline::Rust]
+    );
+
+    let subprojects: Vec<(String, Spline)> = ctx
+        .subprojects
+        .iter()
+        .map(|p| (p.path.to_string_lossy().into_owned(), p.spline()))
+        .collect();
+    assert_eq!(
+        subprojects,
+        vec![
+            ("services/api".to_string(), Spline::Go),
+            ("tools/lint".to_string(), Spline::Rust),
+            ("web".to_string(), Spline::Web),
+        ]
+    );
+    // A sub-project is weighed on its own files only.
+    let web = &ctx.subprojects[2];
+    assert_eq!(web.splines.len(), 1);
+    assert_eq!(web.splines[0].confidence, 1.0);
+}
+
+#[test]
+fn test_memoria_outranks_everything() {
+    let dir = tree(&[
+        ("MEMORIA.md", ""),
+        ("Cargo.toml", ""),
+        ("libs/core/src/lib.rs", "pub fn core() {}"),
+    ]);
+    let matches = detect_splines(dir.path());
+    assert_eq!(ranked(dir.path()), vec![Spline::UnaOS, Spline::Rust]);
+    assert_eq!(matches[0].confidence, 1.0);
+    assert_eq!(Context::new(dir.path()).spline, Spline::UnaOS);
+}
@@ -0,0 +1,349 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use elessar::context::WorkspaceIndexer;
+use elessar::symbols::{SymbolIndex, SymbolKind};
+use std::path::{Path, PathBuf};
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/symbols")
+}
+
+fn build(root: &Path) -> SymbolIndex {
+    let mut workspace = WorkspaceIndexer::new();
+    workspace.scan(root);
+    let index = SymbolIndex::build(&workspace);
+    assert!(index.errors.is_empty(), "{:?}", index.errors);
+    index
+}
+
+/// Line and column of the `nth` occurrence of `needle` in `file`.
+fn at(file: &Path, needle: &str, nth: usize) -> (usize, usize) {
+    let text = std::fs::read_to_string(file).unwrap();
+    let offset = text.match_indices(needle).nth(nth).unwrap().0;
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap().chars().count();
+    (line, column)
+}
+
+fn definition(index: &SymbolIndex, file: &Path, needle: &str, nth: usize) -> Option<String> {
+    let (line, column) = at(file, needle, nth);
+    index
+        .definition_at(file, line, column)
+        .map(|s| s.path.clone())
+}
+
+#[test]
+fn test_items_are_indexed_with_module_paths() {
+    let root = fixture();
+    let index = build(&root);
+    let geometry = root.join("shapes/src/geometry.rs");
+
+    let square = index.find("fixture_shapes::geometry::Square").unwrap();
+    assert_eq!(square.kind, SymbolKind::Struct);
+    assert_eq!(square.location.file, geometry);
+    assert_eq!(
+        (square.location.line, square.location.column),
+        at(&geometry, "Square {", 0)
+    );
+
+    let expect = [
+        ("fixture_shapes::geometry", SymbolKind::Mod),
+        ("fixture_shapes::geometry::solid", SymbolKind::Mod),
+        ("fixture_shapes::geometry::solid::Cube", SymbolKind::Struct),
+        (
+            "fixture_shapes::geometry::solid::Cube::volume",
+            SymbolKind::Method,
+        ),
+        ("fixture_shapes::geometry::Shape", SymbolKind::Trait),
+        ("fixture_shapes::geometry::Shape::area", SymbolKind::Method),
+        ("fixture_shapes::geometry::Shape::name", SymbolKind::Method),
+        ("fixture_shapes::geometry::Square::new", SymbolKind::Method),
+        ("fixture_shapes::geometry::Square::area", SymbolKind::Method),
+        ("fixture_shapes::geometry::<impl Square>", SymbolKind::Impl),
+        (
+            "fixture_shapes::geometry::<impl Shape for Square>",
+            SymbolKind::Impl,
+        ),
+        ("fixture_shapes::geometry::UNIT", SymbolKind::Const),
+        ("fixture_shapes::geometry::Kind", SymbolKind::Enum),
+        ("fixture_shapes::geometry::Kind::Flat", SymbolKind::Variant),
+        ("fixture_shapes::internal::helper", SymbolKind::Fn),
+        ("fixture_shapes::internal::COUNT", SymbolKind::Static),
+        ("fixture_shapes::prelude", SymbolKind::Mod),
+        ("fixture_shapes::square", SymbolKind::Macro),
+        ("fixture_app::main", SymbolKind::Fn),
+    ];
+    for (path, kind) in expect {
+        assert_eq!(index.find(path).map(|s| s.kind), Some(kind), "{path}");
+    }
+    assert_eq!(index.named("area").len(), 2);
+}
+
+#[test]
+fn test_paths_resolve_through_reexports_and_globs() {
+    let index = build(&fixture());
+    let resolve = |p: &str| index.resolve(p).map(|s| s.path.clone());
+    // `pub use geometry::Square;`
+    assert_eq!(
+        resolve("fixture_shapes::Square").as_deref(),
+        Some("fixture_shapes::geometry::Square")
+    );
+    // `pub use internal::*;` from a private module.
+    assert_eq!(
+        resolve("fixture_shapes::COUNT").as_deref(),
+        Some("fixture_shapes::internal::COUNT")
+    );
+    // Renamed, then reached through a re-exported type.
+    assert_eq!(
+        resolve("fixture_shapes::prelude::Sq::new").as_deref(),
+        Some("fixture_shapes::geometry::Square::new")
+    );
+    assert_eq!(resolve("fixture_shapes::geometry::Missing"), None);
+}
+
+#[test]
+fn test_go_to_definition() {
+    let root = fixture();
+    let index = build(&root);
+    let main = root.join("app/src/main.rs");
+    let def = |needle, nth| definition(&index, &main, needle, nth);
+
+    assert_eq!(
+        def("Square::new", 0).as_deref(),
+        Some("fixture_shapes::geometry::Square")
+    );
+    assert_eq!(
+        def("new(2.0)", 0).as_deref(),
+        Some("fixture_shapes::geometry::Square::new")
+    );
+    // Through `prelude::*`, which re-exports `internal::helper`.
+    assert_eq!(
+        def("helper()", 0).as_deref(),
+        Some("fixture_shapes::internal::helper")
+    );
+    assert_eq!(
+        def("Sq::new", 0).as_deref(),
+        Some("fixture_shapes::geometry::Square")
+    );
+    assert_eq!(
+        def("Cube {", 0).as_deref(),
+        Some("fixture_shapes::geometry::solid::Cube")
+    );
+    // `use Kind::*` brings the variants in.
+    assert_eq!(
+        def("Flat", 0).as_deref(),
+        Some("fixture_shapes::geometry::Kind::Flat")
+    );
+    assert_eq!(def("square!", 0).as_deref(), Some("fixture_shapes::square"));
+    assert_eq!(
+        def("UNIT", 0).as_deref(),
+        Some("fixture_shapes::geometry::UNIT")
+    );
+    assert_eq!(
+        def("COUNT", 0).as_deref(),
+        Some("fixture_shapes::internal::COUNT")
+    );
+    assert_eq!(
+        def("solid", 0).as_deref(),
+        Some("fixture_shapes::geometry::solid")
+    );
+    // Method calls: only the name is known, and `area` is ambiguous...
+    assert_eq!(def("area()", 0), None);
+    // ...while `volume` is not.
+    assert_eq!(
+        def("volume()", 0).as_deref(),
+        Some("fixture_shapes::geometry::solid::Cube::volume")
+    );
+    // On a definition itself.
+    let solid = root.join("shapes/src/geometry/solid.rs");
+    assert_eq!(
+        definition(&index, &solid, "volume", 0).as_deref(),
+        Some("fixture_shapes::geometry::solid::Cube::volume")
+    );
+    // `use super::{..}` inside a child module.
+    assert_eq!(
+        definition(&index, &solid, "Square", 1).as_deref(),
+        Some("fixture_shapes::geometry::Square")
+    );
+}
+
+#[test]
+fn test_find_references() {
+    let root = fixture();
+    let index = build(&root);
+    let refs = index.references("fixture_shapes::geometry::Square");
+    let places: Vec<(String, usize)> = refs
+        .iter()
+        .map(|r| {
+            assert!(r.resolved);
+            let file = r.location.file.strip_prefix(&root).unwrap();
+            (file.display().to_string(), r.location.line)
+        })
+        .collect();
+    assert_eq!(
+        places,
+        [
+            ("app/src/main.rs".to_string(), 4),
+            ("app/src/main.rs".to_string(), 7),
+            ("app/src/main.rs".to_string(), 10),
+            ("shapes/src/geometry/solid.rs".to_string(), 1),
+            ("shapes/src/geometry/solid.rs".to_string(), 4),
+            ("shapes/src/geometry.rs".to_string(), 15),
+            ("shapes/src/geometry.rs".to_string(), 17),
+            ("shapes/src/geometry.rs".to_string(), 21),
+            ("shapes/src/lib.rs".to_string(), 4),
+            ("shapes/src/lib.rs".to_string(), 9),
+        ]
+    );
+
+    // Method calls are matched by name and flagged as such.
+    let area = index.references("fixture_shapes::geometry::Shape::area");
+    assert_eq!(area.len(), 2);
+    assert!(area.iter().all(|r| !r.resolved));
+    assert!(index.references("fixture_shapes::nope").is_empty());
+}
+
+#[test]
+fn test_cycle_cut_is_not_remembered() {
+    // Reaching `X` from `a` passes through `b`, whose glob back into `a` is
+    // cut; `b` still sees `X` through `a` when asked on its own.
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"fixture-cycle\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+    std::fs::write(
+        dir.path().join("src/lib.rs"),
+        "pub mod a {\n    pub use crate::b::*;\n    pub use crate::c::*;\n    pub fn one(_: X) {}\n}\n\
+         pub mod b {\n    pub use crate::a::*;\n    pub fn two(_: X) {}\n}\n\
+         pub mod c {\n    pub struct X;\n}\n",
+    )
+    .unwrap();
+    let index = build(dir.path());
+    let lines: Vec<usize> = index
+        .references("fixture_cycle::c::X")
+        .iter()
+        .filter(|r| r.resolved)
+        .map(|r| r.location.line)
+        .collect();
+    assert_eq!(lines, [4, 8]);
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap().flatten() {
+        let target = to.join(entry.file_name());
+        if entry.path().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            std::fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_incremental_updates() {
+    let dir = tempfile::tempdir().unwrap();
+    copy_dir(&fixture(), dir.path());
+    let mut index = build(dir.path());
+    let geometry = dir.path().join("shapes/src/geometry.rs");
+    let solid = dir.path().join("shapes/src/geometry/solid.rs");
+
+    // An edit adds a symbol.
+    let mut text = std::fs::read_to_string(&geometry).unwrap();
+    text.push_str("\npub fn perimeter(s: &Square) -> f64 {\n    4.0 * s.side\n}\n");
+    std::fs::write(&geometry, &text).unwrap();
+    assert!(index.update_file(&geometry));
+    assert_eq!(
+        index
+            .find("fixture_shapes::geometry::perimeter")
+            .map(|s| s.location.line),
+        Some(at(&geometry, "perimeter", 0).0)
+    );
+
+    // Dropping the `mod` drops the file behind it.
+    std::fs::write(&geometry, text.replace("pub mod solid;\n", "")).unwrap();
+    assert!(index.update_file(&geometry));
+    assert!(
+        index
+            .find("fixture_shapes::geometry::solid::Cube")
+            .is_none()
+    );
+
+    // A new file is picked up once something declares it.
+    std::fs::write(
+        dir.path().join("shapes/src/geometry/flat.rs"),
+        "pub struct Tile;\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &geometry,
+        text.replace("pub mod solid;", "pub mod solid;\npub mod flat;"),
+    )
+    .unwrap();
+    assert!(index.update_file(&geometry));
+    assert!(index.find("fixture_shapes::geometry::flat::Tile").is_some());
+    assert!(
+        index
+            .find("fixture_shapes::geometry::solid::Cube")
+            .is_some()
+    );
+
+    // Changes to a child file alone, and its deletion.
+    std::fs::write(&solid, "pub struct Sphere;\n").unwrap();
+    assert!(index.update_file(&solid));
+    assert!(
+        index
+            .find("fixture_shapes::geometry::solid::Sphere")
+            .is_some()
+    );
+    assert!(
+        index
+            .find("fixture_shapes::geometry::solid::Cube")
+            .is_none()
+    );
+    std::fs::remove_file(&solid).unwrap();
+    assert!(index.update_file(&solid));
+    assert!(
+        index
+            .find("fixture_shapes::geometry::solid::Sphere")
+            .is_none()
+    );
+    // It comes back when recreated.
+    std::fs::write(&solid, "pub struct Cone;\n").unwrap();
+    assert!(index.update_file(&solid));
+    assert!(
+        index
+            .resolve("fixture_shapes::geometry::solid::Cone")
+            .is_some()
+    );
+
+    // A broken edit keeps the last good index and reports the error.
+    std::fs::write(&solid, "pub struct {").unwrap();
+    assert!(index.update_file(&solid));
+    assert!(index.errors.contains_key(&solid));
+    assert!(
+        index
+            .find("fixture_shapes::geometry::solid::Cone")
+            .is_some()
+    );
+
+    assert!(!index.update_file(&dir.path().join("unrelated.rs")));
+}
@@ -0,0 +1,661 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Budgets: how often, and how much, a provider may be asked.
+//!
+//! `Budgeted` wraps any provider. Before each call it fits the prompt into
+//! the model's `ContextWindow` and waits on a token-bucket `RateLimiter`
+//! (requests and tokens per minute); afterwards it writes the call into a
+//! `UsageLedger`. The ledger in memory is the source of truth; each record
+//! is also appended to the session's file through `BrainManager`.
+
+use super::stream::{Delta, DeltaStream};
+use super::{BoxFuture, Content, LlmError, LlmProvider, Part, UsageMetadata};
+use crate::persistence::{BrainManager, UsageLedger, UsageRecord};
+use futures::channel::mpsc;
+use futures::stream::{self, StreamExt};
+use log::warn;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Where the limiter reads the time. Swapped for a `ManualClock` in tests.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to.
+#[derive(Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Counts tokens without asking the provider.
+pub trait Tokenizer: Send + Sync {
+    fn count(&self, text: &str) -> u32;
+
+    /// The whole prompt, with a small overhead per message.
+    fn count_history(&self, history: &[Content]) -> u32 {
+        history
+            .iter()
+            .map(|c| {
+                4 + c
+                    .parts
+                    .iter()
+                    .map(|p| match p {
+                        Part::Text { text } => self.count(text),
+                        Part::FileData { file_data } => self.count(&file_data.file_uri),
+                    })
+                    .sum::<u32>()
+            })
+            .sum()
+    }
+}
+
+/// A fixed number of characters per token. Four is about right for
+/// English prose and code.
+#[derive(Debug, Clone)]
+pub struct CharEstimate {
+    pub chars_per_token: f32,
+}
+
+impl Default for CharEstimate {
+    fn default() -> Self {
+        Self {
+            chars_per_token: 4.0,
+        }
+    }
+}
+
+impl Tokenizer for CharEstimate {
+    fn count(&self, text: &str) -> u32 {
+        (text.chars().count() as f32 / self.chars_per_token).ceil() as u32
+    }
+}
+
+/// `None` means unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimits {
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+}
+
+/// A bucket that holds up to one minute's allowance and refills evenly.
+struct Bucket {
+    capacity: f64,
+    per_sec: f64,
+    level: f64,
+    last: Instant,
+}
+
+impl Bucket {
+    fn new(per_minute: u32, now: Instant) -> Self {
+        Self {
+            capacity: per_minute as f64,
+            per_sec: per_minute as f64 / 60.0,
+            level: per_minute as f64,
+            last: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.level = (self.level + elapsed * self.per_sec).min(self.capacity);
+        self.last = self.last.max(now);
+    }
+
+    /// How long until `cost` is available. A cost above capacity only
+    /// needs a full bucket, or it could never pass.
+    fn wait_for(&self, cost: f64) -> Duration {
+        let cost = cost.min(self.capacity);
+        if self.level >= cost {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((cost - self.level) / self.per_sec)
+        }
+    }
+}
+
+/// Token buckets for requests and tokens. A call passes only when both
+/// buckets can pay for it.
+pub struct RateLimiter {
+    clock: Arc<dyn Clock>,
+    requests: Mutex<Option<Bucket>>,
+    tokens: Mutex<Option<Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: &RateLimits, clock: Arc<dyn Clock>) -> Self {
+        let now = clock.now();
+        let bucket = |limit: Option<u32>| limit.filter(|&n| n > 0).map(|n| Bucket::new(n, now));
+        Self {
+            requests: Mutex::new(bucket(limits.requests_per_minute)),
+            tokens: Mutex::new(bucket(limits.tokens_per_minute)),
+            clock,
+        }
+    }
+
+    /// Takes one request and `tokens` tokens if both are available now;
+    /// otherwise takes nothing and says how long to wait.
+    pub fn try_acquire(&self, tokens: u32) -> Result<(), Duration> {
+        let now = self.clock.now();
+        let mut requests = self.requests.lock().unwrap();
+        let mut budget = self.tokens.lock().unwrap();
+
+        let mut wait = Duration::ZERO;
+        for (bucket, cost) in [(&mut *requests, 1.0), (&mut *budget, tokens as f64)] {
+            if let Some(b) = bucket {
+                b.refill(now);
+                wait = wait.max(b.wait_for(cost));
+            }
+        }
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+        if let Some(b) = requests.as_mut() {
+            b.level -= 1.0;
+        }
+        if let Some(b) = budget.as_mut() {
+            b.level -= (tokens as f64).min(b.capacity);
+        }
+        Ok(())
+    }
+
+    /// Waits until `try_acquire` succeeds.
+    pub async fn acquire(&self, tokens: u32) {
+        while let Err(wait) = self.try_acquire(tokens) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Debits tokens spent after the fact (the answer). May overdraw the
+    /// bucket, which delays the next call.
+    pub fn charge(&self, tokens: u32) {
+        if let Some(b) = self.tokens.lock().unwrap().as_mut() {
+            b.refill(self.clock.now());
+            b.level -= tokens as f64;
+        }
+    }
+}
+
+/// What to do with a prompt that does not fit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overflow {
+    /// Fail with `LlmError::ContextWindow`.
+    Reject,
+    /// Drop the oldest messages, then the oldest text, until it fits.
+    Truncate,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContextWindow {
+    pub max_input_tokens: u32,
+    pub overflow: Overflow,
+}
+
+impl ContextWindow {
+    /// `history`, cut down to fit if the policy allows.
+    pub fn fit(
+        &self,
+        history: &[Content],
+        tokenizer: &dyn Tokenizer,
+    ) -> Result<Vec<Content>, LlmError> {
+        let limit = self.max_input_tokens;
+        let needed = tokenizer.count_history(history);
+        if needed <= limit {
+            return Ok(history.to_vec());
+        }
+        let rejected = LlmError::ContextWindow { needed, limit };
+        if self.overflow == Overflow::Reject {
+            return Err(rejected);
+        }
+
+        let mut kept = history.to_vec();
+        while kept.len() > 1 && tokenizer.count_history(&kept) > limit {
+            kept.remove(0);
+        }
+        // A lone message still too big: its front holds the oldest context.
+        while tokenizer.count_history(&kept) > limit {
+            let Some(msg) = kept.first_mut() else {
+                return Err(rejected);
+            };
+            if msg.parts.len() > 1 {
+                msg.parts.remove(0);
+                continue;
+            }
+            let Some(Part::Text { text }) = msg.parts.first() else {
+                return Err(rejected);
+            };
+            let chars: Vec<char> = text.chars().collect();
+            let fits = |drop: usize| {
+                let probe = Content {
+                    role: msg.role.clone(),
+                    parts: vec![Part::text(chars[drop..].iter().collect())],
+                };
+                tokenizer.count_history(std::slice::from_ref(&probe)) <= limit
+            };
+            if !fits(chars.len()) {
+                return Err(rejected);
+            }
+            // Least text dropped that fits.
+            let (mut lo, mut hi) = (0, chars.len());
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                if fits(mid) {
+                    hi = mid;
+                } else {
+                    lo = mid + 1;
+                }
+            }
+            msg.parts[0] = Part::text(chars[lo..].iter().collect());
+        }
+        warn!(
+            "Context window: trimmed prompt from {} to {} tokens",
+            needed,
+            tokenizer.count_history(&kept)
+        );
+        Ok(kept)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BudgetConfig {
+    pub limits: RateLimits,
+    pub window: ContextWindow,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            limits: RateLimits {
+                requests_per_minute: Some(60),
+                tokens_per_minute: Some(4_000_000),
+            },
+            window: ContextWindow {
+                max_input_tokens: 1_000_000,
+                overflow: Overflow::Truncate,
+            },
+        }
+    }
+}
+
+impl BudgetConfig {
+    /// Defaults, overridden by `UNA_LLM_RPM`, `UNA_LLM_TPM` (0 = unlimited)
+    /// and `UNA_LLM_CONTEXT` (max prompt tokens).
+    pub fn from_env() -> Self {
+        let var = |name: &str| -> Option<u32> {
+            let raw = std::env::var(name).ok()?;
+            match raw.trim().parse() {
+                Ok(n) => Some(n),
+                Err(_) => {
+                    warn!("Ignoring {}={:?}: not a number", name, raw);
+                    None
+                }
+            }
+        };
+        let mut config = Self::default();
+        if let Some(n) = var("UNA_LLM_RPM") {
+            config.limits.requests_per_minute = Some(n);
+        }
+        if let Some(n) = var("UNA_LLM_TPM") {
+            config.limits.tokens_per_minute = Some(n);
+        }
+        if let Some(n) = var("UNA_LLM_CONTEXT") {
+            config.window.max_input_tokens = n;
+        }
+        config
+    }
+}
+
+/// A provider kept within its budget. See the module docs.
+pub struct Budgeted {
+    inner: Arc<dyn LlmProvider>,
+    config: BudgetConfig,
+    limiter: RateLimiter,
+    tokenizer: Arc<dyn Tokenizer>,
+    ledger: Mutex<UsageLedger>,
+    brain: Option<BrainManager>,
+}
+
+impl Budgeted {
+    pub fn new(inner: Arc<dyn LlmProvider>, config: BudgetConfig) -> Self {
+        Self {
+            limiter: RateLimiter::new(&config.limits, Arc::new(SystemClock)),
+            inner,
+            config,
+            tokenizer: Arc::new(CharEstimate::default()),
+            ledger: Mutex::new(UsageLedger::default()),
+            brain: None,
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.limiter = RateLimiter::new(&self.config.limits, clock);
+        self
+    }
+
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Records into the ledger of `session`, resuming it if one is saved.
+    pub fn with_ledger(mut self, brain: BrainManager, session: &str) -> Self {
+        self.ledger = Mutex::new(brain.load_ledger(session));
+        self.brain = Some(brain);
+        self
+    }
+
+    pub fn ledger(&self) -> UsageLedger {
+        self.ledger.lock().unwrap().clone()
+    }
+
+    fn prepare(&self, history: &[Content]) -> Result<(Vec<Content>, u32), LlmError> {
+        let fitted = self.config.window.fit(history, self.tokenizer.as_ref())?;
+        let tokens = self.tokenizer.count_history(&fitted);
+        Ok((fitted, tokens))
+    }
+
+    /// Logs one call, preferring the provider's own counts.
+    fn record(&self, kind: &str, prompt: u32, completion: u32, usage: Option<&UsageMetadata>) {
+        let reported = usage.and_then(|u| {
+            Some((
+                u.prompt_token_count? as u32,
+                u.candidates_token_count.unwrap_or(0) as u32,
+            ))
+        });
+        let (prompt_tokens, completion_tokens) = reported.unwrap_or((prompt, completion));
+        self.limiter.charge(completion_tokens);
+
+        let record = UsageRecord {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            provider: self.inner.name(),
+            kind: kind.to_string(),
+            prompt_tokens,
+            completion_tokens,
+            estimated: reported.is_none(),
+        };
+        let session = {
+            let mut ledger = self.ledger.lock().unwrap();
+            ledger.records.push(record.clone());
+            ledger.session.clone()
+        };
+        if let Some(brain) = &self.brain
+            && let Err(e) = brain.append_usage(&session, &record)
+        {
+            warn!("Failed to save usage record: {}", e);
+        }
+    }
+}
+
+impl LlmProvider for Budgeted {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn generate<'a>(
+        &'a self,
+        history: &'a [Content],
+    ) -> BoxFuture<'a, Result<(String, Option<UsageMetadata>), LlmError>> {
+        Box::pin(async move {
+            let (fitted, tokens) = self.prepare(history)?;
+            self.limiter.acquire(tokens).await;
+            let (text, usage) = self.inner.generate(&fitted).await?;
+            self.record(
+                "generate",
+                tokens,
+                self.tokenizer.count(&text),
+                usage.as_ref(),
+            );
+            Ok((text, usage))
+        })
+    }
+
+    fn generate_stream<'a>(&'a self, history: &'a [Content]) -> DeltaStream<'a> {
+        // The fitted prompt is owned by the driver, so the inner stream is
+        // pumped from there and its deltas handed out through a channel.
+        let (tx, rx) = mpsc::unbounded();
+        let driver = async move {
+            let (fitted, tokens) = match self.prepare(history) {
+                Ok(prepared) => prepared,
+                Err(e) => {
+                    let _ = tx.unbounded_send(Err(e));
+                    return;
+                }
+            };
+            self.limiter.acquire(tokens).await;
+
+            let mut inner = self.inner.generate_stream(&fitted);
+            let mut written = 0;
+            let mut usage = None;
+            let mut failed = false;
+            while let Some(delta) = inner.next().await {
+                match &delta {
+                    Ok(Delta::Text(t)) => written += self.tokenizer.count(t),
+                    Ok(Delta::Usage(u)) => usage = Some(u.clone()),
+                    Ok(Delta::FunctionCall { .. }) => {}
+                    Err(_) => failed = true,
+                }
+                if tx.unbounded_send(delta).is_err() {
+                    // Nobody is reading any more.
+                    break;
+                }
+            }
+            if !failed {
+                self.record("generate", tokens, written, usage.as_ref());
+            }
+        };
+        Box::pin(
+            stream::select(
+                rx.map(Some),
+                stream::once(driver).map(|()| None::<Result<Delta, LlmError>>),
+            )
+            .filter_map(futures::future::ready),
+        )
+    }
+
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<f32>, LlmError>> {
+        Box::pin(async move {
+            let tokens = self.tokenizer.count(text);
+            self.limiter.acquire(tokens).await;
+            let vector = self.inner.embed(text).await?;
+            self.record("embed", tokens, 0, None);
+            Ok(vector)
+        })
+    }
+
+    fn count_tokens<'a>(&'a self, history: &'a [Content]) -> BoxFuture<'a, Result<u32, LlmError>> {
+        self.inner.count_tokens(history)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(rpm: Option<u32>, tpm: Option<u32>) -> (RateLimiter, ManualClock) {
+        let clock = ManualClock::new();
+        let limits = RateLimits {
+            requests_per_minute: rpm,
+            tokens_per_minute: tpm,
+        };
+        (RateLimiter::new(&limits, Arc::new(clock.clone())), clock)
+    }
+
+    fn text(role: &str, t: &str) -> Content {
+        Content {
+            role: role.to_string(),
+            parts: vec![Part::text(t.to_string())],
+        }
+    }
+
+    #[test]
+    fn test_request_bucket_refills_evenly() {
+        let (limiter, clock) = limiter(Some(6), None);
+        for _ in 0..6 {
+            assert!(limiter.try_acquire(0).is_ok());
+        }
+        // Six a minute: one every ten seconds.
+        assert_eq!(limiter.try_acquire(0), Err(Duration::from_secs(10)));
+
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(limiter.try_acquire(0), Err(Duration::from_secs(6)));
+
+        clock.advance(Duration::from_secs(6));
+        assert!(limiter.try_acquire(0).is_ok());
+        assert!(limiter.try_acquire(0).is_err());
+
+        // Idling never banks more than a minute's worth.
+        clock.advance(Duration::from_secs(3600));
+        for _ in 0..6 {
+            assert!(limiter.try_acquire(0).is_ok());
+        }
+        assert!(limiter.try_acquire(0).is_err());
+    }
+
+    #[test]
+    fn test_token_bucket_and_both_must_pass() {
+        let (limiter, clock) = limiter(Some(100), Some(600));
+        assert!(limiter.try_acquire(500).is_ok());
+        // 100 left, 200 needed: 10 tokens/s.
+        assert_eq!(limiter.try_acquire(200), Err(Duration::from_secs(10)));
+        // A refused call takes nothing, not even a request.
+        assert!(limiter.try_acquire(100).is_ok());
+
+        clock.advance(Duration::from_secs(60));
+        // Bigger than the bucket: waits for a full one instead of forever.
+        assert!(limiter.try_acquire(10_000).is_ok());
+        assert_eq!(limiter.try_acquire(1), Err(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_charge_overdraws() {
+        let (limiter, clock) = limiter(None, Some(60));
+        limiter.charge(120);
+        // Full bucket of 60, minus 120: a minute to climb back to zero.
+        assert_eq!(limiter.try_acquire(0), Err(Duration::from_secs(60)));
+        clock.advance(Duration::from_secs(60));
+        assert!(limiter.try_acquire(0).is_ok());
+    }
+
+    #[test]
+    fn test_unlimited_never_waits() {
+        let (limiter, _) = limiter(None, Some(0));
+        for _ in 0..1000 {
+            assert!(limiter.try_acquire(u32::MAX).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_window_rejects_or_truncates() {
+        let tokenizer = CharEstimate::default();
+        let history = vec![
+            text("user", &"a".repeat(400)),
+            text("model", &"b".repeat(400)),
+            text("user", &"c".repeat(40)),
+        ];
+        let window = |overflow| ContextWindow {
+            max_input_tokens: 100,
+            overflow,
+        };
+
+        assert_eq!(
+            window(Overflow::Reject)
+                .fit(&history, &tokenizer)
+                .unwrap_err(),
+            LlmError::ContextWindow {
+                needed: 222,
+                limit: 100
+            }
+        );
+
+        let kept = window(Overflow::Truncate)
+            .fit(&history, &tokenizer)
+            .unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].role, "user");
+
+        let fits = vec![text("user", &"d".repeat(40))];
+        assert_eq!(
+            window(Overflow::Reject)
+                .fit(&fits, &tokenizer)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_window_trims_front_of_lone_message() {
+        let tokenizer = CharEstimate::default();
+        let prompt = format!("{}[CURRENT PROMPT]: go", "x".repeat(1000));
+        let window = ContextWindow {
+            max_input_tokens: 14,
+            overflow: Overflow::Truncate,
+        };
+
+        let kept = window.fit(&[text("user", &prompt)], &tokenizer).unwrap();
+        assert!(tokenizer.count_history(&kept) <= 14);
+        match &kept[0].parts[0] {
+            Part::Text { text } => {
+                assert!(text.ends_with("[CURRENT PROMPT]: go"));
+                assert_eq!(text.chars().count(), 40);
+            }
+            other => panic!("Unexpected part: {:?}", other),
+        }
+
+        // Nothing left to trim below the per-message overhead.
+        let tiny = ContextWindow {
+            max_input_tokens: 3,
+            overflow: Overflow::Truncate,
+        };
+        assert!(matches!(
+            tiny.fit(&[text("user", "hi")], &tokenizer),
+            Err(LlmError::ContextWindow { .. })
+        ));
+    }
+}
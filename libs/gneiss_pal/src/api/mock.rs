@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A scripted provider for tests and offline work.
+
+use super::{BoxFuture, Content, LlmError, LlmProvider, UsageMetadata, estimate_tokens};
+use std::collections::VecDeque;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Mutex;
+
+type Reply = Result<(String, Option<UsageMetadata>), LlmError>;
+
+/// Dimension of the fallback embeddings.
+pub const MOCK_EMBEDDING_DIM: usize = 16;
+
+/// Answers `generate` from a script, in order, and records every call.
+///
+/// Once the script runs dry `generate` returns `LlmError::Empty`. `embed`
+/// returns scripted vectors first, then a deterministic one derived from
+/// the text, so equal texts always embed equally.
+#[derive(Default)]
+pub struct MockProvider {
+    replies: Mutex<VecDeque<Reply>>,
+    embeddings: Mutex<VecDeque<Result<Vec<f32>, LlmError>>>,
+    calls: Mutex<Vec<Vec<Content>>>,
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a text answer.
+    pub fn with_text(self, text: &str) -> Self {
+        self.push(Ok((text.to_string(), None)))
+    }
+
+    /// Queues a text answer with usage figures.
+    pub fn with_reply(self, text: &str, usage: UsageMetadata) -> Self {
+        self.push(Ok((text.to_string(), Some(usage))))
+    }
+
+    /// Queues a failure.
+    pub fn with_error(self, error: LlmError) -> Self {
+        self.push(Err(error))
+    }
+
+    /// Queues a vector for the next `embed`.
+    pub fn with_embedding(self, vector: Vec<f32>) -> Self {
+        self.embeddings.lock().unwrap().push_back(Ok(vector));
+        self
+    }
+
+    /// Queues any outcome.
+    pub fn push(self, reply: Reply) -> Self {
+        self.replies.lock().unwrap().push_back(reply);
+        self
+    }
+
+    /// Every history `generate` was called with, oldest first.
+    pub fn calls(&self) -> Vec<Vec<Content>> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Scripted answers not yet consumed.
+    pub fn remaining(&self) -> usize {
+        self.replies.lock().unwrap().len()
+    }
+}
+
+fn fallback_embedding(text: &str) -> Vec<f32> {
+    (0..MOCK_EMBEDDING_DIM)
+        .map(|i| {
+            let mut hasher = DefaultHasher::new();
+            (text, i).hash(&mut hasher);
+            (hasher.finish() % 2001) as f32 / 1000.0 - 1.0
+        })
+        .collect()
+}
+
+impl LlmProvider for MockProvider {
+    fn name(&self) -> String {
+        "mock".to_string()
+    }
+
+    fn generate<'a>(&'a self, history: &'a [Content]) -> BoxFuture<'a, Reply> {
+        Box::pin(async move {
+            self.calls.lock().unwrap().push(history.to_vec());
+            self.replies
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or(Err(LlmError::Empty))
+        })
+    }
+
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<f32>, LlmError>> {
+        Box::pin(async move {
+            self.embeddings
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| Ok(fallback_embedding(text)))
+        })
+    }
+
+    fn count_tokens<'a>(&'a self, history: &'a [Content]) -> BoxFuture<'a, Result<u32, LlmError>> {
+        Box::pin(async move { Ok(estimate_tokens(history)) })
+    }
+}
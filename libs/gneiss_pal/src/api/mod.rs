@@ -14,23 +14,43 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use log::warn;
-use log::{error, info};
-use reqwest::{Client, ClientBuilder, StatusCode};
+//! The Neural Link.
+//!
+//! Everything that talks to a language model goes through `LlmProvider`, so
+//! the rest of the system never knows (or cares) which one is on the other
+//! end. Implementations:
+//! * `VertexGemini`: Gemini on Vertex AI, authenticated through gcloud ADC.
+//! * `OpenAiCompatible`: any server speaking the OpenAI chat/embeddings API.
+//! * `MockProvider`: scripted answers, for tests and offline work.
+//!
+//! The HTTP providers share one retry policy (see `retry`), and can stream
+//! their answers as they are written (see `stream`).
+
+pub mod budget;
+mod mock;
+mod openai;
+pub mod retry;
+pub mod stream;
+mod vertex;
+
+pub use budget::{
+    BudgetConfig, Budgeted, CharEstimate, Clock, ContextWindow, ManualClock, Overflow, RateLimiter,
+    RateLimits, SystemClock, Tokenizer,
+};
+pub use mock::MockProvider;
+pub use openai::{OpenAiCompatible, OpenAiConfig};
+pub use retry::RetryPolicy;
+pub use stream::{Delta, DeltaStream, collect_stream};
+pub use vertex::{TokenSource, VertexConfig, VertexGemini};
+
+use futures::stream::StreamExt;
 use serde::{Deserialize, Serialize};
-use std::process::Command;
-use std::time::Duration;
-#[derive(Serialize)]
-struct GenerateContentRequest {
-    contents: Vec<Content>,
-    #[serde(rename = "generationConfig")]
-    generation_config: GenerationConfig,
-}
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
 
-#[derive(Serialize)]
-struct GenerationConfig {
-    temperature: f32,
-}
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Content {
@@ -73,17 +93,7 @@ pub struct FileData {
     pub file_uri: String,
 }
 
-// Standard Google AI Response (Not Vertex Stream)
-#[derive(Deserialize, Debug)]
-struct GenerateContentResponse {
-    candidates: Option<Vec<Candidate>>,
-    #[serde(rename = "promptFeedback")]
-    prompt_feedback: Option<PromptFeedback>,
-    #[serde(rename = "usageMetadata")]
-    usage_metadata: Option<UsageMetadata>,
-}
-
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct UsageMetadata {
     #[serde(rename = "promptTokenCount")]
     pub prompt_token_count: Option<i32>,
@@ -93,250 +103,109 @@ pub struct UsageMetadata {
     pub total_token_count: Option<i32>,
 }
 
-#[derive(Deserialize, Debug)]
-struct Candidate {
-    content: Option<ContentResponse>,
-    #[serde(rename = "finishReason")]
-    _finish_reason: Option<String>,
-}
-
-#[derive(Deserialize, Debug)]
-struct ContentResponse {
-    parts: Vec<PartResponse>,
-}
-
-#[derive(Deserialize, Debug)]
-struct PartResponse {
-    text: String,
-}
-
-#[derive(Deserialize, Debug)]
-struct PromptFeedback {
-    #[serde(rename = "blockReason")]
-    block_reason: Option<String>,
-}
-
-pub struct ResilientClient {
-    client: Client,
-    model_url: String,
-    token: String,
-}
-
-impl ResilientClient {
-    pub async fn new() -> Result<Self, String> {
-        let token = Self::fetch_token()?;
-
-        // 2. Hardcode to Experimental as requested
-        let model_name = "gemini-3.1-pro-preview";
-
-        // 3. Pure Vertex URL (No API key appended)
-        let model_url = format!(
-            "https://aiplatform.googleapis.com/v1beta1/projects/unauploads-1769528906/locations/global/publishers/google/models/{}:generateContent",
-            model_name
-        );
-
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(300))
-            .build()
-            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
-
-        info!("System Ignited. Target: {} (Vertex API)", model_name);
-
-        Ok(Self {
-            client,
-            model_url,
-            token,
-        })
-    }
-
-    pub fn fetch_token() -> Result<String, String> {
-        info!("Executing gcloud ADC token fetch...");
-        let output = Command::new("gcloud")
-            .args(["auth", "application-default", "print-access-token"])
-            .output()
-            .map_err(|e| {
-                error!("gcloud execution failed: {}", e);
-                format!("Failed to execute gcloud for token: {}", e)
-            })?;
-
-        if !output.status.success() {
-            let err_msg = String::from_utf8_lossy(&output.stderr);
-            error!("gcloud ADC failed: {}", err_msg);
-            return Err(
-                "Failed to retrieve gcloud access token. Ensure gcloud ADC is configured."
-                    .to_string(),
-            );
-        }
-
-        String::from_utf8(output.stdout)
-            .map(|s| s.trim().to_string())
-            .map_err(|_| {
-                error!("Invalid UTF-8 in gcloud token");
-                "Invalid UTF-8 in gcloud token".to_string()
-            })
-    }
-
-    pub async fn refresh_token(&mut self) -> Result<(), String> {
-        info!("Refreshing GCloud Token (Lazarus Protocol)...");
-        match Self::fetch_token() {
-            Ok(t) => {
-                self.token = t;
-                Ok(())
-            }
-            Err(e) => Err(e),
+/// Why a model call failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LlmError {
+    /// The request never got a response (connect, TLS, timeout).
+    Transport(String),
+    /// Credentials were missing or rejected, even after a refresh.
+    Auth(String),
+    /// The server answered with a non-success status.
+    Http { status: u16, body: String },
+    /// The model refused on safety grounds.
+    Blocked(String),
+    /// The answer could not be decoded.
+    Decode(String),
+    /// The model answered with nothing.
+    Empty,
+    /// The prompt does not fit the model's context window.
+    ContextWindow { needed: u32, limit: u32 },
+}
+
+impl LlmError {
+    /// Worth trying again: the network, rate limits and server faults.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            LlmError::Transport(_) => true,
+            LlmError::Http { status, .. } => *status == 429 || *status >= 500,
+            _ => false,
         }
     }
+}
 
-    pub async fn generate_content(
-        &mut self,
-        history: &[Content],
-    ) -> Result<(String, Option<UsageMetadata>), String> {
-        let request_body = GenerateContentRequest {
-            contents: history.to_vec(),
-            generation_config: GenerationConfig { temperature: 0.4 },
-        };
-
-        let mut attempts = 0;
-        loop {
-            attempts += 1;
-            info!("Transmitting to Neural Core (Attempt {})...", attempts);
-
-            let response = self
-                .client
-                .post(&self.model_url)
-                .bearer_auth(&self.token)
-                .json(&request_body)
-                .send()
-                .await
-                .map_err(|e| format!("Transmission Failed: {}", e))?;
-
-            if response.status() == StatusCode::UNAUTHORIZED {
-                if attempts < 2 {
-                    warn!("401 Unauthorized detected. Initiating Lazarus Protocol...");
-                    if let Err(e) = self.refresh_token().await {
-                        return Err(format!("Lazarus Protocol Failed: {}", e));
-                    }
-                    continue;
-                } else {
-                    return Err("Authentication Failed after retry.".to_string());
-                }
-            }
-
-            if !response.status().is_success() {
-                let status = response.status();
-                let text = response.text().await.unwrap_or_default();
-                error!("Hull Breach (API Error): {} - {}", status, text);
-                return Err(format!("System Failure {}: {}", status, text));
-            }
-
-            let data: GenerateContentResponse = response
-                .json()
-                .await
-                .map_err(|e| format!("Failed to decode neural pattern: {}", e))?;
-
-            if let Some(feedback) = data.prompt_feedback {
-                if let Some(reason) = feedback.block_reason {
-                    return Err(format!("Safety Protocols Engaged: {}", reason));
-                }
-            }
-
-            if let Some(candidates) = data.candidates {
-                if let Some(first) = candidates.first() {
-                    if let Some(content) = &first.content {
-                        let mut full_text = String::new();
-                        for part in &content.parts {
-                            full_text.push_str(&part.text);
-                        }
-                        return Ok((full_text, data.usage_metadata));
-                    }
-                }
-            }
-
-            return Err("Neural Core returned silence (Empty Response).".to_string());
+impl fmt::Display for LlmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LlmError::Transport(e) => write!(f, "Transmission Failed: {}", e),
+            LlmError::Auth(e) => write!(f, "Authentication Failed: {}", e),
+            LlmError::Http { status, body } => write!(f, "System Failure {}: {}", status, body),
+            LlmError::Blocked(reason) => write!(f, "Safety Protocols Engaged: {}", reason),
+            LlmError::Decode(e) => write!(f, "Failed to decode neural pattern: {}", e),
+            LlmError::Empty => write!(f, "Neural Core returned silence (Empty Response)."),
+            LlmError::ContextWindow { needed, limit } => write!(
+                f,
+                "Context window exceeded: {} tokens needed, {} allowed",
+                needed, limit
+            ),
         }
     }
+}
 
-    pub async fn list_vertex_models(&self) -> Result<String, String> {
-        Ok("Model listing bypass engaged. Hardcoded to gemini-3.1-pro-preview.".to_string())
-    }
-
-    pub async fn embed_content(&mut self, text: &str) -> Result<Vec<f32>, String> {
-        let url = "https://us-central1-aiplatform.googleapis.com/v1beta1/projects/unauploads-1769528906/locations/us-central1/publishers/google/models/text-embedding-004:predict";
-
-        let request_body = EmbedContentRequest {
-            instances: vec![EmbedContentInstance {
-                content: text.to_string(),
-            }],
-        };
-
-        let mut attempts = 0;
-        loop {
-            attempts += 1;
-
-            let response = self
-                .client
-                .post(url)
-                .bearer_auth(&self.token)
-                .json(&request_body)
-                .send()
-                .await
-                .map_err(|e| format!("Embedding Transmission Failed: {}", e))?;
-
-            if response.status() == StatusCode::UNAUTHORIZED {
-                if attempts < 2 {
-                    warn!("401 Unauthorized (Embedding). Initiating Lazarus Protocol...");
-                    if let Err(e) = self.refresh_token().await {
-                        return Err(format!("Lazarus Protocol Failed: {}", e));
+impl std::error::Error for LlmError {}
+
+/// A language model backend. Object safe, so handlers hold an
+/// `Arc<dyn LlmProvider>` and tests swap in a `MockProvider`.
+pub trait LlmProvider: Send + Sync {
+    /// Short name for logs, e.g. `vertex:gemini-3.1-pro-preview`.
+    fn name(&self) -> String;
+
+    /// Answers the conversation in `history` with text.
+    fn generate<'a>(
+        &'a self,
+        history: &'a [Content],
+    ) -> BoxFuture<'a, Result<(String, Option<UsageMetadata>), LlmError>>;
+
+    /// Like `generate`, but yields the answer piece by piece as it is
+    /// written. Providers without streaming deliver it in one `Text` delta.
+    fn generate_stream<'a>(&'a self, history: &'a [Content]) -> DeltaStream<'a> {
+        Box::pin(
+            futures::stream::once(self.generate(history)).flat_map(|result| {
+                let deltas = match result {
+                    Ok((text, usage)) => {
+                        let mut deltas = vec![Ok(Delta::Text(text))];
+                        deltas.extend(usage.map(|u| Ok(Delta::Usage(u))));
+                        deltas
                     }
-                    continue;
-                } else {
-                    return Err("Authentication Failed after retry.".to_string());
-                }
-            }
-
-            if !response.status().is_success() {
-                let status = response.status();
-                let text = response.text().await.unwrap_or_default();
-                return Err(format!("Embedding Failure {}: {}", status, text));
-            }
-
-            let data: EmbedContentResponse = response
-                .json()
-                .await
-                .map_err(|e| format!("Failed to decode embedding: {}", e))?;
-
-            if let Some(predictions) = data.predictions {
-                if let Some(first) = predictions.first() {
-                    return Ok(first.embeddings.values.clone());
-                }
-            }
-
-            return Err("Neural Core returned no embedding.".to_string());
-        }
+                    Err(e) => vec![Err(e)],
+                };
+                futures::stream::iter(deltas)
+            }),
+        )
     }
-}
-
-#[derive(Serialize)]
-struct EmbedContentRequest {
-    instances: Vec<EmbedContentInstance>,
-}
 
-#[derive(Serialize)]
-struct EmbedContentInstance {
-    content: String,
-}
+    /// Embeds `text` into the provider's vector space.
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<f32>, LlmError>>;
 
-#[derive(Deserialize)]
-struct EmbedContentResponse {
-    predictions: Option<Vec<EmbedPrediction>>,
+    /// How many tokens `history` costs as a prompt.
+    fn count_tokens<'a>(&'a self, history: &'a [Content]) -> BoxFuture<'a, Result<u32, LlmError>>;
 }
 
-#[derive(Deserialize)]
-struct EmbedPrediction {
-    embeddings: EmbedValues,
+/// A rough token count for providers without a counting endpoint:
+/// about four characters per token, plus a little per message.
+pub fn estimate_tokens(history: &[Content]) -> u32 {
+    CharEstimate::default().count_history(history)
 }
 
-#[derive(Deserialize)]
-struct EmbedValues {
-    values: Vec<f32>,
+/// Picks a provider from the environment:
+/// * `UNA_LLM_PROVIDER`: `vertex` (default), `openai` or `mock`.
+/// * `openai` reads `OPENAI_BASE_URL`, `OPENAI_API_KEY`, `UNA_LLM_MODEL`
+///   and `UNA_EMBED_MODEL`.
+pub fn provider_from_env() -> Result<Arc<dyn LlmProvider>, String> {
+    let kind = std::env::var("UNA_LLM_PROVIDER").unwrap_or_else(|_| "vertex".to_string());
+    match kind.as_str() {
+        "vertex" => Ok(Arc::new(VertexGemini::new()?)),
+        "openai" => Ok(Arc::new(OpenAiCompatible::new(OpenAiConfig::from_env())?)),
+        "mock" => Ok(Arc::new(MockProvider::new())),
+        other => Err(format!("Unknown UNA_LLM_PROVIDER '{}'", other)),
+    }
 }
@@ -0,0 +1,356 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Any server speaking the OpenAI chat and embeddings API
+//! (OpenAI itself, llama.cpp, vLLM, Ollama, ...).
+
+use super::retry::{self, RetryPolicy};
+use super::stream::{self, Delta, DeltaStream};
+use super::{BoxFuture, Content, LlmError, LlmProvider, Part, UsageMetadata, estimate_tokens};
+use log::info;
+use reqwest::{Client, ClientBuilder, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct OpenAiConfig {
+    /// Up to and including the version, e.g. `https://api.openai.com/v1`.
+    pub base_url: String,
+    /// Sent as a bearer token. Local servers usually need none.
+    pub api_key: Option<String>,
+    pub model: String,
+    pub embedding_model: String,
+    pub temperature: f32,
+    pub retry: RetryPolicy,
+}
+
+impl Default for OpenAiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://api.openai.com/v1".to_string(),
+            api_key: None,
+            model: "gpt-4o-mini".to_string(),
+            embedding_model: "text-embedding-3-small".to_string(),
+            temperature: 0.4,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+impl OpenAiConfig {
+    /// Reads `OPENAI_BASE_URL`, `OPENAI_API_KEY`, `UNA_LLM_MODEL` and
+    /// `UNA_EMBED_MODEL`, keeping the defaults for any that are unset.
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let defaults = Self::default();
+        Self {
+            base_url: var("OPENAI_BASE_URL").unwrap_or(defaults.base_url),
+            api_key: var("OPENAI_API_KEY"),
+            model: var("UNA_LLM_MODEL").unwrap_or(defaults.model),
+            embedding_model: var("UNA_EMBED_MODEL").unwrap_or(defaults.embedding_model),
+            ..defaults
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage>,
+    temperature: f32,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+#[derive(Serialize)]
+struct StreamOptions {
+    include_usage: bool,
+}
+
+#[derive(Serialize)]
+struct ChatMessage {
+    role: &'static str,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    #[serde(default)]
+    choices: Vec<ChatChoice>,
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatChoiceMessage,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoiceMessage {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChatUsage {
+    prompt_tokens: Option<i32>,
+    completion_tokens: Option<i32>,
+    total_tokens: Option<i32>,
+}
+
+impl From<ChatUsage> for UsageMetadata {
+    fn from(u: ChatUsage) -> Self {
+        UsageMetadata {
+            prompt_token_count: u.prompt_tokens,
+            candidates_token_count: u.completion_tokens,
+            total_token_count: u.total_tokens,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChunkChoice {
+    #[serde(default)]
+    delta: ChunkDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct ChunkDelta {
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ToolCallChunk>,
+}
+
+#[derive(Deserialize)]
+struct ToolCallChunk {
+    #[serde(default)]
+    index: u32,
+    id: Option<String>,
+    function: Option<FunctionChunk>,
+}
+
+#[derive(Deserialize)]
+struct FunctionChunk {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+/// Maps one `data:` payload to deltas. `[DONE]` carries none.
+fn parse_stream_chunk(frame: &str) -> Result<Vec<Delta>, LlmError> {
+    if frame.trim() == "[DONE]" {
+        return Ok(Vec::new());
+    }
+    let chunk: ChatChunk =
+        serde_json::from_str(frame).map_err(|e| LlmError::Decode(e.to_string()))?;
+
+    let mut deltas = Vec::new();
+    if let Some(choice) = chunk.choices.into_iter().next() {
+        if let Some(text) = choice.delta.content.filter(|t| !t.is_empty()) {
+            deltas.push(Delta::Text(text));
+        }
+        for call in choice.delta.tool_calls {
+            let (name, arguments) = match call.function {
+                Some(f) => (f.name, f.arguments.unwrap_or_default()),
+                None => (None, String::new()),
+            };
+            deltas.push(Delta::FunctionCall {
+                index: call.index,
+                id: call.id,
+                name,
+                arguments,
+            });
+        }
+        if choice.finish_reason.as_deref() == Some("content_filter") {
+            return Err(LlmError::Blocked("content_filter".to_string()));
+        }
+    }
+    if let Some(usage) = chunk.usage {
+        deltas.push(Delta::Usage(usage.into()));
+    }
+    Ok(deltas)
+}
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    #[serde(default)]
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+}
+
+/// Gemini roles to chat roles. The API has no file parts, so attachments
+/// are passed by reference.
+fn to_messages(history: &[Content]) -> Vec<ChatMessage> {
+    history
+        .iter()
+        .map(|c| ChatMessage {
+            role: match c.role.as_str() {
+                "model" | "assistant" => "assistant",
+                "system" => "system",
+                _ => "user",
+            },
+            content: c
+                .parts
+                .iter()
+                .map(|p| match p {
+                    Part::Text { text } => text.clone(),
+                    Part::FileData { file_data } => format!(
+                        "[Attachment: {} ({})]",
+                        file_data.file_uri, file_data.mime_type
+                    ),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        })
+        .collect()
+}
+
+pub struct OpenAiCompatible {
+    client: Client,
+    config: OpenAiConfig,
+}
+
+impl OpenAiCompatible {
+    pub fn new(config: OpenAiConfig) -> Result<Self, String> {
+        let client = ClientBuilder::new()
+            .timeout(Duration::from_secs(300))
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+        info!(
+            "System Ignited. Target: {} ({})",
+            config.model, config.base_url
+        );
+
+        Ok(Self { client, config })
+    }
+
+    fn post(&self, path: &str) -> RequestBuilder {
+        let url = format!("{}/{}", self.config.base_url.trim_end_matches('/'), path);
+        let request = self.client.post(url);
+        match &self.config.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+}
+
+impl LlmProvider for OpenAiCompatible {
+    fn name(&self) -> String {
+        format!("openai:{}", self.config.model)
+    }
+
+    fn generate<'a>(
+        &'a self,
+        history: &'a [Content],
+    ) -> BoxFuture<'a, Result<(String, Option<UsageMetadata>), LlmError>> {
+        Box::pin(async move {
+            let body = ChatRequest {
+                model: &self.config.model,
+                messages: to_messages(history),
+                temperature: self.config.temperature,
+                stream: false,
+                stream_options: None,
+            };
+            let response = retry::send(&self.config.retry, &self.config.base_url, || {
+                self.post("chat/completions").json(&body)
+            })
+            .await?;
+            let data: ChatResponse = response
+                .json()
+                .await
+                .map_err(|e| LlmError::Decode(e.to_string()))?;
+
+            let usage = data.usage.map(UsageMetadata::from);
+            let Some(choice) = data.choices.into_iter().next() else {
+                return Err(LlmError::Empty);
+            };
+            if choice.finish_reason.as_deref() == Some("content_filter") {
+                return Err(LlmError::Blocked("content_filter".to_string()));
+            }
+            match choice.message.content {
+                Some(text) if !text.is_empty() => Ok((text, usage)),
+                _ => Err(LlmError::Empty),
+            }
+        })
+    }
+
+    fn generate_stream<'a>(&'a self, history: &'a [Content]) -> DeltaStream<'a> {
+        let connect = async move {
+            let body = ChatRequest {
+                model: &self.config.model,
+                messages: to_messages(history),
+                temperature: self.config.temperature,
+                stream: true,
+                stream_options: Some(StreamOptions {
+                    include_usage: true,
+                }),
+            };
+            retry::send(&self.config.retry, &self.config.base_url, || {
+                self.post("chat/completions").json(&body)
+            })
+            .await
+        };
+        stream::connect_then_decode(connect, parse_stream_chunk)
+    }
+
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<f32>, LlmError>> {
+        Box::pin(async move {
+            let body = EmbeddingRequest {
+                model: &self.config.embedding_model,
+                input: text,
+            };
+            let response = retry::send(&self.config.retry, &self.config.base_url, || {
+                self.post("embeddings").json(&body)
+            })
+            .await?;
+            let data: EmbeddingResponse = response
+                .json()
+                .await
+                .map_err(|e| LlmError::Decode(e.to_string()))?;
+
+            data.data
+                .into_iter()
+                .next()
+                .map(|d| d.embedding)
+                .ok_or(LlmError::Empty)
+        })
+    }
+
+    fn count_tokens<'a>(&'a self, history: &'a [Content]) -> BoxFuture<'a, Result<u32, LlmError>> {
+        // No counting endpoint in this API.
+        Box::pin(async move { Ok(estimate_tokens(history)) })
+    }
+}
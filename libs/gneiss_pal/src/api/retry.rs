@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The shared retry loop of the HTTP providers.
+//!
+//! Transport failures, `429` and `5xx` are retried with exponential backoff
+//! (or the server's `Retry-After`, if it sends one). `401` is handed back at
+//! once as `LlmError::Auth`, so the provider can refresh its credentials.
+//! Anything else fails immediately.
+
+use super::LlmError;
+use log::{error, info, warn};
+use reqwest::{RequestBuilder, Response, StatusCode, header};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total tries, including the first.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    /// Caps both the exponential backoff and `Retry-After`.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// The wait after failed attempt number `attempt` (starting at 1).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Sends the request made by `build` until it succeeds, fails for good, or
+/// the policy runs out of attempts. `build` is called once per attempt.
+pub(crate) async fn send(
+    policy: &RetryPolicy,
+    label: &str,
+    mut build: impl FnMut() -> RequestBuilder,
+) -> Result<Response, LlmError> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        info!("Transmitting to {} (Attempt {})...", label, attempt);
+
+        let (err, retry_after) = match build().send().await {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) if response.status() == StatusCode::UNAUTHORIZED => {
+                let body = response.text().await.unwrap_or_default();
+                return Err(LlmError::Auth(body));
+            }
+            Ok(response) => {
+                let status = response.status().as_u16();
+                let retry_after = response
+                    .headers()
+                    .get(header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim().parse::<u64>().ok())
+                    .map(Duration::from_secs);
+                let body = response.text().await.unwrap_or_default();
+                (LlmError::Http { status, body }, retry_after)
+            }
+            Err(e) => (LlmError::Transport(e.to_string()), None),
+        };
+
+        if !err.is_retryable() || attempt >= policy.max_attempts {
+            error!("Hull Breach ({}): {}", label, err);
+            return Err(err);
+        }
+        let wait = retry_after
+            .unwrap_or_else(|| policy.backoff(attempt))
+            .min(policy.max_backoff);
+        warn!("{} faltered ({}). Retrying in {:?}...", label, err, wait);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(60), Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,351 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Streaming generation.
+//!
+//! Providers stream either server-sent events (`text/event-stream`) or a
+//! chunked JSON body: a top-level array, or newline-delimited objects. The
+//! decoders here turn raw bytes into whole frames no matter where the
+//! network happens to cut them; each provider then turns frames into
+//! `Delta`s.
+
+use super::{LlmError, UsageMetadata};
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use reqwest::{Response, header};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+
+/// One increment of a streamed answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Delta {
+    /// More answer text, to be appended to what came before.
+    Text(String),
+    /// A piece of a function call. Fragments with the same `index` belong
+    /// to the same call; `arguments` are concatenated in order.
+    FunctionCall {
+        index: u32,
+        id: Option<String>,
+        name: Option<String>,
+        arguments: String,
+    },
+    /// Final token accounting. Comes last, if at all.
+    Usage(UsageMetadata),
+}
+
+pub type DeltaStream<'a> = Pin<Box<dyn Stream<Item = Result<Delta, LlmError>> + Send + 'a>>;
+
+/// Drains `stream`, handing every delta to `on_delta`, and returns the full
+/// text with the final usage. Stops at the first error.
+pub async fn collect_stream(
+    mut stream: DeltaStream<'_>,
+    mut on_delta: impl FnMut(&Delta),
+) -> Result<(String, Option<UsageMetadata>), LlmError> {
+    let mut text = String::new();
+    let mut usage = None;
+    let mut called = false;
+    while let Some(delta) = stream.next().await {
+        let delta = delta?;
+        on_delta(&delta);
+        match delta {
+            Delta::Text(t) => text.push_str(&t),
+            Delta::FunctionCall { .. } => called = true,
+            Delta::Usage(u) => usage = Some(u),
+        }
+    }
+    if text.is_empty() && !called {
+        return Err(LlmError::Empty);
+    }
+    Ok((text, usage))
+}
+
+/// Splits a `text/event-stream` body into the `data` of each event.
+/// Multi-line data is joined with `\n`; comments and other fields are
+/// dropped.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buf: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds bytes in; returns every event they complete.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buf.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buf.iter().position(|&b| b == b'\n') {
+            let mut line: Vec<u8> = self.buf.drain(..=pos).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            // Whole lines only, so a multi-byte character is never cut.
+            let line = String::from_utf8_lossy(&line);
+            if line.is_empty() {
+                events.extend(self.dispatch());
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((&line, ""));
+            if field == "data" {
+                self.data
+                    .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+        events
+    }
+
+    /// Ends the body; returns an event the server did not terminate.
+    pub fn finish(&mut self) -> Option<String> {
+        if !self.buf.is_empty() {
+            let mut rest = std::mem::take(&mut self.buf);
+            rest.push(b'\n');
+            // A trailing partial line is still a line.
+            if let Some(event) = self.push(&rest).pop() {
+                return Some(event);
+            }
+        }
+        self.dispatch()
+    }
+
+    fn dispatch(&mut self) -> Option<String> {
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(std::mem::take(&mut self.data).join("\n"))
+    }
+}
+
+/// Splits a chunked JSON body into its top-level objects, whether they sit
+/// in an array (`[{..},{..}]`) or follow each other (`{..}\n{..}`).
+#[derive(Debug, Default)]
+pub struct JsonChunkDecoder {
+    buf: Vec<u8>,
+    /// How far `buf` has been scanned.
+    pos: usize,
+    /// Where the current object began, if inside one.
+    start: Option<usize>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl JsonChunkDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds bytes in; returns every object they complete.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buf.extend_from_slice(bytes);
+        let mut objects = Vec::new();
+        while self.pos < self.buf.len() {
+            let b = self.buf[self.pos];
+            self.pos += 1;
+
+            if self.start.is_none() {
+                // Between objects: skip `[`, `,`, `]` and whitespace.
+                if b == b'{' {
+                    self.start = Some(self.pos - 1);
+                    self.depth = 1;
+                }
+                continue;
+            }
+            if self.in_string {
+                match b {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match b {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        let start = self.start.take().unwrap_or(0);
+                        objects
+                            .push(String::from_utf8_lossy(&self.buf[start..self.pos]).into_owned());
+                        self.buf.drain(..self.pos);
+                        self.pos = 0;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if self.start.is_none() {
+            self.buf.clear();
+            self.pos = 0;
+        }
+        objects
+    }
+
+    /// True if the body ended inside an object.
+    pub fn is_incomplete(&self) -> bool {
+        self.start.is_some()
+    }
+}
+
+/// Picks a decoder from the response's `Content-Type`.
+enum FrameDecoder {
+    Sse(SseDecoder),
+    Json(JsonChunkDecoder),
+}
+
+impl FrameDecoder {
+    fn for_response(response: &Response) -> Self {
+        let is_sse = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("event-stream"));
+        if is_sse {
+            FrameDecoder::Sse(SseDecoder::new())
+        } else {
+            FrameDecoder::Json(JsonChunkDecoder::new())
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        match self {
+            FrameDecoder::Sse(d) => d.push(bytes),
+            FrameDecoder::Json(d) => d.push(bytes),
+        }
+    }
+
+    fn finish(&mut self) -> Result<Option<String>, LlmError> {
+        match self {
+            FrameDecoder::Sse(d) => Ok(d.finish()),
+            FrameDecoder::Json(d) if d.is_incomplete() => Err(LlmError::Decode(
+                "Stream ended inside a JSON object".to_string(),
+            )),
+            FrameDecoder::Json(_) => Ok(None),
+        }
+    }
+}
+
+struct Frames<P> {
+    body: BoxStream<'static, reqwest::Result<Vec<u8>>>,
+    decoder: FrameDecoder,
+    parse: P,
+    pending: VecDeque<Result<Delta, LlmError>>,
+    done: bool,
+}
+
+impl<P: FnMut(&str) -> Result<Vec<Delta>, LlmError>> Frames<P> {
+    fn queue(&mut self, frame: &str) {
+        match (self.parse)(frame) {
+            Ok(deltas) => self.pending.extend(deltas.into_iter().map(Ok)),
+            Err(e) => self.pending.push_back(Err(e)),
+        }
+    }
+}
+
+/// Turns a streaming response into deltas. `parse` maps one frame to the
+/// deltas it carries. The stream ends after the first error.
+pub(crate) fn decode<P>(response: Response, parse: P) -> DeltaStream<'static>
+where
+    P: FnMut(&str) -> Result<Vec<Delta>, LlmError> + Send + 'static,
+{
+    let frames = Frames {
+        decoder: FrameDecoder::for_response(&response),
+        body: response
+            .bytes_stream()
+            .map(|chunk| chunk.map(|b| b.to_vec()))
+            .boxed(),
+        parse,
+        pending: VecDeque::new(),
+        done: false,
+    };
+    Box::pin(stream::unfold(frames, |mut s| async move {
+        loop {
+            if let Some(item) = s.pending.pop_front() {
+                if item.is_err() {
+                    s.pending.clear();
+                    s.done = true;
+                }
+                return Some((item, s));
+            }
+            if s.done {
+                return None;
+            }
+            match s.body.next().await {
+                Some(Ok(bytes)) => {
+                    for frame in s.decoder.push(&bytes) {
+                        s.queue(&frame);
+                    }
+                }
+                Some(Err(e)) => s.pending.push_back(Err(LlmError::Transport(e.to_string()))),
+                None => {
+                    match s.decoder.finish() {
+                        Ok(Some(frame)) => s.queue(&frame),
+                        Ok(None) => {}
+                        Err(e) => s.pending.push_back(Err(e)),
+                    }
+                    s.done = true;
+                }
+            }
+        }
+    }))
+}
+
+/// Sends the request with `connect`, then decodes the response. A failed
+/// request becomes the stream's only item.
+pub(crate) fn connect_then_decode<'a, F, P>(connect: F, parse: P) -> DeltaStream<'a>
+where
+    F: Future<Output = Result<Response, LlmError>> + Send + 'a,
+    P: FnMut(&str) -> Result<Vec<Delta>, LlmError> + Send + 'static,
+{
+    Box::pin(
+        stream::once(async move {
+            match connect.await {
+                Ok(response) => decode(response, parse),
+                Err(e) => Box::pin(stream::iter([Err(e)])) as DeltaStream<'static>,
+            }
+        })
+        .flatten(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_ignores_comments_and_other_fields() {
+        let mut d = SseDecoder::new();
+        let events = d.push(b": keep-alive\nevent: message\nid: 7\ndata: a\ndata:b\n\n");
+        assert_eq!(events, vec!["a\nb".to_string()]);
+        assert_eq!(d.finish(), None);
+    }
+
+    #[test]
+    fn test_json_chunks_without_array() {
+        let mut d = JsonChunkDecoder::new();
+        let objects = d.push(b"{\"a\":\"}\"}\n{\"b\":[1,{}]}\n");
+        assert_eq!(objects, vec![r#"{"a":"}"}"#, r#"{"b":[1,{}]}"#]);
+        assert!(!d.is_incomplete());
+    }
+}
@@ -0,0 +1,396 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Gemini on Vertex AI.
+
+use super::retry::{self, RetryPolicy};
+use super::stream::{self, Delta, DeltaStream};
+use super::{BoxFuture, Content, LlmError, LlmProvider, UsageMetadata};
+use log::{error, info, warn};
+use reqwest::{Client, ClientBuilder, Response};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const VERTEX_PROJECT: &str = "unauploads-1769528906";
+
+#[derive(Serialize)]
+struct GenerateContentRequest<'a> {
+    contents: &'a [Content],
+    #[serde(rename = "generationConfig")]
+    generation_config: GenerationConfig,
+}
+
+#[derive(Serialize)]
+struct GenerationConfig {
+    temperature: f32,
+}
+
+#[derive(Serialize)]
+struct CountTokensRequest<'a> {
+    contents: &'a [Content],
+}
+
+#[derive(Deserialize)]
+struct CountTokensResponse {
+    #[serde(rename = "totalTokens")]
+    total_tokens: u32,
+}
+
+// One response, or one chunk of a streamed one.
+#[derive(Deserialize, Debug)]
+struct GenerateContentResponse {
+    candidates: Option<Vec<Candidate>>,
+    #[serde(rename = "promptFeedback")]
+    prompt_feedback: Option<PromptFeedback>,
+    #[serde(rename = "usageMetadata")]
+    usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Candidate {
+    content: Option<ContentResponse>,
+    #[serde(rename = "finishReason")]
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ContentResponse {
+    parts: Vec<PartResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PartResponse {
+    text: Option<String>,
+    #[serde(rename = "functionCall")]
+    function_call: Option<FunctionCallResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FunctionCallResponse {
+    name: String,
+    #[serde(default)]
+    args: serde_json::Value,
+}
+
+#[derive(Deserialize, Debug)]
+struct PromptFeedback {
+    #[serde(rename = "blockReason")]
+    block_reason: Option<String>,
+}
+
+#[derive(Serialize)]
+struct EmbedContentRequest {
+    instances: Vec<EmbedContentInstance>,
+}
+
+#[derive(Serialize)]
+struct EmbedContentInstance {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct EmbedContentResponse {
+    predictions: Option<Vec<EmbedPrediction>>,
+}
+
+#[derive(Deserialize)]
+struct EmbedPrediction {
+    embeddings: EmbedValues,
+}
+
+#[derive(Deserialize)]
+struct EmbedValues {
+    values: Vec<f32>,
+}
+
+/// Where the bearer token comes from.
+#[derive(Debug, Clone)]
+pub enum TokenSource {
+    /// `gcloud auth application-default print-access-token`, refreshed on 401.
+    Gcloud,
+    /// A fixed token (tests, or one minted elsewhere).
+    Static(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct VertexConfig {
+    pub model: String,
+    pub generate_url: String,
+    pub stream_url: String,
+    pub count_tokens_url: String,
+    pub embed_url: String,
+    pub token: TokenSource,
+    pub temperature: f32,
+    pub retry: RetryPolicy,
+}
+
+impl Default for VertexConfig {
+    fn default() -> Self {
+        // Hardcode to Experimental as requested
+        let model = "gemini-3.1-pro-preview".to_string();
+        // Pure Vertex URL (No API key appended)
+        let base = format!(
+            "https://aiplatform.googleapis.com/v1beta1/projects/{}/locations/global/publishers/google/models/{}",
+            VERTEX_PROJECT, model
+        );
+        Self {
+            generate_url: format!("{}:generateContent", base),
+            stream_url: format!("{}:streamGenerateContent?alt=sse", base),
+            count_tokens_url: format!("{}:countTokens", base),
+            embed_url: format!(
+                "https://us-central1-aiplatform.googleapis.com/v1beta1/projects/{}/locations/us-central1/publishers/google/models/text-embedding-004:predict",
+                VERTEX_PROJECT
+            ),
+            model,
+            token: TokenSource::Gcloud,
+            temperature: 0.4,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+pub struct VertexGemini {
+    client: Client,
+    config: VertexConfig,
+    /// Fetched on first use, replaced on 401.
+    token: Mutex<Option<String>>,
+}
+
+impl VertexGemini {
+    pub fn new() -> Result<Self, String> {
+        Self::with_config(VertexConfig::default())
+    }
+
+    pub fn with_config(config: VertexConfig) -> Result<Self, String> {
+        let client = ClientBuilder::new()
+            .timeout(Duration::from_secs(300))
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+        info!("System Ignited. Target: {} (Vertex API)", config.model);
+
+        Ok(Self {
+            client,
+            config,
+            token: Mutex::new(None),
+        })
+    }
+
+    pub fn fetch_token() -> Result<String, String> {
+        info!("Executing gcloud ADC token fetch...");
+        let output = Command::new("gcloud")
+            .args(["auth", "application-default", "print-access-token"])
+            .output()
+            .map_err(|e| {
+                error!("gcloud execution failed: {}", e);
+                format!("Failed to execute gcloud for token: {}", e)
+            })?;
+
+        if !output.status.success() {
+            let err_msg = String::from_utf8_lossy(&output.stderr);
+            error!("gcloud ADC failed: {}", err_msg);
+            return Err(
+                "Failed to retrieve gcloud access token. Ensure gcloud ADC is configured."
+                    .to_string(),
+            );
+        }
+
+        String::from_utf8(output.stdout)
+            .map(|s| s.trim().to_string())
+            .map_err(|_| {
+                error!("Invalid UTF-8 in gcloud token");
+                "Invalid UTF-8 in gcloud token".to_string()
+            })
+    }
+
+    fn token(&self) -> Result<String, LlmError> {
+        if let Some(token) = self.token.lock().unwrap().clone() {
+            return Ok(token);
+        }
+        self.refresh_token()
+    }
+
+    fn refresh_token(&self) -> Result<String, LlmError> {
+        let token = match &self.config.token {
+            TokenSource::Gcloud => Self::fetch_token().map_err(LlmError::Auth)?,
+            TokenSource::Static(token) => token.clone(),
+        };
+        *self.token.lock().unwrap() = Some(token.clone());
+        Ok(token)
+    }
+
+    /// Posts `body` under the retry policy, refreshing the token once on 401.
+    async fn post<T: Serialize + Sync>(&self, url: &str, body: &T) -> Result<Response, LlmError> {
+        let mut refreshed = false;
+        loop {
+            let token = self.token()?;
+            let sent = retry::send(&self.config.retry, "Neural Core", || {
+                self.client.post(url).bearer_auth(&token).json(body)
+            })
+            .await;
+            match sent {
+                Err(LlmError::Auth(_)) if !refreshed => {
+                    warn!("401 Unauthorized detected. Initiating Lazarus Protocol...");
+                    info!("Refreshing GCloud Token (Lazarus Protocol)...");
+                    self.refresh_token()?;
+                    refreshed = true;
+                }
+                Err(LlmError::Auth(body)) => {
+                    return Err(LlmError::Auth(format!(
+                        "Failed after token refresh: {}",
+                        body
+                    )));
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+/// Maps one streamed chunk to deltas. Gemini sends whole function calls, so
+/// `next_call` only numbers them. Usage is cumulative; only the last chunk's
+/// (the one with a finish reason) is reported.
+fn parse_stream_chunk(frame: &str, next_call: &mut u32) -> Result<Vec<Delta>, LlmError> {
+    let data: GenerateContentResponse =
+        serde_json::from_str(frame).map_err(|e| LlmError::Decode(e.to_string()))?;
+
+    if let Some(reason) = data.prompt_feedback.and_then(|f| f.block_reason) {
+        return Err(LlmError::Blocked(reason));
+    }
+
+    let mut deltas = Vec::new();
+    let mut finished = false;
+    if let Some(first) = data.candidates.and_then(|c| c.into_iter().next()) {
+        for part in first.content.map(|c| c.parts).unwrap_or_default() {
+            if let Some(text) = part.text.filter(|t| !t.is_empty()) {
+                deltas.push(Delta::Text(text));
+            }
+            if let Some(call) = part.function_call {
+                deltas.push(Delta::FunctionCall {
+                    index: *next_call,
+                    id: None,
+                    name: Some(call.name),
+                    arguments: call.args.to_string(),
+                });
+                *next_call += 1;
+            }
+        }
+        match first.finish_reason.as_deref() {
+            Some("SAFETY") => return Err(LlmError::Blocked("SAFETY".to_string())),
+            Some(_) => finished = true,
+            None => {}
+        }
+    }
+    if finished && let Some(usage) = data.usage_metadata {
+        deltas.push(Delta::Usage(usage));
+    }
+    Ok(deltas)
+}
+
+async fn decode<T: serde::de::DeserializeOwned>(response: Response) -> Result<T, LlmError> {
+    response
+        .json()
+        .await
+        .map_err(|e| LlmError::Decode(e.to_string()))
+}
+
+impl LlmProvider for VertexGemini {
+    fn name(&self) -> String {
+        format!("vertex:{}", self.config.model)
+    }
+
+    fn generate<'a>(
+        &'a self,
+        history: &'a [Content],
+    ) -> BoxFuture<'a, Result<(String, Option<UsageMetadata>), LlmError>> {
+        Box::pin(async move {
+            let request_body = GenerateContentRequest {
+                contents: history,
+                generation_config: GenerationConfig {
+                    temperature: self.config.temperature,
+                },
+            };
+            let response = self.post(&self.config.generate_url, &request_body).await?;
+            let data: GenerateContentResponse = decode(response).await?;
+
+            if let Some(reason) = data.prompt_feedback.and_then(|f| f.block_reason) {
+                return Err(LlmError::Blocked(reason));
+            }
+
+            if let Some(content) = data
+                .candidates
+                .as_ref()
+                .and_then(|c| c.first())
+                .and_then(|first| first.content.as_ref())
+            {
+                let full_text: String = content
+                    .parts
+                    .iter()
+                    .filter_map(|p| p.text.as_deref())
+                    .collect();
+                return Ok((full_text, data.usage_metadata));
+            }
+
+            Err(LlmError::Empty)
+        })
+    }
+
+    fn generate_stream<'a>(&'a self, history: &'a [Content]) -> DeltaStream<'a> {
+        let connect = async move {
+            let request_body = GenerateContentRequest {
+                contents: history,
+                generation_config: GenerationConfig {
+                    temperature: self.config.temperature,
+                },
+            };
+            self.post(&self.config.stream_url, &request_body).await
+        };
+        let mut next_call = 0;
+        stream::connect_then_decode(connect, move |frame| {
+            parse_stream_chunk(frame, &mut next_call)
+        })
+    }
+
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<f32>, LlmError>> {
+        Box::pin(async move {
+            let request_body = EmbedContentRequest {
+                instances: vec![EmbedContentInstance {
+                    content: text.to_string(),
+                }],
+            };
+            let response = self.post(&self.config.embed_url, &request_body).await?;
+            let data: EmbedContentResponse = decode(response).await?;
+
+            data.predictions
+                .and_then(|p| p.into_iter().next())
+                .map(|first| first.embeddings.values)
+                .ok_or(LlmError::Empty)
+        })
+    }
+
+    fn count_tokens<'a>(&'a self, history: &'a [Content]) -> BoxFuture<'a, Result<u32, LlmError>> {
+        Box::pin(async move {
+            let request_body = CountTokensRequest { contents: history };
+            let response = self
+                .post(&self.config.count_tokens_url, &request_body)
+                .await?;
+            let data: CountTokensResponse = decode(response).await?;
+            Ok(data.total_tokens)
+        })
+    }
+}
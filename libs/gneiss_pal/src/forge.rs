@@ -14,8 +14,385 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use octocrab::Octocrab;
+//! The Forge: GitHub, through octocrab.
+//!
+//! Every call goes through octocrab's REST routes and comes back as one of
+//! the types below, so callers never see octocrab models or base64.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use octocrab::{Octocrab, Page};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::env;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForgeError {
+    /// No token, or GitHub refused it (401/403).
+    Auth(String),
+    /// 404: the repository, path, ref or number does not exist.
+    NotFound(String),
+    /// 409/422: the request clashed with the repository's state, e.g. a
+    /// branch that already exists or a stale file sha.
+    Conflict(String),
+    /// Any other error status.
+    Api { status: u16, message: String },
+    /// The request never got an answer.
+    Transport(String),
+    /// The answer was not what we expected.
+    Decode(String),
+}
+
+impl fmt::Display for ForgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForgeError::Auth(m) => write!(f, "Forge authentication failed: {}", m),
+            ForgeError::NotFound(m) => write!(f, "Not found: {}", m),
+            ForgeError::Conflict(m) => write!(f, "Conflict: {}", m),
+            ForgeError::Api { status, message } => {
+                write!(f, "GitHub error {}: {}", status, message)
+            }
+            ForgeError::Transport(m) => write!(f, "Forge transport failure: {}", m),
+            ForgeError::Decode(m) => write!(f, "Failed to decode forge response: {}", m),
+        }
+    }
+}
+
+impl std::error::Error for ForgeError {}
+
+impl From<octocrab::Error> for ForgeError {
+    fn from(e: octocrab::Error) -> Self {
+        match e {
+            octocrab::Error::GitHub { source, .. } => {
+                let message = source.message.clone();
+                match source.status_code.as_u16() {
+                    401 | 403 => ForgeError::Auth(message),
+                    404 => ForgeError::NotFound(message),
+                    409 | 422 => ForgeError::Conflict(message),
+                    status => ForgeError::Api { status, message },
+                }
+            }
+            octocrab::Error::Serde { source, .. } => ForgeError::Decode(source.to_string()),
+            octocrab::Error::Json { source, .. } => ForgeError::Decode(source.to_string()),
+            other => ForgeError::Transport(other.to_string()),
+        }
+    }
+}
+
+/// A file, decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileContent {
+    pub path: String,
+    /// Blob sha; pass it back to `commit_file` to update the file.
+    pub sha: String,
+    pub bytes: Vec<u8>,
+}
+
+impl FileContent {
+    /// The content as text, unless it is binary.
+    pub fn text(&self) -> Option<&str> {
+        std::str::from_utf8(&self.bytes).ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Blob,
+    Tree,
+    /// A submodule.
+    Commit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeEntry {
+    pub path: String,
+    pub mode: String,
+    pub kind: EntryKind,
+    pub sha: String,
+    /// Blobs only.
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tree {
+    pub sha: String,
+    pub entries: Vec<TreeEntry>,
+    /// GitHub stopped listing early (very large trees).
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch {
+    pub name: String,
+    pub sha: String,
+}
+
+/// A create or update through the contents API.
+#[derive(Debug, Clone)]
+pub struct CommitFile<'a> {
+    pub path: &'a str,
+    pub content: &'a [u8],
+    pub message: &'a str,
+    /// Defaults to the repository's default branch.
+    pub branch: Option<&'a str>,
+    /// Sha of the blob being replaced. Looked up when `None`.
+    pub sha: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitResult {
+    pub commit_sha: String,
+    pub content_sha: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ItemState {
+    #[default]
+    Open,
+    Closed,
+    All,
+}
+
+impl ItemState {
+    fn as_str(self) -> &'static str {
+        match self {
+            ItemState::Open => "open",
+            ItemState::Closed => "closed",
+            ItemState::All => "all",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub body: String,
+    pub state: String,
+    pub draft: bool,
+    /// Branch names.
+    pub head: String,
+    pub base: String,
+    pub author: String,
+    pub html_url: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NewPullRequest<'a> {
+    pub title: &'a str,
+    pub head: &'a str,
+    pub base: &'a str,
+    pub body: &'a str,
+    pub draft: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub body: String,
+    pub state: String,
+    pub labels: Vec<String>,
+    pub author: String,
+    pub html_url: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NewIssue<'a> {
+    pub title: &'a str,
+    pub body: &'a str,
+    pub labels: &'a [&'a str],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub id: u64,
+    pub body: String,
+    pub author: String,
+    pub html_url: String,
+}
+
+// --- Wire formats (only the fields we read) ---
+
+#[derive(Deserialize)]
+struct RawUser {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct RawContent {
+    path: String,
+    sha: String,
+    #[serde(rename = "type")]
+    kind: String,
+    content: Option<String>,
+    encoding: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawTree {
+    sha: String,
+    tree: Vec<RawTreeEntry>,
+    #[serde(default)]
+    truncated: bool,
+}
+
+#[derive(Deserialize)]
+struct RawTreeEntry {
+    path: String,
+    mode: String,
+    #[serde(rename = "type")]
+    kind: String,
+    sha: String,
+    size: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct RawRef {
+    #[serde(rename = "ref")]
+    name: String,
+    object: RawObject,
+}
+
+#[derive(Deserialize)]
+struct RawObject {
+    sha: String,
+}
+
+#[derive(Serialize)]
+struct CreateRef<'a> {
+    #[serde(rename = "ref")]
+    name: String,
+    sha: &'a str,
+}
+
+#[derive(Serialize)]
+struct PutContent<'a> {
+    message: &'a str,
+    content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    branch: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct PutContentResponse {
+    content: RawContentRef,
+    commit: RawObject,
+}
+
+#[derive(Deserialize)]
+struct RawContentRef {
+    path: String,
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct RawBranchRef {
+    #[serde(rename = "ref")]
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct RawPull {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    head: RawBranchRef,
+    base: RawBranchRef,
+    user: RawUser,
+    html_url: String,
+}
+
+impl From<RawPull> for PullRequest {
+    fn from(p: RawPull) -> Self {
+        PullRequest {
+            number: p.number,
+            title: p.title,
+            body: p.body.unwrap_or_default(),
+            state: p.state,
+            draft: p.draft,
+            head: p.head.name,
+            base: p.base.name,
+            author: p.user.login,
+            html_url: p.html_url,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CreatePull<'a> {
+    title: &'a str,
+    head: &'a str,
+    base: &'a str,
+    body: &'a str,
+    draft: bool,
+}
+
+#[derive(Deserialize)]
+struct RawLabel {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct RawIssue {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    labels: Vec<RawLabel>,
+    user: RawUser,
+    html_url: String,
+    /// Present when the "issue" is really a pull request.
+    pull_request: Option<serde_json::Value>,
+}
+
+impl From<RawIssue> for Issue {
+    fn from(i: RawIssue) -> Self {
+        Issue {
+            number: i.number,
+            title: i.title,
+            body: i.body.unwrap_or_default(),
+            state: i.state,
+            labels: i.labels.into_iter().map(|l| l.name).collect(),
+            author: i.user.login,
+            html_url: i.html_url,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CreateIssue<'a> {
+    title: &'a str,
+    body: &'a str,
+    labels: &'a [&'a str],
+}
+
+#[derive(Serialize)]
+struct CreateComment<'a> {
+    body: &'a str,
+}
+
+#[derive(Deserialize)]
+struct RawComment {
+    id: u64,
+    body: Option<String>,
+    user: RawUser,
+    html_url: String,
+}
+
+#[derive(Serialize)]
+struct ListParams {
+    state: &'static str,
+    per_page: u8,
+}
 
 pub struct ForgeClient {
     pub inner: Octocrab,
@@ -24,14 +401,39 @@ pub struct ForgeClient {
 impl ForgeClient {
     pub fn new() -> Result<Self, String> {
         let token = env::var("GITHUB_TOKEN").map_err(|_| "GITHUB_TOKEN not set".to_string())?;
-        let instance = Octocrab::builder()
-            .personal_token(token)
+        Self::with_config(&token, None).map_err(|e| e.to_string())
+    }
+
+    /// A client for `base_uri` (GitHub Enterprise, or a test server)
+    /// instead of api.github.com.
+    pub fn with_config(token: &str, base_uri: Option<&str>) -> Result<Self, ForgeError> {
+        let mut builder = Octocrab::builder().personal_token(token.to_string());
+        if let Some(uri) = base_uri {
+            builder = builder
+                .base_uri(uri)
+                .map_err(|e| ForgeError::Transport(format!("Invalid base URI: {}", e)))?;
+        }
+        let instance = builder
             .build()
-            .map_err(|e| format!("Failed to build Octocrab: {}", e))?;
+            .map_err(|e| ForgeError::Transport(format!("Failed to build Octocrab: {}", e)))?;
 
         Ok(Self { inner: instance })
     }
 
+    async fn get<T: DeserializeOwned>(&self, route: &str) -> Result<T, ForgeError> {
+        Ok(self.inner.get(route, None::<&()>).await?)
+    }
+
+    /// Every item of a list route, following `Link: rel="next"` page by page.
+    async fn get_all<T: DeserializeOwned>(
+        &self,
+        route: String,
+        params: &ListParams,
+    ) -> Result<Vec<T>, ForgeError> {
+        let first: Page<T> = self.inner.get(route, Some(params)).await?;
+        Ok(self.inner.all_pages(first).await?)
+    }
+
     pub async fn get_user_info(&self) -> Result<String, String> {
         match self.inner.current().user().await {
             Ok(user) => Ok(format!("Logged in as: {}", user.login)),
@@ -50,6 +452,45 @@ impl ForgeClient {
             .map_err(|e| format!("Failed to list repos: {}", e))
     }
 
+    /// A file's decoded content at `git_ref` (default branch if `None`).
+    pub async fn get_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        git_ref: Option<&str>,
+    ) -> Result<FileContent, ForgeError> {
+        let mut route = format!("/repos/{}/{}/contents/{}", owner, repo, encode_path(path));
+        if let Some(r) = git_ref {
+            route = format!("{}?ref={}", route, encode_path(r));
+        }
+        // A directory comes back as an array and fails to decode here.
+        let raw: RawContent = self.get(&route).await.map_err(|e| match e {
+            ForgeError::Decode(_) => ForgeError::Decode(format!("{} is not a file", path)),
+            other => other,
+        })?;
+        if raw.kind != "file" {
+            return Err(ForgeError::Decode(format!("{} is a {}", path, raw.kind)));
+        }
+        let bytes = match (raw.encoding.as_deref(), raw.content) {
+            (Some("base64"), Some(content)) => base64_decode(&content)?,
+            (Some("none"), _) | (_, None) => {
+                // Over 1 MB the contents API sends no content.
+                return Err(ForgeError::Decode(format!(
+                    "{} is too large to fetch inline",
+                    path
+                )));
+            }
+            (_, Some(content)) => content.into_bytes(),
+        };
+        Ok(FileContent {
+            path: raw.path,
+            sha: raw.sha,
+            bytes,
+        })
+    }
+
+    /// A file as text. Binary files are an error.
     pub async fn get_file_content(
         &self,
         owner: &str,
@@ -57,54 +498,269 @@ impl ForgeClient {
         path: &str,
         branch: Option<&str>,
     ) -> Result<String, String> {
-        let repo_handler = self.inner.repos(owner, repo);
-        let mut builder = repo_handler.get_content().path(path);
+        let file = self
+            .get_file(owner, repo, path, branch)
+            .await
+            .map_err(|e| format!("Failed to fetch file content: {}", e))?;
+        file.text()
+            .map(str::to_string)
+            .ok_or_else(|| format!("{} is binary ({} bytes)", path, file.bytes.len()))
+    }
 
-        if let Some(b) = branch {
-            builder = builder.r#ref(b);
+    /// The tree at `git_ref` (a branch, tag or sha).
+    pub async fn list_tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+        recursive: bool,
+    ) -> Result<Tree, ForgeError> {
+        let mut route = format!(
+            "/repos/{}/{}/git/trees/{}",
+            owner,
+            repo,
+            encode_path(git_ref)
+        );
+        if recursive {
+            route.push_str("?recursive=1");
         }
+        let raw: RawTree = self.get(&route).await?;
+        let entries = raw
+            .tree
+            .into_iter()
+            .map(|e| {
+                let kind = match e.kind.as_str() {
+                    "blob" => EntryKind::Blob,
+                    "tree" => EntryKind::Tree,
+                    "commit" => EntryKind::Commit,
+                    other => {
+                        return Err(ForgeError::Decode(format!(
+                            "Unknown tree entry type {}",
+                            other
+                        )));
+                    }
+                };
+                Ok(TreeEntry {
+                    path: e.path,
+                    mode: e.mode,
+                    kind,
+                    sha: e.sha,
+                    size: e.size,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Tree {
+            sha: raw.sha,
+            entries,
+            truncated: raw.truncated,
+        })
+    }
 
-        let content_items = builder
-            .send()
-            .await
-            .map_err(|e| format!("Failed to fetch file content: {}", e))?;
+    /// Where branch `name` points.
+    pub async fn get_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        name: &str,
+    ) -> Result<Branch, ForgeError> {
+        let route = format!(
+            "/repos/{}/{}/git/ref/heads/{}",
+            owner,
+            repo,
+            encode_path(name)
+        );
+        let raw: RawRef = self.get(&route).await?;
+        Ok(Branch {
+            name: strip_heads(&raw.name),
+            sha: raw.object.sha,
+        })
+    }
+
+    /// Creates branch `name` at the tip of branch `from`.
+    pub async fn create_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        name: &str,
+        from: &str,
+    ) -> Result<Branch, ForgeError> {
+        let base = self.get_branch(owner, repo, from).await?;
+        let body = CreateRef {
+            name: format!("refs/heads/{}", name),
+            sha: &base.sha,
+        };
+        let raw: RawRef = self
+            .inner
+            .post(format!("/repos/{}/{}/git/refs", owner, repo), Some(&body))
+            .await?;
+        Ok(Branch {
+            name: strip_heads(&raw.name),
+            sha: raw.object.sha,
+        })
+    }
 
-        // Octocrab's get_content returns a ContentItems struct which can contain multiple items (directory listing)
-        // or a single file. For this method, we expect a single file.
-        // We iterate and decode.
+    /// Creates or updates one file in a single commit.
+    pub async fn commit_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        file: CommitFile<'_>,
+    ) -> Result<CommitResult, ForgeError> {
+        let existing;
+        let sha = match file.sha {
+            Some(sha) => Some(sha),
+            None => {
+                existing = match self.get_file(owner, repo, file.path, file.branch).await {
+                    Ok(current) => Some(current.sha),
+                    Err(ForgeError::NotFound(_)) => None,
+                    Err(e) => return Err(e),
+                };
+                existing.as_deref()
+            }
+        };
+        let body = PutContent {
+            message: file.message,
+            content: STANDARD.encode(file.content),
+            branch: file.branch,
+            sha,
+        };
+        let route = format!(
+            "/repos/{}/{}/contents/{}",
+            owner,
+            repo,
+            encode_path(file.path)
+        );
+        let raw: PutContentResponse = self.inner.put(route, Some(&body)).await?;
+        Ok(CommitResult {
+            commit_sha: raw.commit.sha,
+            content_sha: raw.content.sha,
+            path: raw.content.path,
+        })
+    }
 
-        if content_items.items.is_empty() {
-            return Err("File not found or empty".to_string());
-        }
+    /// Every pull request in `state`, across all pages.
+    pub async fn list_pull_requests(
+        &self,
+        owner: &str,
+        repo: &str,
+        state: ItemState,
+    ) -> Result<Vec<PullRequest>, ForgeError> {
+        let params = ListParams {
+            state: state.as_str(),
+            per_page: 100,
+        };
+        let raw: Vec<RawPull> = self
+            .get_all(format!("/repos/{}/{}/pulls", owner, repo), &params)
+            .await?;
+        Ok(raw.into_iter().map(PullRequest::from).collect())
+    }
+
+    pub async fn open_pull_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr: NewPullRequest<'_>,
+    ) -> Result<PullRequest, ForgeError> {
+        let body = CreatePull {
+            title: pr.title,
+            head: pr.head,
+            base: pr.base,
+            body: pr.body,
+            draft: pr.draft,
+        };
+        let raw: RawPull = self
+            .inner
+            .post(format!("/repos/{}/{}/pulls", owner, repo), Some(&body))
+            .await?;
+        Ok(raw.into())
+    }
+
+    /// Comments on the conversation of pull request (or issue) `number`.
+    pub async fn comment_on_pull_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        body: &str,
+    ) -> Result<Comment, ForgeError> {
+        let route = format!("/repos/{}/{}/issues/{}/comments", owner, repo, number);
+        let raw: RawComment = self
+            .inner
+            .post(route, Some(&CreateComment { body }))
+            .await?;
+        Ok(Comment {
+            id: raw.id,
+            body: raw.body.unwrap_or_default(),
+            author: raw.user.login,
+            html_url: raw.html_url,
+        })
+    }
+
+    /// Every issue in `state`, across all pages. Issues only; GitHub lists
+    /// pull requests here too, and they are dropped.
+    pub async fn list_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        state: ItemState,
+    ) -> Result<Vec<Issue>, ForgeError> {
+        let params = ListParams {
+            state: state.as_str(),
+            per_page: 100,
+        };
+        let raw: Vec<RawIssue> = self
+            .get_all(format!("/repos/{}/{}/issues", owner, repo), &params)
+            .await?;
+        Ok(raw
+            .into_iter()
+            .filter(|i| i.pull_request.is_none())
+            .map(Issue::from)
+            .collect())
+    }
 
-        let first_item = &content_items.items[0];
-
-        // Ensure it has content to decode
-        if let Some(encoded_content) = &first_item.content {
-            // Octocrab handles base64 decoding internally if we use the right helper,
-            // but here we might get the raw base64 string with newlines.
-            // simpler: ContentItems usually has a helper or we just use the raw bytes if available.
-            // Actually, octocrab 0.38 models: repo::Content has `content`, `encoding` (usually "base64").
-            // The `decoded_content()` method on `Content` is available in recent versions.
-
-            // Let's try the safest manual decode to avoid version ambiguities if `decoded_content` is missing
-            // or just use the `content` string (newlines stripped) and decode.
-            let clean_b64 = encoded_content.replace("\n", "");
-            // NOTE: base64 removed from dependencies. Assuming text content or relying on raw response if needed.
-            // For now, since base64 crate was removed per directive, we cannot decode without it.
-            // We will return the raw content if it looks like text, or a placeholder.
-            // Actually, octocrab might handle it? No, we used base64 explicitly.
-            // Given the strict directive to remove base64, we will assume the content is either raw or we can't decode it here.
-            // However, `get_file_content` is mostly unused in current Vein flow.
-            // We will attempt to return it as-is or error if base64 is strictly required.
-            // Simplest fix to compile: Just return the cleaned string (it's base64 encoded, but the method returns String).
-            // The caller will get base64. If they need to decode, they can't without the crate.
-            // But since `get_file_content` is unused (as per previous warnings), this is safe for compilation.
-            Ok(clean_b64)
-        } else {
-            Err("No content in file response".to_string())
+    pub async fn create_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        issue: NewIssue<'_>,
+    ) -> Result<Issue, ForgeError> {
+        let body = CreateIssue {
+            title: issue.title,
+            body: issue.body,
+            labels: issue.labels,
+        };
+        let raw: RawIssue = self
+            .inner
+            .post(format!("/repos/{}/{}/issues", owner, repo), Some(&body))
+            .await?;
+        Ok(raw.into())
+    }
+}
+
+fn strip_heads(name: &str) -> String {
+    name.strip_prefix("refs/heads/").unwrap_or(name).to_string()
+}
+
+/// Percent-encodes a repository path, keeping its slashes.
+fn encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
         }
     }
+    out
+}
+
+/// Decodes GitHub's base64, which is wrapped at 60 columns.
+fn base64_decode(text: &str) -> Result<Vec<u8>, ForgeError> {
+    let unwrapped: String = text.split_ascii_whitespace().collect();
+    STANDARD
+        .decode(unwrapped)
+        .map_err(|e| ForgeError::Decode(format!("Invalid base64 content: {}", e)))
 }
 
 #[cfg(test)]
@@ -118,4 +774,30 @@ mod tests {
         let client = ForgeClient::new();
         assert!(client.is_err());
     }
+
+    #[test]
+    fn test_base64_round_trip() {
+        for input in [
+            &b""[..],
+            b"f",
+            b"fo",
+            b"foo",
+            b"foob",
+            b"fooba",
+            b"foobar",
+            &[0, 255, 128, 7],
+        ] {
+            let encoded = STANDARD.encode(input);
+            assert_eq!(base64_decode(&encoded).unwrap(), input);
+        }
+        // GitHub wraps lines.
+        assert_eq!(base64_decode("Zm9v\nYmFy\n").unwrap(), b"foobar");
+        assert!(base64_decode("Zm9v!").is_err());
+    }
+
+    #[test]
+    fn test_encode_path_keeps_slashes() {
+        assert_eq!(encode_path("docs/My File.md"), "docs/My%20File.md");
+        assert_eq!(encode_path("feature/x"), "feature/x");
+    }
 }
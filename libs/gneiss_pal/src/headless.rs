@@ -0,0 +1,302 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A front end without a screen, for tests.
+//!
+//! `Headless` stands where the GTK front end stands: it holds the receiving
+//! end of the `GuiUpdate` channel and feeds `Event`s to an `AppHandler`
+//! through the same one-at-a-time loop lumen runs on Tokio. Every update is
+//! kept with the time it arrived, so a test can wait for the one it expects
+//! or read the whole timeline afterwards.
+//!
+//! ```ignore
+//! let (gui_tx, gui_rx) = async_channel::unbounded();
+//! let ui = Headless::start(MyHandler::new(gui_tx), gui_rx);
+//! ui.send(Event::NavSelect(2));
+//! expect_update!(ui, GuiUpdate::ConsoleLog(s) if s.contains("index 2"), Duration::from_secs(1));
+//! assert_eq!(ui.view().await.active_nav_index, 2);
+//! ```
+
+use crate::types::{AppHandler, DashboardState, Event, GuiUpdate};
+use async_channel::{Receiver, Sender};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// One update, and when it arrived (since `Headless::start`).
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub at: Duration,
+    pub update: GuiUpdate,
+}
+
+/// An expected update that did not come.
+#[derive(Debug)]
+pub struct Missing {
+    pub waited: Duration,
+    /// What arrived instead, since the last matched expectation.
+    pub seen: Vec<GuiUpdate>,
+}
+
+impl fmt::Display for Missing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nothing matched within {:?}", self.waited)?;
+        if self.seen.is_empty() {
+            return write!(f, "; no updates arrived");
+        }
+        write!(f, "; saw:")?;
+        for update in &self.seen {
+            write!(f, "\n  {:?}", update)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Missing {}
+
+/// Waits for an update matching a pattern, panicking with the timeline if it
+/// does not arrive in time. Evaluates to the update.
+///
+/// `expect_update!(ui, GuiUpdate::StreamEnd, Duration::from_secs(1))`
+#[macro_export]
+macro_rules! expect_update {
+    ($ui:expr, $pattern:pat $(if $guard:expr)?, $within:expr $(,)?) => {
+        match $ui
+            .expect_update(|u| matches!(u, $pattern $(if $guard)?), $within)
+            .await
+        {
+            Ok(update) => update,
+            Err(missing) => panic!("expected {}: {}", stringify!($pattern), missing),
+        }
+    };
+}
+
+/// How long `settle` waits for the handler to catch up.
+pub const SETTLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct Headless<H> {
+    handler: Arc<Mutex<H>>,
+    events: Sender<Event>,
+    updates: Receiver<GuiUpdate>,
+    timeline: Mutex<Vec<Recorded>>,
+    /// Where the next `expect_update` starts looking.
+    cursor: Mutex<usize>,
+    sent: AtomicUsize,
+    handled: Arc<AtomicUsize>,
+    started: Instant,
+    /// Taken once the loop is found dead, to read its panic.
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl<H: AppHandler + Send> Headless<H> {
+    /// Runs `handler` on the current Tokio runtime. `updates` is the
+    /// receiving end of the channel the handler was given.
+    pub fn start(handler: H, updates: Receiver<GuiUpdate>) -> Self {
+        let handler = Arc::new(Mutex::new(handler));
+        let (events, event_rx) = async_channel::unbounded::<Event>();
+        let handled = Arc::new(AtomicUsize::new(0));
+
+        let loop_handler = handler.clone();
+        let loop_handled = handled.clone();
+        let task = tokio::spawn(async move {
+            while let Ok(event) = event_rx.recv().await {
+                loop_handler.lock().unwrap().handle_event(event);
+                loop_handled.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        Self {
+            handler,
+            events,
+            updates,
+            timeline: Mutex::new(Vec::new()),
+            cursor: Mutex::new(0),
+            sent: AtomicUsize::new(0),
+            handled,
+            started: Instant::now(),
+            task: Mutex::new(Some(task)),
+        }
+    }
+
+    /// Queues `event` as if the user had just done it.
+    pub fn send(&self, event: Event) {
+        self.sent.fetch_add(1, Ordering::SeqCst);
+        let _ = self.events.try_send(event);
+    }
+
+    pub fn script(&self, events: impl IntoIterator<Item = Event>) {
+        for event in events {
+            self.send(event);
+        }
+    }
+
+    /// Waits until the handler has taken every event sent so far. If the
+    /// handler panicked, so does this, with the handler's panic; if it takes
+    /// longer than `SETTLE_TIMEOUT`, this panics too.
+    pub async fn settle(&self) {
+        let deadline = tokio::time::Instant::now() + SETTLE_TIMEOUT;
+        while self.handled.load(Ordering::SeqCst) < self.sent.load(Ordering::SeqCst) {
+            let dead = {
+                let mut task = self.task.lock().unwrap();
+                match task.as_ref() {
+                    Some(t) if !t.is_finished() => None,
+                    _ => Some(task.take()),
+                }
+            };
+            match dead {
+                None => {}
+                Some(Some(task)) => match task.await {
+                    Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+                    _ => panic!("The handler loop stopped with events unhandled"),
+                },
+                Some(None) => panic!("The handler loop has already died"),
+            }
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "The handler took {} of {} events within {:?}",
+                self.handled.load(Ordering::SeqCst),
+                self.sent.load(Ordering::SeqCst),
+                SETTLE_TIMEOUT
+            );
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+    }
+
+    /// The handler's view, once it has caught up.
+    pub async fn view(&self) -> DashboardState {
+        self.with(|h| h.view()).await
+    }
+
+    /// Runs `f` on the handler, once it has caught up.
+    pub async fn with<R>(&self, f: impl FnOnce(&H) -> R) -> R {
+        self.settle().await;
+        f(&self.handler.lock().unwrap())
+    }
+}
+
+impl<H> Headless<H> {
+    fn push(&self, update: GuiUpdate) {
+        let at = self.started.elapsed();
+        self.timeline.lock().unwrap().push(Recorded { at, update });
+    }
+
+    /// Moves whatever has arrived into the timeline.
+    fn drain(&self) {
+        while let Ok(update) = self.updates.try_recv() {
+            self.push(update);
+        }
+    }
+
+    /// Every update so far, oldest first.
+    pub fn timeline(&self) -> Vec<Recorded> {
+        self.drain();
+        self.timeline.lock().unwrap().clone()
+    }
+
+    pub fn updates(&self) -> Vec<GuiUpdate> {
+        self.timeline().into_iter().map(|r| r.update).collect()
+    }
+
+    /// The console as the front end would show it: every `ConsoleLog` since
+    /// the last `ClearConsole`.
+    pub fn console(&self) -> String {
+        let mut console = String::new();
+        for update in self.updates() {
+            match update {
+                GuiUpdate::ConsoleLog(text) => console.push_str(&text),
+                GuiUpdate::ClearConsole => console.clear(),
+                _ => {}
+            }
+        }
+        console
+    }
+
+    /// Finds the first update after the last one matched by an earlier call
+    /// that satisfies `wanted`, waiting up to `within` for it to arrive.
+    /// Expectations therefore also check order.
+    pub async fn expect_update(
+        &self,
+        wanted: impl Fn(&GuiUpdate) -> bool,
+        within: Duration,
+    ) -> Result<GuiUpdate, Missing> {
+        let deadline = tokio::time::Instant::now() + within;
+        self.drain();
+        loop {
+            {
+                let timeline = self.timeline.lock().unwrap();
+                let mut cursor = self.cursor.lock().unwrap();
+                if let Some(offset) = timeline[*cursor..].iter().position(|r| wanted(&r.update)) {
+                    *cursor += offset + 1;
+                    return Ok(timeline[*cursor - 1].update.clone());
+                }
+            }
+            match tokio::time::timeout_at(deadline, self.updates.recv()).await {
+                Ok(Ok(update)) => self.push(update),
+                // Timed out, or every sender is gone and nothing more can come.
+                _ => {
+                    let timeline = self.timeline.lock().unwrap();
+                    let cursor = *self.cursor.lock().unwrap();
+                    return Err(Missing {
+                        waited: within,
+                        seen: timeline[cursor..]
+                            .iter()
+                            .map(|r| r.update.clone())
+                            .collect(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Fails with the offending update if one matching `unwanted` arrives
+    /// within `within`, or has already arrived.
+    pub async fn expect_no_update(
+        &self,
+        unwanted: impl Fn(&GuiUpdate) -> bool,
+        within: Duration,
+    ) -> Result<(), GuiUpdate> {
+        let deadline = tokio::time::Instant::now() + within;
+        self.drain();
+        if let Some(r) = self
+            .timeline
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|r| unwanted(&r.update))
+        {
+            return Err(r.update.clone());
+        }
+        while let Ok(Ok(update)) = tokio::time::timeout_at(deadline, self.updates.recv()).await {
+            let hit = unwanted(&update);
+            self.push(update.clone());
+            if hit {
+                return Err(update);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<H> Drop for Headless<H> {
+    fn drop(&mut self) {
+        let task = self.task.get_mut().unwrap_or_else(|e| e.into_inner());
+        if let Some(task) = task.take() {
+            task.abort();
+        }
+    }
+}
@@ -19,10 +19,12 @@
 
 pub mod api;
 pub mod forge;
+pub mod headless;
 pub mod io;
 pub mod paths;
 pub mod persistence;
 pub mod shard;
+pub mod supervisor;
 pub mod types;
 
 // Re-export types so consumers see them at the root
@@ -14,40 +14,538 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+//! Where UnaOS keeps things on the host.
+//!
+//! Each location is resolved on its own, first match wins:
+//!
+//! 1. its environment variable (`UNA_DATA_DIR`, `UNA_LOG_DIR`, ...)
+//! 2. the `paths.toml` config file
+//! 3. `UNA_HOME`, which holds everything in one tree (`UNA_ROOT` is the old
+//!    name and still works)
+//! 4. the platform: XDG base directories on Linux, `~/Library` on macOS
+//!
+//! The cortex and the vault live inside the data directory unless told
+//! otherwise. The config file is `paths.toml` in the config directory, or
+//! whatever `UNA_PATHS_FILE` names. It takes the lowercase location names
+//! as keys; relative values are relative to the file, `~/` is the home.
+//! `config` is the one key it rejects, since the file is found through the
+//! config directory: move that with `UNA_CONFIG_DIR`.
+//!
+//! ```toml
+//! data = "/srv/una"
+//! logs = "~/logs/una"
+//! ```
+//!
+//! Older releases kept config in `<data>/principia` and logs in
+//! `<data>/logs`. `UnaPaths::awaken` moves them to their new homes; see
+//! `Layout::migrate_legacy`.
+//!
+//! `UnaPaths` resolves once per process. If that fails (no `HOME`, or a
+//! broken `paths.toml`) the error is logged and the accessors fall back to
+//! the defaults; `awaken` reports the error itself.
+
+use serde::Deserialize;
 use std::env;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-/// The Spatial Truth of UnaOS.
-pub struct UnaPaths;
+const APP_DIR: &str = "unaos";
 
-impl UnaPaths {
-    pub fn root() -> PathBuf {
-        if let Ok(root) = env::var("UNA_ROOT") {
-            return PathBuf::from(root);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathKind {
+    Data,
+    Cortex,
+    Vault,
+    Config,
+    Logs,
+    Cache,
+}
+
+impl PathKind {
+    pub const ALL: [PathKind; 6] = [
+        PathKind::Data,
+        PathKind::Cortex,
+        PathKind::Vault,
+        PathKind::Config,
+        PathKind::Logs,
+        PathKind::Cache,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PathKind::Data => "data",
+            PathKind::Cortex => "cortex",
+            PathKind::Vault => "vault",
+            PathKind::Config => "config",
+            PathKind::Logs => "logs",
+            PathKind::Cache => "cache",
+        }
+    }
+
+    /// The variable that overrides this location.
+    pub fn env_var(self) -> &'static str {
+        match self {
+            PathKind::Data => "UNA_DATA_DIR",
+            PathKind::Cortex => "UNA_CORTEX_DIR",
+            PathKind::Vault => "UNA_VAULT_DIR",
+            PathKind::Config => "UNA_CONFIG_DIR",
+            PathKind::Logs => "UNA_LOG_DIR",
+            PathKind::Cache => "UNA_CACHE_DIR",
+        }
+    }
+
+    /// Where it sits under `UNA_HOME`, which keeps the original single-tree
+    /// layout.
+    fn under_home(self, home: &Path) -> PathBuf {
+        match self {
+            PathKind::Data => home.to_path_buf(),
+            PathKind::Config => home.join("principia"),
+            other => home.join(other.name()),
+        }
+    }
+}
+
+/// Which rule picked a location.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Env(&'static str),
+    ConfigFile(PathBuf),
+    UnaHome,
+    Xdg(&'static str),
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Env(var) => write!(f, "${}", var),
+            Source::ConfigFile(path) => write!(f, "{}", path.display()),
+            Source::UnaHome => write!(f, "$UNA_HOME"),
+            Source::Xdg(var) => write!(f, "${}", var),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub kind: PathKind,
+    pub path: PathBuf,
+    pub source: Source,
+}
+
+/// Something `Layout::validate` found wrong with a location.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathIssue {
+    NotADirectory {
+        kind: PathKind,
+        path: PathBuf,
+    },
+    Unwritable {
+        kind: PathKind,
+        path: PathBuf,
+        reason: String,
+    },
+    /// Group or world writable.
+    Permissive {
+        kind: PathKind,
+        path: PathBuf,
+        mode: u32,
+    },
+    /// Not on the data directory's filesystem, so files cannot be renamed
+    /// across and one snapshot will not catch everything.
+    CrossDevice {
+        kind: PathKind,
+        path: PathBuf,
+    },
+}
+
+impl PathIssue {
+    /// Whether UnaOS cannot run with this.
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            PathIssue::NotADirectory { .. } | PathIssue::Unwritable { .. }
+        )
+    }
+}
+
+impl fmt::Display for PathIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathIssue::NotADirectory { kind, path } => {
+                write!(f, "{} ({}) is not a directory", kind.name(), path.display())
+            }
+            PathIssue::Unwritable { kind, path, reason } => write!(
+                f,
+                "{} ({}) is not writable: {}",
+                kind.name(),
+                path.display(),
+                reason
+            ),
+            PathIssue::Permissive { kind, path, mode } => write!(
+                f,
+                "{} ({}) is writable by others (mode {:o})",
+                kind.name(),
+                path.display(),
+                mode
+            ),
+            PathIssue::CrossDevice { kind, path } => write!(
+                f,
+                "{} ({}) is on a different filesystem than data",
+                kind.name(),
+                path.display()
+            ),
         }
+    }
+}
 
-        let mut vault = PathBuf::from(
-            env::var("HOME").expect("CRITICAL: HOME environment variable missing. Engine stalled."),
-        );
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct PathsFile {
+    /// Only read to reject it with a useful message.
+    config: Option<PathBuf>,
+    data: Option<PathBuf>,
+    cortex: Option<PathBuf>,
+    vault: Option<PathBuf>,
+    logs: Option<PathBuf>,
+    cache: Option<PathBuf>,
+}
 
-        #[cfg(target_os = "macos")]
-        {
-            vault.push("Library/Application Support/unaos");
+impl PathsFile {
+    fn get(&self, kind: PathKind) -> Option<&PathBuf> {
+        match kind {
+            PathKind::Data => self.data.as_ref(),
+            PathKind::Cortex => self.cortex.as_ref(),
+            PathKind::Vault => self.vault.as_ref(),
+            // The file lives in the config directory; it cannot move it.
+            PathKind::Config => None,
+            PathKind::Logs => self.logs.as_ref(),
+            PathKind::Cache => self.cache.as_ref(),
         }
+    }
+}
+
+/// Every location, resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    locations: Vec<Location>,
+    /// The config file that was read, if one existed.
+    pub config_file: Option<PathBuf>,
+}
+
+impl Layout {
+    /// Resolves against the process environment.
+    pub fn from_env() -> Result<Self, String> {
+        Self::resolve(|name| env::var(name).ok())
+    }
+
+    /// Resolves with `var` standing in for the environment.
+    pub fn resolve(var: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        Self::resolve_with(var, true)
+    }
+
+    /// What is left when `from_env` fails: no config file, and the temp
+    /// directory standing in for a missing `HOME`. Cannot fail.
+    fn fallback() -> Self {
+        let var = |name: &str| match name {
+            "HOME" => env::var(name)
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or_else(|| Some(env::temp_dir().to_string_lossy().into_owned())),
+            _ => env::var(name).ok(),
+        };
+        Self::resolve_with(var, false).expect("Nothing left to fail without a file or a HOME")
+    }
+
+    fn resolve_with(var: impl Fn(&str) -> Option<String>, read_file: bool) -> Result<Self, String> {
+        let var = |name: &str| var(name).filter(|v| !v.is_empty());
+        let home = var("HOME").map(PathBuf::from);
+        let una_home = var("UNA_HOME")
+            .or_else(|| var("UNA_ROOT"))
+            .map(PathBuf::from);
+
+        let expand = |path: PathBuf| -> PathBuf {
+            match (path.strip_prefix("~"), &home) {
+                (Ok(rest), Some(home)) => home.join(rest),
+                _ => path,
+            }
+        };
+        // XDG says relative values are to be ignored.
+        let xdg = |name: &'static str| {
+            var(name)
+                .map(PathBuf::from)
+                .filter(|p| p.is_absolute())
+                .map(|p| (p, Source::Xdg(name)))
+        };
+        let need_home = || {
+            home.clone().ok_or_else(|| {
+                "CRITICAL: HOME environment variable missing. Engine stalled.".to_string()
+            })
+        };
+
+        // Where a location goes when nobody names it.
+        let fallback = |kind: PathKind, data: &Path| -> Result<(PathBuf, Source), String> {
+            match (kind, &una_home) {
+                (PathKind::Cortex | PathKind::Vault, _) => {
+                    Ok((data.join(kind.name()), Source::Default))
+                }
+                (_, Some(root)) => Ok((kind.under_home(root), Source::UnaHome)),
+                (_, None) => platform_default(kind, &xdg, need_home()?),
+            }
+        };
+
+        let config = match var(PathKind::Config.env_var()) {
+            Some(path) => (
+                expand(PathBuf::from(path)),
+                Source::Env(PathKind::Config.env_var()),
+            ),
+            None => fallback(PathKind::Config, Path::new(""))?,
+        };
+
+        let config_file = var("UNA_PATHS_FILE")
+            .map(|p| expand(PathBuf::from(p)))
+            .unwrap_or_else(|| config.0.join("paths.toml"));
+        let (file, config_file) = if read_file && config_file.exists() {
+            let text = fs::read_to_string(&config_file)
+                .map_err(|e| format!("{}: {}", config_file.display(), e))?;
+            let file: PathsFile =
+                toml::from_str(&text).map_err(|e| format!("{}: {}", config_file.display(), e))?;
+            if file.config.is_some() {
+                return Err(format!(
+                    "{}: `config` cannot be set in the file that is looked up in the config \
+                     directory; set UNA_CONFIG_DIR instead",
+                    config_file.display()
+                ));
+            }
+            (file, Some(config_file))
+        } else {
+            (PathsFile::default(), None)
+        };
+        let file_dir = config_file
+            .as_ref()
+            .and_then(|f| f.parent())
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
 
-        #[cfg(not(target_os = "macos"))]
-        {
-            // Linux / Default to XDG-ish structure
-            vault.push(".local/share/unaos");
+        let mut locations: Vec<Location> = Vec::with_capacity(PathKind::ALL.len());
+        for kind in PathKind::ALL {
+            let (path, source) = if kind == PathKind::Config {
+                config.clone()
+            } else if let Some(path) = var(kind.env_var()) {
+                (expand(PathBuf::from(path)), Source::Env(kind.env_var()))
+            } else if let (Some(path), Some(file)) = (file.get(kind), &config_file) {
+                (
+                    file_dir.join(expand(path.clone())),
+                    Source::ConfigFile(file.clone()),
+                )
+            } else {
+                // Data comes first in `ALL`, so it is already resolved here.
+                let data = locations
+                    .first()
+                    .map(|l| l.path.clone())
+                    .unwrap_or_default();
+                fallback(kind, &data)?
+            };
+            locations.push(Location { kind, path, source });
         }
 
-        vault
+        Ok(Self {
+            locations,
+            config_file,
+        })
+    }
+
+    pub fn get(&self, kind: PathKind) -> &Location {
+        &self.locations[PathKind::ALL.iter().position(|k| *k == kind).unwrap()]
+    }
+
+    pub fn path(&self, kind: PathKind) -> &Path {
+        &self.get(kind).path
+    }
+
+    pub fn locations(&self) -> &[Location] {
+        &self.locations
+    }
+
+    /// Creates every missing directory (private to the user) and checks that
+    /// each one can be written. Returns what is wrong; see
+    /// `PathIssue::is_fatal` for what matters.
+    pub fn validate(&self) -> Vec<PathIssue> {
+        let mut issues = Vec::new();
+        for location in &self.locations {
+            let (kind, path) = (location.kind, location.path.clone());
+            if path.exists() && !path.is_dir() {
+                issues.push(PathIssue::NotADirectory { kind, path });
+                continue;
+            }
+            if let Err(e) = create_private_dir(&path).and_then(|_| probe_write(&path)) {
+                issues.push(PathIssue::Unwritable {
+                    kind,
+                    path,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+            if let Some(mode) = permissive_mode(&path) {
+                issues.push(PathIssue::Permissive { kind, path, mode });
+                continue;
+            }
+            if kind != PathKind::Data && !same_device(&path, self.path(PathKind::Data)) {
+                issues.push(PathIssue::CrossDevice { kind, path });
+            }
+        }
+        issues
+    }
+
+    /// Moves config and logs out of the data directory, where older releases
+    /// kept them (`principia` and `logs`), unless the new location already
+    /// holds something. Returns what moved, as `(from, to)`.
+    pub fn migrate_legacy(&self) -> Vec<(PathBuf, PathBuf)> {
+        let data = self.path(PathKind::Data);
+        let mut moved = Vec::new();
+        for (kind, old_name) in [(PathKind::Config, "principia"), (PathKind::Logs, "logs")] {
+            let (from, to) = (data.join(old_name), self.path(kind).to_path_buf());
+            if !from.is_dir() || from == to || !is_missing_or_empty(&to) {
+                continue;
+            }
+            match move_dir(&from, &to) {
+                Ok(()) => {
+                    log::info!("Moved {} to {}", from.display(), to.display());
+                    moved.push((from, to));
+                }
+                Err(e) => log::warn!(
+                    "Could not move {} to {} ({}); move it by hand",
+                    from.display(),
+                    to.display(),
+                    e
+                ),
+            }
+        }
+        moved
+    }
+}
+
+/// Renames `from` to `to`, which is missing or an empty directory.
+fn move_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // An empty directory is in the way of the rename, not of the move.
+    if to.exists() {
+        fs::remove_dir(to)?;
+    }
+    fs::rename(from, to)
+}
+
+fn is_missing_or_empty(dir: &Path) -> bool {
+    match fs::read_dir(dir) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(_) => !dir.exists(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn platform_default(
+    kind: PathKind,
+    _xdg: &impl Fn(&'static str) -> Option<(PathBuf, Source)>,
+    home: PathBuf,
+) -> Result<(PathBuf, Source), String> {
+    let library = home.join("Library");
+    let support = library.join("Application Support").join(APP_DIR);
+    let path = match kind {
+        PathKind::Config => support.join("principia"),
+        PathKind::Logs => library.join("Logs").join(APP_DIR),
+        PathKind::Cache => library.join("Caches").join(APP_DIR),
+        _ => support,
+    };
+    Ok((path, Source::Default))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn platform_default(
+    kind: PathKind,
+    xdg: &impl Fn(&'static str) -> Option<(PathBuf, Source)>,
+    home: PathBuf,
+) -> Result<(PathBuf, Source), String> {
+    let (var, fallback, sub) = match kind {
+        PathKind::Config => ("XDG_CONFIG_HOME", ".config", None),
+        PathKind::Logs => ("XDG_STATE_HOME", ".local/state", Some("logs")),
+        PathKind::Cache => ("XDG_CACHE_HOME", ".cache", None),
+        _ => ("XDG_DATA_HOME", ".local/share", None),
+    };
+    let (base, source) = xdg(var).unwrap_or_else(|| (home.join(fallback), Source::Default));
+    let path = base.join(APP_DIR);
+    Ok((sub.map_or(path.clone(), |s| path.join(s)), source))
+}
+
+fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(path)
+}
+
+fn probe_write(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(format!(".una-probe-{}", std::process::id()));
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)
+}
+
+#[cfg(unix)]
+fn permissive_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(path).ok()?.permissions().mode() & 0o777;
+    (mode & 0o022 != 0).then_some(mode)
+}
+
+#[cfg(not(unix))]
+fn permissive_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn same_device(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev(),
+        _ => true,
+    }
+}
+
+#[cfg(not(unix))]
+fn same_device(_a: &Path, _b: &Path) -> bool {
+    true
+}
+
+/// The Spatial Truth of UnaOS.
+pub struct UnaPaths;
+
+impl UnaPaths {
+    fn layout() -> &'static Layout {
+        static LAYOUT: OnceLock<Layout> = OnceLock::new();
+        LAYOUT.get_or_init(|| {
+            Layout::from_env().unwrap_or_else(|e| {
+                log::error!("Path resolution failed, using the defaults: {}", e);
+                Layout::fallback()
+            })
+        })
+    }
+
+    /// The data directory, home of everything that is not config, logs or cache.
+    pub fn root() -> PathBuf {
+        Self::layout().path(PathKind::Data).to_path_buf()
     }
 
     /// The AI Cortex (Vein) - LLM Models and Subconscious
     pub fn cortex() -> PathBuf {
-        Self::root().join("cortex")
+        Self::layout().path(PathKind::Cortex).to_path_buf()
     }
 
     /// The Subconscious Vault (Raw Telemetry)
@@ -57,7 +555,7 @@ impl UnaPaths {
 
     /// The Memory Vault (UnaFS) - The Encrypted Block Storage
     pub fn vault() -> PathBuf {
-        Self::root().join("vault")
+        Self::layout().path(PathKind::Vault).to_path_buf()
     }
 
     /// The Primary Conscious Memory (Replaces lumen_storage)
@@ -67,30 +565,32 @@ impl UnaPaths {
 
     /// System Policy (Principia) - OS Configuration
     pub fn config() -> PathBuf {
-        Self::root().join("principia")
+        Self::layout().path(PathKind::Config).to_path_buf()
+    }
+
+    /// The telemetry vault.
+    pub fn logs() -> PathBuf {
+        Self::layout().path(PathKind::Logs).to_path_buf()
     }
 
-    /// Bootstraps the physical directory structure. Fails hard if the host rejects us.
+    pub fn cache() -> PathBuf {
+        Self::layout().path(PathKind::Cache).to_path_buf()
+    }
+
+    /// Bootstraps the physical directory structure, moving legacy config and
+    /// logs over first. Fails hard if the host rejects us.
     pub fn awaken() -> Result<(), String> {
-        let required_nodes = [
-            Self::cortex(),
-            Self::vault(),
-            Self::config(),
-            Self::root().join("logs"),
-        ];
-
-        for node in required_nodes {
-            if !node.exists() {
-                fs::create_dir_all(&node).map_err(|e| {
-                    format!(
-                        "CRITICAL: Failed to carve spatial anchor at {}: {}",
-                        node.display(),
-                        e
-                    )
-                })?;
+        let layout = Layout::from_env()?;
+        layout.migrate_legacy();
+        for issue in layout.validate() {
+            if issue.is_fatal() {
+                return Err(format!(
+                    "CRITICAL: Failed to carve spatial anchor: {}",
+                    issue
+                ));
             }
+            log::warn!("Spatial anchor: {}", issue);
         }
-
         Ok(())
     }
 }
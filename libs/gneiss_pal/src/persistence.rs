@@ -14,20 +14,202 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+//! The brain file: the saved conversation, a version stamp, and an
+//! append-only trail of every state it has been in.
+//!
+//! Layout, next to the brain file `history.json`:
+//!
+//! ```text
+//! history.json                     the current state
+//! revisions/history/00000001.json  the most recent saved states
+//! usage/<session>.jsonl            usage ledgers, one record per line
+//! directive.txt                    the active directive
+//! ```
+//!
+//! Revisions are never rewritten. Once there are more than `max_revisions`
+//! the oldest are deleted, so the numbers keep counting up.
+//!
+//! Every file is replaced by write-then-rename, so a crash leaves either the
+//! old contents or the new ones. Ledgers are the exception: each call is
+//! appended as one line, and a torn last line is skipped on load.
+
+use log::warn;
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
+use serde_json::{Value, json};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// How many revisions a brain keeps unless told otherwise.
+pub const DEFAULT_MAX_REVISIONS: usize = 200;
+
+/// Layout version of the brain file. Bump it together with a new entry in
+/// `MIGRATIONS`.
+pub const SCHEMA_VERSION: u32 = 2;
+
+type Migration = fn(Value) -> Result<Value, String>;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// `MIGRATIONS[n]` lifts a version `n + 1` document to version `n + 2`.
+const MIGRATIONS: &[Migration] = &[v1_to_v2];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SavedMessage {
     pub role: String, // "user" or "model"
     pub content: String,
     pub timestamp: Option<String>,
 }
 
+/// What the brain file holds.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BrainState {
+    pub schema_version: u32,
+    /// The revision this state was saved as; 0 for never.
+    pub revision: u64,
+    pub saved_at: Option<String>,
+    pub messages: Vec<SavedMessage>,
+}
+
+/// One entry of the history, without its messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RevisionInfo {
+    pub number: u64,
+    pub saved_at: Option<String>,
+    pub messages: usize,
+}
+
+/// How revision `to` differs from revision `from`. Conversations mostly grow
+/// at the end, so the diff is the shared head and tail plus whatever sits
+/// between them on either side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RevisionDiff {
+    pub from: u64,
+    pub to: u64,
+    pub common_prefix: usize,
+    pub common_suffix: usize,
+    /// In `from` only.
+    pub removed: Vec<SavedMessage>,
+    /// In `to` only.
+    pub added: Vec<SavedMessage>,
+}
+
+impl RevisionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+}
+
+/// Version 1 was the bare message array.
+fn v1_to_v2(doc: Value) -> Result<Value, String> {
+    match doc {
+        Value::Array(messages) => Ok(json!({
+            "schema_version": 2,
+            "revision": 0,
+            "saved_at": null,
+            "messages": messages,
+        })),
+        _ => Err("version 1 brain is not a message array".to_string()),
+    }
+}
+
+/// Brings a brain document of any known version up to `SCHEMA_VERSION`.
+pub fn migrate(mut doc: Value) -> Result<BrainState, String> {
+    // Version 1 carried no stamp at all.
+    let mut version = doc
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(1) as u32;
+    if version == 0 || version > SCHEMA_VERSION {
+        return Err(format!(
+            "brain schema version {} is not supported (this build reads up to {})",
+            version, SCHEMA_VERSION
+        ));
+    }
+    while version < SCHEMA_VERSION {
+        doc = MIGRATIONS[(version - 1) as usize](doc)
+            .map_err(|e| format!("migrating from version {}: {}", version, e))?;
+        version += 1;
+    }
+    serde_json::from_value(doc).map_err(|e| e.to_string())
+}
+
+fn parse_state(data: &str) -> Result<BrainState, String> {
+    migrate(serde_json::from_str(data).map_err(|e| e.to_string())?)
+}
+
+/// Writes through a sibling temp file and a rename, so readers see either the
+/// old file or the new one, never a torn write.
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("{} has no file name", path.display()))?;
+    // Unique per write, so two saves in one process never share a temp file.
+    static NEXT_TMP: AtomicU64 = AtomicU64::new(0);
+    let tmp = dir.join(format!(
+        ".{}.tmp-{}-{}",
+        name.to_string_lossy(),
+        std::process::id(),
+        NEXT_TMP.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let written = File::create(&tmp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("{}: {}", path.display(), e));
+    }
+    // Make the rename itself durable.
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// One model call, as billed (or, failing that, as estimated).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UsageRecord {
+    pub timestamp: String,
+    pub provider: String,
+    pub kind: String, // "generate" or "embed"
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    /// True if the provider reported nothing and the counts are our guess.
+    pub estimated: bool,
+}
+
+/// Every model call of one session.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct UsageLedger {
+    pub session: String,
+    pub records: Vec<UsageRecord>,
+}
+
+impl UsageLedger {
+    pub fn new(session: &str) -> Self {
+        Self {
+            session: session.to_string(),
+            records: Vec::new(),
+        }
+    }
+
+    /// (prompt, completion) tokens over the whole session.
+    pub fn totals(&self) -> (u64, u64) {
+        self.records.iter().fold((0, 0), |(p, c), r| {
+            (p + r.prompt_tokens as u64, c + r.completion_tokens as u64)
+        })
+    }
+}
+
 #[derive(Clone)]
 pub struct BrainManager {
     file_path: PathBuf,
+    max_revisions: usize,
 }
 
 impl BrainManager {
@@ -36,24 +218,272 @@ impl BrainManager {
             fs::create_dir_all(parent).expect("Failed to create data directory");
         }
 
-        Self { file_path }
+        Self {
+            file_path,
+            max_revisions: DEFAULT_MAX_REVISIONS,
+        }
+    }
+
+    /// Keeps only the newest `max` revisions (at least one).
+    pub fn with_max_revisions(mut self, max: usize) -> Self {
+        self.max_revisions = max.max(1);
+        self
+    }
+
+    /// Saves `messages` as a new revision and makes it the current state.
+    /// Returns the revision number; saving an unchanged conversation returns
+    /// the latest one without adding to the history.
+    pub fn save(&self, messages: &[SavedMessage]) -> Result<u64, String> {
+        let mut latest = self.latest_revision()?;
+
+        // A brain from before the history existed becomes its first entry.
+        if latest.is_none()
+            && let Ok(Some(old)) = self.load_current()
+            && !old.messages.is_empty()
+        {
+            self.write_revision(1, &old.messages, old.saved_at)?;
+            latest = Some(1);
+        }
+
+        if let Some(n) = latest
+            && self.load_revision(n)? == messages
+        {
+            return Ok(n);
+        }
+
+        let number = latest.map_or(1, |n| n + 1);
+        let state =
+            self.write_revision(number, messages, Some(chrono::Local::now().to_rfc3339()))?;
+        let json = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
+        write_atomic(&self.file_path, json.as_bytes())?;
+        self.prune_revisions();
+        Ok(number)
     }
 
-    pub fn save(&self, messages: &[SavedMessage]) {
-        if let Ok(json) = serde_json::to_string_pretty(messages) {
-            let _ = fs::write(&self.file_path, json);
+    /// Deletes the oldest revisions beyond `max_revisions`. The save has
+    /// already succeeded, so failures are only logged.
+    fn prune_revisions(&self) {
+        let numbers = match self.revision_numbers() {
+            Ok(numbers) => numbers,
+            Err(e) => {
+                warn!("Failed to list revisions for pruning: {}", e);
+                return;
+            }
+        };
+        let excess = numbers.len().saturating_sub(self.max_revisions);
+        for number in &numbers[..excess] {
+            if let Err(e) = fs::remove_file(self.revision_path(*number)) {
+                warn!("Failed to prune revision {}: {}", number, e);
+            }
         }
     }
 
+    /// The current conversation, or nothing if there is none or it cannot be
+    /// read at all. An unreadable brain file falls back to the newest revision.
     pub fn load(&self) -> Vec<SavedMessage> {
+        let current = match self.load_current() {
+            Ok(current) => current,
+            Err(e) => {
+                warn!("Brain file unreadable, falling back to history: {}", e);
+                None
+            }
+        };
+        let latest = self.latest_revision().unwrap_or_else(|e| {
+            warn!("Brain history unreadable: {}", e);
+            None
+        });
+
+        match (current, latest) {
+            // A save that wrote its revision but died before the swap.
+            (Some(state), Some(n)) if n > state.revision => {
+                self.load_revision(n).unwrap_or(state.messages)
+            }
+            (Some(state), _) => state.messages,
+            (None, Some(n)) => self.load_revision(n).unwrap_or_default(),
+            (None, None) => vec![],
+        }
+    }
+
+    /// The brain file, migrated. `None` if it does not exist.
+    fn load_current(&self) -> Result<Option<BrainState>, String> {
         if !self.file_path.exists() {
-            return vec![];
+            return Ok(None);
+        }
+        let data = fs::read_to_string(&self.file_path).map_err(|e| e.to_string())?;
+        parse_state(&data).map(Some)
+    }
+
+    /// Revisions live in `revisions/<stem>/` next to the brain file.
+    fn revisions_dir(&self) -> PathBuf {
+        let stem = self
+            .file_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "brain".to_string());
+        self.file_path
+            .parent()
+            .map(|p| p.join("revisions"))
+            .unwrap_or_else(|| PathBuf::from("revisions"))
+            .join(stem)
+    }
+
+    fn revision_path(&self, number: u64) -> PathBuf {
+        self.revisions_dir().join(format!("{:08}.json", number))
+    }
+
+    fn write_revision(
+        &self,
+        number: u64,
+        messages: &[SavedMessage],
+        saved_at: Option<String>,
+    ) -> Result<BrainState, String> {
+        let path = self.revision_path(number);
+        if path.exists() {
+            return Err(format!("revision {} already exists", number));
+        }
+        fs::create_dir_all(self.revisions_dir()).map_err(|e| e.to_string())?;
+        let state = BrainState {
+            schema_version: SCHEMA_VERSION,
+            revision: number,
+            saved_at,
+            messages: messages.to_vec(),
+        };
+        let json = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
+        write_atomic(&path, json.as_bytes())?;
+        Ok(state)
+    }
+
+    /// Revision numbers on disk, ascending. Leftover temp files are skipped.
+    fn revision_numbers(&self) -> Result<Vec<u64>, String> {
+        let entries = match fs::read_dir(self.revisions_dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut numbers: Vec<u64> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                name.to_str()?.strip_suffix(".json")?.parse().ok()
+            })
+            .collect();
+        numbers.sort_unstable();
+        Ok(numbers)
+    }
+
+    fn latest_revision(&self) -> Result<Option<u64>, String> {
+        Ok(self.revision_numbers()?.last().copied())
+    }
+
+    /// Every saved state, oldest first.
+    pub fn list_revisions(&self) -> Result<Vec<RevisionInfo>, String> {
+        self.revision_numbers()?
+            .into_iter()
+            .map(|number| {
+                let state = self.load_revision_state(number)?;
+                Ok(RevisionInfo {
+                    number,
+                    saved_at: state.saved_at,
+                    messages: state.messages.len(),
+                })
+            })
+            .collect()
+    }
+
+    fn load_revision_state(&self, number: u64) -> Result<BrainState, String> {
+        let data = fs::read_to_string(self.revision_path(number))
+            .map_err(|e| format!("revision {}: {}", number, e))?;
+        parse_state(&data).map_err(|e| format!("revision {}: {}", number, e))
+    }
+
+    pub fn load_revision(&self, number: u64) -> Result<Vec<SavedMessage>, String> {
+        self.load_revision_state(number).map(|state| state.messages)
+    }
+
+    pub fn diff_revisions(&self, from: u64, to: u64) -> Result<RevisionDiff, String> {
+        let old = self.load_revision(from)?;
+        let new = self.load_revision(to)?;
+
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        Ok(RevisionDiff {
+            from,
+            to,
+            common_prefix: prefix,
+            common_suffix: suffix,
+            removed: old[prefix..old.len() - suffix].to_vec(),
+            added: new[prefix..new.len() - suffix].to_vec(),
+        })
+    }
+
+    /// Ledgers live in `usage/<session>.jsonl` next to the history file.
+    fn ledger_path(&self, session: &str) -> PathBuf {
+        self.usage_dir().join(format!("{}.jsonl", session))
+    }
+
+    /// Where ledgers were kept as a single pretty-printed document.
+    fn legacy_ledger_path(&self, session: &str) -> PathBuf {
+        self.usage_dir().join(format!("{}.json", session))
+    }
+
+    fn usage_dir(&self) -> PathBuf {
+        self.file_path
+            .parent()
+            .map(|p| p.join("usage"))
+            .unwrap_or_else(|| PathBuf::from("usage"))
+    }
+
+    /// Replaces the saved ledger of `ledger.session` as a whole.
+    pub fn save_ledger(&self, ledger: &UsageLedger) -> Result<(), String> {
+        let path = self.ledger_path(&ledger.session);
+        fs::create_dir_all(self.usage_dir()).map_err(|e| e.to_string())?;
+        let mut lines = String::new();
+        for record in &ledger.records {
+            lines.push_str(&serde_json::to_string(record).map_err(|e| e.to_string())?);
+            lines.push('\n');
         }
-        if let Ok(data) = fs::read_to_string(&self.file_path) {
-            serde_json::from_str(&data).unwrap_or_default()
-        } else {
-            vec![]
+        write_atomic(&path, lines.as_bytes())?;
+        // Its records are in the new file now.
+        let _ = fs::remove_file(self.legacy_ledger_path(&ledger.session));
+        Ok(())
+    }
+
+    /// Adds one record to the saved ledger of `session`.
+    pub fn append_usage(&self, session: &str, record: &UsageRecord) -> Result<(), String> {
+        let path = self.ledger_path(session);
+        fs::create_dir_all(self.usage_dir()).map_err(|e| e.to_string())?;
+        let mut line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// The saved ledger of `session`, or an empty one.
+    pub fn load_ledger(&self, session: &str) -> UsageLedger {
+        let mut ledger: UsageLedger = fs::read_to_string(self.legacy_ledger_path(session))
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_else(|| UsageLedger::new(session));
+
+        if let Ok(data) = fs::read_to_string(self.ledger_path(session)) {
+            for line in data.lines().filter(|l| !l.trim().is_empty()) {
+                match serde_json::from_str(line) {
+                    Ok(record) => ledger.records.push(record),
+                    Err(e) => warn!("Skipping a damaged usage record of {}: {}", session, e),
+                }
+            }
         }
+        ledger
     }
 
     pub fn get_active_directive(&self) -> String {
@@ -66,4 +496,29 @@ impl BrainManager {
         }
         "Directive 055".to_string() // Default as per mission
     }
+
+    pub fn set_active_directive(&self, directive: &str) -> Result<(), String> {
+        let parent = self.file_path.parent().unwrap_or(Path::new("."));
+        write_atomic(&parent.join("directive.txt"), directive.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v1_array_migrates() {
+        let doc = json!([{"role": "user", "content": "hi", "timestamp": null}]);
+        let state = migrate(doc).unwrap();
+        assert_eq!(state.schema_version, SCHEMA_VERSION);
+        assert_eq!(state.revision, 0);
+        assert_eq!(state.messages[0].content, "hi");
+    }
+
+    #[test]
+    fn test_future_version_is_refused() {
+        let doc = json!({"schema_version": SCHEMA_VERSION + 1, "messages": []});
+        assert!(migrate(doc).unwrap_err().contains("not supported"));
+    }
 }
@@ -17,7 +17,7 @@
 // libs/gneiss_pal/src/shard.rs
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ShardRole {
     Root,    // Una-Prime (The Command Deck)
     Builder, // S9 (CI/CD)
@@ -35,6 +35,7 @@ pub enum ShardStatus {
     Paused,   // Yellow
     Error,    // Red
     Offline,  // Grey
+    Failed,   // Black (crash loop; the supervisor gave up)
 }
 
 #[derive(Debug, Clone)]
@@ -47,7 +48,8 @@ pub struct Shard {
     pub children: Vec<Shard>,
 }
 
-#[derive(Debug, Clone)]
+/// What a supervised shard prints, one JSON object per line, to say it is alive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Heartbeat {
     pub id: String,
     pub status: ShardStatus,
@@ -0,0 +1,495 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Keeps shard processes alive.
+//!
+//! The `Supervisor` launches every shard of a TOML manifest, reads the
+//! `Heartbeat` lines each one prints on stdout, and restarts the ones that
+//! crash or fall silent, backing off per `ShardRole`. A shard that crashes
+//! more often than its policy allows is given up on and reported as
+//! `ShardStatus::Failed`. Every status change goes out on the bus as
+//! `GuiUpdate::ShardStatusChanged`.
+//!
+//! ```toml
+//! heartbeat_timeout_ms = 5000
+//!
+//! [[shard]]
+//! id = "s9-mule"
+//! name = "S9-Mule"
+//! role = "Builder"
+//! command = "bin/s9"          # relative to the manifest
+//! args = ["--ci"]
+//! env = { RUST_LOG = "info" }
+//!
+//! [policy.Builder]
+//! initial_backoff_ms = 2000
+//! max_restarts = 3
+//! ```
+//!
+//! A shard exiting with status 0 is done and is not restarted.
+
+use crate::shard::{Heartbeat, Shard, ShardRole, ShardStatus};
+use crate::types::GuiUpdate;
+use async_channel::Sender;
+use log::{error, info, warn};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader, Lines};
+use tokio::process::{ChildStdout, Command};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// How a role is restarted after a crash.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestartPolicy {
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Crashes tolerated within `window`; one more is a crash loop.
+    pub max_restarts: u32,
+    pub window: Duration,
+}
+
+impl RestartPolicy {
+    pub fn for_role(role: &ShardRole) -> Self {
+        let (initial_ms, max_ms, max_restarts, window_s) = match role {
+            // The command deck must come back, and fast.
+            ShardRole::Root => (250, 10_000, 10, 60),
+            // Builds fail for real reasons; do not hammer the CI box.
+            ShardRole::Builder => (1_000, 60_000, 5, 600),
+            ShardRole::Storage => (500, 30_000, 5, 300),
+            // A crashing hardware probe usually needs a human.
+            ShardRole::Kernel => (2_000, 30_000, 3, 300),
+            ShardRole::Unknown => (1_000, 30_000, 3, 60),
+        };
+        Self {
+            initial_backoff: Duration::from_millis(initial_ms),
+            max_backoff: Duration::from_millis(max_ms),
+            max_restarts,
+            window: Duration::from_secs(window_s),
+        }
+    }
+
+    /// The wait before restart number `restart` (starting at 1).
+    pub fn backoff(&self, restart: u32) -> Duration {
+        let factor = 2u32.saturating_pow(restart.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// One `[[shard]]` of the manifest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardSpec {
+    pub id: String,
+    pub name: String,
+    pub role: ShardRole,
+    pub command: PathBuf,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub heartbeat_timeout: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub shards: Vec<ShardSpec>,
+    pub policies: HashMap<ShardRole, RestartPolicy>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawManifest {
+    #[serde(default = "default_heartbeat_timeout_ms")]
+    heartbeat_timeout_ms: u64,
+    #[serde(default)]
+    shard: Vec<RawShard>,
+    #[serde(default)]
+    policy: HashMap<ShardRole, RawPolicy>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawShard {
+    id: String,
+    name: Option<String>,
+    role: ShardRole,
+    command: PathBuf,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    heartbeat_timeout_ms: Option<u64>,
+}
+
+/// Overrides on top of `RestartPolicy::for_role`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPolicy {
+    initial_backoff_ms: Option<u64>,
+    max_backoff_ms: Option<u64>,
+    max_restarts: Option<u32>,
+    window_ms: Option<u64>,
+}
+
+fn default_heartbeat_timeout_ms() -> u64 {
+    5_000
+}
+
+impl Manifest {
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let raw: RawManifest = toml::from_str(text).map_err(|e| e.to_string())?;
+
+        let mut seen = HashSet::new();
+        let mut shards = Vec::with_capacity(raw.shard.len());
+        for s in raw.shard {
+            if !seen.insert(s.id.clone()) {
+                return Err(format!("shard '{}' is listed twice", s.id));
+            }
+            shards.push(ShardSpec {
+                name: s.name.unwrap_or_else(|| s.id.clone()),
+                id: s.id,
+                role: s.role,
+                command: s.command,
+                args: s.args,
+                env: s.env,
+                heartbeat_timeout: Duration::from_millis(
+                    s.heartbeat_timeout_ms.unwrap_or(raw.heartbeat_timeout_ms),
+                ),
+            });
+        }
+
+        let policies = raw
+            .policy
+            .into_iter()
+            .map(|(role, p)| {
+                let base = RestartPolicy::for_role(&role);
+                let policy = RestartPolicy {
+                    initial_backoff: p
+                        .initial_backoff_ms
+                        .map_or(base.initial_backoff, Duration::from_millis),
+                    max_backoff: p
+                        .max_backoff_ms
+                        .map_or(base.max_backoff, Duration::from_millis),
+                    max_restarts: p.max_restarts.unwrap_or(base.max_restarts),
+                    window: p.window_ms.map_or(base.window, Duration::from_millis),
+                };
+                (role, policy)
+            })
+            .collect();
+
+        Ok(Self { shards, policies })
+    }
+
+    /// Reads a manifest file. Commands given as relative paths are taken
+    /// relative to the manifest; bare names are left to `PATH`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut manifest =
+            Self::from_toml(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        for shard in &mut manifest.shards {
+            if shard.command.is_relative() && shard.command.components().count() > 1 {
+                shard.command = base.join(&shard.command);
+            }
+        }
+        Ok(manifest)
+    }
+
+    pub fn policy_for(&self, role: &ShardRole) -> RestartPolicy {
+        self.policies
+            .get(role)
+            .cloned()
+            .unwrap_or_else(|| RestartPolicy::for_role(role))
+    }
+}
+
+struct Tracked {
+    shard: Shard,
+    restarts: u32,
+}
+
+/// What the shard tasks share with the `Supervisor`.
+#[derive(Clone)]
+struct Shared {
+    shards: Arc<Mutex<HashMap<String, Tracked>>>,
+    bus: Sender<GuiUpdate>,
+}
+
+impl Shared {
+    /// Records `status` and announces it, unless nothing changed.
+    async fn set_status(&self, id: &str, status: ShardStatus) {
+        let changed = {
+            let mut shards = self.shards.lock().unwrap();
+            match shards.get_mut(id) {
+                Some(t) if t.shard.status != status => {
+                    t.shard.status = status.clone();
+                    true
+                }
+                _ => false,
+            }
+        };
+        if changed {
+            let _ = self
+                .bus
+                .send(GuiUpdate::ShardStatusChanged {
+                    id: id.to_string(),
+                    status,
+                })
+                .await;
+        }
+    }
+
+    async fn beat(&self, id: &str, beat: Heartbeat) {
+        if let Some(t) = self.shards.lock().unwrap().get_mut(id) {
+            t.shard.cpu_load = beat.cpu_load;
+        }
+        self.set_status(id, beat.status).await;
+    }
+
+    fn count_restart(&self, id: &str) {
+        if let Some(t) = self.shards.lock().unwrap().get_mut(id) {
+            t.restarts += 1;
+        }
+    }
+}
+
+/// How one run of a shard ended.
+enum Outcome {
+    Exited,
+    Crashed(String),
+    Stopped,
+}
+
+type StdoutLines = Lines<BufReader<ChildStdout>>;
+
+pub struct Supervisor {
+    order: Vec<String>,
+    shared: Shared,
+    stop: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Supervisor {
+    /// Launches every shard of `manifest` on the current Tokio runtime.
+    pub fn start(manifest: Manifest, bus: Sender<GuiUpdate>) -> Self {
+        let shards = manifest
+            .shards
+            .iter()
+            .map(|spec| {
+                let tracked = Tracked {
+                    shard: Shard::new(&spec.id, &spec.name, spec.role.clone()),
+                    restarts: 0,
+                };
+                (spec.id.clone(), tracked)
+            })
+            .collect();
+        let shared = Shared {
+            shards: Arc::new(Mutex::new(shards)),
+            bus,
+        };
+        let (stop, _) = watch::channel(false);
+
+        let tasks = manifest
+            .shards
+            .iter()
+            .map(|spec| {
+                let policy = manifest.policy_for(&spec.role);
+                tokio::spawn(supervise(
+                    spec.clone(),
+                    policy,
+                    shared.clone(),
+                    stop.subscribe(),
+                ))
+            })
+            .collect();
+
+        Self {
+            order: manifest.shards.iter().map(|s| s.id.clone()).collect(),
+            shared,
+            stop,
+            tasks,
+        }
+    }
+
+    pub fn status(&self, id: &str) -> Option<ShardStatus> {
+        let shards = self.shared.shards.lock().unwrap();
+        shards.get(id).map(|t| t.shard.status.clone())
+    }
+
+    /// How often `id` has been restarted so far.
+    pub fn restarts(&self, id: &str) -> Option<u32> {
+        let shards = self.shared.shards.lock().unwrap();
+        shards.get(id).map(|t| t.restarts)
+    }
+
+    /// Every shard as last seen, in manifest order.
+    pub fn shards(&self) -> Vec<Shard> {
+        let shards = self.shared.shards.lock().unwrap();
+        self.order
+            .iter()
+            .filter_map(|id| shards.get(id).map(|t| t.shard.clone()))
+            .collect()
+    }
+
+    /// Waits until no shard is left running: each has exited or failed.
+    pub async fn join(self) {
+        for task in self.tasks {
+            let _ = task.await;
+        }
+    }
+
+    /// Kills every shard and waits for them to go.
+    pub async fn shutdown(self) {
+        let _ = self.stop.send(true);
+        for task in self.tasks {
+            let _ = task.await;
+        }
+    }
+}
+
+/// Runs `spec` until it exits cleanly, crash-loops, or the supervisor stops.
+async fn supervise(
+    spec: ShardSpec,
+    policy: RestartPolicy,
+    shared: Shared,
+    mut stop: watch::Receiver<bool>,
+) {
+    let mut crashes: VecDeque<Instant> = VecDeque::new();
+    loop {
+        match run_once(&spec, &shared, &mut stop).await {
+            Outcome::Stopped => return,
+            Outcome::Exited => {
+                info!("SUPERVISOR :: {} exited cleanly", spec.id);
+                shared.set_status(&spec.id, ShardStatus::Offline).await;
+                return;
+            }
+            Outcome::Crashed(reason) => {
+                warn!("SUPERVISOR :: {} crashed: {}", spec.id, reason);
+                let now = Instant::now();
+                crashes.push_back(now);
+                while crashes
+                    .front()
+                    .is_some_and(|t| now.duration_since(*t) > policy.window)
+                {
+                    crashes.pop_front();
+                }
+
+                if crashes.len() as u32 > policy.max_restarts {
+                    error!(
+                        "SUPERVISOR :: {} crashed {} times within {:?}; giving up",
+                        spec.id,
+                        crashes.len(),
+                        policy.window
+                    );
+                    shared.set_status(&spec.id, ShardStatus::Failed).await;
+                    return;
+                }
+
+                shared.set_status(&spec.id, ShardStatus::Error).await;
+                tokio::select! {
+                    _ = tokio::time::sleep(policy.backoff(crashes.len() as u32)) => {}
+                    _ = stop.changed() => return,
+                }
+                shared.count_restart(&spec.id);
+            }
+        }
+    }
+}
+
+/// One life of the shard process.
+async fn run_once(spec: &ShardSpec, shared: &Shared, stop: &mut watch::Receiver<bool>) -> Outcome {
+    let mut child = match Command::new(&spec.command)
+        .args(&spec.args)
+        .envs(&spec.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return Outcome::Crashed(format!("failed to launch: {}", e)),
+    };
+    let mut lines = child
+        .stdout
+        .take()
+        .map(|stdout| BufReader::new(stdout).lines());
+    let mut deadline = Instant::now() + spec.heartbeat_timeout;
+
+    loop {
+        tokio::select! {
+            biased;
+            _ = stop.changed() => {
+                let _ = child.kill().await;
+                return Outcome::Stopped;
+            }
+            line = next_line(&mut lines) => match line {
+                Some(line) => match serde_json::from_str::<Heartbeat>(&line) {
+                    Ok(beat) => {
+                        deadline = Instant::now() + spec.heartbeat_timeout;
+                        shared.beat(&spec.id, beat).await;
+                    }
+                    Err(_) => info!("[{}] {}", spec.id, line),
+                },
+                // Stdout closed; the exit status decides.
+                None => lines = None,
+            },
+            status = child.wait() => {
+                drain(&spec.id, lines, shared).await;
+                return match status {
+                    Ok(status) if status.success() => Outcome::Exited,
+                    Ok(status) => Outcome::Crashed(status.to_string()),
+                    Err(e) => Outcome::Crashed(e.to_string()),
+                };
+            }
+            _ = tokio::time::sleep_until(deadline) => {
+                let _ = child.kill().await;
+                return Outcome::Crashed(format!(
+                    "no heartbeat for {:?}",
+                    spec.heartbeat_timeout
+                ));
+            }
+        }
+    }
+}
+
+/// Reads what a dead shard left in its stdout pipe, so last words count.
+/// Bounded in time: an orphaned grandchild may hold the pipe open.
+async fn drain(id: &str, lines: Option<StdoutLines>, shared: &Shared) {
+    let Some(mut lines) = lines else { return };
+    let read = async {
+        while let Ok(Some(line)) = lines.next_line().await {
+            match serde_json::from_str::<Heartbeat>(&line) {
+                Ok(beat) => shared.beat(id, beat).await,
+                Err(_) => info!("[{}] {}", id, line),
+            }
+        }
+    };
+    let _ = tokio::time::timeout(Duration::from_millis(500), read).await;
+}
+
+/// The next stdout line, or never once stdout is closed.
+async fn next_line(lines: &mut Option<StdoutLines>) -> Option<String> {
+    match lines {
+        Some(lines) => lines.next_line().await.ok().flatten(),
+        None => std::future::pending().await,
+    }
+}
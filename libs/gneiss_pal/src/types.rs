@@ -49,6 +49,10 @@ pub enum GuiUpdate {
     ActiveDirective(String),
     ReviewPayload(PreFlightPayload), // The Interceptor
     SynapseError(String), // Discrete failure signal
+    /// More of the answer being written, to be appended to what came before.
+    StreamDelta(String),
+    /// The streamed answer is finished (or abandoned).
+    StreamEnd,
 }
 
 #[derive(Clone, Debug, PartialEq)]
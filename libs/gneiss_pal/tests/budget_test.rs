@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use futures::StreamExt;
+use gneiss_pal::api::{
+    BudgetConfig, Budgeted, Content, ContextWindow, LlmError, LlmProvider, MockProvider, Overflow,
+    Part, RateLimits, UsageMetadata, collect_stream,
+};
+use gneiss_pal::persistence::{BrainManager, UsageLedger, UsageRecord};
+use std::sync::Arc;
+
+fn history(text: &str) -> Vec<Content> {
+    vec![Content {
+        role: "user".to_string(),
+        parts: vec![Part::text(text.to_string())],
+    }]
+}
+
+fn config(max_input_tokens: u32, overflow: Overflow) -> BudgetConfig {
+    BudgetConfig {
+        limits: RateLimits::default(),
+        window: ContextWindow {
+            max_input_tokens,
+            overflow,
+        },
+    }
+}
+
+#[tokio::test]
+async fn test_ledger_records_and_persists() {
+    let dir = tempfile::tempdir().unwrap();
+    let brain = BrainManager::new(dir.path().join("history.json"));
+    let mock = Arc::new(
+        MockProvider::new()
+            .with_reply(
+                "billed",
+                UsageMetadata {
+                    prompt_token_count: Some(11),
+                    candidates_token_count: Some(2),
+                    total_token_count: Some(13),
+                },
+            )
+            .with_text("guessed answer"),
+    );
+
+    let llm =
+        Budgeted::new(mock, config(1000, Overflow::Reject)).with_ledger(brain.clone(), "session-1");
+    llm.generate(&history("first")).await.unwrap();
+    llm.generate(&history("second")).await.unwrap();
+    llm.embed("vector me").await.unwrap();
+
+    let ledger = llm.ledger();
+    assert_eq!(ledger.records.len(), 3);
+    assert_eq!(ledger.records[0].prompt_tokens, 11);
+    assert!(!ledger.records[0].estimated);
+    // "second" is 2 tokens plus 4 for the message; "guessed answer" is 4.
+    assert_eq!(
+        (
+            ledger.records[1].prompt_tokens,
+            ledger.records[1].completion_tokens
+        ),
+        (6, 4)
+    );
+    assert!(ledger.records[1].estimated);
+    assert_eq!(ledger.records[2].kind, "embed");
+    assert_eq!(ledger.totals(), (11 + 6 + 3, 2 + 4));
+
+    // Appended after every call, and resumed by the next session object.
+    let saved = std::fs::read_to_string(dir.path().join("usage/session-1.jsonl")).unwrap();
+    assert_eq!(saved.lines().count(), 3);
+    assert_eq!(brain.load_ledger("session-1"), ledger);
+    let resumed = Budgeted::new(Arc::new(MockProvider::new()), BudgetConfig::default())
+        .with_ledger(brain.clone(), "session-1");
+    assert_eq!(resumed.ledger().records.len(), 3);
+    assert!(brain.load_ledger("other").records.is_empty());
+}
+
+#[test]
+fn test_ledger_reads_legacy_file_and_skips_torn_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let brain = BrainManager::new(dir.path().join("history.json"));
+    let record = |kind: &str| UsageRecord {
+        timestamp: "2026-03-01T09:00:00+00:00".to_string(),
+        provider: "mock".to_string(),
+        kind: kind.to_string(),
+        prompt_tokens: 5,
+        completion_tokens: 1,
+        estimated: false,
+    };
+
+    // A ledger saved before records were appended line by line.
+    let mut legacy = UsageLedger::new("s");
+    legacy.records.push(record("generate"));
+    std::fs::create_dir_all(dir.path().join("usage")).unwrap();
+    std::fs::write(
+        dir.path().join("usage/s.json"),
+        serde_json::to_string_pretty(&legacy).unwrap(),
+    )
+    .unwrap();
+
+    brain.append_usage("s", &record("embed")).unwrap();
+    let jsonl = dir.path().join("usage/s.jsonl");
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&jsonl)
+        .unwrap();
+    std::io::Write::write_all(&mut file, b"{\"timestamp\":\"2026").unwrap();
+
+    let ledger = brain.load_ledger("s");
+    let kinds: Vec<&str> = ledger.records.iter().map(|r| r.kind.as_str()).collect();
+    assert_eq!(kinds, ["generate", "embed"]);
+
+    // Saving whole folds the legacy file into the new one.
+    brain.save_ledger(&ledger).unwrap();
+    assert!(!dir.path().join("usage/s.json").exists());
+    assert_eq!(brain.load_ledger("s"), ledger);
+}
+
+#[tokio::test]
+async fn test_oversized_prompt_never_reaches_provider() {
+    let mock = Arc::new(MockProvider::new().with_text("unused"));
+    let llm = Budgeted::new(mock.clone(), config(10, Overflow::Reject));
+
+    let err = llm.generate(&history(&"z".repeat(100))).await.unwrap_err();
+    assert_eq!(
+        err,
+        LlmError::ContextWindow {
+            needed: 29,
+            limit: 10
+        }
+    );
+    assert!(mock.calls().is_empty());
+
+    let streamed: Vec<_> = llm
+        .generate_stream(&history(&"z".repeat(100)))
+        .collect()
+        .await;
+    assert_eq!(streamed, vec![Err(err)]);
+    assert!(llm.ledger().records.is_empty());
+}
+
+#[tokio::test]
+async fn test_truncated_prompt_is_what_the_provider_sees() {
+    let mock = Arc::new(MockProvider::new().with_text("ok"));
+    let llm = Budgeted::new(mock.clone(), config(20, Overflow::Truncate));
+
+    let mut long = history(&"old ".repeat(50));
+    long.push(Content {
+        role: "user".to_string(),
+        parts: vec![Part::text("the actual question".to_string())],
+    });
+    let (text, _) = collect_stream(llm.generate_stream(&long), |_| {})
+        .await
+        .unwrap();
+    assert_eq!(text, "ok");
+
+    let seen = mock.calls();
+    assert_eq!(seen.len(), 1);
+    assert_eq!(seen[0].len(), 1);
+    assert!(matches!(&seen[0][0].parts[0], Part::Text { text } if text == "the actual question"));
+    assert_eq!(llm.ledger().records.len(), 1);
+}
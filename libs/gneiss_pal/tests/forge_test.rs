@@ -0,0 +1,369 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use axum::Router;
+use axum::http::{HeaderMap, Method, StatusCode, Uri, header};
+use axum::response::{IntoResponse, Response};
+use gneiss_pal::forge::{
+    CommitFile, EntryKind, ForgeClient, ForgeError, ItemState, NewIssue, NewPullRequest,
+};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+const TOKEN: &str = "test-token";
+
+/// (method, path and query, body) of every request the stub saw.
+type Log = Arc<Mutex<Vec<(Method, String, String)>>>;
+
+fn fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/forge")
+        .join(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+/// Answers like GitHub for the owner/repo `una/core`, from recorded fixtures.
+async fn github(log: Log, method: Method, uri: Uri, headers: HeaderMap, body: String) -> Response {
+    let target = uri
+        .path_and_query()
+        .map(|p| p.to_string())
+        .unwrap_or_default();
+    log.lock()
+        .unwrap()
+        .push((method.clone(), target.clone(), body.clone()));
+
+    let authorized = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.ends_with(TOKEN));
+    if !authorized {
+        return reply(StatusCode::UNAUTHORIZED, "error_bad_credentials.json");
+    }
+
+    let path = uri.path().trim_start_matches("/repos/una/core");
+    match (method, path) {
+        (Method::GET, "/contents/README.md") => reply(StatusCode::OK, "content_readme.json"),
+        (Method::GET, "/contents/docs") => reply(StatusCode::OK, "contents_dir.json"),
+        (Method::GET, "/git/trees/main") => reply(StatusCode::OK, "tree_recursive.json"),
+        (Method::GET, "/git/ref/heads/main") => reply(StatusCode::OK, "ref_main.json"),
+        (Method::POST, "/git/refs") if body.contains("refs/heads/main") => {
+            reply(StatusCode::UNPROCESSABLE_ENTITY, "error_ref_exists.json")
+        }
+        (Method::POST, "/git/refs") => reply(StatusCode::CREATED, "ref_created.json"),
+        (Method::PUT, p) if p.starts_with("/contents/") => {
+            reply(StatusCode::CREATED, "contents_put.json")
+        }
+        (Method::GET, "/pulls") => reply(StatusCode::OK, "pulls_list.json"),
+        (Method::POST, "/pulls") => reply(StatusCode::CREATED, "pull_created.json"),
+        (Method::POST, "/issues/13/comments") => reply(StatusCode::CREATED, "comment_created.json"),
+        (Method::GET, "/issues") if target.contains("page=2") => {
+            reply(StatusCode::OK, "issues_list_page2.json")
+        }
+        (Method::GET, "/issues") => {
+            // The rest is on a second page, as GitHub links it.
+            let host = headers
+                .get(header::HOST)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default();
+            let next = format!(
+                "<http://{}/repos/una/core/issues?state=all&per_page=100&page=2>; rel=\"next\"",
+                host
+            );
+            let mut response = reply(StatusCode::OK, "issues_list.json");
+            response
+                .headers_mut()
+                .insert(header::LINK, next.parse().unwrap());
+            response
+        }
+        (Method::POST, "/issues") => reply(StatusCode::CREATED, "issue_created.json"),
+        _ => reply(StatusCode::NOT_FOUND, "error_not_found.json"),
+    }
+}
+
+fn reply(status: StatusCode, name: &str) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/json; charset=utf-8")],
+        fixture(name),
+    )
+        .into_response()
+}
+
+async fn serve() -> (String, Log) {
+    let log: Log = Log::default();
+    let log_handler = log.clone();
+    let app = Router::new().fallback(
+        move |method: Method, uri: Uri, headers: HeaderMap, body: String| {
+            github(log_handler.clone(), method, uri, headers, body)
+        },
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}", addr), log)
+}
+
+async fn client() -> (ForgeClient, Log) {
+    let (base, log) = serve().await;
+    // octocrab's hyper stack needs a process-level provider, as in lumen.
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    (ForgeClient::with_config(TOKEN, Some(&base)).unwrap(), log)
+}
+
+fn last_body(log: &Log) -> serde_json::Value {
+    let log = log.lock().unwrap();
+    serde_json::from_str(&log.last().unwrap().2).unwrap()
+}
+
+#[tokio::test]
+async fn test_file_content_is_decoded() {
+    let (forge, log) = client().await;
+
+    let file = forge
+        .get_file("una", "core", "README.md", Some("main"))
+        .await
+        .unwrap();
+    assert_eq!(file.path, "README.md");
+    assert_eq!(file.sha, "3d21ec53a331a6f037a91c368710b99387d012c1");
+    let text = file.text().unwrap();
+    assert!(text.starts_with("# UnaOS\n\nThe Architect's forge. Ω\n"));
+    assert_eq!(text.matches("# UnaOS").count(), 3);
+    assert_eq!(
+        log.lock().unwrap()[0].1,
+        "/repos/una/core/contents/README.md?ref=main"
+    );
+
+    // The String flavour used by vein.
+    let text = forge
+        .get_file_content("una", "core", "README.md", None)
+        .await
+        .unwrap();
+    assert!(text.contains('Ω'));
+}
+
+#[tokio::test]
+async fn test_file_errors_are_typed() {
+    let (forge, _) = client().await;
+
+    assert!(matches!(
+        forge.get_file("una", "core", "missing.txt", None).await,
+        Err(ForgeError::NotFound(m)) if m == "Not Found"
+    ));
+    // A directory is not a file.
+    assert!(matches!(
+        forge.get_file("una", "core", "docs", None).await,
+        Err(ForgeError::Decode(_))
+    ));
+
+    let (base, _) = serve().await;
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    let intruder = ForgeClient::with_config("wrong", Some(&base)).unwrap();
+    assert_eq!(
+        intruder.get_file("una", "core", "README.md", None).await,
+        Err(ForgeError::Auth("Bad credentials".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_list_tree() {
+    let (forge, log) = client().await;
+
+    let tree = forge.list_tree("una", "core", "main", true).await.unwrap();
+    assert!(!tree.truncated);
+    assert_eq!(tree.entries.len(), 4);
+    assert_eq!(tree.entries[0].path, "Cargo.toml");
+    assert_eq!(tree.entries[0].size, Some(1406));
+    assert_eq!(tree.entries[1].kind, EntryKind::Tree);
+    assert_eq!(tree.entries[3].kind, EntryKind::Commit);
+    assert_eq!(
+        log.lock().unwrap()[0].1,
+        "/repos/una/core/git/trees/main?recursive=1"
+    );
+}
+
+#[tokio::test]
+async fn test_create_branch() {
+    let (forge, log) = client().await;
+
+    let branch = forge
+        .create_branch("una", "core", "feature/forge", "main")
+        .await
+        .unwrap();
+    assert_eq!(branch.name, "feature/forge");
+    assert_eq!(branch.sha, "aa218f56b14c9653891f9e74264a383fa43fefbd");
+    assert_eq!(
+        last_body(&log),
+        serde_json::json!({
+            "ref": "refs/heads/feature/forge",
+            "sha": "aa218f56b14c9653891f9e74264a383fa43fefbd"
+        })
+    );
+
+    assert!(matches!(
+        forge.create_branch("una", "core", "main", "main").await,
+        Err(ForgeError::Conflict(m)) if m == "Reference already exists"
+    ));
+}
+
+#[tokio::test]
+async fn test_commit_file_creates_and_updates() {
+    let (forge, log) = client().await;
+
+    // New file: the sha lookup 404s and the PUT carries none.
+    let result = forge
+        .commit_file(
+            "una",
+            "core",
+            CommitFile {
+                path: "docs/NOTES.md",
+                content: b"Ship it.\n",
+                message: "Add notes",
+                branch: Some("feature/forge"),
+                sha: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        result.commit_sha,
+        "7638417db6d59f3c431d3e1f261cc637155684cd"
+    );
+    assert_eq!(result.path, "docs/NOTES.md");
+    assert_eq!(
+        last_body(&log),
+        serde_json::json!({
+            "message": "Add notes",
+            "content": "U2hpcCBpdC4K",
+            "branch": "feature/forge"
+        })
+    );
+
+    // Existing file: the current blob sha is looked up and sent back.
+    forge
+        .commit_file(
+            "una",
+            "core",
+            CommitFile {
+                path: "README.md",
+                content: "# UnaOS\n".as_bytes(),
+                message: "Trim README",
+                branch: None,
+                sha: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        last_body(&log)["sha"],
+        "3d21ec53a331a6f037a91c368710b99387d012c1"
+    );
+    let methods: Vec<Method> = log.lock().unwrap().iter().map(|r| r.0.clone()).collect();
+    assert_eq!(
+        methods,
+        vec![Method::GET, Method::PUT, Method::GET, Method::PUT]
+    );
+}
+
+#[tokio::test]
+async fn test_pull_requests() {
+    let (forge, log) = client().await;
+
+    let pulls = forge
+        .list_pull_requests("una", "core", ItemState::Open)
+        .await
+        .unwrap();
+    assert_eq!(pulls.len(), 2);
+    assert_eq!(pulls[0].number, 12);
+    assert_eq!(pulls[0].head, "feature/forge");
+    assert_eq!(pulls[0].body, "Decodes base64 transparently.");
+    assert!(pulls[1].draft);
+    assert_eq!(pulls[1].body, "");
+    assert_eq!(
+        log.lock().unwrap()[0].1,
+        "/repos/una/core/pulls?state=open&per_page=100"
+    );
+
+    let pr = forge
+        .open_pull_request(
+            "una",
+            "core",
+            NewPullRequest {
+                title: "Add notes",
+                head: "feature/forge",
+                base: "main",
+                body: "Notes for the forge.",
+                draft: false,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(pr.number, 13);
+    assert_eq!(pr.author, "architect");
+    assert_eq!(last_body(&log)["head"], "feature/forge");
+
+    let comment = forge
+        .comment_on_pull_request("una", "core", 13, "Looks good to me.")
+        .await
+        .unwrap();
+    assert_eq!(comment.id, 1453001);
+    assert_eq!(comment.author, "una-prime");
+    assert_eq!(
+        last_body(&log),
+        serde_json::json!({"body": "Looks good to me."})
+    );
+}
+
+#[tokio::test]
+async fn test_issues() {
+    let (forge, log) = client().await;
+
+    let issues = forge
+        .list_issues("una", "core", ItemState::All)
+        .await
+        .unwrap();
+    // Pull request #12 is listed by GitHub but is not an issue, and #3
+    // comes from the second page.
+    let numbers: Vec<u64> = issues.iter().map(|i| i.number).collect();
+    assert_eq!(numbers, vec![14, 7, 3]);
+    assert_eq!(issues[0].labels, vec!["bug", "vein"]);
+    assert_eq!(
+        log.lock().unwrap()[0].1,
+        "/repos/una/core/issues?state=all&per_page=100"
+    );
+    assert_eq!(
+        log.lock().unwrap()[1].1,
+        "/repos/una/core/issues?state=all&per_page=100&page=2"
+    );
+
+    let issue = forge
+        .create_issue(
+            "una",
+            "core",
+            NewIssue {
+                title: "Spline drops keystrokes",
+                body: "Under load.",
+                labels: &["bug"],
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(issue.number, 15);
+    assert_eq!(issue.labels, vec!["bug"]);
+    assert_eq!(
+        last_body(&log),
+        serde_json::json!({"title": "Spline drops keystrokes", "body": "Under load.", "labels": ["bug"]})
+    );
+}
@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use async_channel::Sender;
+use gneiss_pal::headless::Headless;
+use gneiss_pal::{AppHandler, DashboardState, Event, GuiUpdate, expect_update};
+use std::time::Duration;
+
+const SOON: Duration = Duration::from_secs(2);
+
+/// Echoes navigation to the console, answering late when asked to.
+struct Echo {
+    gui_tx: Sender<GuiUpdate>,
+    nav: usize,
+}
+
+impl AppHandler for Echo {
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::NavSelect(idx) => {
+                self.nav = idx;
+                let _ = self
+                    .gui_tx
+                    .try_send(GuiUpdate::ConsoleLog(format!("nav {}\n", idx)));
+            }
+            Event::Input { text, .. } => {
+                let gui_tx = self.gui_tx.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    let _ = gui_tx.send(GuiUpdate::ConsoleLog(text)).await;
+                });
+            }
+            Event::Timer => {
+                let _ = self.gui_tx.try_send(GuiUpdate::ClearConsole);
+            }
+            _ => {}
+        }
+    }
+
+    fn view(&self) -> DashboardState {
+        DashboardState {
+            active_nav_index: self.nav,
+            ..DashboardState::default()
+        }
+    }
+}
+
+fn echo() -> Headless<Echo> {
+    let (gui_tx, gui_rx) = async_channel::unbounded();
+    Headless::start(Echo { gui_tx, nav: 0 }, gui_rx)
+}
+
+#[tokio::test]
+async fn test_scripted_events_reach_the_handler() {
+    let ui = echo();
+    ui.script([Event::NavSelect(1), Event::NavSelect(2)]);
+    assert_eq!(ui.view().await.active_nav_index, 2);
+
+    let first = expect_update!(ui, GuiUpdate::ConsoleLog(_), SOON);
+    assert!(matches!(first, GuiUpdate::ConsoleLog(s) if s == "nav 1\n"));
+    expect_update!(ui, GuiUpdate::ConsoleLog(s) if s == "nav 2\n", SOON);
+    assert_eq!(ui.console(), "nav 1\nnav 2\n");
+}
+
+#[tokio::test]
+async fn test_expect_update_waits_and_keeps_order() {
+    let ui = echo();
+    ui.send(Event::Input {
+        target: "console".into(),
+        text: "late".into(),
+    });
+    ui.send(Event::NavSelect(3));
+
+    // The late echo arrives after "nav 3"; matching it moves past both.
+    expect_update!(ui, GuiUpdate::ConsoleLog(s) if s == "late", SOON);
+    let missing = ui
+        .expect_update(
+            |u| matches!(u, GuiUpdate::ConsoleLog(s) if s.starts_with("nav")),
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap_err();
+    assert!(missing.seen.is_empty());
+
+    let timeline = ui.timeline();
+    assert_eq!(timeline.len(), 2);
+    assert!(timeline[1].at >= Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn test_missing_update_reports_what_came() {
+    let ui = echo();
+    ui.send(Event::NavSelect(7));
+    let missing = ui
+        .expect_update(
+            |u| matches!(u, GuiUpdate::StreamEnd),
+            Duration::from_millis(50),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(missing.seen.len(), 1);
+    assert!(missing.to_string().contains("nav 7"));
+}
+
+#[tokio::test]
+async fn test_console_and_quiet_checks() {
+    let ui = echo();
+    ui.script([Event::NavSelect(1), Event::Timer, Event::NavSelect(2)]);
+    ui.settle().await;
+    assert_eq!(ui.console(), "nav 2\n");
+
+    assert!(
+        ui.expect_no_update(
+            |u| matches!(u, GuiUpdate::SynapseError(_)),
+            Duration::from_millis(20)
+        )
+        .await
+        .is_ok()
+    );
+    assert!(matches!(
+        ui.expect_no_update(|u| matches!(u, GuiUpdate::ClearConsole), Duration::ZERO)
+            .await,
+        Err(GuiUpdate::ClearConsole)
+    ));
+}
+
+/// Panics on its first event.
+struct Fragile;
+
+impl AppHandler for Fragile {
+    fn handle_event(&mut self, _event: Event) {
+        panic!("handler blew up");
+    }
+
+    fn view(&self) -> DashboardState {
+        DashboardState::default()
+    }
+}
+
+#[tokio::test]
+#[should_panic(expected = "handler blew up")]
+async fn test_settle_reraises_a_handler_panic() {
+    let (_gui_tx, gui_rx) = async_channel::unbounded();
+    let ui = Headless::start(Fragile, gui_rx);
+    ui.send(Event::Timer);
+    ui.settle().await;
+}
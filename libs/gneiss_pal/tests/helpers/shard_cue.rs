@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A stand-in shard for the supervisor tests, run inside the test binary
+//! itself (see `supervisor_test::shard_cue_entry`).
+//!
+//! `run(<cue>, <beats>, [state-file])` prints `<beats>` heartbeats, 20ms
+//! apart, and then:
+//!
+//! * `crash`: exits with status 3
+//! * `hang`: stays alive without another word
+//! * `exit`: exits with status 0
+//! * `beat`: keeps beating forever
+//! * `flaky`: crashes while `state-file` counts fewer than `<beats>` runs,
+//!   then keeps beating forever
+
+use std::io::Write;
+use std::time::Duration;
+use std::{fs, process, thread};
+
+fn beat() {
+    println!(r#"{{"id": "cue", "status": "Online", "cpu_load": 7}}"#);
+    std::io::stdout().flush().unwrap();
+    thread::sleep(Duration::from_millis(20));
+}
+
+pub fn run(args: &[&str]) -> ! {
+    let cue = args.first().copied().unwrap_or("beat");
+    let beats: u32 = args.get(1).and_then(|n| n.parse().ok()).unwrap_or(1);
+
+    if cue == "flaky" {
+        let state = args.get(2).expect("flaky needs a state file");
+        let runs: u32 = fs::read_to_string(state)
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0);
+        fs::write(state, (runs + 1).to_string()).unwrap();
+        if runs < beats {
+            beat();
+            process::exit(3);
+        }
+        loop {
+            beat();
+        }
+    }
+
+    for _ in 0..beats {
+        beat();
+    }
+    // Not a heartbeat; the supervisor logs it.
+    println!("cue: {}", cue);
+    match cue {
+        "crash" => process::exit(3),
+        "hang" => loop {
+            thread::sleep(Duration::from_secs(60));
+        },
+        "exit" => process::exit(0),
+        _ => loop {
+            beat();
+        },
+    }
+}
@@ -0,0 +1,308 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use axum::Router;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use gneiss_pal::api::{
+    Content, LlmError, LlmProvider, MockProvider, OpenAiCompatible, OpenAiConfig, Part,
+    RetryPolicy, TokenSource, UsageMetadata, VertexConfig, VertexGemini,
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// One canned answer: status, optional `Retry-After`, JSON body.
+type Canned = (u16, Option<&'static str>, &'static str);
+
+#[derive(Default)]
+struct Stub {
+    script: Mutex<VecDeque<Canned>>,
+    requests: Mutex<Vec<(String, String)>>,
+}
+
+impl Stub {
+    fn hits(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+}
+
+async fn answer(State(stub): State<Arc<Stub>>, headers: HeaderMap, body: String) -> Response {
+    let auth = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    stub.requests.lock().unwrap().push((auth, body));
+    let (status, retry_after, body) =
+        stub.script
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or((500, None, "script exhausted"));
+    let mut response = (StatusCode::from_u16(status).unwrap(), body.to_string()).into_response();
+    if let Some(secs) = retry_after {
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, secs.parse().unwrap());
+    }
+    response
+}
+
+/// Serves `script` on every path; returns the base URL.
+async fn serve(script: Vec<Canned>) -> (String, Arc<Stub>) {
+    let stub = Arc::new(Stub {
+        script: Mutex::new(script.into()),
+        ..Stub::default()
+    });
+    let app = Router::new()
+        .fallback(post(answer))
+        .with_state(stub.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}", addr), stub)
+}
+
+fn fast_retry(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::from_millis(5),
+        max_backoff: Duration::from_millis(20),
+    }
+}
+
+fn vertex(base: &str, retry: RetryPolicy) -> VertexGemini {
+    VertexGemini::with_config(VertexConfig {
+        generate_url: format!("{}/gen", base),
+        count_tokens_url: format!("{}/count", base),
+        embed_url: format!("{}/embed", base),
+        token: TokenSource::Static("secret".to_string()),
+        retry,
+        ..VertexConfig::default()
+    })
+    .unwrap()
+}
+
+fn openai(base: &str, retry: RetryPolicy) -> OpenAiCompatible {
+    OpenAiCompatible::new(OpenAiConfig {
+        base_url: base.to_string(),
+        api_key: Some("sk-test".to_string()),
+        retry,
+        ..OpenAiConfig::default()
+    })
+    .unwrap()
+}
+
+fn history(text: &str) -> Vec<Content> {
+    vec![Content {
+        role: "user".to_string(),
+        parts: vec![Part::text(text.to_string())],
+    }]
+}
+
+const GEMINI_OK: &str = r#"{
+    "candidates": [{"content": {"parts": [{"text": "Hello, "}, {"text": "Architect."}]}}],
+    "usageMetadata": {"promptTokenCount": 3, "candidatesTokenCount": 4, "totalTokenCount": 7}
+}"#;
+
+#[tokio::test]
+async fn test_retries_server_errors_until_success() {
+    let (base, stub) = serve(vec![
+        (503, None, "busy"),
+        (503, None, "busy"),
+        (200, None, GEMINI_OK),
+    ])
+    .await;
+    let llm = vertex(&base, fast_retry(4));
+
+    let (text, usage) = llm.generate(&history("hi")).await.unwrap();
+    assert_eq!(text, "Hello, Architect.");
+    assert_eq!(usage.unwrap().total_token_count, Some(7));
+    assert_eq!(stub.hits(), 3);
+    assert_eq!(stub.requests.lock().unwrap()[0].0, "Bearer secret");
+}
+
+#[tokio::test]
+async fn test_honours_retry_after_on_429() {
+    let (base, stub) = serve(vec![(429, Some("0"), "slow down"), (200, None, GEMINI_OK)]).await;
+    let llm = vertex(&base, fast_retry(2));
+
+    assert!(llm.generate(&history("hi")).await.is_ok());
+    assert_eq!(stub.hits(), 2);
+}
+
+#[tokio::test]
+async fn test_client_errors_are_not_retried() {
+    let (base, stub) = serve(vec![(400, None, "bad request"), (200, None, GEMINI_OK)]).await;
+    let llm = vertex(&base, fast_retry(4));
+
+    let err = llm.generate(&history("hi")).await.unwrap_err();
+    assert_eq!(
+        err,
+        LlmError::Http {
+            status: 400,
+            body: "bad request".to_string()
+        }
+    );
+    assert!(!err.is_retryable());
+    assert_eq!(stub.hits(), 1);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_attempts() {
+    let (base, stub) = serve(vec![(503, None, "busy"); 5]).await;
+    let llm = vertex(&base, fast_retry(3));
+
+    let err = llm.generate(&history("hi")).await.unwrap_err();
+    assert!(matches!(err, LlmError::Http { status: 503, .. }));
+    assert_eq!(stub.hits(), 3);
+}
+
+#[tokio::test]
+async fn test_unauthorized_refreshes_token_once() {
+    let (base, stub) = serve(vec![(401, None, "expired"), (200, None, GEMINI_OK)]).await;
+    let llm = vertex(&base, fast_retry(1));
+    assert!(llm.generate(&history("hi")).await.is_ok());
+    assert_eq!(stub.hits(), 2);
+
+    let (base, stub) = serve(vec![(401, None, "nope"); 3]).await;
+    let llm = vertex(&base, fast_retry(4));
+    let err = llm.generate(&history("hi")).await.unwrap_err();
+    assert!(matches!(err, LlmError::Auth(_)));
+    // Auth failures skip the retry loop: one try, one refresh, one more try.
+    assert_eq!(stub.hits(), 2);
+}
+
+#[tokio::test]
+async fn test_vertex_safety_block_and_silence() {
+    let (base, _stub) = serve(vec![
+        (
+            200,
+            None,
+            r#"{"promptFeedback": {"blockReason": "SAFETY"}}"#,
+        ),
+        (200, None, r#"{"candidates": []}"#),
+    ])
+    .await;
+    let llm = vertex(&base, fast_retry(1));
+
+    assert_eq!(
+        llm.generate(&history("hi")).await.unwrap_err(),
+        LlmError::Blocked("SAFETY".to_string())
+    );
+    assert_eq!(
+        llm.generate(&history("hi")).await.unwrap_err(),
+        LlmError::Empty
+    );
+}
+
+#[tokio::test]
+async fn test_vertex_count_tokens_and_embed() {
+    let (base, stub) = serve(vec![
+        (200, None, r#"{"totalTokens": 42}"#),
+        (
+            200,
+            None,
+            r#"{"predictions": [{"embeddings": {"values": [0.5, -0.5]}}]}"#,
+        ),
+    ])
+    .await;
+    let llm = vertex(&base, fast_retry(1));
+
+    assert_eq!(llm.count_tokens(&history("hi")).await.unwrap(), 42);
+    assert_eq!(llm.embed("hi").await.unwrap(), vec![0.5, -0.5]);
+    assert!(stub.requests.lock().unwrap()[1].1.contains("\"instances\""));
+}
+
+#[tokio::test]
+async fn test_openai_chat_and_embeddings() {
+    let (base, stub) = serve(vec![
+        (
+            200,
+            None,
+            r#"{"choices": [{"message": {"role": "assistant", "content": "Acknowledged."}, "finish_reason": "stop"}],
+                "usage": {"prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7}}"#,
+        ),
+        (200, None, r#"{"data": [{"embedding": [1.0, 2.0, 3.0]}]}"#),
+    ])
+    .await;
+    let llm = openai(&base, fast_retry(1));
+
+    let mut convo = history("status?");
+    convo.push(Content {
+        role: "model".to_string(),
+        parts: vec![Part::text("nominal".to_string())],
+    });
+    let (text, usage) = llm.generate(&convo).await.unwrap();
+    assert_eq!(text, "Acknowledged.");
+    assert_eq!(
+        usage,
+        Some(UsageMetadata {
+            prompt_token_count: Some(5),
+            candidates_token_count: Some(2),
+            total_token_count: Some(7),
+        })
+    );
+    assert_eq!(llm.embed("vector").await.unwrap(), vec![1.0, 2.0, 3.0]);
+
+    let requests = stub.requests.lock().unwrap();
+    assert_eq!(requests[0].0, "Bearer sk-test");
+    let sent: serde_json::Value = serde_json::from_str(&requests[0].1).unwrap();
+    assert_eq!(sent["messages"][1]["role"], "assistant");
+    assert_eq!(sent["model"], "gpt-4o-mini");
+}
+
+#[tokio::test]
+async fn test_connection_refused_is_transport_error() {
+    // Bind and drop, so nothing listens on the port.
+    let port = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+    let llm = openai(&format!("http://127.0.0.1:{}", port), fast_retry(2));
+
+    let err = llm.embed("anyone?").await.unwrap_err();
+    assert!(matches!(err, LlmError::Transport(_)));
+    assert!(err.is_retryable());
+}
+
+#[tokio::test]
+async fn test_mock_provider_follows_script() {
+    let mock = MockProvider::new()
+        .with_text("first")
+        .with_error(LlmError::Blocked("SAFETY".to_string()))
+        .with_embedding(vec![9.0]);
+
+    assert_eq!(mock.generate(&history("a")).await.unwrap().0, "first");
+    assert!(matches!(
+        mock.generate(&history("b")).await,
+        Err(LlmError::Blocked(_))
+    ));
+    assert_eq!(
+        mock.generate(&history("c")).await.unwrap_err(),
+        LlmError::Empty
+    );
+    assert_eq!(mock.calls().len(), 3);
+    assert_eq!(mock.remaining(), 0);
+
+    assert_eq!(mock.embed("x").await.unwrap(), vec![9.0]);
+    let a = mock.embed("same").await.unwrap();
+    assert_eq!(a, mock.embed("same").await.unwrap());
+    assert_ne!(a, mock.embed("other").await.unwrap());
+}
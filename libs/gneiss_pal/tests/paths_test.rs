@@ -0,0 +1,295 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use gneiss_pal::paths::{Layout, PathIssue, PathKind, Source};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Resolves with only `vars` set, as if under a fresh login.
+fn resolve(vars: &[(&str, &Path)]) -> Result<Layout, String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string_lossy().into_owned()))
+        .collect();
+    Layout::resolve(|name| vars.get(name).cloned())
+}
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn test_xdg_defaults_under_home() {
+    let home = tempfile::tempdir().unwrap();
+    let h = home.path();
+    let layout = resolve(&[("HOME", h)]).unwrap();
+
+    let data = h.join(".local/share/unaos");
+    assert_eq!(layout.path(PathKind::Data), data);
+    assert_eq!(layout.path(PathKind::Cortex), data.join("cortex"));
+    assert_eq!(layout.path(PathKind::Vault), data.join("vault"));
+    assert_eq!(layout.path(PathKind::Config), h.join(".config/unaos"));
+    assert_eq!(
+        layout.path(PathKind::Logs),
+        h.join(".local/state/unaos/logs")
+    );
+    assert_eq!(layout.path(PathKind::Cache), h.join(".cache/unaos"));
+    assert!(
+        layout
+            .locations()
+            .iter()
+            .all(|l| l.source == Source::Default)
+    );
+    assert_eq!(layout.config_file, None);
+}
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn test_xdg_variables() {
+    let home = tempfile::tempdir().unwrap();
+    let xdg = tempfile::tempdir().unwrap();
+    let x = xdg.path();
+    let layout = resolve(&[
+        ("HOME", home.path()),
+        ("XDG_DATA_HOME", &x.join("data")),
+        ("XDG_STATE_HOME", &x.join("state")),
+        // Relative, so ignored.
+        ("XDG_CACHE_HOME", Path::new("cache")),
+    ])
+    .unwrap();
+
+    assert_eq!(layout.path(PathKind::Data), x.join("data/unaos"));
+    assert_eq!(
+        layout.get(PathKind::Data).source,
+        Source::Xdg("XDG_DATA_HOME")
+    );
+    assert_eq!(layout.path(PathKind::Vault), x.join("data/unaos/vault"));
+    assert_eq!(layout.path(PathKind::Logs), x.join("state/unaos/logs"));
+    assert_eq!(
+        layout.path(PathKind::Cache),
+        home.path().join(".cache/unaos")
+    );
+    assert_eq!(layout.get(PathKind::Cache).source, Source::Default);
+}
+
+#[test]
+fn test_una_home_keeps_one_tree() {
+    let home = tempfile::tempdir().unwrap();
+    let una = tempfile::tempdir().unwrap();
+    let u = una.path();
+    let layout = resolve(&[("HOME", home.path()), ("UNA_HOME", u)]).unwrap();
+
+    assert_eq!(layout.path(PathKind::Data), u);
+    assert_eq!(layout.path(PathKind::Cortex), u.join("cortex"));
+    assert_eq!(layout.path(PathKind::Config), u.join("principia"));
+    assert_eq!(layout.path(PathKind::Logs), u.join("logs"));
+    assert_eq!(layout.get(PathKind::Logs).source, Source::UnaHome);
+
+    // The old name, and no HOME needed.
+    let legacy = resolve(&[("UNA_ROOT", u)]).unwrap();
+    assert_eq!(legacy.path(PathKind::Vault), u.join("vault"));
+}
+
+#[test]
+fn test_env_overrides_win() {
+    let home = tempfile::tempdir().unwrap();
+    let una = tempfile::tempdir().unwrap();
+    let layout = resolve(&[
+        ("HOME", home.path()),
+        ("UNA_HOME", una.path()),
+        ("UNA_VAULT_DIR", Path::new("/mnt/vault")),
+        ("UNA_LOG_DIR", Path::new("~/una-logs")),
+        ("UNA_CACHE_DIR", Path::new("")),
+    ])
+    .unwrap();
+
+    assert_eq!(layout.path(PathKind::Vault), Path::new("/mnt/vault"));
+    assert_eq!(
+        layout.get(PathKind::Vault).source,
+        Source::Env("UNA_VAULT_DIR")
+    );
+    assert_eq!(layout.path(PathKind::Logs), home.path().join("una-logs"));
+    // Empty means unset.
+    assert_eq!(layout.path(PathKind::Cache), una.path().join("cache"));
+}
+
+#[test]
+fn test_config_file_layer() {
+    let home = tempfile::tempdir().unwrap();
+    let una = tempfile::tempdir().unwrap();
+    let u = una.path();
+    fs::create_dir_all(u.join("principia")).unwrap();
+    let file = u.join("principia/paths.toml");
+    fs::write(
+        &file,
+        "data = \"/srv/una\"\nlogs = \"../journal\"\ncache = \"~/cache\"\n",
+    )
+    .unwrap();
+
+    let layout = resolve(&[
+        ("HOME", home.path()),
+        ("UNA_HOME", u),
+        ("UNA_CACHE_DIR", Path::new("/var/cache/una")),
+    ])
+    .unwrap();
+    assert_eq!(layout.config_file.as_deref(), Some(file.as_path()));
+    assert_eq!(layout.path(PathKind::Data), Path::new("/srv/una"));
+    assert_eq!(
+        layout.get(PathKind::Data).source,
+        Source::ConfigFile(file.clone())
+    );
+    // Derived locations follow the data directory wherever it went.
+    assert_eq!(layout.path(PathKind::Cortex), Path::new("/srv/una/cortex"));
+    assert_eq!(layout.path(PathKind::Logs), u.join("principia/../journal"));
+    // The environment beats the file.
+    assert_eq!(layout.path(PathKind::Cache), Path::new("/var/cache/una"));
+
+    // UNA_PATHS_FILE points elsewhere.
+    let other = home.path().join("paths.toml");
+    fs::write(&other, "vault = \"/vaults/una\"\n").unwrap();
+    let layout = resolve(&[("UNA_HOME", u), ("UNA_PATHS_FILE", &other)]).unwrap();
+    assert_eq!(layout.path(PathKind::Data), u);
+    assert_eq!(layout.path(PathKind::Vault), Path::new("/vaults/una"));
+
+    fs::write(&file, "config = \"/etc/una\"\n").unwrap();
+    let err = resolve(&[("UNA_HOME", u)]).unwrap_err();
+    assert!(err.contains("paths.toml"), "{}", err);
+    assert!(err.contains("UNA_CONFIG_DIR"), "{}", err);
+}
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn test_legacy_config_and_logs_are_moved() {
+    let home = tempfile::tempdir().unwrap();
+    let h = home.path();
+    let data = h.join(".local/share/unaos");
+    fs::create_dir_all(data.join("principia")).unwrap();
+    fs::write(data.join("principia/paths.toml"), "").unwrap();
+    fs::create_dir_all(data.join("logs")).unwrap();
+    fs::write(data.join("logs/vein.log"), "old\n").unwrap();
+    // An empty new config directory does not block the move.
+    fs::create_dir_all(h.join(".config/unaos")).unwrap();
+
+    let layout = resolve(&[("HOME", h)]).unwrap();
+    assert_eq!(layout.migrate_legacy().len(), 2);
+    assert!(h.join(".config/unaos/paths.toml").exists());
+    assert_eq!(
+        fs::read_to_string(h.join(".local/state/unaos/logs/vein.log")).unwrap(),
+        "old\n"
+    );
+    assert!(!data.join("principia").exists());
+    assert!(!data.join("logs").exists());
+
+    // Once something lives in the new place, the old one is left alone.
+    fs::create_dir_all(data.join("logs")).unwrap();
+    assert!(layout.migrate_legacy().is_empty());
+    assert!(data.join("logs").exists());
+}
+
+#[test]
+fn test_missing_home_is_an_error() {
+    assert!(resolve(&[]).unwrap_err().contains("HOME"));
+}
+
+#[test]
+fn test_validate_creates_private_directories() {
+    let una = tempfile::tempdir().unwrap();
+    let layout = resolve(&[("UNA_HOME", &una.path().join("tree"))]).unwrap();
+    assert_eq!(layout.validate(), vec![]);
+
+    for location in layout.locations() {
+        assert!(location.path.is_dir(), "{}", location.path.display());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&location.path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700, "{}", location.path.display());
+        }
+    }
+    // Nothing left behind by the write probe.
+    assert_eq!(
+        fs::read_dir(layout.path(PathKind::Cache)).unwrap().count(),
+        0
+    );
+}
+
+#[test]
+fn test_validate_reports_unusable_locations() {
+    let una = tempfile::tempdir().unwrap();
+    let u = una.path();
+    fs::write(u.join("logs"), "not a directory").unwrap();
+    fs::write(u.join("blocker"), "").unwrap();
+    let layout = resolve(&[
+        ("UNA_HOME", u),
+        // Cannot be created under a file, whoever runs the test.
+        ("UNA_CACHE_DIR", &u.join("blocker/cache")),
+    ])
+    .unwrap();
+
+    let issues = layout.validate();
+    assert!(issues.contains(&PathIssue::NotADirectory {
+        kind: PathKind::Logs,
+        path: u.join("logs"),
+    }));
+    assert!(issues.iter().any(|i| matches!(
+        i,
+        PathIssue::Unwritable {
+            kind: PathKind::Cache,
+            ..
+        }
+    )));
+    assert!(issues.iter().all(PathIssue::is_fatal));
+}
+
+#[test]
+#[cfg(unix)]
+fn test_validate_reports_permissive_directories() {
+    use std::os::unix::fs::PermissionsExt;
+    let una = tempfile::tempdir().unwrap();
+    let vault = una.path().join("vault");
+    fs::create_dir(&vault).unwrap();
+    fs::set_permissions(&vault, fs::Permissions::from_mode(0o777)).unwrap();
+
+    let layout = resolve(&[("UNA_HOME", una.path())]).unwrap();
+    let issues = layout.validate();
+    assert_eq!(
+        issues,
+        vec![PathIssue::Permissive {
+            kind: PathKind::Vault,
+            path: vault,
+            mode: 0o777,
+        }]
+    );
+    assert!(!issues[0].is_fatal());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_validate_reports_cross_device_locations() {
+    use std::os::unix::fs::MetadataExt;
+    let una = tempfile::tempdir().unwrap();
+    let shm = Path::new("/dev/shm");
+    let apart =
+        shm.exists() && fs::metadata(shm).unwrap().dev() != fs::metadata(una.path()).unwrap().dev();
+    if !apart {
+        return; // No second filesystem to try.
+    }
+
+    let cache = tempfile::tempdir_in(shm).unwrap();
+    let layout = resolve(&[("UNA_HOME", una.path()), ("UNA_CACHE_DIR", cache.path())]).unwrap();
+    assert!(layout.validate().contains(&PathIssue::CrossDevice {
+        kind: PathKind::Cache,
+        path: cache.path().to_path_buf(),
+    }));
+}
@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use gneiss_pal::persistence::{BrainManager, SCHEMA_VERSION, SavedMessage};
+use std::fs;
+use std::path::Path;
+
+fn msg(role: &str, content: &str) -> SavedMessage {
+    SavedMessage {
+        role: role.to_string(),
+        content: content.to_string(),
+        timestamp: None,
+    }
+}
+
+fn brain(dir: &Path) -> BrainManager {
+    BrainManager::new(dir.join("history.json"))
+}
+
+#[test]
+fn test_save_records_revisions() {
+    let dir = tempfile::tempdir().unwrap();
+    let brain = brain(dir.path());
+    assert!(brain.load().is_empty());
+    assert!(brain.list_revisions().unwrap().is_empty());
+
+    let first = vec![msg("user", "hello")];
+    let second = vec![msg("user", "hello"), msg("model", "hi")];
+    assert_eq!(brain.save(&first).unwrap(), 1);
+    assert_eq!(brain.save(&second).unwrap(), 2);
+    // Nothing changed, nothing recorded.
+    assert_eq!(brain.save(&second).unwrap(), 2);
+
+    assert_eq!(brain.load(), second);
+    let revisions = brain.list_revisions().unwrap();
+    assert_eq!(
+        revisions
+            .iter()
+            .map(|r| (r.number, r.messages))
+            .collect::<Vec<_>>(),
+        vec![(1, 1), (2, 2)]
+    );
+    assert!(revisions[0].saved_at.is_some());
+    assert_eq!(brain.load_revision(1).unwrap(), first);
+    assert!(brain.load_revision(3).is_err());
+
+    let head: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("history.json")).unwrap())
+            .unwrap();
+    assert_eq!(head["schema_version"], SCHEMA_VERSION);
+    assert_eq!(head["revision"], 2);
+}
+
+#[test]
+fn test_old_revisions_are_pruned() {
+    let dir = tempfile::tempdir().unwrap();
+    let brain = brain(dir.path()).with_max_revisions(3);
+
+    let mut messages = Vec::new();
+    for i in 0..5 {
+        messages.push(msg("user", &i.to_string()));
+        brain.save(&messages).unwrap();
+    }
+
+    let numbers: Vec<u64> = brain
+        .list_revisions()
+        .unwrap()
+        .iter()
+        .map(|r| r.number)
+        .collect();
+    assert_eq!(numbers, vec![3, 4, 5]);
+    assert!(brain.load_revision(2).is_err());
+    assert_eq!(brain.load(), messages);
+
+    // Numbering carries on past the pruned ones.
+    messages.push(msg("model", "done"));
+    assert_eq!(brain.save(&messages).unwrap(), 6);
+}
+
+#[test]
+fn test_diff_revisions() {
+    let dir = tempfile::tempdir().unwrap();
+    let brain = brain(dir.path());
+    brain
+        .save(&[msg("user", "a"), msg("model", "b"), msg("user", "c")])
+        .unwrap();
+    brain
+        .save(&[
+            msg("user", "a"),
+            msg("model", "B"),
+            msg("user", "c"),
+            msg("model", "d"),
+        ])
+        .unwrap();
+
+    let diff = brain.diff_revisions(1, 2).unwrap();
+    assert_eq!(diff.common_prefix, 1);
+    assert_eq!(diff.common_suffix, 0);
+    assert_eq!(diff.removed, vec![msg("model", "b"), msg("user", "c")]);
+    assert_eq!(
+        diff.added,
+        vec![msg("model", "B"), msg("user", "c"), msg("model", "d")]
+    );
+    assert!(brain.diff_revisions(2, 2).unwrap().is_empty());
+}
+
+#[test]
+fn test_legacy_brain_is_migrated_and_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    // The version 1 format: a bare array.
+    fs::write(
+        dir.path().join("history.json"),
+        r#"[{"role": "user", "content": "from before", "timestamp": "2026-01-01"}]"#,
+    )
+    .unwrap();
+    let brain = brain(dir.path());
+    assert_eq!(brain.load()[0].content, "from before");
+
+    let revision = brain
+        .save(&[msg("user", "from before"), msg("model", "after")])
+        .unwrap();
+    assert_eq!(revision, 2);
+    assert_eq!(brain.load_revision(1).unwrap()[0].content, "from before");
+}
+
+#[test]
+fn test_interrupted_write_leaves_last_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let brain = brain(dir.path());
+    let saved = vec![msg("user", "durable")];
+    brain.save(&saved).unwrap();
+
+    // A writer killed mid-save leaves half-written temp files behind; the
+    // real files were never touched.
+    fs::write(dir.path().join(".history.json.tmp-4242"), "{\"schema_ver").unwrap();
+    fs::write(
+        dir.path().join("revisions/history/.00000002.json.tmp-4242"),
+        "[{\"role\": \"us",
+    )
+    .unwrap();
+    assert_eq!(brain.load(), saved);
+    assert_eq!(brain.list_revisions().unwrap().len(), 1);
+
+    // The next save goes through as if nothing happened.
+    let next = vec![msg("user", "durable"), msg("model", "still here")];
+    assert_eq!(brain.save(&next).unwrap(), 2);
+    assert_eq!(brain.load(), next);
+}
+
+#[test]
+fn test_torn_brain_file_falls_back_to_history() {
+    let dir = tempfile::tempdir().unwrap();
+    let brain = brain(dir.path());
+    let saved = vec![msg("user", "one"), msg("model", "two")];
+    brain.save(&saved).unwrap();
+
+    // What a plain overwrite cut short looks like.
+    fs::write(
+        dir.path().join("history.json"),
+        "{\"schema_version\": 2, \"rev",
+    )
+    .unwrap();
+    assert_eq!(brain.load(), saved);
+}
+
+#[test]
+fn test_revision_newer_than_brain_file_wins() {
+    let dir = tempfile::tempdir().unwrap();
+    let brain = brain(dir.path());
+    brain.save(&[msg("user", "one")]).unwrap();
+    let head = fs::read_to_string(dir.path().join("history.json")).unwrap();
+    brain
+        .save(&[msg("user", "one"), msg("model", "two")])
+        .unwrap();
+
+    // Died after recording revision 2 but before swapping the brain file.
+    fs::write(dir.path().join("history.json"), head).unwrap();
+    assert_eq!(brain.load().len(), 2);
+}
+
+#[test]
+fn test_directive_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let brain = brain(dir.path());
+    assert_eq!(brain.get_active_directive(), "Directive 055");
+    brain.set_active_directive("Directive 070\n").unwrap();
+    assert_eq!(brain.get_active_directive(), "Directive 070");
+}
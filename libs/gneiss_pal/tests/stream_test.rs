@@ -0,0 +1,281 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use axum::Router;
+use axum::body::Body;
+use axum::http::header;
+use axum::response::Response;
+use axum::routing::post;
+use futures::StreamExt;
+use gneiss_pal::api::stream::{JsonChunkDecoder, SseDecoder};
+use gneiss_pal::api::{
+    Content, Delta, LlmError, LlmProvider, MockProvider, OpenAiCompatible, OpenAiConfig, Part,
+    RetryPolicy, TokenSource, UsageMetadata, VertexConfig, VertexGemini, collect_stream,
+};
+use std::time::Duration;
+
+// Recorded from a Gemini `streamGenerateContent?alt=sse` session, with CRLF
+// line endings, a keep-alive comment and multi-byte text.
+const GEMINI_SSE: &str = concat!(
+    ": keep-alive\r\n\r\n",
+    "data: {\"candidates\": [{\"content\": {\"role\": \"model\", \"parts\": [{\"text\": \"Ωmega \"}]}}],",
+    " \"usageMetadata\": {\"promptTokenCount\": 9}}\r\n\r\n",
+    "data: {\"candidates\": [{\"content\": {\"role\": \"model\", \"parts\": [{\"text\": \"🜂 \\\"lit\\\" {}\"}]}}]}\r\n\r\n",
+    "data: {\"candidates\": [{\"content\": {\"role\": \"model\", \"parts\": [{\"functionCall\": {\"name\": \"ignite\", \"args\": {\"core\": 1}}}]},",
+    " \"finishReason\": \"STOP\"}],",
+    " \"usageMetadata\": {\"promptTokenCount\": 9, \"candidatesTokenCount\": 5, \"totalTokenCount\": 14}}\r\n\r\n",
+);
+
+// The same answer without `alt=sse`: one JSON array, streamed.
+const GEMINI_ARRAY: &str = concat!(
+    "[{\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"Ωmega \"}]}}]}\n",
+    ",\r\n{\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"🜂 \\\"lit\\\" {}\"}]}}]}\n",
+    ",\r\n{\"candidates\": [{\"content\": {\"parts\": [{\"functionCall\": {\"name\": \"ignite\", \"args\": {\"core\": 1}}}]},",
+    " \"finishReason\": \"STOP\"}],",
+    " \"usageMetadata\": {\"promptTokenCount\": 9, \"candidatesTokenCount\": 5, \"totalTokenCount\": 14}}\n]",
+);
+
+// An OpenAI chat completion stream with a tool call split across chunks.
+const OPENAI_SSE: &str = concat!(
+    "data: {\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"\"}}]}\n\n",
+    "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Scanning\"}}]}\n\n",
+    "data: {\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"function\":{\"name\":\"scan\",\"arguments\":\"\"}}]}}]}\n\n",
+    "data: {\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{\\\"depth\\\":\"}}]}}]}\n\n",
+    "data: {\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"2}\"}}]},\"finish_reason\":\"tool_calls\"}]}\n\n",
+    "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":4,\"completion_tokens\":6,\"total_tokens\":10}}\n\n",
+    "data: [DONE]\n\n",
+);
+
+fn sse_events(chunks: &[&[u8]]) -> Vec<String> {
+    let mut decoder = SseDecoder::new();
+    let mut events: Vec<String> = chunks.iter().flat_map(|c| decoder.push(c)).collect();
+    events.extend(decoder.finish());
+    events
+}
+
+fn json_objects(chunks: &[&[u8]]) -> Vec<String> {
+    let mut decoder = JsonChunkDecoder::new();
+    let objects = chunks.iter().flat_map(|c| decoder.push(c)).collect();
+    assert!(!decoder.is_incomplete());
+    objects
+}
+
+#[test]
+fn test_sse_split_at_every_byte() {
+    let bytes = GEMINI_SSE.as_bytes();
+    let whole = sse_events(&[bytes]);
+    assert_eq!(whole.len(), 3);
+    assert!(whole[0].contains("Ωmega"));
+
+    // Two pieces, cut everywhere, including inside "\r\n" and inside
+    // multi-byte characters.
+    for cut in 0..=bytes.len() {
+        assert_eq!(
+            sse_events(&[&bytes[..cut], &bytes[cut..]]),
+            whole,
+            "cut at {}",
+            cut
+        );
+    }
+    // One byte at a time.
+    let singles: Vec<&[u8]> = bytes.chunks(1).collect();
+    assert_eq!(sse_events(&singles), whole);
+}
+
+#[test]
+fn test_sse_multiline_data_and_unterminated_tail() {
+    let events = sse_events(&[b"data: line one\ndata: line two\n\ndata: tail"]);
+    assert_eq!(events, vec!["line one\nline two", "tail"]);
+}
+
+#[test]
+fn test_json_chunks_split_at_every_byte() {
+    let bytes = GEMINI_ARRAY.as_bytes();
+    let whole = json_objects(&[bytes]);
+    assert_eq!(whole.len(), 3);
+    // Braces and escaped quotes inside strings do not end an object.
+    assert!(whole[1].contains(r#"\"lit\" {}"#));
+    for text in &whole {
+        serde_json::from_str::<serde_json::Value>(text).unwrap();
+    }
+
+    for cut in 0..=bytes.len() {
+        assert_eq!(
+            json_objects(&[&bytes[..cut], &bytes[cut..]]),
+            whole,
+            "cut at {}",
+            cut
+        );
+    }
+    let threes: Vec<&[u8]> = bytes.chunks(3).collect();
+    assert_eq!(json_objects(&threes), whole);
+}
+
+#[test]
+fn test_json_chunks_report_truncation() {
+    let mut decoder = JsonChunkDecoder::new();
+    assert!(decoder.push(b"[{\"a\": \"}").is_empty());
+    assert!(decoder.is_incomplete());
+}
+
+/// Serves `body` in `cut`-byte pieces, a few milliseconds apart.
+async fn serve_stream(body: &'static str, content_type: &'static str, cut: usize) -> String {
+    let app = Router::new().fallback(post(move || async move {
+        let pieces: Vec<Vec<u8>> = body.as_bytes().chunks(cut).map(<[u8]>::to_vec).collect();
+        let chunks = futures::stream::iter(pieces).then(|piece| async move {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            Ok::<_, std::io::Error>(piece)
+        });
+        Response::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from_stream(chunks))
+            .unwrap()
+    }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+fn vertex(base: &str) -> VertexGemini {
+    VertexGemini::with_config(VertexConfig {
+        stream_url: format!("{}/stream", base),
+        token: TokenSource::Static("secret".to_string()),
+        retry: RetryPolicy::none(),
+        ..VertexConfig::default()
+    })
+    .unwrap()
+}
+
+fn history() -> Vec<Content> {
+    vec![Content {
+        role: "user".to_string(),
+        parts: vec![Part::text("status".to_string())],
+    }]
+}
+
+async fn deltas(llm: &dyn LlmProvider) -> Vec<Result<Delta, LlmError>> {
+    llm.generate_stream(&history()).collect().await
+}
+
+fn gemini_expected() -> Vec<Result<Delta, LlmError>> {
+    vec![
+        Ok(Delta::Text("Ωmega ".to_string())),
+        Ok(Delta::Text("🜂 \"lit\" {}".to_string())),
+        Ok(Delta::FunctionCall {
+            index: 0,
+            id: None,
+            name: Some("ignite".to_string()),
+            arguments: r#"{"core":1}"#.to_string(),
+        }),
+        Ok(Delta::Usage(UsageMetadata {
+            prompt_token_count: Some(9),
+            candidates_token_count: Some(5),
+            total_token_count: Some(14),
+        })),
+    ]
+}
+
+#[tokio::test]
+async fn test_vertex_streams_sse() {
+    let base = serve_stream(GEMINI_SSE, "text/event-stream", 7).await;
+    assert_eq!(deltas(&vertex(&base)).await, gemini_expected());
+}
+
+#[tokio::test]
+async fn test_vertex_streams_json_array() {
+    let base = serve_stream(GEMINI_ARRAY, "application/json; charset=UTF-8", 5).await;
+    assert_eq!(deltas(&vertex(&base)).await, gemini_expected());
+}
+
+#[tokio::test]
+async fn test_truncated_stream_ends_with_decode_error() {
+    let base = serve_stream(&GEMINI_ARRAY[..60], "application/json", 16).await;
+    let got = deltas(&vertex(&base)).await;
+    assert!(matches!(got.last(), Some(Err(LlmError::Decode(_)))));
+}
+
+#[tokio::test]
+async fn test_openai_streams_text_tool_call_and_usage() {
+    let base = serve_stream(OPENAI_SSE, "text/event-stream", 11).await;
+    let llm = OpenAiCompatible::new(OpenAiConfig {
+        base_url: base,
+        retry: RetryPolicy::none(),
+        ..OpenAiConfig::default()
+    })
+    .unwrap();
+
+    let got: Vec<Delta> = deltas(&llm).await.into_iter().map(Result::unwrap).collect();
+    assert_eq!(got[0], Delta::Text("Scanning".to_string()));
+    let arguments: String = got
+        .iter()
+        .filter_map(|d| match d {
+            Delta::FunctionCall {
+                index: 0,
+                arguments,
+                ..
+            } => Some(arguments.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(arguments, r#"{"depth":2}"#);
+    assert!(matches!(
+        &got[1],
+        Delta::FunctionCall { id: Some(id), name: Some(name), .. } if id == "call_1" && name == "scan"
+    ));
+    assert_eq!(
+        got.last(),
+        Some(&Delta::Usage(UsageMetadata {
+            prompt_token_count: Some(4),
+            candidates_token_count: Some(6),
+            total_token_count: Some(10),
+        }))
+    );
+}
+
+#[tokio::test]
+async fn test_collect_stream_forwards_every_delta() {
+    let base = serve_stream(GEMINI_SSE, "text/event-stream", 13).await;
+    let llm = vertex(&base);
+
+    let mut seen = Vec::new();
+    let (text, usage) = collect_stream(llm.generate_stream(&history()), |d| seen.push(d.clone()))
+        .await
+        .unwrap();
+    assert_eq!(text, "Ωmega 🜂 \"lit\" {}");
+    assert_eq!(usage.unwrap().total_token_count, Some(14));
+    assert_eq!(seen.len(), 4);
+}
+
+#[tokio::test]
+async fn test_non_streaming_provider_falls_back_to_one_delta() {
+    let usage = UsageMetadata {
+        total_token_count: Some(3),
+        ..UsageMetadata::default()
+    };
+    let mock = MockProvider::new().with_reply("whole answer", usage.clone());
+    assert_eq!(
+        deltas(&mock).await,
+        vec![
+            Ok(Delta::Text("whole answer".to_string())),
+            Ok(Delta::Usage(usage)),
+        ]
+    );
+    assert_eq!(
+        collect_stream(mock.generate_stream(&history()), |_| {}).await,
+        Err(LlmError::Empty)
+    );
+}
@@ -0,0 +1,273 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use async_channel::Receiver;
+use gneiss_pal::supervisor::{Manifest, RestartPolicy, Supervisor};
+use gneiss_pal::{GuiUpdate, ShardRole, ShardStatus};
+use std::time::Duration;
+
+#[path = "helpers/shard_cue.rs"]
+mod shard_cue;
+
+/// Set on the shard's environment to the cue arguments, tab separated.
+const CUE_VAR: &str = "SHARD_CUE";
+
+/// Not a test: the body of the stand-in shard. `manifest` runs this test
+/// binary again, filtered down to this entry and with `SHARD_CUE` set.
+/// `--quiet` keeps the harness off the heartbeat lines.
+#[test]
+fn shard_cue_entry() {
+    if let Ok(args) = std::env::var(CUE_VAR) {
+        shard_cue::run(&args.split('\t').collect::<Vec<_>>());
+    }
+}
+
+/// A manifest with one shard playing `shard_cue` with `args`, restarted
+/// quickly, and declared hung after 300ms of silence.
+fn manifest(args: &[&str], max_restarts: u32) -> Manifest {
+    let exe = std::env::current_exe().unwrap().display().to_string();
+    Manifest::from_toml(&format!(
+        r#"
+        heartbeat_timeout_ms = 300
+
+        [[shard]]
+        id = "cue"
+        role = "Builder"
+        command = {:?}
+        args = ["shard_cue_entry", "--exact", "--nocapture", "--quiet"]
+        env = {{ {} = {:?} }}
+
+        [policy.Builder]
+        initial_backoff_ms = 10
+        max_backoff_ms = 40
+        max_restarts = {}
+        "#,
+        exe,
+        CUE_VAR,
+        args.join("\t"),
+        max_restarts
+    ))
+    .unwrap()
+}
+
+/// Every status the bus announced until it went quiet for `quiet`.
+async fn statuses(bus: &Receiver<GuiUpdate>, quiet: Duration) -> Vec<ShardStatus> {
+    let mut seen = Vec::new();
+    while let Ok(Ok(update)) = tokio::time::timeout(quiet, bus.recv()).await {
+        if let GuiUpdate::ShardStatusChanged { id, status } = update {
+            assert_eq!(id, "cue");
+            seen.push(status);
+        }
+    }
+    seen
+}
+
+#[test]
+fn test_manifest_policies() {
+    let manifest = Manifest::from_toml(
+        r#"
+        [[shard]]
+        id = "una-prime"
+        name = "Una-Prime"
+        role = "Root"
+        command = "una-prime"
+        env = { RUST_LOG = "debug" }
+
+        [[shard]]
+        id = "probe"
+        role = "Kernel"
+        command = "bin/probe"
+        heartbeat_timeout_ms = 100
+
+        [policy.Root]
+        max_restarts = 2
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(manifest.shards[0].name, "Una-Prime");
+    assert_eq!(manifest.shards[0].env["RUST_LOG"], "debug");
+    assert_eq!(manifest.shards[0].heartbeat_timeout, Duration::from_secs(5));
+    assert_eq!(manifest.shards[1].name, "probe");
+    assert_eq!(
+        manifest.shards[1].heartbeat_timeout,
+        Duration::from_millis(100)
+    );
+
+    // Overrides sit on top of the role's defaults.
+    let root = manifest.policy_for(&ShardRole::Root);
+    assert_eq!(root.max_restarts, 2);
+    assert_eq!(
+        root.initial_backoff,
+        RestartPolicy::for_role(&ShardRole::Root).initial_backoff
+    );
+    assert_eq!(
+        manifest.policy_for(&ShardRole::Kernel),
+        RestartPolicy::for_role(&ShardRole::Kernel)
+    );
+
+    let policy = RestartPolicy::for_role(&ShardRole::Builder);
+    assert_eq!(policy.backoff(1), Duration::from_secs(1));
+    assert_eq!(policy.backoff(3), Duration::from_secs(4));
+    assert_eq!(policy.backoff(30), policy.max_backoff);
+}
+
+#[test]
+fn test_manifest_rejects_bad_input() {
+    let twice = r#"
+        [[shard]]
+        id = "a"
+        role = "Root"
+        command = "a"
+
+        [[shard]]
+        id = "a"
+        role = "Storage"
+        command = "b"
+    "#;
+    assert!(
+        Manifest::from_toml(twice)
+            .unwrap_err()
+            .contains("listed twice")
+    );
+    assert!(
+        Manifest::from_toml("[[shard]]\nid = \"a\"\nrole = \"Wizard\"\ncommand = \"a\"").is_err()
+    );
+}
+
+#[test]
+fn test_manifest_load_resolves_relative_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("shards.toml");
+    std::fs::write(
+        &path,
+        "[[shard]]\nid = \"a\"\nrole = \"Storage\"\ncommand = \"bin/mule\"\n\n\
+         [[shard]]\nid = \"b\"\nrole = \"Storage\"\ncommand = \"mule\"\n",
+    )
+    .unwrap();
+    let manifest = Manifest::load(&path).unwrap();
+    assert_eq!(manifest.shards[0].command, dir.path().join("bin/mule"));
+    assert_eq!(manifest.shards[1].command, std::path::PathBuf::from("mule"));
+}
+
+#[tokio::test]
+async fn test_clean_exit_is_not_restarted() {
+    let (tx, rx) = async_channel::unbounded();
+    let supervisor = Supervisor::start(manifest(&["exit", "2"], 3), tx);
+    // Shards start out Offline, so wait for the bus rather than the status.
+    let seen = statuses(&rx, Duration::from_secs(2)).await;
+    assert_eq!(seen, vec![ShardStatus::Online, ShardStatus::Offline]);
+
+    let shard = supervisor.shards().remove(0);
+    assert_eq!(shard.status, ShardStatus::Offline);
+    assert_eq!(shard.cpu_load, 7);
+    assert_eq!(supervisor.restarts("cue"), Some(0));
+    tokio::time::timeout(Duration::from_secs(1), supervisor.join())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_crash_loop_escalates_to_failed() {
+    let (tx, rx) = async_channel::unbounded();
+    let supervisor = Supervisor::start(manifest(&["crash", "1"], 2), tx);
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while supervisor.status("cue") != Some(ShardStatus::Failed) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(supervisor.restarts("cue"), Some(2));
+    supervisor.join().await;
+
+    use ShardStatus::*;
+    assert_eq!(
+        statuses(&rx, Duration::from_millis(100)).await,
+        vec![Online, Error, Online, Error, Online, Failed]
+    );
+}
+
+#[tokio::test]
+async fn test_silent_shard_is_killed_and_restarted() {
+    let (tx, rx) = async_channel::unbounded();
+    let supervisor = Supervisor::start(manifest(&["hang", "1"], 5), tx);
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while supervisor.restarts("cue") < Some(1) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    supervisor.shutdown().await;
+
+    let seen = statuses(&rx, Duration::from_millis(100)).await;
+    assert_eq!(&seen[..2], &[ShardStatus::Online, ShardStatus::Error]);
+}
+
+#[tokio::test]
+async fn test_flaky_shard_recovers() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = dir.path().join("runs");
+    let state = state.to_str().unwrap();
+    let (tx, rx) = async_channel::unbounded();
+    let supervisor = Supervisor::start(manifest(&["flaky", "2", state], 3), tx);
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while supervisor.restarts("cue") < Some(2) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    // Outlives the heartbeat timeout, so it is really beating.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(supervisor.status("cue"), Some(ShardStatus::Online));
+    assert_eq!(supervisor.restarts("cue"), Some(2));
+    supervisor.shutdown().await;
+
+    use ShardStatus::*;
+    assert_eq!(
+        statuses(&rx, Duration::from_millis(100)).await,
+        vec![Online, Error, Online, Error, Online]
+    );
+}
+
+#[tokio::test]
+async fn test_missing_binary_counts_as_crash() {
+    let manifest = Manifest::from_toml(
+        r#"
+        [[shard]]
+        id = "cue"
+        role = "Unknown"
+        command = "/nonexistent/shard"
+
+        [policy.Unknown]
+        initial_backoff_ms = 1
+        max_restarts = 1
+        "#,
+    )
+    .unwrap();
+    let (tx, rx) = async_channel::unbounded();
+    let supervisor = Supervisor::start(manifest, tx);
+    tokio::time::timeout(Duration::from_secs(10), supervisor.join())
+        .await
+        .unwrap();
+    assert_eq!(
+        statuses(&rx, Duration::from_millis(100)).await,
+        vec![ShardStatus::Error, ShardStatus::Failed]
+    );
+}
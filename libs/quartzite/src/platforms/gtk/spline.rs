@@ -73,6 +73,58 @@ fn enable_spelling(view: &SourceView) {
 use gneiss_pal::shard::ShardStatus;
 use gneiss_pal::{GuiUpdate, WolfpackState};
 
+/// The console bubble of the answer being streamed, shared by both UIs.
+struct LiveAnswer {
+    store: gio::ListStore,
+    /// The bubble and where it was last seen in `store`.
+    row: RefCell<Option<(DispatchObject, u32)>>,
+}
+
+impl LiveAnswer {
+    fn new(store: gio::ListStore) -> Self {
+        Self {
+            store,
+            row: RefCell::new(None),
+        }
+    }
+
+    /// Grows the bubble by `text`, opening it on the first piece.
+    fn push(&self, text: &str) {
+        let mut row = self.row.borrow_mut();
+        let (obj, position) = row.get_or_insert_with(|| {
+            let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
+            let id = format!("{}", chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0));
+            let obj = DispatchObject::new(&id, "Una-Prime", "Log", &timestamp, "", true);
+            self.store.append(&obj);
+            (obj, self.store.n_items() - 1)
+        });
+        obj.set_content(format!("{}{}", obj.content(), text));
+        if let Some(found) = self.locate(obj, *position) {
+            *position = found;
+            self.store.items_changed(found, 1, 1);
+        }
+    }
+
+    /// Drops the bubble: the whole answer follows as a `ConsoleLog`.
+    fn end(&self) {
+        if let Some((obj, position)) = self.row.borrow_mut().take()
+            && let Some(found) = self.locate(&obj, position)
+        {
+            self.store.remove(found);
+        }
+    }
+
+    /// Where `obj` is now. Rows above it may have come or gone since.
+    fn locate(&self, obj: &DispatchObject, position: u32) -> Option<u32> {
+        let unmoved = self.store.item(position).and_downcast::<DispatchObject>();
+        if unmoved.as_ref() == Some(obj) {
+            Some(position)
+        } else {
+            self.store.find(obj)
+        }
+    }
+}
+
 
 pub struct CommsSpline {}
 
@@ -1276,6 +1328,7 @@ fn build_gnome_ui(
     let console_store_async = console_store.clone();
 
     glib::MainContext::default().spawn_local(async move {
+        let live_answer = LiveAnswer::new(console_store_async.clone());
         while let Ok(update) = rx.recv().await {
             match update {
                 GuiUpdate::ConsoleLog(text) => {
@@ -1437,6 +1490,8 @@ fn build_gnome_ui(
                     let err_obj = DispatchObject::new(&id, "System Error", "Log", &timestamp, &err_msg, true);
                     console_store_async.append(&err_obj);
                 }
+                GuiUpdate::StreamDelta(text) => live_answer.push(&text),
+                GuiUpdate::StreamEnd => live_answer.end(),
                 _ => {}
             }
         }
@@ -2627,6 +2682,7 @@ fn build_gtk_ui(
     let tx_interceptor_async = tx_event.clone();
 
     glib::MainContext::default().spawn_local(async move {
+        let live_answer = LiveAnswer::new(console_store_async.clone());
         while let Ok(update) = rx.recv().await {
             match update {
                 GuiUpdate::ConsoleLog(text) => {
@@ -2788,6 +2844,8 @@ fn build_gtk_ui(
                     let err_obj = DispatchObject::new(&id, "System Error", "Log", &timestamp, &err_msg, true);
                     console_store_async.append(&err_obj);
                 }
+                GuiUpdate::StreamDelta(text) => live_answer.push(&text),
+                GuiUpdate::StreamEnd => live_answer.end(),
                 _ => {}
             }
         }
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use resonance::{AudioCommand, AudioEngine, create_test_graph};
+use resonance::{AudioEngine, NodeId, create_test_graph};
 use std::io::{self, Write};
 use std::thread;
 use std::time::Duration;
@@ -26,8 +26,8 @@ fn main() -> Result<(), anyhow::Error> {
     let graph = create_test_graph();
 
     // Start the engine
-    // We get back the engine (to keep stream alive) and the producer (to send commands)
-    let (_engine, mut producer) = AudioEngine::new(graph)?;
+    // We get back the engine (to keep stream alive) and the controller (to send commands)
+    let (_engine, mut controller) = AudioEngine::new(graph)?;
 
     println!("Audio Engine started. Playing 440Hz tone.");
     println!("Commands:");
@@ -54,16 +54,14 @@ fn main() -> Result<(), anyhow::Error> {
         let trimmed = input.trim();
         if trimmed.eq_ignore_ascii_case("stop") || trimmed.eq_ignore_ascii_case("exit") {
             // Send stop command just in case (though we exit process)
-            let _ = producer.push(AudioCommand::Stop);
+            let _ = controller.stop();
             break;
         }
 
         if let Ok(freq) = trimmed.parse::<f64>() {
             println!("Setting frequency to {:.2} Hz", freq);
-            if producer
-                .push(AudioCommand::SetMasterFrequency(freq))
-                .is_err()
-            {
+            // Glide over 50 ms rather than jumping, which would click.
+            if controller.ramp_param(NodeId(0), 0, freq, 2205).is_err() {
                 eprintln!("Command queue full!");
             }
         } else {
@@ -14,12 +14,11 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::commands::AudioCommand;
-use crate::graph::AudioGraph;
+use crate::graph::{AudioGraph, ChannelMix, mix_channels};
+use crate::live::{GraphController, GraphProcessor};
 use crate::{BLOCK_SIZE, Sample};
 use bandy::{BandyMember, SMessage};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use ringbuf::{HeapRb, traits::*};
 
 /// The engine that manages the audio driver and drives the graph.
 pub struct AudioEngine {
@@ -35,10 +34,9 @@ impl AudioEngine {
     /// This function initializes the default host and output device, configures
     /// the stream, and starts the processing loop.
     ///
-    /// Returns the engine instance (which must be kept alive) and a command producer.
-    pub fn new(
-        mut graph: AudioGraph,
-    ) -> Result<(Self, impl Producer<Item = AudioCommand>), anyhow::Error> {
+    /// Returns the engine instance (which must be kept alive) and the
+    /// controller for editing the graph while it plays.
+    pub fn new(graph: AudioGraph) -> Result<(Self, GraphController), anyhow::Error> {
         let host = cpal::default_host();
 
         let device = host
@@ -53,15 +51,15 @@ impl AudioEngine {
         let channels = config.channels as usize;
 
         // Command Channel
-        // Create a ring buffer for commands.
-        let ring = HeapRb::<AudioCommand>::new(128);
-        let (producer, mut consumer) = ring.split();
+        // The processor half of the graph moves into the closure; the
+        // controller sends it commands over a lock-free queue.
+        let (controller, mut processor) = graph.into_live(128);
 
         // State for the callback
-        // We move the graph into the closure.
         // We need a cursor to track where we are in the current block.
+        // One block per device channel, allocated here rather than in the callback.
         let mut block_offset = BLOCK_SIZE;
-        let mut current_block = [0.0; BLOCK_SIZE];
+        let mut current_block = vec![[0.0; BLOCK_SIZE]; channels];
 
         let err_fn = |err| eprintln!("an error occurred on stream: {}", err);
 
@@ -72,10 +70,9 @@ impl AudioEngine {
                     write_output_f32(
                         data,
                         channels,
-                        &mut graph,
+                        &mut processor,
                         &mut current_block,
                         &mut block_offset,
-                        &mut consumer,
                     );
                 },
                 err_fn,
@@ -97,7 +94,7 @@ impl AudioEngine {
                 sample_rate,
                 is_active: true,
             },
-            producer,
+            controller,
         ))
     }
 
@@ -135,56 +132,28 @@ impl BandyMember for AudioEngine {
 fn write_output_f32(
     output: &mut [f32],
     channels: usize,
-    graph: &mut AudioGraph,
-    current_block: &mut [Sample; BLOCK_SIZE],
+    processor: &mut GraphProcessor,
+    current_block: &mut [[Sample; BLOCK_SIZE]],
     block_offset: &mut usize,
-    consumer: &mut impl Consumer<Item = AudioCommand>,
 ) {
     // Iterate over frames (chunks of samples, one per channel)
     for frame in output.chunks_mut(channels) {
         // If we have exhausted the current block, generate a new one
         if *block_offset >= BLOCK_SIZE {
-            // 1. Process Commands (Before generating the next block)
-            process_commands(graph, consumer);
-
-            // 2. Generate Audio
-            let processed = graph.process();
-            // Copy to our local cache because 'processed' is a reference to graph internal memory
-            current_block.copy_from_slice(processed);
+            // Generate Audio (pending commands are applied first).
+            let processed = processor.render();
+            // Copy to our local cache because 'processed' is a reference to graph internal memory.
+            // The graph's channels are mixed onto the device's (mono goes to every speaker).
+            mix_channels(processed, current_block, ChannelMix::Speakers);
             *block_offset = 0;
         }
 
-        // Get sample from current block
+        // Get one sample per channel from the current block
         // Convert f64 -> f32
-        let sample = current_block[*block_offset] as f32;
-        *block_offset += 1;
-
-        // Write to all channels in the frame
-        for sample_out in frame.iter_mut() {
-            *sample_out = sample;
-        }
-    }
-}
-
-/// Consumes commands from the ring buffer and applies them to the graph.
-fn process_commands(
-    _graph: &mut AudioGraph,
-    consumer: &mut impl Consumer<Item = AudioCommand>,
-) {
-    while let Some(cmd) = consumer.try_pop() {
-        match cmd {
-            AudioCommand::SetMasterFrequency(_freq) => {
-                // _graph.set_node_param(crate::NodeId(0), 0, _freq);
-            }
-            AudioCommand::SetParam {
-                node_id: _node_id,
-                param_id: _param_id,
-                value: _value,
-            } => {
-                // _graph.set_node_param(crate::NodeId(_node_id), _param_id, _value);
-            }
-            AudioCommand::Stop => {}
+        for (sample_out, channel) in frame.iter_mut().zip(current_block.iter()) {
+            *sample_out = channel[*block_offset] as f32;
         }
+        *block_offset += 1;
     }
 }
 
@@ -205,7 +174,9 @@ pub fn create_test_graph() -> AudioGraph {
     let gain_id = graph.add_node(gain);
 
     // Connect Osc -> Gain
-    graph.connect(osc_id, gain_id, 0);
+    graph
+        .connect(osc_id, gain_id, 0)
+        .expect("two nodes cannot form a cycle");
 
     graph
 }
@@ -14,8 +14,13 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::graph::{NodeSlot, NodeTable, Topology};
+use std::fmt;
+
 /// Commands sent from the UI thread to the Audio Engine.
-#[derive(Debug, Clone, Copy, PartialEq)]
+///
+/// Structural commands carry everything already built, so applying one only
+/// moves pointers. Build them through `GraphController` rather than by hand.
 pub enum AudioCommand {
     /// Update a specific parameter on a specific node.
     ///
@@ -29,10 +34,90 @@ pub enum AudioCommand {
         value: f64,
     },
 
+    /// Move a parameter linearly to `value` over `frames` samples.
+    ///
+    /// Commands are applied between blocks, so the ramp starts on the first
+    /// sample of the next block; there is no offset within a block, and the
+    /// start may land up to `BLOCK_SIZE` frames after the command was sent.
+    RampParam {
+        node_id: usize,
+        param_id: usize,
+        value: f64,
+        frames: usize,
+    },
+
     /// Stop the audio engine immediately (panic button).
     Stop,
 
     /// Update the master frequency (assumes Node 0 is an oscillator).
     /// For the prototype: Just change the oscillator pitch.
     SetMasterFrequency(f64),
+
+    /// Put a prepared node into its slot. The slot must already exist.
+    AddNode { node_id: usize, slot: NodeSlot },
+
+    /// Take a node out; it is handed back to the control thread.
+    RemoveNode { node_id: usize },
+
+    /// Swap in a new processing order and set of buffers.
+    Rewire(Box<Topology>),
+
+    /// Swap in a larger, empty node table; the nodes move across.
+    Reserve(NodeTable),
+}
+
+impl AudioCommand {
+    /// Whether applying this command hands something back to be freed.
+    pub(crate) fn returns_garbage(&self) -> bool {
+        matches!(
+            self,
+            AudioCommand::RemoveNode { .. } | AudioCommand::Rewire(_) | AudioCommand::Reserve(_)
+        )
+    }
+}
+
+impl fmt::Debug for AudioCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioCommand::SetParam {
+                node_id,
+                param_id,
+                value,
+            } => f
+                .debug_struct("SetParam")
+                .field("node_id", node_id)
+                .field("param_id", param_id)
+                .field("value", value)
+                .finish(),
+            AudioCommand::RampParam {
+                node_id,
+                param_id,
+                value,
+                frames,
+            } => f
+                .debug_struct("RampParam")
+                .field("node_id", node_id)
+                .field("param_id", param_id)
+                .field("value", value)
+                .field("frames", frames)
+                .finish(),
+            AudioCommand::Stop => f.write_str("Stop"),
+            AudioCommand::SetMasterFrequency(hz) => {
+                f.debug_tuple("SetMasterFrequency").field(hz).finish()
+            }
+            AudioCommand::AddNode { node_id, slot } => f
+                .debug_struct("AddNode")
+                .field("node_id", node_id)
+                .field("slot", slot)
+                .finish(),
+            AudioCommand::RemoveNode { node_id } => f
+                .debug_struct("RemoveNode")
+                .field("node_id", node_id)
+                .finish(),
+            AudioCommand::Rewire(topology) => f.debug_tuple("Rewire").field(topology).finish(),
+            AudioCommand::Reserve(table) => {
+                f.debug_tuple("Reserve").field(&table.0.capacity()).finish()
+            }
+        }
+    }
 }
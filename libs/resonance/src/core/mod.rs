@@ -36,6 +36,10 @@ impl GraphContext {
 pub trait AudioNode {
     /// Process a block of audio.
     ///
+    /// Ports are laid out flat, one buffer per channel: every channel of
+    /// input port 0, then every channel of port 1, and so on. The graph
+    /// passes ports up to the highest one connected; gaps are silence.
+    ///
     /// # Arguments
     ///
     /// * `inputs` - A slice of references to input buffers. Each buffer is a fixed-size array of `BLOCK_SIZE` samples.
@@ -56,4 +60,102 @@ pub trait AudioNode {
     fn set_param(&mut self, _id: usize, _value: f64) {
         // Default implementation does nothing.
     }
+
+    /// Moves a parameter linearly to `value` over `frames` samples,
+    /// starting with the next block (see `ParamRamp`). Nodes without
+    /// sample-accurate parameters jump straight to the value.
+    fn ramp_param(&mut self, id: usize, value: f64, _frames: usize) {
+        self.set_param(id, value);
+    }
+
+    /// Input ports with a channel count of their own; any port past these
+    /// takes the last one's count. Port layouts are read once, when the node
+    /// is added to a graph.
+    fn input_ports(&self) -> usize {
+        1
+    }
+
+    /// Channels expected on input `port`. Whatever is connected there is
+    /// up- or down-mixed to this count by the graph.
+    fn input_channels(&self, _port: usize) -> usize {
+        1
+    }
+
+    /// Number of output ports.
+    fn output_ports(&self) -> usize {
+        1
+    }
+
+    /// Channels produced on output `port`.
+    fn output_channels(&self, _port: usize) -> usize {
+        1
+    }
+
+    /// A node answering true outputs what it was fed during the previous
+    /// block. The graph runs such nodes ahead of everything else and feeds
+    /// them afterwards, which is the only way a connection may close a cycle.
+    fn is_block_delay(&self) -> bool {
+        false
+    }
+}
+
+/// A linear parameter ramp, advanced once per sample.
+///
+/// The first call to `advance` already takes one step, and the `frames`-th
+/// lands exactly on the target, so a ramp over a block's worth of frames
+/// reaches its value on the block's last sample.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ParamRamp {
+    value: f64,
+    target: f64,
+    step: f64,
+    remaining: usize,
+}
+
+impl ParamRamp {
+    /// A ramp from `from` to `to` over `frames` samples. With zero frames the
+    /// value is already `to`.
+    pub fn new(from: f64, to: f64, frames: usize) -> Self {
+        if frames == 0 {
+            return Self::hold(to);
+        }
+        Self {
+            value: from,
+            target: to,
+            step: (to - from) / frames as f64,
+            remaining: frames,
+        }
+    }
+
+    /// A ramp that has arrived at `value` and stays there.
+    pub fn hold(value: f64) -> Self {
+        Self {
+            value,
+            target: value,
+            step: 0.0,
+            remaining: 0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// The current value, without advancing.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Advances one sample and returns the new value.
+    pub fn advance(&mut self) -> f64 {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            self.value = if self.remaining == 0 {
+                self.target
+            } else {
+                self.value + self.step
+            };
+        }
+        self.value
+    }
 }
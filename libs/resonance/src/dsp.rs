@@ -14,9 +14,15 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+//! Spectral tools: a radix-2 FFT, its real-input form, analysis windows
+//! and a short-time transform with overlap-add resynthesis.
+//!
+//! Everything is `f32` and allocates only on construction, so the
+//! per-frame methods are safe to call from an audio callback.
+
 use std::f32::consts::PI;
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Complex {
     pub re: f32,
     pub im: f32,
@@ -26,6 +32,15 @@ impl Complex {
     pub fn new(re: f32, im: f32) -> Self {
         Self { re, im }
     }
+
+    pub fn conj(self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+
+    /// The magnitude.
+    pub fn norm(self) -> f32 {
+        self.re.hypot(self.im)
+    }
 }
 
 pub struct FftContext {
@@ -41,14 +56,14 @@ impl FftContext {
         // Pre-compute bit reversal
         let mut rev_table = vec![0; size];
         let bits = size.trailing_zeros();
-        for i in 0..size {
+        for (i, rev) in rev_table.iter_mut().enumerate() {
             let mut r = 0;
             for j in 0..bits {
                 if (i >> j) & 1 == 1 {
                     r |= 1 << (bits - 1 - j);
                 }
             }
-            rev_table[i] = r;
+            *rev = r;
         }
 
         // Pre-compute twiddles
@@ -67,7 +82,27 @@ impl FftContext {
         }
     }
 
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The forward transform, in place and unscaled.
     pub fn process(&self, buffer: &mut [Complex]) {
+        self.transform(buffer, false);
+    }
+
+    /// The inverse transform, in place and scaled by 1/N, so that it undoes
+    /// `process` exactly (up to rounding).
+    pub fn inverse(&self, buffer: &mut [Complex]) {
+        self.transform(buffer, true);
+        let scale = 1.0 / self.size as f32;
+        for c in buffer.iter_mut() {
+            c.re *= scale;
+            c.im *= scale;
+        }
+    }
+
+    fn transform(&self, buffer: &mut [Complex], inverse: bool) {
         assert_eq!(buffer.len(), self.size);
 
         // Bit-reversal permutation
@@ -89,8 +124,10 @@ impl FftContext {
                     // Twiddle factor: W_m^j = exp(-2pi i j / m)
                     // We precomputed W_N^k.
                     // We need to map index j in m-sized FFT to index in N-sized twiddles.
+                    // The inverse turns the other way round the circle.
                     let tw_idx = j * step;
                     let w = self.twiddles[tw_idx];
+                    let w = if inverse { w.conj() } else { w };
 
                     let u = buffer[k + j];
                     let t = complex_mul(w, buffer[k + j + mh]);
@@ -104,6 +141,349 @@ impl FftContext {
     }
 }
 
+/// An FFT of real input, at about half the cost of the complex one.
+///
+/// The N real samples are packed into N/2 complex ones (evens real, odds
+/// imaginary), transformed at half size, then untangled into the N/2 + 1
+/// non-redundant bins from DC to Nyquist.
+pub struct RealFft {
+    size: usize,
+    half: FftContext,
+    /// exp(-2 pi i k / N) for k up to N/2.
+    twiddles: Vec<Complex>,
+}
+
+impl RealFft {
+    pub fn new(size: usize) -> Self {
+        assert!(
+            size.is_power_of_two() && size >= 2,
+            "real FFT size must be a power of two, at least 2"
+        );
+        let twiddles = (0..=size / 2)
+            .map(|k| {
+                let (s, c) = (-2.0 * PI * k as f32 / size as f32).sin_cos();
+                Complex::new(c, s)
+            })
+            .collect();
+        Self {
+            size,
+            half: FftContext::new(size / 2),
+            twiddles,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Bins produced: DC to Nyquist inclusive.
+    pub fn bins(&self) -> usize {
+        self.size / 2 + 1
+    }
+
+    /// Transforms `input` (N samples) into `spectrum` (N/2 + 1 bins),
+    /// unscaled like `FftContext::process`.
+    pub fn forward(&self, input: &[f32], spectrum: &mut [Complex]) {
+        assert_eq!(input.len(), self.size);
+        assert_eq!(spectrum.len(), self.bins());
+        let m = self.size / 2;
+
+        let (pairs, _) = input.as_chunks::<2>();
+        for (packed, &[re, im]) in spectrum[..m].iter_mut().zip(pairs) {
+            *packed = Complex::new(re, im);
+        }
+        self.half.process(&mut spectrum[..m]);
+
+        // Z[k] and Z[M-k] give the transforms of the evens (E) and odds (O):
+        // E = (Z[k] + Z*[M-k]) / 2, O = (Z[k] - Z*[M-k]) / 2i, and
+        // X[k] = E + W^k O. Bins k and M-k are done together, in place.
+        spectrum[m] = spectrum[0];
+        for k in 0..=m / 2 {
+            let (a, b) = (spectrum[k], spectrum[m - k]);
+            spectrum[k] = self.untangle(a, b, k);
+            if k != m - k {
+                spectrum[m - k] = self.untangle(b, a, m - k);
+            }
+        }
+    }
+
+    fn untangle(&self, z: Complex, mirror: Complex, k: usize) -> Complex {
+        let mirror = mirror.conj();
+        let even = complex_scale(complex_add(z, mirror), 0.5);
+        let diff = complex_sub(z, mirror);
+        // diff / 2i
+        let odd = Complex::new(diff.im * 0.5, -diff.re * 0.5);
+        complex_add(even, complex_mul(self.twiddles[k], odd))
+    }
+
+    /// Transforms `spectrum` (N/2 + 1 bins) back into `output` (N samples),
+    /// scaled to undo `forward`. The spectrum is used as scratch space.
+    pub fn inverse(&self, spectrum: &mut [Complex], output: &mut [f32]) {
+        assert_eq!(spectrum.len(), self.bins());
+        assert_eq!(output.len(), self.size);
+        let m = self.size / 2;
+
+        // The reverse of `forward`: E = (X[k] + X*[M-k]) / 2,
+        // O = (X[k] - X*[M-k]) W^-k / 2, Z[k] = E + iO.
+        for k in 0..=m / 2 {
+            let (a, b) = (spectrum[k], spectrum[m - k]);
+            spectrum[k] = self.tangle(a, b, k);
+            if k != m - k {
+                spectrum[m - k] = self.tangle(b, a, m - k);
+            }
+        }
+        self.half.inverse(&mut spectrum[..m]);
+
+        let (pairs, _) = output.as_chunks_mut::<2>();
+        for (pair, packed) in pairs.iter_mut().zip(&spectrum[..m]) {
+            *pair = [packed.re, packed.im];
+        }
+    }
+
+    fn tangle(&self, x: Complex, mirror: Complex, k: usize) -> Complex {
+        let mirror = mirror.conj();
+        let even = complex_scale(complex_add(x, mirror), 0.5);
+        let diff = complex_scale(complex_sub(x, mirror), 0.5);
+        let odd = complex_mul(self.twiddles[k].conj(), diff);
+        // even + i * odd
+        Complex::new(even.re - odd.im, even.im + odd.re)
+    }
+}
+
+/// Analysis windows. `coefficients` gives the periodic (DFT-even) form,
+/// which is the one that overlap-adds to a constant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+    /// The four-term Blackman-Harris: sidelobes below -92 dB.
+    BlackmanHarris,
+    /// Kaiser with shape `beta`: 0 is rectangular, larger trades main-lobe
+    /// width for lower sidelobes (about 8.6 gives -90 dB).
+    Kaiser(f32),
+}
+
+impl Window {
+    pub fn coefficients(&self, size: usize) -> Vec<f32> {
+        let n = size as f64;
+        // a0 - a1 cos(x) + a2 cos(2x) - ...
+        let cosines = |terms: &[f64]| -> Vec<f32> {
+            (0..size)
+                .map(|i| {
+                    let x = 2.0 * std::f64::consts::PI * i as f64 / n;
+                    let mut w = 0.0;
+                    for (k, &a) in terms.iter().enumerate() {
+                        let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                        w += sign * a * (k as f64 * x).cos();
+                    }
+                    w as f32
+                })
+                .collect()
+        };
+        match *self {
+            Window::Rectangular => vec![1.0; size],
+            Window::Hann => cosines(&[0.5, 0.5]),
+            Window::Hamming => cosines(&[0.54, 0.46]),
+            Window::BlackmanHarris => cosines(&[0.35875, 0.48829, 0.14128, 0.01168]),
+            Window::Kaiser(beta) => {
+                let beta = beta as f64;
+                let denominator = bessel_i0(beta);
+                (0..size)
+                    .map(|i| {
+                        let r = 2.0 * i as f64 / n - 1.0;
+                        (bessel_i0(beta * (1.0 - r * r).max(0.0).sqrt()) / denominator) as f32
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// The window's mean: how much it scales a bin-centred sinusoid.
+    pub fn coherent_gain(&self, size: usize) -> f32 {
+        let coefficients = self.coefficients(size);
+        (coefficients.iter().map(|&w| w as f64).sum::<f64>() / size as f64) as f32
+    }
+}
+
+/// The zeroth-order modified Bessel function of the first kind, by its
+/// power series (which converges quickly for the betas windows use).
+fn bessel_i0(x: f64) -> f64 {
+    let quarter_x2 = x * x / 4.0;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..64 {
+        term *= quarter_x2 / (k * k) as f64;
+        sum += term;
+        if term < sum * 1e-16 {
+            break;
+        }
+    }
+    sum
+}
+
+/// Windowed amplitude spectra, corrected so that a sinusoid of amplitude A
+/// centred on a bin reads A there, whatever the window.
+pub struct SpectrumAnalyzer {
+    fft: RealFft,
+    window: Vec<f32>,
+    /// Per-bin scale: 2 / (N * coherent gain), halved at DC and Nyquist.
+    scale: f32,
+    frame: Vec<f32>,
+    spectrum: Vec<Complex>,
+}
+
+impl SpectrumAnalyzer {
+    pub fn new(size: usize, window: Window) -> Self {
+        let window = window.coefficients(size);
+        let gain: f32 = window.iter().sum::<f32>() / size as f32;
+        let fft = RealFft::new(size);
+        Self {
+            scale: 2.0 / (size as f32 * gain),
+            frame: vec![0.0; size],
+            spectrum: vec![Complex::default(); fft.bins()],
+            fft,
+            window,
+        }
+    }
+
+    pub fn bins(&self) -> usize {
+        self.fft.bins()
+    }
+
+    /// Writes the amplitude of each bin of `input` into `amplitudes`.
+    pub fn amplitudes(&mut self, input: &[f32], amplitudes: &mut [f32]) {
+        assert_eq!(input.len(), self.fft.size());
+        assert_eq!(amplitudes.len(), self.bins());
+        for ((frame, &x), &w) in self.frame.iter_mut().zip(input).zip(&self.window) {
+            *frame = x * w;
+        }
+        self.fft.forward(&self.frame, &mut self.spectrum);
+        let last = amplitudes.len() - 1;
+        for (k, (amplitude, c)) in amplitudes.iter_mut().zip(&self.spectrum).enumerate() {
+            let edge = if k == 0 || k == last { 0.5 } else { 1.0 };
+            *amplitude = c.norm() * self.scale * edge;
+        }
+    }
+}
+
+/// A streaming short-time Fourier transform with overlap-add resynthesis.
+///
+/// Every `hop` samples, the last `size` are windowed and transformed, the
+/// caller may change the spectrum, and the frame is transformed back and
+/// overlap-added into the output. When the window is COLA (constant
+/// overlap-add) at this hop, such as Hann at size/2 or size/4 or
+/// Blackman-Harris at size/4, an untouched spectrum comes back as the
+/// input exactly, `size` samples late.
+pub struct Stft {
+    size: usize,
+    hop: usize,
+    fft: RealFft,
+    window: Vec<f32>,
+    /// Undoes the window's overlap sum: hop / sum(window).
+    scale: f32,
+    /// The last `size` input samples, circular, oldest at `pos`.
+    input: Vec<f32>,
+    /// Overlap-added output, circular, aligned with `input`.
+    output: Vec<f32>,
+    pos: usize,
+    /// Samples taken since the last frame.
+    pending: usize,
+    frame: Vec<f32>,
+    spectrum: Vec<Complex>,
+}
+
+impl Stft {
+    pub fn new(size: usize, hop: usize, window: Window) -> Self {
+        assert!(
+            hop > 0 && hop <= size,
+            "the hop must be between 1 and the frame size"
+        );
+        let window = window.coefficients(size);
+        let sum: f32 = window.iter().sum();
+        let fft = RealFft::new(size);
+        Self {
+            size,
+            hop,
+            scale: hop as f32 / sum,
+            input: vec![0.0; size],
+            output: vec![0.0; size],
+            pos: 0,
+            pending: 0,
+            frame: vec![0.0; size],
+            spectrum: vec![Complex::default(); fft.bins()],
+            fft,
+            window,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+
+    /// How many samples the output trails the input.
+    pub fn latency(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the window overlap-adds to a constant at this hop, i.e.
+    /// whether resynthesis is exact.
+    pub fn is_cola(&self) -> bool {
+        let sums: Vec<f32> = (0..self.hop)
+            .map(|n| self.window.iter().skip(n).step_by(self.hop).sum())
+            .collect();
+        let mean = sums.iter().sum::<f32>() / sums.len() as f32;
+        sums.iter().all(|s| (s - mean).abs() <= mean * 1e-4)
+    }
+
+    /// Feeds `input` through, writing the same number of samples to
+    /// `output`. `modify` sees each frame's N/2 + 1 bins, DC to Nyquist.
+    pub fn process(
+        &mut self,
+        input: &[f32],
+        output: &mut [f32],
+        mut modify: impl FnMut(&mut [Complex]),
+    ) {
+        assert_eq!(input.len(), output.len());
+        for (&x, y) in input.iter().zip(output.iter_mut()) {
+            // The slot being reused holds input from `size` samples ago,
+            // whose every frame has now been added.
+            *y = self.output[self.pos];
+            self.output[self.pos] = 0.0;
+            self.input[self.pos] = x;
+            self.pos = (self.pos + 1) % self.size;
+            self.pending += 1;
+            if self.pending == self.hop {
+                self.pending = 0;
+                self.frame_complete(&mut modify);
+            }
+        }
+    }
+
+    fn frame_complete(&mut self, modify: &mut impl FnMut(&mut [Complex])) {
+        let (newer, older) = self.input.split_at(self.pos);
+        for ((frame, &x), &w) in self
+            .frame
+            .iter_mut()
+            .zip(older.iter().chain(newer))
+            .zip(&self.window)
+        {
+            *frame = x * w;
+        }
+        self.fft.forward(&self.frame, &mut self.spectrum);
+        modify(&mut self.spectrum);
+        self.fft.inverse(&mut self.spectrum, &mut self.frame);
+
+        for (i, &y) in self.frame.iter().enumerate() {
+            self.output[(self.pos + i) % self.size] += y * self.scale;
+        }
+    }
+}
+
 fn complex_add(a: Complex, b: Complex) -> Complex {
     Complex {
         re: a.re + b.re,
@@ -124,3 +504,10 @@ fn complex_mul(a: Complex, b: Complex) -> Complex {
         im: a.re * b.im + a.im * b.re,
     }
 }
+
+fn complex_scale(a: Complex, s: f32) -> Complex {
+    Complex {
+        re: a.re * s,
+        im: a.im * s,
+    }
+}
@@ -15,81 +15,668 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::core::{AudioNode, GraphContext};
+use crate::live::{self, GraphController, GraphProcessor};
 use crate::{BLOCK_SIZE, Sample};
+use std::collections::BTreeSet;
+use std::fmt;
+
+type Block = [Sample; BLOCK_SIZE];
 
 /// Unique identifier for a node in the graph.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NodeId(pub usize);
 
-/// The maximum number of input ports we track for a node.
-const MAX_INPUTS: usize = 8;
+/// How a connection reconciles differing channel counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelMix {
+    /// Mono spreads to every channel; anything folds down to mono by
+    /// averaging; wider layouts fold onto narrower ones by averaging every
+    /// channel `i` with `i + n`, `i + 2n`, ... (quad to stereo gives
+    /// `L = (L + SL) / 2`). Upmixing from more than one channel fills the
+    /// extra channels with silence.
+    #[default]
+    Speakers,
+    /// Channel `i` feeds channel `i`. Extra source channels are dropped,
+    /// missing ones are silent.
+    Discrete,
+}
 
-/// The graph engine that owns nodes and manages signal flow.
-pub struct AudioGraph {
-    /// The processing nodes.
-    nodes: Vec<Box<dyn AudioNode + Send>>,
+/// Copies `src` into `dst`, converting the channel count by `mix`.
+pub fn mix_channels(src: &[Block], dst: &mut [Block], mix: ChannelMix) {
+    let (n, m) = (src.len(), dst.len());
+    if n == m || mix == ChannelMix::Discrete || (n > 1 && n < m) {
+        for (i, out) in dst.iter_mut().enumerate() {
+            match src.get(i) {
+                Some(channel) => out.copy_from_slice(channel),
+                None => out.fill(0.0),
+            }
+        }
+    } else if n == 0 {
+        dst.iter_mut().for_each(|out| out.fill(0.0));
+    } else if n == 1 {
+        dst.iter_mut().for_each(|out| out.copy_from_slice(&src[0]));
+    } else {
+        // Fold n > m channels onto m.
+        for (i, out) in dst.iter_mut().enumerate() {
+            let folded = src.iter().skip(i).step_by(m);
+            let count = folded.clone().count() as Sample;
+            out.fill(0.0);
+            for channel in folded {
+                for (o, s) in out.iter_mut().zip(channel) {
+                    *o += s;
+                }
+            }
+            out.iter_mut().for_each(|o| *o /= count);
+        }
+    }
+}
+
+/// A rejected change to the graph's wiring. The graph is left as it was.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// The connection would close a loop that no block delay breaks.
+    /// Lists the nodes around it, the first repeated at the end.
+    Cycle(Vec<NodeId>),
+    /// The source node has no output port with this index.
+    NoSuchOutput { node: NodeId, port: usize },
+    /// The audio thread has not caught up with earlier commands (or their
+    /// garbage has not been collected); try again after the next block.
+    QueueFull,
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::Cycle(path) => {
+                let path: Vec<String> = path.iter().map(|id| id.0.to_string()).collect();
+                write!(f, "cycle without a block delay: {}", path.join(" -> "))
+            }
+            GraphError::NoSuchOutput { node, port } => {
+                write!(f, "node {} has no output port {}", node.0, port)
+            }
+            GraphError::QueueFull => write!(f, "the audio command queue is full"),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// One wire into an input port.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Connection {
+    src: NodeId,
+    port: usize,
+    mix: ChannelMix,
+}
+
+/// What the wiring needs to know about a node, read once when it is added.
+#[derive(Debug, Clone)]
+pub(crate) struct Shape {
+    is_delay: bool,
+    /// Channels of the declared input ports; later ports repeat the last.
+    input_channels: Vec<usize>,
+    /// Where each output port starts in the output buffers, plus the total.
+    output_offsets: Vec<usize>,
+}
+
+impl Shape {
+    fn of(node: &dyn AudioNode) -> Self {
+        let input_channels = (0..node.input_ports().max(1))
+            .map(|port| node.input_channels(port))
+            .collect();
+        let mut output_offsets = vec![0];
+        for port in 0..node.output_ports() {
+            output_offsets.push(output_offsets[port] + node.output_channels(port));
+        }
+        Self {
+            is_delay: node.is_block_delay(),
+            input_channels,
+            output_offsets,
+        }
+    }
+
+    fn input_channels(&self, port: usize) -> usize {
+        let last = self.input_channels.len() - 1;
+        self.input_channels[port.min(last)]
+    }
+
+    pub(crate) fn output_channels(&self, port: usize) -> Option<usize> {
+        match self.output_offsets.get(port..port + 2) {
+            Some(&[start, end]) => Some(end - start),
+            _ => None,
+        }
+    }
+}
+
+/// A node and its output buffers, allocated before it reaches the audio
+/// thread.
+pub struct NodeSlot {
+    node: Box<dyn AudioNode + Send>,
+    output_offsets: Vec<usize>,
+    outputs: Vec<Block>,
+}
+
+impl NodeSlot {
+    fn new(node: Box<dyn AudioNode + Send>, shape: &Shape) -> Self {
+        let channels = shape.output_offsets[shape.output_offsets.len() - 1];
+        Self {
+            node,
+            output_offsets: shape.output_offsets.clone(),
+            outputs: vec![[0.0; BLOCK_SIZE]; channels],
+        }
+    }
+
+    /// The channels of output `port`; empty if there is no such port.
+    fn output(&self, port: usize) -> &[Block] {
+        match self.output_offsets.get(port..port + 2) {
+            Some(&[start, end]) => &self.outputs[start..end],
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Debug for NodeSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeSlot")
+            .field("channels", &self.outputs.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A larger node table, handed to the audio thread before the current one
+/// fills up so that adding nodes never grows a `Vec` there.
+#[derive(Debug)]
+pub struct NodeTable(pub(crate) Vec<Option<NodeSlot>>);
+
+/// Everything the audio thread needs to run one wiring of the graph, planned
+/// and allocated ahead of time so that switching to it is a pointer swap.
+pub struct Topology {
+    /// inputs[node][port] = the wire feeding that port, if any.
+    inputs: Vec<Vec<Option<Connection>>>,
+    /// Channels per input port, up to the highest one connected.
+    input_channels: Vec<Vec<usize>>,
+    /// The mixed-down input channels of every node, flat by port.
+    input_buffers: Vec<Vec<Block>>,
+    /// Non-delay nodes, sources first.
+    order: Vec<usize>,
+    /// Block-delay nodes, run before `order` and fed after it.
+    delays: Vec<usize>,
+    /// The node whose first output port is the graph's output.
+    output: Option<usize>,
+    /// Reused reference lists for `AudioNode::process`, sized for the widest
+    /// node so that processing never allocates.
+    input_refs: Vec<&'static Block>,
+    output_refs: Vec<&'static mut Block>,
+}
+
+impl fmt::Debug for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Topology")
+            .field("order", &self.order)
+            .field("delays", &self.delays)
+            .field("output", &self.output)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The control side's record of the graph: node shapes and wires. The audio
+/// thread never sees it, only the `Topology` planned from it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Wiring {
+    /// By node id; `None` once removed. Ids are never reused.
+    shapes: Vec<Option<Shape>>,
+    inputs: Vec<Vec<Option<Connection>>>,
+    output: Option<usize>,
+}
+
+impl Wiring {
+    pub(crate) fn add(&mut self, shape: Shape) -> NodeId {
+        self.shapes.push(Some(shape));
+        self.inputs.push(Vec::new());
+        NodeId(self.shapes.len() - 1)
+    }
+
+    fn shape(&self, id: NodeId) -> &Shape {
+        match self.shapes.get(id.0) {
+            Some(Some(shape)) => shape,
+            _ => panic!("Invalid node ID"),
+        }
+    }
+
+    pub(crate) fn remove(&mut self, id: NodeId) {
+        self.shape(id);
+        self.shapes[id.0] = None;
+        self.inputs[id.0].clear();
+        for dst in 0..self.inputs.len() {
+            for port in 0..self.inputs[dst].len() {
+                if self.inputs[dst][port].is_some_and(|w| w.src == id) {
+                    self.disconnect(NodeId(dst), port);
+                }
+            }
+        }
+        if self.output == Some(id.0) {
+            self.output = None;
+        }
+    }
+
+    pub(crate) fn connect(
+        &mut self,
+        src: NodeId,
+        output: usize,
+        dst: NodeId,
+        input: usize,
+        mix: ChannelMix,
+    ) -> Result<(), GraphError> {
+        if self.shape(src).output_channels(output).is_none() {
+            return Err(GraphError::NoSuchOutput {
+                node: src,
+                port: output,
+            });
+        }
+        self.shape(dst);
+
+        let previous = self.inputs[dst.0].clone();
+        let inputs = &mut self.inputs[dst.0];
+        if inputs.len() <= input {
+            inputs.resize(input + 1, None);
+        }
+        inputs[input] = Some(Connection {
+            src,
+            port: output,
+            mix,
+        });
+        if let Err(e) = self.order() {
+            self.inputs[dst.0] = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn disconnect(&mut self, dst: NodeId, input: usize) {
+        let Some(inputs) = self.inputs.get_mut(dst.0) else {
+            return;
+        };
+        if let Some(wire) = inputs.get_mut(input) {
+            *wire = None;
+        }
+        while inputs.last() == Some(&None) {
+            inputs.pop();
+        }
+    }
+
+    pub(crate) fn set_output(&mut self, node: NodeId) {
+        self.shape(node);
+        self.output = Some(node.0);
+    }
+
+    /// The chosen output node, or else the most recently added one.
+    fn output_node(&self) -> Option<usize> {
+        self.output
+            .or_else(|| self.shapes.iter().rposition(Option::is_some))
+    }
+
+    pub(crate) fn output_channels(&self) -> usize {
+        self.output_node()
+            .and_then(|id| self.shapes[id].as_ref())
+            .and_then(|shape| shape.output_channels(0))
+            .unwrap_or(0)
+    }
+
+    /// Kahn's algorithm over the non-delay nodes, lowest id first so that
+    /// unrelated nodes keep their insertion order. Returns the order and the
+    /// delay nodes.
+    fn order(&self) -> Result<(Vec<usize>, Vec<usize>), GraphError> {
+        let count = self.shapes.len();
+        let is_delay = |i: usize| self.shapes[i].as_ref().is_some_and(|s| s.is_delay);
+        let live = |i: usize| self.shapes[i].is_some();
+
+        let mut indegree = vec![0usize; count];
+        let mut consumers = vec![Vec::new(); count];
+        for (dst, inputs) in self.inputs.iter().enumerate() {
+            for wire in inputs.iter().flatten() {
+                if !is_delay(dst) && !is_delay(wire.src.0) {
+                    indegree[dst] += 1;
+                    consumers[wire.src.0].push(dst);
+                }
+            }
+        }
+        let mut ready: BTreeSet<usize> = (0..count)
+            .filter(|&i| live(i) && !is_delay(i) && indegree[i] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(count);
+        while let Some(id) = ready.pop_first() {
+            order.push(id);
+            for &next in &consumers[id] {
+                indegree[next] -= 1;
+                if indegree[next] == 0 {
+                    ready.insert(next);
+                }
+            }
+        }
+        let delays: Vec<usize> = (0..count).filter(|&i| is_delay(i)).collect();
+        let nodes = (0..count).filter(|&i| live(i)).count();
+        if order.len() + delays.len() < nodes {
+            return Err(GraphError::Cycle(self.find_cycle(&indegree)));
+        }
+        Ok((order, delays))
+    }
 
-    /// The connections (wires).
-    /// connections[dst_node_id.0][input_port_index] = Some(src_node_id)
-    connections: Vec<Vec<Option<NodeId>>>,
+    /// Walks backwards along unresolved wires until a node repeats.
+    fn find_cycle(&self, indegree: &[usize]) -> Vec<NodeId> {
+        let stuck = |i: usize| indegree[i] > 0;
+        let start = (0..indegree.len())
+            .find(|&i| stuck(i))
+            .expect("a cycle leaves a node with unresolved inputs");
+        let mut path = vec![start];
+        let mut current = start;
+        loop {
+            current = self.inputs[current]
+                .iter()
+                .flatten()
+                .map(|wire| wire.src.0)
+                .find(|&src| stuck(src))
+                .expect("every node left on a cycle has a feeder on it");
+            if let Some(at) = path.iter().position(|&n| n == current) {
+                // We walked against the signal; turn it around and start
+                // from the lowest id so the report is stable.
+                let mut cycle: Vec<NodeId> = path[at..].iter().rev().map(|&n| NodeId(n)).collect();
+                let lowest = (0..cycle.len()).min_by_key(|&i| cycle[i].0).unwrap_or(0);
+                cycle.rotate_left(lowest);
+                cycle.push(cycle[0]);
+                return cycle;
+            }
+            path.push(current);
+        }
+    }
 
-    /// The output buffers for each node.
-    /// outputs[node_id.0] is the buffer written to by node_id.
-    outputs: Vec<[Sample; BLOCK_SIZE]>,
+    /// Lays out buffers and scratch space for the current wiring, which
+    /// `connect` has already checked for cycles.
+    pub(crate) fn plan(&self) -> Box<Topology> {
+        let (order, delays) = self.order().expect("the wiring is kept acyclic");
+        let input_channels: Vec<Vec<usize>> = self
+            .inputs
+            .iter()
+            .zip(&self.shapes)
+            .map(|(inputs, shape)| match shape {
+                Some(shape) => (0..inputs.len()).map(|p| shape.input_channels(p)).collect(),
+                None => Vec::new(),
+            })
+            .collect();
+        let input_buffers: Vec<Vec<Block>> = input_channels
+            .iter()
+            .map(|ports| vec![[0.0; BLOCK_SIZE]; ports.iter().sum()])
+            .collect();
+        let widest_in = input_buffers.iter().map(Vec::len).max().unwrap_or(0);
+        let widest_out = self
+            .shapes
+            .iter()
+            .flatten()
+            .map(|s| s.output_offsets[s.output_offsets.len() - 1])
+            .max()
+            .unwrap_or(0);
 
-    /// A silent buffer used for unconnected inputs or as a default reference.
-    silence: [Sample; BLOCK_SIZE],
+        Box::new(Topology {
+            inputs: self.inputs.clone(),
+            input_channels,
+            input_buffers,
+            order,
+            delays,
+            output: self.output_node(),
+            input_refs: Vec::with_capacity(widest_in),
+            output_refs: Vec::with_capacity(widest_out),
+        })
+    }
+}
 
+/// The audio side: the nodes, their buffers and the wiring in force.
+/// Nothing here allocates or frees once built.
+pub(crate) struct Runtime {
+    nodes: Vec<Option<NodeSlot>>,
+    topology: Box<Topology>,
+    /// A silent buffer returned when there is no output.
+    silence: [Block; 1],
     /// The global context (sample rate, etc.).
     context: GraphContext,
 }
 
+impl Runtime {
+    fn new(sample_rate: Sample) -> Self {
+        Self {
+            nodes: Vec::new(),
+            topology: Wiring::default().plan(),
+            silence: [[0.0; BLOCK_SIZE]],
+            context: GraphContext::new(sample_rate),
+        }
+    }
+
+    /// Puts `slot` at `id`, which is at most one past the end. Pushing stays
+    /// within capacity when the table was reserved ahead.
+    pub(crate) fn insert(&mut self, id: usize, slot: NodeSlot) -> Option<NodeSlot> {
+        if id < self.nodes.len() {
+            self.nodes[id].replace(slot)
+        } else {
+            self.nodes.push(Some(slot));
+            None
+        }
+    }
+
+    pub(crate) fn remove(&mut self, id: usize) -> Option<NodeSlot> {
+        self.nodes.get_mut(id).and_then(Option::take)
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    /// Moves the nodes into `table` and returns the old, empty one.
+    pub(crate) fn adopt(&mut self, mut table: NodeTable) -> NodeTable {
+        table.0.append(&mut self.nodes);
+        NodeTable(std::mem::replace(&mut self.nodes, table.0))
+    }
+
+    /// Switches to `next` and returns the topology it replaces. Delay nodes
+    /// keep the block they captured, so feedback survives a rewire.
+    pub(crate) fn rewire(&mut self, mut next: Box<Topology>) -> Box<Topology> {
+        for &id in &next.delays {
+            if let Some(old) = self.topology.input_buffers.get(id) {
+                let new = &mut next.input_buffers[id];
+                let shared = old.len().min(new.len());
+                new[..shared].copy_from_slice(&old[..shared]);
+            }
+        }
+        std::mem::replace(&mut self.topology, next)
+    }
+
+    pub(crate) fn set_param(&mut self, node: usize, param_id: usize, value: f64) {
+        if let Some(Some(slot)) = self.nodes.get_mut(node) {
+            slot.node.set_param(param_id, value);
+        }
+    }
+
+    pub(crate) fn ramp_param(&mut self, node: usize, param_id: usize, value: f64, frames: usize) {
+        if let Some(Some(slot)) = self.nodes.get_mut(node) {
+            slot.node.ramp_param(param_id, value, frames);
+        }
+    }
+
+    pub(crate) fn silence(&self) -> &[Block] {
+        &self.silence
+    }
+
+    pub(crate) fn render(&mut self) -> &[Block] {
+        let Runtime {
+            nodes,
+            topology,
+            context,
+            ..
+        } = self;
+        // Delays first, replaying what they captured last block, so that
+        // everything downstream of them this block hears the same thing.
+        for i in 0..topology.delays.len() {
+            let id = topology.delays[i];
+            run(nodes, topology, context, id);
+        }
+        for i in 0..topology.order.len() {
+            let id = topology.order[i];
+            gather(nodes, topology, id);
+            run(nodes, topology, context, id);
+        }
+        // Then capture this block's input for the next. Every delay reads
+        // outputs fixed at the start of the block, so a chain of delays still
+        // adds one block per link.
+        for i in 0..topology.delays.len() {
+            let id = topology.delays[i];
+            gather(nodes, topology, id);
+        }
+
+        match self.topology.output.and_then(|id| self.nodes[id].as_ref()) {
+            Some(slot) => slot.output(0),
+            None => &self.silence,
+        }
+    }
+}
+
+/// Mixes whatever feeds each input port of `id` into its input buffers.
+fn gather(nodes: &[Option<NodeSlot>], topology: &mut Topology, id: usize) {
+    let Topology {
+        inputs,
+        input_channels,
+        input_buffers,
+        ..
+    } = topology;
+    let mut offset = 0;
+    for (port, &channels) in input_channels[id].iter().enumerate() {
+        let dst = &mut input_buffers[id][offset..offset + channels];
+        let src = inputs[id][port]
+            .and_then(|wire| Some((nodes[wire.src.0].as_ref()?.output(wire.port), wire.mix)));
+        match src {
+            Some((src, mix)) => mix_channels(src, dst, mix),
+            None => dst.iter_mut().for_each(|b| b.fill(0.0)),
+        }
+        offset += channels;
+    }
+}
+
+fn run(nodes: &mut [Option<NodeSlot>], topology: &mut Topology, context: &GraphContext, id: usize) {
+    let Some(slot) = nodes[id].as_mut() else {
+        return;
+    };
+    let Topology {
+        input_buffers,
+        input_refs,
+        output_refs,
+        ..
+    } = topology;
+
+    let mut inputs: Vec<&Block> = recycle(std::mem::take(input_refs));
+    inputs.extend(input_buffers[id].iter());
+    let mut outputs: Vec<&mut Block> = recycle(std::mem::take(output_refs));
+    outputs.extend(slot.outputs.iter_mut());
+
+    slot.node.process(&inputs, &mut outputs, context);
+
+    *input_refs = recycle(inputs);
+    *output_refs = recycle(outputs);
+}
+
+/// Empties `v` and hands back its allocation under another element type.
+/// std collects a mapped `IntoIter` in place when the layouts match, as they
+/// do for the reference lists here, so this never allocates.
+fn recycle<T, U>(mut v: Vec<T>) -> Vec<U> {
+    v.clear();
+    v.into_iter().map(|_| unreachable!()).collect()
+}
+
+/// The graph engine that owns nodes and manages signal flow.
+///
+/// Every change to the wiring recomputes a topological order, so nodes may be
+/// added in any order. Block-delay nodes sit outside that order: they run
+/// first, replaying the input they captured at the end of the previous
+/// block, which is what lets feedback loops through them.
+///
+/// To edit the graph while it plays, split it with `into_live`.
+pub struct AudioGraph {
+    wiring: Wiring,
+    runtime: Runtime,
+}
+
 impl AudioGraph {
     /// Creates a new audio graph with the specified sample rate.
     pub fn new(sample_rate: Sample) -> Self {
         Self {
-            nodes: Vec::new(),
-            connections: Vec::new(),
-            outputs: Vec::new(),
-            silence: [0.0; BLOCK_SIZE],
-            context: GraphContext::new(sample_rate),
+            wiring: Wiring::default(),
+            runtime: Runtime::new(sample_rate),
         }
     }
 
     /// Adds a node to the graph and returns its ID.
     ///
-    /// The node is initialized with a blank output buffer and no input connections.
+    /// The node is initialized with blank output buffers and no input connections.
     pub fn add_node(&mut self, node: Box<dyn AudioNode + Send>) -> NodeId {
-        let id = NodeId(self.nodes.len());
-        self.nodes.push(node);
-        self.outputs.push([0.0; BLOCK_SIZE]);
-        self.connections.push(Vec::new());
+        let shape = Shape::of(&*node);
+        let slot = NodeSlot::new(node, &shape);
+        let id = self.wiring.add(shape);
+        self.runtime.insert(id.0, slot);
+        self.replan();
         id
     }
 
-    /// Connects an output of a source node to an input of a destination node.
+    /// Removes a node and every wire to or from it. Its ID is not reused.
+    pub fn remove_node(&mut self, node: NodeId) {
+        self.wiring.remove(node);
+        self.replan();
+        self.runtime.remove(node.0);
+    }
+
+    /// Connects output port 0 of a source node to an input of a destination node.
     ///
     /// # Arguments
     ///
     /// * `src` - The ID of the source node providing the signal.
     /// * `dst` - The ID of the destination node receiving the signal.
-    /// * `input_index` - The input port index on the destination node (0 to MAX_INPUTS - 1).
-    pub fn connect(&mut self, src: NodeId, dst: NodeId, input_index: usize) {
-        if input_index >= MAX_INPUTS {
-            panic!(
-                "Input index {} exceeds MAX_INPUTS {}",
-                input_index, MAX_INPUTS
-            );
-        }
-        if src.0 >= self.nodes.len() || dst.0 >= self.nodes.len() {
-            panic!("Invalid node ID");
-        }
+    /// * `input_index` - The input port index on the destination node.
+    pub fn connect(
+        &mut self,
+        src: NodeId,
+        dst: NodeId,
+        input_index: usize,
+    ) -> Result<(), GraphError> {
+        self.connect_ports(src, 0, dst, input_index, ChannelMix::Speakers)
+    }
 
-        let inputs = &mut self.connections[dst.0];
-        if inputs.len() <= input_index {
-            inputs.resize(input_index + 1, None);
-        }
-        inputs[input_index] = Some(src);
+    /// Connects any output port to any input port, replacing whatever fed
+    /// that input before.
+    ///
+    /// Fails without changing the graph if the connection would close a
+    /// cycle that does not pass through a block delay.
+    pub fn connect_ports(
+        &mut self,
+        src: NodeId,
+        output: usize,
+        dst: NodeId,
+        input: usize,
+        mix: ChannelMix,
+    ) -> Result<(), GraphError> {
+        self.wiring.connect(src, output, dst, input, mix)?;
+        self.replan();
+        Ok(())
+    }
+
+    /// Removes whatever feeds `input` on `dst`.
+    pub fn disconnect(&mut self, dst: NodeId, input: usize) {
+        self.wiring.disconnect(dst, input);
+        self.replan();
+    }
+
+    /// Chooses the node whose first output port `process` returns.
+    pub fn set_output(&mut self, node: NodeId) {
+        self.wiring.set_output(node);
+        self.replan();
+    }
+
+    /// Channels of the graph's output.
+    pub fn output_channels(&self) -> usize {
+        self.wiring.output_channels()
     }
 
     /// Sets a parameter on a specific node.
@@ -101,67 +688,64 @@ impl AudioGraph {
     /// * `param_id` - The parameter ID.
     /// * `value` - The new value.
     pub fn set_node_param(&mut self, node: NodeId, param_id: usize, value: f64) {
-        if let Some(n) = self.nodes.get_mut(node.0) {
-            n.set_param(param_id, value);
-        }
+        self.runtime.set_param(node.0, param_id, value);
+    }
+
+    /// Moves a parameter linearly to `value` over `frames` samples, starting
+    /// with the next block. See `AudioNode::ramp_param`.
+    pub fn ramp_node_param(&mut self, node: NodeId, param_id: usize, value: f64, frames: usize) {
+        self.runtime.ramp_param(node.0, param_id, value, frames);
     }
 
     /// Processes one block of audio through the entire graph.
     ///
-    /// Iterates through nodes in the order they were added (topological order is expected).
-    /// Returns a reference to the output buffer of the last node in the chain.
+    /// Returns the first channel of the output node (mono view); see
+    /// `render` for every channel.
     pub fn process(&mut self) -> &[Sample; BLOCK_SIZE] {
-        // Iterate through every node by index
-        for id in 0..self.nodes.len() {
-            // Split outputs into past (inputs) and current (output).
-            // This enables borrowing past outputs immutably while mutating current output.
-            let (past_outputs, current_and_future) = self.outputs.split_at_mut(id);
-            let (current_output, _future) = current_and_future.split_at_mut(1);
-            let output_buffer = &mut current_output[0];
-
-            // Build the input slice on the stack.
-            // We use a fixed-size array of references, defaulting to silence.
-            // This avoids any heap allocation (Vec) inside the audio thread.
-            let mut input_refs: [&[Sample; BLOCK_SIZE]; MAX_INPUTS] = [&self.silence; MAX_INPUTS];
-
-            // Resolve connections
-            let input_map = &self.connections[id];
-
-            // Determine the maximum input index used to slice correctly
-            let mut max_input_index = 0;
-
-            for (port_index, source_option) in input_map.iter().enumerate().take(MAX_INPUTS) {
-                if let Some(src_id) = source_option {
-                    if src_id.0 < id {
-                        // Safe: src_id < id, so it's in past_outputs
-                        input_refs[port_index] = &past_outputs[src_id.0];
-                        if port_index + 1 > max_input_index {
-                            max_input_index = port_index + 1;
-                        }
-                    } else {
-                        // Connecting to a future node (or self) is not supported in this strict topological loop.
-                        // We ignore it (effectively silence) for now.
-                    }
-                }
-            }
-
-            // Execute the node process
-            // Only pass the slice up to the highest connected input port (or 0 if none)
-            let inputs_slice = &input_refs[0..max_input_index];
+        if self.output_channels() == 0 {
+            self.runtime.render();
+            return &self.runtime.silence()[0];
+        }
+        &self.render()[0]
+    }
 
-            // We need to pass a slice of mutable outputs, even though we only have one.
-            // The trait expects `&mut [&mut [Sample; BLOCK_SIZE]]`.
-            // We construct a temporary array of mutable references on the stack.
-            let mut output_refs = [output_buffer];
+    /// Processes one block and returns every channel of the output node's
+    /// first port, or a single silent channel if the graph is empty.
+    pub fn render(&mut self) -> &[Block] {
+        self.runtime.render()
+    }
 
-            self.nodes[id].process(inputs_slice, &mut output_refs, &self.context);
+    /// Renders `frames` samples offline, one `Vec` per output channel.
+    ///
+    /// Whole blocks are processed; the last is cut short to fit.
+    pub fn render_frames(&mut self, frames: usize) -> Vec<Vec<Sample>> {
+        let mut out: Vec<Vec<Sample>> = Vec::new();
+        for start in (0..frames).step_by(BLOCK_SIZE) {
+            let take = BLOCK_SIZE.min(frames - start);
+            let block = self.render();
+            out.resize_with(block.len(), || Vec::with_capacity(frames));
+            for (channel, buffer) in out.iter_mut().zip(block) {
+                channel.extend_from_slice(&buffer[..take]);
+            }
         }
+        out
+    }
 
-        // Return the last buffer or silence if graph is empty
-        if let Some(last) = self.outputs.last() {
-            last
-        } else {
-            &self.silence
-        }
+    /// Splits the graph for live use: the controller stays on the control
+    /// thread, the processor moves into the audio callback. Up to `capacity`
+    /// commands may be in flight between them.
+    pub fn into_live(self, capacity: usize) -> (GraphController, GraphProcessor) {
+        live::split(self.wiring, self.runtime, capacity)
+    }
+
+    /// Builds the slot and shape for a node, off the audio thread.
+    pub(crate) fn prepare(node: Box<dyn AudioNode + Send>) -> (NodeSlot, Shape) {
+        let shape = Shape::of(&*node);
+        (NodeSlot::new(node, &shape), shape)
+    }
+
+    fn replan(&mut self) {
+        let next = self.wiring.plan();
+        self.runtime.rewire(next);
     }
 }
@@ -22,12 +22,21 @@ pub mod commands;
 pub mod core;
 pub mod dsp;
 pub mod graph;
+pub mod live;
 pub mod nodes;
 
 pub use audio::{AudioEngine, create_test_graph};
 pub use commands::AudioCommand;
-pub use core::{AudioNode, GraphContext};
-pub use graph::{AudioGraph, NodeId};
+pub use core::{AudioNode, GraphContext, ParamRamp};
+pub use graph::{AudioGraph, ChannelMix, GraphError, NodeId, mix_channels};
+pub use live::{GraphController, GraphProcessor};
+pub use nodes::block_delay::BlockDelay;
+pub use nodes::delay::Delay;
+pub use nodes::dynamics::Compressor;
+pub use nodes::envelope::Adsr;
+pub use nodes::filter::{Biquad, FilterKind};
 pub use nodes::gain::Gain;
 pub use nodes::mixer::Mixer;
-pub use nodes::oscillators::SineOscillator;
+pub use nodes::oscillators::{PolyBlepOscillator, SineOscillator, Waveform};
+pub use nodes::pan::Pan;
+pub use nodes::reverb::Reverb;
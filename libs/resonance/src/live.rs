@@ -0,0 +1,311 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Editing a graph while it plays.
+//!
+//! The controller keeps its own record of the wiring, plans every change
+//! on the control thread (cycle checks, buffer layout, scratch space) and
+//! sends the result as `AudioCommand`s over a wait-free single-producer,
+//! single-consumer ring. The processor applies them between blocks by moving
+//! pointers only. Whatever it replaces (old topologies, removed nodes, an
+//! outgrown node table) travels back over a second ring, so the audio
+//! callback never allocates or frees.
+
+use crate::commands::AudioCommand;
+use crate::core::AudioNode;
+use crate::graph::{
+    AudioGraph, ChannelMix, GraphError, NodeId, NodeSlot, NodeTable, Runtime, Topology, Wiring,
+};
+use crate::{BLOCK_SIZE, Sample};
+use ringbuf::traits::{Consumer, Observer, Producer, Split};
+use ringbuf::{HeapCons, HeapProd, HeapRb};
+
+/// The fewest commands a queue must hold: adding a node may take three.
+const MIN_CAPACITY: usize = 4;
+
+/// What the audio thread hands back to be freed.
+enum Garbage {
+    Node(Option<NodeSlot>),
+    Topology(Box<Topology>),
+    Table(NodeTable),
+}
+
+pub(crate) fn split(
+    wiring: Wiring,
+    runtime: Runtime,
+    capacity: usize,
+) -> (GraphController, GraphProcessor) {
+    let capacity = capacity.max(MIN_CAPACITY);
+    let (commands_tx, commands_rx) = HeapRb::<AudioCommand>::new(capacity).split();
+    let (garbage_tx, garbage_rx) = HeapRb::<Garbage>::new(capacity).split();
+    let controller = GraphController {
+        wiring,
+        commands: commands_tx,
+        garbage: garbage_rx,
+        in_flight: 0,
+        capacity,
+        reserved: runtime.capacity(),
+    };
+    let processor = GraphProcessor {
+        runtime,
+        commands: commands_rx,
+        garbage: garbage_tx,
+        stopped: false,
+    };
+    (controller, processor)
+}
+
+/// The control thread's handle on a live graph.
+///
+/// Every method either queues the whole change or, with
+/// `GraphError::QueueFull`, none of it. Changes take effect at the start of
+/// the processor's next block.
+pub struct GraphController {
+    wiring: Wiring,
+    commands: HeapProd<AudioCommand>,
+    garbage: HeapCons<Garbage>,
+    /// Commands sent that will hand something back, minus what has been
+    /// collected. Kept within the garbage ring's capacity, so the audio
+    /// thread always has room to return things.
+    in_flight: usize,
+    capacity: usize,
+    /// Slots in the audio side's node table.
+    reserved: usize,
+}
+
+impl GraphController {
+    /// Adds a node and returns its ID. On `QueueFull` the node is dropped.
+    pub fn add_node(&mut self, node: Box<dyn AudioNode + Send>) -> Result<NodeId, GraphError> {
+        let (slot, shape) = AudioGraph::prepare(node);
+        let mut wiring = self.wiring.clone();
+        let id = wiring.add(shape);
+
+        let mut batch = Vec::with_capacity(3);
+        let mut reserved = self.reserved;
+        if id.0 >= reserved {
+            reserved = (reserved * 2).max(id.0 + 1).max(8);
+            batch.push(AudioCommand::Reserve(NodeTable(Vec::with_capacity(
+                reserved,
+            ))));
+        }
+        batch.push(AudioCommand::AddNode {
+            node_id: id.0,
+            slot,
+        });
+        batch.push(AudioCommand::Rewire(wiring.plan()));
+
+        self.send(batch)?;
+        self.wiring = wiring;
+        self.reserved = reserved;
+        Ok(id)
+    }
+
+    /// Removes a node and every wire to or from it. The node is freed here,
+    /// by a later `collect_garbage`, never on the audio thread.
+    pub fn remove_node(&mut self, node: NodeId) -> Result<(), GraphError> {
+        let mut wiring = self.wiring.clone();
+        wiring.remove(node);
+        let batch = vec![
+            AudioCommand::Rewire(wiring.plan()),
+            AudioCommand::RemoveNode { node_id: node.0 },
+        ];
+        self.send(batch)?;
+        self.wiring = wiring;
+        Ok(())
+    }
+
+    /// As `AudioGraph::connect`.
+    pub fn connect(&mut self, src: NodeId, dst: NodeId, input: usize) -> Result<(), GraphError> {
+        self.connect_ports(src, 0, dst, input, ChannelMix::Speakers)
+    }
+
+    /// As `AudioGraph::connect_ports`.
+    pub fn connect_ports(
+        &mut self,
+        src: NodeId,
+        output: usize,
+        dst: NodeId,
+        input: usize,
+        mix: ChannelMix,
+    ) -> Result<(), GraphError> {
+        let mut wiring = self.wiring.clone();
+        wiring.connect(src, output, dst, input, mix)?;
+        self.rewire(wiring)
+    }
+
+    /// Removes whatever feeds `input` on `dst`.
+    pub fn disconnect(&mut self, dst: NodeId, input: usize) -> Result<(), GraphError> {
+        let mut wiring = self.wiring.clone();
+        wiring.disconnect(dst, input);
+        self.rewire(wiring)
+    }
+
+    /// Chooses the node whose first output port the processor renders.
+    pub fn set_output(&mut self, node: NodeId) -> Result<(), GraphError> {
+        let mut wiring = self.wiring.clone();
+        wiring.set_output(node);
+        self.rewire(wiring)
+    }
+
+    /// Channels of the graph's output, as of the last change sent.
+    pub fn output_channels(&self) -> usize {
+        self.wiring.output_channels()
+    }
+
+    pub fn set_param(
+        &mut self,
+        node: NodeId,
+        param_id: usize,
+        value: f64,
+    ) -> Result<(), GraphError> {
+        self.send(vec![AudioCommand::SetParam {
+            node_id: node.0,
+            param_id,
+            value,
+        }])
+    }
+
+    /// Moves a parameter linearly to `value` over `frames` samples, starting
+    /// with the next block. The start is only as precise as the block
+    /// boundary: it cannot fall mid-block.
+    pub fn ramp_param(
+        &mut self,
+        node: NodeId,
+        param_id: usize,
+        value: f64,
+        frames: usize,
+    ) -> Result<(), GraphError> {
+        self.send(vec![AudioCommand::RampParam {
+            node_id: node.0,
+            param_id,
+            value,
+            frames,
+        }])
+    }
+
+    /// Silences the processor for good (panic button).
+    pub fn stop(&mut self) -> Result<(), GraphError> {
+        self.send(vec![AudioCommand::Stop])
+    }
+
+    /// Frees whatever the audio thread has handed back, returning how many
+    /// items that was. Called by every other method; call it directly when
+    /// the graph sits unchanged for a while.
+    pub fn collect_garbage(&mut self) -> usize {
+        let mut collected = 0;
+        while let Some(garbage) = self.garbage.try_pop() {
+            match garbage {
+                Garbage::Node(slot) => drop(slot),
+                Garbage::Topology(topology) => drop(topology),
+                Garbage::Table(table) => drop(table),
+            }
+            collected += 1;
+        }
+        self.in_flight -= collected;
+        collected
+    }
+
+    fn rewire(&mut self, wiring: Wiring) -> Result<(), GraphError> {
+        self.send(vec![AudioCommand::Rewire(wiring.plan())])?;
+        self.wiring = wiring;
+        Ok(())
+    }
+
+    /// Queues all of `batch` or none of it.
+    fn send(&mut self, batch: Vec<AudioCommand>) -> Result<(), GraphError> {
+        self.collect_garbage();
+        let returns = batch.iter().filter(|c| c.returns_garbage()).count();
+        if self.commands.vacant_len() < batch.len() || self.in_flight + returns > self.capacity {
+            return Err(GraphError::QueueFull);
+        }
+        for command in batch {
+            self.commands
+                .try_push(command)
+                .expect("room was checked above");
+        }
+        self.in_flight += returns;
+        Ok(())
+    }
+}
+
+/// The audio thread's half of a live graph.
+pub struct GraphProcessor {
+    runtime: Runtime,
+    commands: HeapCons<AudioCommand>,
+    garbage: HeapProd<Garbage>,
+    stopped: bool,
+}
+
+impl GraphProcessor {
+    /// Applies every pending command, then processes one block and returns
+    /// every channel of the output. Never allocates or frees.
+    pub fn render(&mut self) -> &[[Sample; BLOCK_SIZE]] {
+        self.apply();
+        if self.stopped {
+            return self.runtime.silence();
+        }
+        self.runtime.render()
+    }
+
+    fn apply(&mut self) {
+        while let Some(command) = self.commands.try_pop() {
+            match command {
+                AudioCommand::SetParam {
+                    node_id,
+                    param_id,
+                    value,
+                } => self.runtime.set_param(node_id, param_id, value),
+                AudioCommand::RampParam {
+                    node_id,
+                    param_id,
+                    value,
+                    frames,
+                } => self.runtime.ramp_param(node_id, param_id, value, frames),
+                AudioCommand::SetMasterFrequency(frequency) => {
+                    // Assumes node 0 is an oscillator, as the prototype did.
+                    self.runtime.set_param(0, 0, frequency);
+                }
+                AudioCommand::Stop => self.stopped = true,
+                AudioCommand::AddNode { node_id, slot } => {
+                    if let Some(old) = self.runtime.insert(node_id, slot) {
+                        // Ids are never reused, so this is only a safeguard.
+                        std::mem::forget(old);
+                    }
+                }
+                AudioCommand::RemoveNode { node_id } => {
+                    let slot = self.runtime.remove(node_id);
+                    self.discard(Garbage::Node(slot));
+                }
+                AudioCommand::Rewire(topology) => {
+                    let old = self.runtime.rewire(topology);
+                    self.discard(Garbage::Topology(old));
+                }
+                AudioCommand::Reserve(table) => {
+                    let old = self.runtime.adopt(table);
+                    self.discard(Garbage::Table(old));
+                }
+            }
+        }
+    }
+
+    fn discard(&mut self, garbage: Garbage) {
+        if let Err(garbage) = self.garbage.try_push(garbage) {
+            // The controller never has more in flight than the ring holds,
+            // so this cannot happen; leaking beats freeing on this thread.
+            std::mem::forget(garbage);
+        }
+    }
+}
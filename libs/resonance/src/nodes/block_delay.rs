@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::core::{AudioNode, GraphContext};
+use crate::{BLOCK_SIZE, Sample};
+
+/// A one-block delay.
+///
+/// On its own it simply passes its input through; inside an `AudioGraph` its
+/// input is captured at the end of each block and replayed at the start of
+/// the next, so its consumers hear what it was fed one block earlier.
+/// Feedback loops must pass through one of these.
+#[derive(Debug, Clone)]
+pub struct BlockDelay {
+    channels: usize,
+}
+
+impl BlockDelay {
+    /// Creates a delay carrying `channels` channels.
+    pub fn new(channels: usize) -> Self {
+        Self { channels }
+    }
+}
+
+impl Default for BlockDelay {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl AudioNode for BlockDelay {
+    fn process(
+        &mut self,
+        inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        _context: &GraphContext,
+    ) {
+        for (channel, out) in outputs.iter_mut().enumerate() {
+            match inputs.get(channel) {
+                Some(input) => out.copy_from_slice(*input),
+                None => out.fill(0.0),
+            }
+        }
+    }
+
+    fn input_channels(&self, _port: usize) -> usize {
+        self.channels
+    }
+
+    fn output_channels(&self, _port: usize) -> usize {
+        self.channels
+    }
+
+    fn is_block_delay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::core::{AudioNode, GraphContext, ParamRamp};
+use crate::{BLOCK_SIZE, Sample};
+
+/// A delay line with feedback, for echoes and combs.
+///
+/// The delay time may be fractional (the line is read with linear
+/// interpolation) and ramping it bends the pitch like tape, rather than
+/// clicking. Unlike `BlockDelay` this runs inside the block, so it does not
+/// break cycles in the graph; its feedback is internal.
+///
+/// Inputs:
+/// - 0: Audio Signal
+///
+/// Parameters:
+/// - 0: Delay time in seconds (rampable), up to the maximum given at construction
+/// - 1: Feedback (-1.0 to 1.0, exclusive)
+/// - 2: Mix (0.0 is dry only, 1.0 is delayed only)
+#[derive(Debug, Clone)]
+pub struct Delay {
+    /// The delay time in seconds.
+    pub time: Sample,
+    pub feedback: Sample,
+    pub mix: Sample,
+    ramp: ParamRamp,
+    line: Vec<Sample>,
+    write: usize,
+}
+
+impl Delay {
+    /// A delay of up to `max_time` seconds, starting at that time with no
+    /// feedback and only the delayed signal in the output.
+    ///
+    /// The line is allocated here, off the audio thread, so `sample_rate`
+    /// should be the graph's; at a higher rate the reachable time shrinks.
+    pub fn new(max_time: Sample, sample_rate: Sample) -> Self {
+        // Two spare samples: one for the interpolation, one for the write.
+        let len = (max_time * sample_rate).ceil().max(1.0) as usize + 2;
+        Self {
+            time: max_time,
+            feedback: 0.0,
+            mix: 1.0,
+            ramp: ParamRamp::hold(max_time),
+            line: vec![0.0; len],
+            write: 0,
+        }
+    }
+
+    /// Reads `delay` samples behind the write head.
+    fn read(&self, delay: Sample) -> Sample {
+        let len = self.line.len();
+        let whole = delay.floor() as usize;
+        let frac = delay - delay.floor();
+        let a = self.line[(self.write + len - whole) % len];
+        let b = self.line[(self.write + len - whole - 1) % len];
+        a + (b - a) * frac
+    }
+}
+
+impl AudioNode for Delay {
+    fn process(
+        &mut self,
+        inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        context: &GraphContext,
+    ) {
+        if outputs.is_empty() {
+            return;
+        }
+        let out = &mut outputs[0];
+        let longest = (self.line.len() - 2) as Sample;
+        let feedback = self.feedback.clamp(-0.999, 0.999);
+
+        for i in 0..BLOCK_SIZE {
+            if self.ramp.is_active() {
+                self.time = self.ramp.advance();
+            }
+            let x = inputs.first().map_or(0.0, |input| input[i]);
+            let delayed = self.read((self.time * context.sample_rate).clamp(1.0, longest));
+            self.line[self.write] = x + delayed * feedback;
+            self.write = (self.write + 1) % self.line.len();
+            out[i] = x + (delayed - x) * self.mix;
+        }
+    }
+
+    fn set_param(&mut self, id: usize, value: f64) {
+        match id {
+            0 => {
+                self.time = value;
+                self.ramp = ParamRamp::hold(value);
+            }
+            1 => self.feedback = value,
+            2 => self.mix = value,
+            _ => {}
+        }
+    }
+
+    fn ramp_param(&mut self, id: usize, value: f64, frames: usize) {
+        if id == 0 {
+            self.ramp = ParamRamp::new(self.time, value, frames);
+            self.time = self.ramp.value();
+        } else {
+            self.set_param(id, value);
+        }
+    }
+}
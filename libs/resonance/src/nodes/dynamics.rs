@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::core::{AudioNode, GraphContext};
+use crate::{BLOCK_SIZE, Sample};
+
+/// Levels below this are treated as silence by the level detector.
+const FLOOR_DB: Sample = -120.0;
+
+/// A feed-forward compressor, and with an infinite ratio, a limiter.
+///
+/// The gain computer works in decibels with an optional soft knee; the
+/// gain reduction is then smoothed with separate attack and release times.
+/// With a hard knee and zero attack, nothing leaves above the threshold
+/// (plus makeup).
+///
+/// Inputs:
+/// - 0: Audio Signal
+/// - 1: Sidechain (the level to react to, e.g. for ducking) - Optional
+///
+/// Parameters:
+/// - 0: Threshold in dBFS
+/// - 1: Ratio (e.g. 4.0 for 4:1; infinity limits)
+/// - 2: Attack time in seconds
+/// - 3: Release time in seconds
+/// - 4: Makeup gain in dB
+/// - 5: Knee width in dB
+#[derive(Debug, Clone)]
+pub struct Compressor {
+    pub threshold_db: Sample,
+    pub ratio: Sample,
+    pub attack: Sample,
+    pub release: Sample,
+    pub makeup_db: Sample,
+    pub knee_db: Sample,
+    /// The smoothed gain reduction, in dB.
+    reduction_db: Sample,
+}
+
+impl Compressor {
+    /// A compressor with a 10 ms attack, 100 ms release and a hard knee.
+    pub fn new(threshold_db: Sample, ratio: Sample) -> Self {
+        Self {
+            threshold_db,
+            ratio,
+            attack: 0.010,
+            release: 0.100,
+            makeup_db: 0.0,
+            knee_db: 0.0,
+            reduction_db: 0.0,
+        }
+    }
+
+    /// A brickwall limiter: nothing passes above `ceiling_db`.
+    pub fn limiter(ceiling_db: Sample) -> Self {
+        Self {
+            attack: 0.0,
+            release: 0.050,
+            ..Self::new(ceiling_db, Sample::INFINITY)
+        }
+    }
+
+    /// The gain reduction in dB currently applied.
+    pub fn reduction_db(&self) -> Sample {
+        self.reduction_db
+    }
+
+    /// The static curve: how many dB a steady level is turned down.
+    pub fn static_reduction_db(&self, level_db: Sample) -> Sample {
+        let slope = 1.0 - 1.0 / self.ratio.max(1.0);
+        let over = level_db - self.threshold_db;
+        let knee = self.knee_db.max(0.0);
+        if 2.0 * over <= -knee {
+            0.0
+        } else if 2.0 * over < knee {
+            slope * (over + knee / 2.0).powi(2) / (2.0 * knee)
+        } else {
+            slope * over
+        }
+    }
+}
+
+/// The one-pole smoothing coefficient for a time constant in seconds.
+fn coefficient(time: Sample, sample_rate: Sample) -> Sample {
+    if time <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (time * sample_rate)).exp()
+    }
+}
+
+impl AudioNode for Compressor {
+    fn process(
+        &mut self,
+        inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        context: &GraphContext,
+    ) {
+        if outputs.is_empty() {
+            return;
+        }
+        let out = &mut outputs[0];
+        let attack = coefficient(self.attack, context.sample_rate);
+        let release = coefficient(self.release, context.sample_rate);
+
+        for i in 0..BLOCK_SIZE {
+            let x = inputs.first().map_or(0.0, |input| input[i]);
+            let key = inputs.get(1).map_or(x, |sidechain| sidechain[i]);
+            let level_db = (20.0 * key.abs().log10()).max(FLOOR_DB);
+            let target = self.static_reduction_db(level_db);
+
+            let coefficient = if target > self.reduction_db {
+                attack
+            } else {
+                release
+            };
+            self.reduction_db = target + (self.reduction_db - target) * coefficient;
+            out[i] = x * 10f64.powf((self.makeup_db - self.reduction_db) / 20.0);
+        }
+    }
+
+    fn set_param(&mut self, id: usize, value: f64) {
+        match id {
+            0 => self.threshold_db = value,
+            1 => self.ratio = value,
+            2 => self.attack = value,
+            3 => self.release = value,
+            4 => self.makeup_db = value,
+            5 => self.knee_db = value,
+            _ => {}
+        }
+    }
+}
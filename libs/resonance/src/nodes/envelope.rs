@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::core::{AudioNode, GraphContext};
+use crate::{BLOCK_SIZE, Sample};
+
+/// Where an envelope is in its cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// A linear Attack-Decay-Sustain-Release envelope generator.
+///
+/// Opening the gate starts the attack from wherever the level is, so a
+/// retrigger never clicks; closing it releases from wherever it is. The
+/// output is a control signal from 0.0 to 1.0, meant for a `Gain`'s
+/// modulation input.
+///
+/// Inputs:
+/// - 0: Gate Signal (open at 0.5 and above) - Optional
+///
+/// Parameters:
+/// - 0: Attack time in seconds
+/// - 1: Decay time in seconds
+/// - 2: Sustain level (0.0 to 1.0)
+/// - 3: Release time in seconds
+/// - 4: Gate (open at 0.5 and above); the gate is open if either this or the input is
+#[derive(Debug, Clone)]
+pub struct Adsr {
+    pub attack: Sample,
+    pub decay: Sample,
+    pub sustain: Sample,
+    pub release: Sample,
+    gate: bool,
+    was_open: bool,
+    stage: Stage,
+    level: Sample,
+    /// Level lost per sample during this release.
+    release_step: Sample,
+}
+
+impl Adsr {
+    pub fn new(attack: Sample, decay: Sample, sustain: Sample, release: Sample) -> Self {
+        Self {
+            attack,
+            decay,
+            sustain,
+            release,
+            gate: false,
+            was_open: false,
+            stage: Stage::Idle,
+            level: 0.0,
+            release_step: 0.0,
+        }
+    }
+
+    /// Opens or closes the gate parameter.
+    pub fn set_gate(&mut self, open: bool) {
+        self.gate = open;
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    pub fn level(&self) -> Sample {
+        self.level
+    }
+
+    /// Advances one sample with the gate open or closed.
+    fn tick(&mut self, open: bool, sample_rate: Sample) -> Sample {
+        if open && !self.was_open {
+            self.stage = Stage::Attack;
+        } else if !open && self.was_open {
+            self.stage = Stage::Release;
+            self.release_step = self.level / (self.release * sample_rate).max(1.0);
+        }
+        self.was_open = open;
+
+        let sustain = self.sustain.clamp(0.0, 1.0);
+        match self.stage {
+            Stage::Idle => self.level = 0.0,
+            Stage::Attack => {
+                self.level += 1.0 / (self.attack * sample_rate).max(1.0);
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = Stage::Decay;
+                }
+            }
+            Stage::Decay => {
+                self.level -= (1.0 - sustain) / (self.decay * sample_rate).max(1.0);
+                if self.level <= sustain {
+                    self.level = sustain;
+                    self.stage = Stage::Sustain;
+                }
+            }
+            Stage::Sustain => self.level = sustain,
+            Stage::Release => {
+                self.level -= self.release_step;
+                if self.level <= 0.0 {
+                    self.level = 0.0;
+                    self.stage = Stage::Idle;
+                }
+            }
+        }
+        self.level
+    }
+}
+
+impl AudioNode for Adsr {
+    fn process(
+        &mut self,
+        inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        context: &GraphContext,
+    ) {
+        if outputs.is_empty() {
+            return;
+        }
+        let out = &mut outputs[0];
+
+        for i in 0..BLOCK_SIZE {
+            let open = self.gate || inputs.first().is_some_and(|gate| gate[i] >= 0.5);
+            out[i] = self.tick(open, context.sample_rate);
+        }
+    }
+
+    fn set_param(&mut self, id: usize, value: f64) {
+        match id {
+            0 => self.attack = value,
+            1 => self.decay = value,
+            2 => self.sustain = value,
+            3 => self.release = value,
+            4 => self.gate = value >= 0.5,
+            _ => {}
+        }
+    }
+}
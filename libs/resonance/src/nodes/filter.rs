@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::core::{AudioNode, GraphContext, ParamRamp};
+use crate::{BLOCK_SIZE, Sample};
+use std::f64::consts::TAU;
+
+/// The response shapes a `Biquad` can take, after Robert Bristow-Johnson's
+/// Audio EQ Cookbook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    LowPass,
+    HighPass,
+    /// Constant 0 dB peak gain at the centre frequency.
+    BandPass,
+    Notch,
+    /// A bell boosting or cutting by the gain around the centre frequency.
+    Peaking,
+    LowShelf,
+    HighShelf,
+}
+
+/// Normalised coefficients (a0 = 1).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Coefficients {
+    b0: Sample,
+    b1: Sample,
+    b2: Sample,
+    a1: Sample,
+    a2: Sample,
+}
+
+impl Coefficients {
+    fn design(
+        kind: FilterKind,
+        frequency: Sample,
+        q: Sample,
+        gain_db: Sample,
+        sample_rate: Sample,
+    ) -> Self {
+        // Keep the centre strictly inside (0, Nyquist), where the formulas hold.
+        let frequency = frequency.clamp(1.0, sample_rate * 0.499);
+        let w0 = TAU * frequency / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q.max(1e-3));
+        let a = 10f64.powf(gain_db / 40.0);
+        let shelf = 2.0 * a.sqrt() * alpha;
+
+        let (b0, b1, b2, a0, a1, a2) = match kind {
+            FilterKind::LowPass => (
+                (1.0 - cos) / 2.0,
+                1.0 - cos,
+                (1.0 - cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            FilterKind::HighPass => (
+                (1.0 + cos) / 2.0,
+                -(1.0 + cos),
+                (1.0 + cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            FilterKind::BandPass => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            FilterKind::Notch => (1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            FilterKind::Peaking => (
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+            FilterKind::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos + shelf),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - shelf),
+                (a + 1.0) + (a - 1.0) * cos + shelf,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - shelf,
+            ),
+            FilterKind::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos + shelf),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - shelf),
+                (a + 1.0) - (a - 1.0) * cos + shelf,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - shelf,
+            ),
+        };
+
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// A second-order IIR filter (transposed direct form II).
+///
+/// Inputs:
+/// - 0: Audio Signal
+///
+/// Parameters:
+/// - 0: Frequency in Hz (rampable; the coefficients follow sample by sample)
+/// - 1: Q (for shelves, the shelf slope; 1/sqrt(2) is the steepest without overshoot)
+/// - 2: Gain in dB (peaking and shelves only)
+#[derive(Debug, Clone)]
+pub struct Biquad {
+    pub kind: FilterKind,
+    frequency: Sample,
+    q: Sample,
+    gain_db: Sample,
+    ramp: ParamRamp,
+    coefficients: Coefficients,
+    /// The sample rate `coefficients` were designed for; zero when stale.
+    designed_for: Sample,
+    z1: Sample,
+    z2: Sample,
+}
+
+impl Biquad {
+    pub fn new(kind: FilterKind, frequency: Sample, q: Sample, gain_db: Sample) -> Self {
+        Self {
+            kind,
+            frequency,
+            q,
+            gain_db,
+            ramp: ParamRamp::hold(frequency),
+            coefficients: Coefficients::default(),
+            designed_for: 0.0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    pub fn low_pass(frequency: Sample, q: Sample) -> Self {
+        Self::new(FilterKind::LowPass, frequency, q, 0.0)
+    }
+
+    pub fn high_pass(frequency: Sample, q: Sample) -> Self {
+        Self::new(FilterKind::HighPass, frequency, q, 0.0)
+    }
+
+    pub fn band_pass(frequency: Sample, q: Sample) -> Self {
+        Self::new(FilterKind::BandPass, frequency, q, 0.0)
+    }
+
+    pub fn notch(frequency: Sample, q: Sample) -> Self {
+        Self::new(FilterKind::Notch, frequency, q, 0.0)
+    }
+
+    pub fn peaking(frequency: Sample, q: Sample, gain_db: Sample) -> Self {
+        Self::new(FilterKind::Peaking, frequency, q, gain_db)
+    }
+
+    /// A shelf at the steepest slope that does not overshoot.
+    pub fn low_shelf(frequency: Sample, gain_db: Sample) -> Self {
+        Self::new(
+            FilterKind::LowShelf,
+            frequency,
+            std::f64::consts::FRAC_1_SQRT_2,
+            gain_db,
+        )
+    }
+
+    /// A shelf at the steepest slope that does not overshoot.
+    pub fn high_shelf(frequency: Sample, gain_db: Sample) -> Self {
+        Self::new(
+            FilterKind::HighShelf,
+            frequency,
+            std::f64::consts::FRAC_1_SQRT_2,
+            gain_db,
+        )
+    }
+
+    pub fn frequency(&self) -> Sample {
+        self.frequency
+    }
+
+    /// Clears the filter's memory, as if it had only ever heard silence.
+    pub fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+
+    fn design(&mut self, sample_rate: Sample) {
+        self.coefficients =
+            Coefficients::design(self.kind, self.frequency, self.q, self.gain_db, sample_rate);
+        self.designed_for = sample_rate;
+    }
+}
+
+impl AudioNode for Biquad {
+    fn process(
+        &mut self,
+        inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        context: &GraphContext,
+    ) {
+        if outputs.is_empty() {
+            return;
+        }
+        let out = &mut outputs[0];
+        if self.designed_for != context.sample_rate {
+            self.design(context.sample_rate);
+        }
+
+        for i in 0..BLOCK_SIZE {
+            if self.ramp.is_active() {
+                self.frequency = self.ramp.advance();
+                self.design(context.sample_rate);
+            }
+            let x = inputs.first().map_or(0.0, |input| input[i]);
+            let c = self.coefficients;
+            let y = c.b0 * x + self.z1;
+            self.z1 = c.b1 * x - c.a1 * y + self.z2;
+            self.z2 = c.b2 * x - c.a2 * y;
+            out[i] = y;
+        }
+    }
+
+    fn set_param(&mut self, id: usize, value: f64) {
+        match id {
+            0 => {
+                self.frequency = value;
+                self.ramp = ParamRamp::hold(value);
+            }
+            1 => self.q = value,
+            2 => self.gain_db = value,
+            _ => return,
+        }
+        self.designed_for = 0.0;
+    }
+
+    fn ramp_param(&mut self, id: usize, value: f64, frames: usize) {
+        if id == 0 {
+            self.ramp = ParamRamp::new(self.frequency, value, frames);
+            self.frequency = self.ramp.value();
+            self.designed_for = 0.0;
+        } else {
+            self.set_param(id, value);
+        }
+    }
+}
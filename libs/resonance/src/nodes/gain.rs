@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::core::{AudioNode, GraphContext};
+use crate::core::{AudioNode, GraphContext, ParamRamp};
 use crate::{BLOCK_SIZE, Sample};
 
 /// A Voltage Controlled Amplifier (VCA) node.
@@ -22,16 +22,31 @@ use crate::{BLOCK_SIZE, Sample};
 /// Inputs:
 /// - 0: Audio Signal
 /// - 1: Control Signal (Modulation) - Optional
+///
+/// Parameters:
+/// - 0: Base gain (rampable)
 #[derive(Debug, Clone)]
 pub struct Gain {
     /// The base gain factor.
     pub base_gain: Sample,
+    /// A ramp in progress on `base_gain`, if any.
+    ramp: ParamRamp,
 }
 
 impl Gain {
     /// Creates a new Gain node with the specified base gain.
     pub fn new(base_gain: Sample) -> Self {
-        Self { base_gain }
+        Self {
+            base_gain,
+            ramp: ParamRamp::hold(base_gain),
+        }
+    }
+
+    /// Takes one sample's step along the ramp, if one is running.
+    fn step_ramp(&mut self) {
+        if self.ramp.is_active() {
+            self.base_gain = self.ramp.advance();
+        }
     }
 }
 
@@ -56,13 +71,17 @@ impl AudioNode for Gain {
 
         match inputs.len() {
             0 => {
-                // 0 Inputs: Output Silence.
+                // 0 Inputs: Output Silence, though a ramp still runs its course.
                 out.fill(0.0);
+                for _ in 0..BLOCK_SIZE {
+                    self.step_ramp();
+                }
             }
             1 => {
                 // 1 Input (Signal Only): Output = Input * base_gain.
                 let signal = inputs[0];
                 for i in 0..BLOCK_SIZE {
+                    self.step_ramp();
                     out[i] = signal[i] * self.base_gain;
                 }
             }
@@ -71,9 +90,24 @@ impl AudioNode for Gain {
                 let signal = inputs[0];
                 let modulation = inputs[1];
                 for i in 0..BLOCK_SIZE {
+                    self.step_ramp();
                     out[i] = signal[i] * (self.base_gain + modulation[i]);
                 }
             }
         }
     }
+
+    fn set_param(&mut self, id: usize, value: f64) {
+        if id == 0 {
+            self.base_gain = value;
+            self.ramp = ParamRamp::hold(value);
+        }
+    }
+
+    fn ramp_param(&mut self, id: usize, value: f64, frames: usize) {
+        if id == 0 {
+            self.ramp = ParamRamp::new(self.base_gain, value, frames);
+            self.base_gain = self.ramp.value();
+        }
+    }
 }
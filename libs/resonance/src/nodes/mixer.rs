@@ -19,13 +19,27 @@ use crate::{BLOCK_SIZE, Sample};
 
 /// A Summing Mixer node.
 ///
-/// Sums all connected inputs to the output.
-#[derive(Debug, Clone, Default)]
-pub struct Mixer;
+/// Sums all connected inputs to the output, channel by channel. Every input
+/// port and the output carry the same number of channels.
+#[derive(Debug, Clone)]
+pub struct Mixer {
+    channels: usize,
+}
 
 impl Mixer {
+    /// A mono mixer.
     pub fn new() -> Self {
-        Self
+        Self::with_channels(1)
+    }
+
+    pub fn with_channels(channels: usize) -> Self {
+        Self { channels }
+    }
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -36,33 +50,31 @@ impl AudioNode for Mixer {
         outputs: &mut [&mut [Sample; BLOCK_SIZE]],
         _context: &GraphContext,
     ) {
-        // Must have at least one output buffer.
-        if outputs.is_empty() {
-            return;
-        }
-        let out = &mut outputs[0];
-
-        match inputs.len() {
-            0 => {
+        for (channel, out) in outputs.iter_mut().enumerate() {
+            // Inputs are flat by port: this channel of every port.
+            let mut ports = inputs.iter().skip(channel).step_by(self.channels.max(1));
+            match ports.next() {
                 // 0 Inputs: Output Silence.
-                out.fill(0.0);
-            }
-            1 => {
-                // 1 Input: Copy input to output.
-                out.copy_from_slice(inputs[0]);
-            }
-            _ => {
-                // 2+ Inputs: Accumulate.
-                // Start by copying the first input to avoid zeroing.
-                out.copy_from_slice(inputs[0]);
-
-                // Add subsequent inputs.
-                for input in inputs.iter().skip(1) {
-                    for i in 0..BLOCK_SIZE {
-                        out[i] += input[i];
+                None => out.fill(0.0),
+                Some(first) => {
+                    // Start by copying the first input to avoid zeroing,
+                    // then accumulate the rest.
+                    out.copy_from_slice(*first);
+                    for input in ports {
+                        for i in 0..BLOCK_SIZE {
+                            out[i] += input[i];
+                        }
                     }
                 }
             }
         }
     }
+
+    fn input_channels(&self, _port: usize) -> usize {
+        self.channels
+    }
+
+    fn output_channels(&self, _port: usize) -> usize {
+        self.channels
+    }
 }
@@ -14,6 +14,13 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+pub mod block_delay;
+pub mod delay;
+pub mod dynamics;
+pub mod envelope;
+pub mod filter;
 pub mod gain;
 pub mod mixer;
 pub mod oscillators;
+pub mod pan;
+pub mod reverb;
@@ -14,17 +14,22 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::core::{AudioNode, GraphContext};
+use crate::core::{AudioNode, GraphContext, ParamRamp};
 use crate::{BLOCK_SIZE, Sample};
 use std::f64::consts::TAU;
 
 /// A simple sine wave oscillator with optional frequency modulation.
+///
+/// Parameters:
+/// - 0: Frequency in Hz (rampable)
 #[derive(Debug, Clone)]
 pub struct SineOscillator {
     /// The base frequency in Hz.
     pub frequency: Sample,
     /// The current phase (0.0 to 1.0).
     pub phase: Sample,
+    /// A glide in progress on `frequency`, if any.
+    ramp: ParamRamp,
 }
 
 impl SineOscillator {
@@ -33,6 +38,7 @@ impl SineOscillator {
         Self {
             frequency,
             phase: 0.0,
+            ramp: ParamRamp::hold(frequency),
         }
     }
 }
@@ -61,6 +67,9 @@ impl AudioNode for SineOscillator {
         for i in 0..BLOCK_SIZE {
             // The modulation value for this sample
             let modulation = if let Some(fm) = fm_input { fm[i] } else { 0.0 };
+            if self.ramp.is_active() {
+                self.frequency = self.ramp.advance();
+            }
 
             // Calculate the sine value: sin(TAU * self.phase).
             out[i] = (self.phase * TAU).sin();
@@ -81,10 +90,134 @@ impl AudioNode for SineOscillator {
     }
 
     fn set_param(&mut self, id: usize, value: f64) {
-        match id {
-            // Param 0: Frequency
-            0 => self.frequency = value,
-            _ => {}
+        // Param 0: Frequency
+        if id == 0 {
+            self.frequency = value;
+            self.ramp = ParamRamp::hold(value);
+        }
+    }
+
+    fn ramp_param(&mut self, id: usize, value: f64, frames: usize) {
+        if id == 0 {
+            self.ramp = ParamRamp::new(self.frequency, value, frames);
+            self.frequency = self.ramp.value();
+        }
+    }
+}
+
+/// The shapes a `PolyBlepOscillator` can produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    /// Rises from -1 to 1, then drops.
+    Saw,
+    Square,
+    Triangle,
+}
+
+/// A band-limited saw, square or triangle oscillator.
+///
+/// The naive waveforms jump, and the jumps alias. PolyBLEP smooths each
+/// jump with a two-sample polynomial residual, which removes most of the
+/// aliasing at almost no cost. The triangle is the integrated square, so its
+/// corners are band-limited as well.
+///
+/// Inputs:
+/// - 0: Frequency Modulation in Hz - Optional
+///
+/// Parameters:
+/// - 0: Frequency in Hz (rampable)
+#[derive(Debug, Clone)]
+pub struct PolyBlepOscillator {
+    pub waveform: Waveform,
+    /// The base frequency in Hz.
+    pub frequency: Sample,
+    /// The current phase (0.0 to 1.0).
+    pub phase: Sample,
+    ramp: ParamRamp,
+    /// The running integral of the square, for the triangle.
+    integrator: Sample,
+}
+
+impl PolyBlepOscillator {
+    pub fn new(waveform: Waveform, frequency: Sample) -> Self {
+        Self {
+            waveform,
+            frequency,
+            phase: 0.0,
+            ramp: ParamRamp::hold(frequency),
+            // The square starts high, so the triangle starts at its trough.
+            integrator: -1.0,
+        }
+    }
+
+    fn square(&self, dt: Sample) -> Sample {
+        let naive = if self.phase < 0.5 { 1.0 } else { -1.0 };
+        naive + poly_blep(self.phase, dt) - poly_blep((self.phase + 0.5).fract(), dt)
+    }
+}
+
+/// The correction for a unit upward jump at phase 0, spread over the sample
+/// either side of it; `dt` is the phase increment per sample.
+fn poly_blep(t: Sample, dt: Sample) -> Sample {
+    if t < dt {
+        let t = t / dt;
+        t + t - t * t - 1.0
+    } else if t > 1.0 - dt {
+        let t = (t - 1.0) / dt;
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+impl AudioNode for PolyBlepOscillator {
+    fn process(
+        &mut self,
+        inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        context: &GraphContext,
+    ) {
+        if outputs.is_empty() {
+            return;
+        }
+        let out = &mut outputs[0];
+
+        for i in 0..BLOCK_SIZE {
+            if self.ramp.is_active() {
+                self.frequency = self.ramp.advance();
+            }
+            let modulation = inputs.first().map_or(0.0, |fm| fm[i]);
+            let increment = (self.frequency + modulation) * context.inv_sample_rate;
+            // The residual needs a positive step below Nyquist.
+            let dt = increment.abs().clamp(1e-9, 0.5);
+
+            out[i] = match self.waveform {
+                Waveform::Saw => 2.0 * self.phase - 1.0 - poly_blep(self.phase, dt),
+                Waveform::Square => self.square(dt),
+                Waveform::Triangle => {
+                    // A leaky integrator: slope 4 dt spans -1..1 in half a
+                    // period, and the leak keeps the result centred.
+                    self.integrator = 4.0 * dt * self.square(dt) + (1.0 - dt) * self.integrator;
+                    self.integrator
+                }
+            };
+
+            self.phase += increment;
+            self.phase -= self.phase.floor();
+        }
+    }
+
+    fn set_param(&mut self, id: usize, value: f64) {
+        if id == 0 {
+            self.frequency = value;
+            self.ramp = ParamRamp::hold(value);
+        }
+    }
+
+    fn ramp_param(&mut self, id: usize, value: f64, frames: usize) {
+        if id == 0 {
+            self.ramp = ParamRamp::new(self.frequency, value, frames);
+            self.frequency = self.ramp.value();
         }
     }
 }
@@ -136,4 +269,19 @@ mod tests {
         osc.set_param(0, 880.0);
         assert_eq!(osc.frequency, 880.0);
     }
+
+    #[test]
+    fn test_sine_oscillator_ramp_param() {
+        let mut osc = SineOscillator::new(440.0);
+        let context = GraphContext::new(44100.0);
+        let mut output = [0.0; BLOCK_SIZE];
+        let mut outputs = [&mut output];
+        let inputs: &[&[Sample; BLOCK_SIZE]] = &[];
+
+        osc.ramp_param(0, 440.0 + BLOCK_SIZE as f64 * 2.0, BLOCK_SIZE * 2);
+        osc.process(inputs, &mut outputs, &context);
+        assert!((osc.frequency - (440.0 + BLOCK_SIZE as f64)).abs() < 1e-9);
+        osc.process(inputs, &mut outputs, &context);
+        assert_eq!(osc.frequency, 440.0 + BLOCK_SIZE as f64 * 2.0);
+    }
 }
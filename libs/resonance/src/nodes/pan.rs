@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::core::{AudioNode, GraphContext, ParamRamp};
+use crate::{BLOCK_SIZE, Sample};
+use std::f64::consts::FRAC_PI_4;
+
+/// Places a mono signal in the stereo field with the constant-power
+/// (sine/cosine) law: the centre is 3 dB down on each side, and the total
+/// power is the same everywhere.
+///
+/// Inputs:
+/// - 0: Audio Signal (mono)
+///
+/// Outputs: stereo.
+///
+/// Parameters:
+/// - 0: Position, from -1.0 (left) through 0.0 (centre) to 1.0 (right) (rampable)
+#[derive(Debug, Clone)]
+pub struct Pan {
+    pub position: Sample,
+    ramp: ParamRamp,
+}
+
+impl Pan {
+    pub fn new(position: Sample) -> Self {
+        Self {
+            position,
+            ramp: ParamRamp::hold(position),
+        }
+    }
+
+    /// The left and right gains for `position`.
+    pub fn gains(position: Sample) -> (Sample, Sample) {
+        let angle = (position.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+        (angle.cos(), angle.sin())
+    }
+}
+
+impl Default for Pan {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl AudioNode for Pan {
+    fn process(
+        &mut self,
+        inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        _context: &GraphContext,
+    ) {
+        let [left, right] = outputs else {
+            return;
+        };
+        let mut gains = Self::gains(self.position);
+
+        for i in 0..BLOCK_SIZE {
+            if self.ramp.is_active() {
+                self.position = self.ramp.advance();
+                gains = Self::gains(self.position);
+            }
+            let x = inputs.first().map_or(0.0, |input| input[i]);
+            left[i] = x * gains.0;
+            right[i] = x * gains.1;
+        }
+    }
+
+    fn set_param(&mut self, id: usize, value: f64) {
+        if id == 0 {
+            self.position = value;
+            self.ramp = ParamRamp::hold(value);
+        }
+    }
+
+    fn ramp_param(&mut self, id: usize, value: f64, frames: usize) {
+        if id == 0 {
+            self.ramp = ParamRamp::new(self.position, value, frames);
+            self.position = self.ramp.value();
+        }
+    }
+
+    fn output_channels(&self, _port: usize) -> usize {
+        2
+    }
+}
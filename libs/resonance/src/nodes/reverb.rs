@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::core::{AudioNode, GraphContext};
+use crate::{BLOCK_SIZE, Sample};
+
+/// Freeverb's tunings, in samples at 44.1 kHz.
+const COMB_TUNINGS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_TUNINGS: [usize; 4] = [556, 441, 341, 225];
+/// How much longer the right channel's lines are, decorrelating the two.
+const STEREO_SPREAD: usize = 23;
+const TUNING_RATE: Sample = 44100.0;
+
+const INPUT_GAIN: Sample = 0.015;
+const SCALE_WET: Sample = 3.0;
+const SCALE_DRY: Sample = 2.0;
+const SCALE_DAMPING: Sample = 0.4;
+const SCALE_ROOM: Sample = 0.28;
+const OFFSET_ROOM: Sample = 0.7;
+const ALLPASS_FEEDBACK: Sample = 0.5;
+
+/// A feedback comb with a one-pole low-pass in the loop.
+#[derive(Debug, Clone)]
+struct Comb {
+    line: Vec<Sample>,
+    index: usize,
+    filtered: Sample,
+}
+
+impl Comb {
+    fn new(len: usize) -> Self {
+        Self {
+            line: vec![0.0; len.max(1)],
+            index: 0,
+            filtered: 0.0,
+        }
+    }
+
+    fn tick(&mut self, input: Sample, feedback: Sample, damping: Sample) -> Sample {
+        let out = self.line[self.index];
+        self.filtered = out * (1.0 - damping) + self.filtered * damping;
+        self.line[self.index] = input + self.filtered * feedback;
+        self.index = (self.index + 1) % self.line.len();
+        out
+    }
+}
+
+/// Freeverb's (not quite) all-pass diffuser.
+#[derive(Debug, Clone)]
+struct Allpass {
+    line: Vec<Sample>,
+    index: usize,
+}
+
+impl Allpass {
+    fn new(len: usize) -> Self {
+        Self {
+            line: vec![0.0; len.max(1)],
+            index: 0,
+        }
+    }
+
+    fn tick(&mut self, input: Sample) -> Sample {
+        let delayed = self.line[self.index];
+        self.line[self.index] = input + delayed * ALLPASS_FEEDBACK;
+        self.index = (self.index + 1) % self.line.len();
+        delayed - input
+    }
+}
+
+/// One channel's network: parallel combs into series all-passes.
+#[derive(Debug, Clone)]
+struct Tank {
+    combs: Vec<Comb>,
+    allpasses: Vec<Allpass>,
+}
+
+impl Tank {
+    fn new(scale: Sample, spread: usize) -> Self {
+        let len = |tuning: usize| ((tuning + spread) as Sample * scale).round() as usize;
+        Self {
+            combs: COMB_TUNINGS.iter().map(|&t| Comb::new(len(t))).collect(),
+            allpasses: ALLPASS_TUNINGS
+                .iter()
+                .map(|&t| Allpass::new(len(t)))
+                .collect(),
+        }
+    }
+
+    fn tick(&mut self, input: Sample, feedback: Sample, damping: Sample) -> Sample {
+        let mut out = 0.0;
+        for comb in &mut self.combs {
+            out += comb.tick(input, feedback, damping);
+        }
+        for allpass in &mut self.allpasses {
+            out = allpass.tick(out);
+        }
+        out
+    }
+}
+
+/// A stereo Schroeder reverb, after Jezar's public-domain Freeverb.
+///
+/// Inputs:
+/// - 0: Audio Signal (mono)
+///
+/// Outputs: stereo.
+///
+/// Parameters:
+/// - 0: Room size (0.0 to 1.0)
+/// - 1: Damping of high frequencies (0.0 to 1.0)
+/// - 2: Wet level
+/// - 3: Dry level
+/// - 4: Stereo width (0.0 to 1.0)
+#[derive(Debug, Clone)]
+pub struct Reverb {
+    pub room_size: Sample,
+    pub damping: Sample,
+    pub wet: Sample,
+    pub dry: Sample,
+    pub width: Sample,
+    left: Tank,
+    right: Tank,
+}
+
+impl Reverb {
+    /// A medium room, fully wet, as Freeverb starts.
+    ///
+    /// The delay lines are allocated here, off the audio thread, and scaled
+    /// to `sample_rate`, which should be the graph's.
+    pub fn new(sample_rate: Sample) -> Self {
+        let scale = sample_rate / TUNING_RATE;
+        Self {
+            room_size: 0.5,
+            damping: 0.5,
+            wet: 1.0 / SCALE_WET,
+            dry: 0.0,
+            width: 1.0,
+            left: Tank::new(scale, 0),
+            right: Tank::new(scale, STEREO_SPREAD),
+        }
+    }
+}
+
+impl AudioNode for Reverb {
+    fn process(
+        &mut self,
+        inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        _context: &GraphContext,
+    ) {
+        let feedback = self.room_size.clamp(0.0, 1.0) * SCALE_ROOM + OFFSET_ROOM;
+        let damping = self.damping.clamp(0.0, 1.0) * SCALE_DAMPING;
+        let wet = self.wet * SCALE_WET;
+        let dry = self.dry * SCALE_DRY;
+        let width = self.width.clamp(0.0, 1.0);
+        let wet_same = wet * (width / 2.0 + 0.5);
+        let wet_cross = wet * ((1.0 - width) / 2.0);
+
+        for i in 0..BLOCK_SIZE {
+            let x = inputs.first().map_or(0.0, |input| input[i]);
+            let left = self.left.tick(x * INPUT_GAIN, feedback, damping);
+            let right = self.right.tick(x * INPUT_GAIN, feedback, damping);
+            let mixed = [
+                left * wet_same + right * wet_cross + x * dry,
+                right * wet_same + left * wet_cross + x * dry,
+            ];
+            for (out, sample) in outputs.iter_mut().zip(mixed) {
+                out[i] = sample;
+            }
+        }
+    }
+
+    fn set_param(&mut self, id: usize, value: f64) {
+        match id {
+            0 => self.room_size = value,
+            1 => self.damping = value,
+            2 => self.wet = value,
+            3 => self.dry = value,
+            4 => self.width = value,
+            _ => {}
+        }
+    }
+
+    fn output_channels(&self, _port: usize) -> usize {
+        2
+    }
+}
@@ -0,0 +1,216 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use resonance::dsp::{Complex, FftContext, RealFft, SpectrumAnalyzer, Stft, Window};
+use std::f32::consts::TAU;
+
+/// Deterministic noise in [-1, 1).
+fn noise(len: usize, seed: u32) -> Vec<f32> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1 << 23) as f32 - 1.0
+        })
+        .collect()
+}
+
+fn tone(len: usize, cycles_per_frame: f32, amplitude: f32) -> Vec<f32> {
+    (0..len)
+        .map(|i| amplitude * (TAU * cycles_per_frame * i as f32 / len as f32).sin())
+        .collect()
+}
+
+fn max_error(a: &[f32], b: &[f32]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).abs())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn test_fft_inverse_round_trip() {
+    let fft = FftContext::new(512);
+    let re = noise(512, 1);
+    let im = noise(512, 2);
+    let original: Vec<Complex> = re
+        .iter()
+        .zip(&im)
+        .map(|(&r, &i)| Complex::new(r, i))
+        .collect();
+
+    let mut buffer = original.clone();
+    fft.process(&mut buffer);
+    fft.inverse(&mut buffer);
+    for (a, b) in buffer.iter().zip(&original) {
+        assert!((a.re - b.re).abs() < 1e-5 && (a.im - b.im).abs() < 1e-5);
+    }
+
+    // A lone DC bin comes back as a constant 1/N of it.
+    let mut buffer = vec![Complex::default(); 512];
+    buffer[0] = Complex::new(512.0, 0.0);
+    fft.inverse(&mut buffer);
+    assert!(
+        buffer
+            .iter()
+            .all(|c| (c.re - 1.0).abs() < 1e-6 && c.im.abs() < 1e-6)
+    );
+}
+
+#[test]
+fn test_real_fft_matches_complex() {
+    for size in [2, 4, 64, 1024] {
+        let input = noise(size, size as u32);
+        let mut expected: Vec<Complex> = input.iter().map(|&x| Complex::new(x, 0.0)).collect();
+        FftContext::new(size).process(&mut expected);
+
+        let real = RealFft::new(size);
+        let mut spectrum = vec![Complex::default(); real.bins()];
+        real.forward(&input, &mut spectrum);
+        for (k, (a, b)) in spectrum.iter().zip(&expected).enumerate() {
+            assert!(
+                (a.re - b.re).abs() < 1e-3 && (a.im - b.im).abs() < 1e-3,
+                "size {size}, bin {k}: {a:?} != {b:?}"
+            );
+        }
+
+        let mut output = vec![0.0; size];
+        real.inverse(&mut spectrum, &mut output);
+        assert!(max_error(&output, &input) < 1e-5, "size {size} round trip");
+    }
+}
+
+#[test]
+fn test_window_coherent_gains() {
+    let gains = [
+        (Window::Rectangular, 1.0),
+        (Window::Hann, 0.5),
+        (Window::Hamming, 0.54),
+        (Window::BlackmanHarris, 0.35875),
+        (Window::Kaiser(0.0), 1.0),
+    ];
+    for (window, gain) in gains {
+        assert!(
+            (window.coherent_gain(1024) - gain).abs() < 1e-5,
+            "{window:?}"
+        );
+    }
+
+    // Periodic: the first sample is the minimum and there is no repeat at the end.
+    let hann = Window::Hann.coefficients(8);
+    assert_eq!(hann[0], 0.0);
+    assert!((hann[4] - 1.0).abs() < 1e-6);
+    assert!((hann[1] - hann[7]).abs() < 1e-6);
+    let kaiser = Window::Kaiser(8.6).coefficients(64);
+    assert!((kaiser[32] - 1.0).abs() < 1e-6 && kaiser[0] < 2e-3);
+}
+
+#[test]
+fn test_analyzer_reads_tone_amplitude() {
+    const SIZE: usize = 1024;
+    let windows = [
+        Window::Rectangular,
+        Window::Hann,
+        Window::Hamming,
+        Window::BlackmanHarris,
+        Window::Kaiser(8.6),
+    ];
+    for window in windows {
+        let mut analyzer = SpectrumAnalyzer::new(SIZE, window);
+        let mut amplitudes = vec![0.0; analyzer.bins()];
+        let input: Vec<f32> = tone(SIZE, 37.0, 0.8).iter().map(|x| x + 0.25).collect();
+        analyzer.amplitudes(&input, &mut amplitudes);
+        assert!(
+            (amplitudes[37] - 0.8).abs() < 1e-3,
+            "{window:?}: {}",
+            amplitudes[37]
+        );
+        assert!((amplitudes[0] - 0.25).abs() < 1e-3, "{window:?} DC");
+    }
+}
+
+#[test]
+fn test_windows_contain_leakage() {
+    const SIZE: usize = 1024;
+    // Halfway between bins: the worst case for leakage.
+    let input = tone(SIZE, 100.5, 1.0);
+    let far = |window| {
+        let mut analyzer = SpectrumAnalyzer::new(SIZE, window);
+        let mut amplitudes = vec![0.0; analyzer.bins()];
+        analyzer.amplitudes(&input, &mut amplitudes);
+        20.0 * amplitudes[300].log10()
+    };
+    assert!(far(Window::Rectangular) > -60.0);
+    assert!(far(Window::Hann) < -80.0);
+    assert!(far(Window::BlackmanHarris) < -90.0);
+    assert!(far(Window::Kaiser(8.6)) < -90.0);
+}
+
+/// Feeds `input` through in uneven chunks and returns what came out.
+fn stft_round_trip(stft: &mut Stft, input: &[f32], gain: f32) -> Vec<f32> {
+    let mut output = vec![0.0; input.len()];
+    let mut start = 0;
+    let mut chunk = 1;
+    while start < input.len() {
+        let end = (start + chunk).min(input.len());
+        stft.process(&input[start..end], &mut output[start..end], |bins| {
+            for c in bins.iter_mut() {
+                c.re *= gain;
+                c.im *= gain;
+            }
+        });
+        start = end;
+        chunk = chunk * 3 % 101 + 1;
+    }
+    output
+}
+
+#[test]
+fn test_stft_reconstructs_under_cola() {
+    const SIZE: usize = 512;
+    let input = noise(8192, 7);
+    let cases = [
+        (Window::Hann, SIZE / 2),
+        (Window::Hann, SIZE / 4),
+        (Window::Hamming, SIZE / 2),
+        (Window::BlackmanHarris, SIZE / 4),
+        (Window::Rectangular, SIZE),
+    ];
+    for (window, hop) in cases {
+        let mut stft = Stft::new(SIZE, hop, window);
+        assert!(stft.is_cola(), "{window:?} at hop {hop}");
+        let output = stft_round_trip(&mut stft, &input, 1.0);
+        let latency = stft.latency();
+        assert!(output[..latency].iter().all(|&y| y.abs() < 1e-6));
+        let error = max_error(&output[latency..], &input[..input.len() - latency]);
+        assert!(error < 1e-4, "{window:?} at hop {hop}: error {error}");
+    }
+}
+
+#[test]
+fn test_stft_spectral_changes_and_non_cola() {
+    const SIZE: usize = 256;
+    let input = noise(4096, 11);
+
+    let mut stft = Stft::new(SIZE, SIZE / 4, Window::Hann);
+    let output = stft_round_trip(&mut stft, &input, 0.5);
+    let halved: Vec<f32> = input.iter().map(|x| x * 0.5).collect();
+    assert!(max_error(&output[SIZE..], &halved[..input.len() - SIZE]) < 1e-4);
+
+    // Kaiser never quite overlap-adds to a constant.
+    let stft = Stft::new(SIZE, SIZE / 2, Window::Kaiser(8.6));
+    assert!(!stft.is_cola());
+}
@@ -14,7 +14,10 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use resonance::{AudioGraph, BLOCK_SIZE, Gain, Mixer, Sample, SineOscillator};
+use resonance::{
+    AudioGraph, AudioNode, BLOCK_SIZE, BlockDelay, ChannelMix, Gain, GraphContext, GraphError,
+    Mixer, NodeId, Sample, SineOscillator,
+};
 
 #[test]
 fn test_graph_chain_mixer() {
@@ -36,9 +39,9 @@ fn test_graph_chain_mixer() {
 
     // 2. Connect
     // Osc1 -> Mixer Input 0
-    graph.connect(osc1_id, mixer_id, 0);
+    graph.connect(osc1_id, mixer_id, 0).unwrap();
     // Osc2 -> Mixer Input 1
-    graph.connect(osc2_id, mixer_id, 1);
+    graph.connect(osc2_id, mixer_id, 1).unwrap();
 
     // 3. Process
     let output_block = graph.process();
@@ -70,7 +73,7 @@ fn test_graph_chain_gain() {
     let gain_id = graph.add_node(gain);
 
     // Osc -> Gain Input 0
-    graph.connect(osc_id, gain_id, 0);
+    graph.connect(osc_id, gain_id, 0).unwrap();
 
     // Process
     let output_block = graph.process();
@@ -99,5 +102,240 @@ fn test_invalid_connection_panic() {
     // Try to connect a non-existent node
     use resonance::NodeId;
     let bad_id = NodeId(999);
-    graph.connect(id, bad_id, 0);
+    let _ = graph.connect(id, bad_id, 0);
+}
+
+/// Emits fixed values, one per channel, on a single output port.
+struct Constant(Vec<Sample>);
+
+impl AudioNode for Constant {
+    fn process(
+        &mut self,
+        _inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        _context: &GraphContext,
+    ) {
+        for (out, value) in outputs.iter_mut().zip(&self.0) {
+            out.fill(*value);
+        }
+    }
+
+    fn output_channels(&self, _port: usize) -> usize {
+        self.0.len()
+    }
+}
+
+/// A single 1.0 at the very first sample, then silence.
+struct Impulse(bool);
+
+impl AudioNode for Impulse {
+    fn process(
+        &mut self,
+        _inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        _context: &GraphContext,
+    ) {
+        outputs[0].fill(0.0);
+        if !self.0 {
+            outputs[0][0] = 1.0;
+            self.0 = true;
+        }
+    }
+}
+
+/// Counts up by one per sample, across blocks.
+struct Ramp(Sample);
+
+impl AudioNode for Ramp {
+    fn process(
+        &mut self,
+        _inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        _context: &GraphContext,
+    ) {
+        for sample in outputs[0].iter_mut() {
+            *sample = self.0;
+            self.0 += 1.0;
+        }
+    }
+}
+
+/// Takes any number of channels on port 0 and reports them back unchanged.
+struct Probe(usize);
+
+impl AudioNode for Probe {
+    fn process(
+        &mut self,
+        inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        _context: &GraphContext,
+    ) {
+        for (out, input) in outputs.iter_mut().zip(inputs) {
+            out.copy_from_slice(*input);
+        }
+    }
+
+    fn input_channels(&self, _port: usize) -> usize {
+        self.0
+    }
+
+    fn output_channels(&self, _port: usize) -> usize {
+        self.0
+    }
+}
+
+fn probe(source: Vec<Sample>, channels: usize, mix: ChannelMix) -> Vec<Sample> {
+    let mut graph = AudioGraph::new(48000.0);
+    let src = graph.add_node(Box::new(Constant(source)));
+    let dst = graph.add_node(Box::new(Probe(channels)));
+    graph.connect_ports(src, 0, dst, 0, mix).unwrap();
+    graph.render().iter().map(|channel| channel[0]).collect()
+}
+
+#[test]
+fn test_nodes_added_out_of_order_are_sorted() {
+    let mut graph = AudioGraph::new(48000.0);
+    // Consumers first, the source last: insertion order would read stale buffers.
+    let gain = graph.add_node(Box::new(Gain::new(2.0)));
+    let mixer = graph.add_node(Box::new(Mixer::new()));
+    let ramp = graph.add_node(Box::new(Ramp(0.0)));
+    graph.connect(ramp, mixer, 0).unwrap();
+    graph.connect(mixer, gain, 0).unwrap();
+    graph.set_output(gain);
+
+    let out = graph.render_frames(BLOCK_SIZE + 3);
+    assert_eq!(out.len(), 1);
+    let expected: Vec<Sample> = (0..BLOCK_SIZE + 3).map(|i| 2.0 * i as Sample).collect();
+    assert_eq!(out[0], expected);
+}
+
+#[test]
+fn test_cycles_are_rejected_and_leave_the_graph_intact() {
+    let mut graph = AudioGraph::new(48000.0);
+    let ramp = graph.add_node(Box::new(Ramp(0.0)));
+    let a = graph.add_node(Box::new(Mixer::new()));
+    let b = graph.add_node(Box::new(Gain::new(1.0)));
+    graph.connect(ramp, a, 0).unwrap();
+    graph.connect(a, b, 0).unwrap();
+
+    assert_eq!(
+        graph.connect(b, a, 1),
+        Err(GraphError::Cycle(vec![a, b, a]))
+    );
+    assert_eq!(graph.connect(b, b, 1), Err(GraphError::Cycle(vec![b, b])));
+    assert_eq!(
+        graph.connect_ports(ramp, 1, b, 1, ChannelMix::Speakers),
+        Err(GraphError::NoSuchOutput {
+            node: ramp,
+            port: 1
+        })
+    );
+    assert_eq!(
+        graph.connect(b, a, 1).unwrap_err().to_string(),
+        "cycle without a block delay: 1 -> 2 -> 1"
+    );
+
+    // The rejected wires were never made.
+    let out = graph.render_frames(4);
+    assert_eq!(out[0], vec![0.0, 1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn test_feedback_through_a_block_delay() {
+    // impulse -> mixer -> output, and mixer -> delay -> gain(0.5) -> mixer.
+    let mut graph = AudioGraph::new(48000.0);
+    let impulse = graph.add_node(Box::new(Impulse(false)));
+    let mixer = graph.add_node(Box::new(Mixer::new()));
+    let delay = graph.add_node(Box::new(BlockDelay::new(1)));
+    let gain = graph.add_node(Box::new(Gain::new(0.5)));
+    graph.connect(impulse, mixer, 0).unwrap();
+    graph.connect(mixer, delay, 0).unwrap();
+    graph.connect(delay, gain, 0).unwrap();
+    graph.connect(gain, mixer, 1).unwrap();
+    graph.set_output(mixer);
+
+    let out = graph.render_frames(4 * BLOCK_SIZE);
+    for block in 0..4 {
+        let start = block * BLOCK_SIZE;
+        assert_eq!(out[0][start], 0.5f64.powi(block as i32), "block {block}");
+        assert!(
+            out[0][start + 1..start + BLOCK_SIZE]
+                .iter()
+                .all(|&s| s == 0.0)
+        );
+    }
+}
+
+#[test]
+fn test_chained_delays_add_a_block_each() {
+    let mut graph = AudioGraph::new(48000.0);
+    let impulse = graph.add_node(Box::new(Impulse(false)));
+    let first = graph.add_node(Box::new(BlockDelay::new(1)));
+    let second = graph.add_node(Box::new(BlockDelay::new(1)));
+    graph.connect(impulse, first, 0).unwrap();
+    graph.connect(first, second, 0).unwrap();
+    let out = graph.render_frames(3 * BLOCK_SIZE);
+
+    let hits: Vec<usize> = (0..out[0].len()).filter(|&i| out[0][i] != 0.0).collect();
+    assert_eq!(hits, vec![2 * BLOCK_SIZE]);
+}
+
+#[test]
+fn test_channel_up_and_down_mixing() {
+    use ChannelMix::{Discrete, Speakers};
+
+    // Mono spreads, anything folds to mono by averaging.
+    assert_eq!(probe(vec![0.5], 2, Speakers), vec![0.5, 0.5]);
+    assert_eq!(probe(vec![1.0, 3.0], 1, Speakers), vec![2.0]);
+    // Quad folds onto stereo: L with SL, R with SR.
+    assert_eq!(probe(vec![1.0, 2.0, 3.0, 6.0], 2, Speakers), vec![2.0, 4.0]);
+    // Stereo into quad fills the surrounds with silence.
+    assert_eq!(probe(vec![1.0, 2.0], 4, Speakers), vec![1.0, 2.0, 0.0, 0.0]);
+    // Discrete: channel for channel.
+    assert_eq!(probe(vec![0.5], 2, Discrete), vec![0.5, 0.0]);
+    assert_eq!(probe(vec![1.0, 3.0], 1, Discrete), vec![1.0]);
+}
+
+#[test]
+fn test_stereo_mixer_with_wide_fan_in() {
+    let mut graph = AudioGraph::new(48000.0);
+    let mixer = graph.add_node(Box::new(Mixer::with_channels(2)));
+    // Twelve inputs, well past the old limit of eight; mono ones spread to both sides.
+    for port in 0..12 {
+        let source = if port % 2 == 0 {
+            Constant(vec![1.0])
+        } else {
+            Constant(vec![1.0, -1.0])
+        };
+        let id = graph.add_node(Box::new(source));
+        graph.connect(id, mixer, port).unwrap();
+    }
+    graph.set_output(mixer);
+
+    assert_eq!(graph.output_channels(), 2);
+    let out = graph.render_frames(BLOCK_SIZE);
+    assert!(out[0].iter().all(|&s| s == 12.0));
+    assert!(out[1].iter().all(|&s| s == 0.0));
+}
+
+#[test]
+fn test_disconnect_and_output_selection() {
+    let mut graph = AudioGraph::new(48000.0);
+    let a = graph.add_node(Box::new(Constant(vec![1.0])));
+    let b = graph.add_node(Box::new(Constant(vec![2.0])));
+    let mixer = graph.add_node(Box::new(Mixer::new()));
+    graph.connect(a, mixer, 0).unwrap();
+    graph.connect(b, mixer, 3).unwrap();
+    assert_eq!(graph.render_frames(1), vec![vec![3.0]]);
+
+    graph.disconnect(mixer, 3);
+    assert_eq!(graph.render_frames(1), vec![vec![1.0]]);
+
+    graph.set_output(b);
+    assert_eq!(graph.render_frames(2), vec![vec![2.0, 2.0]]);
+    assert_eq!(graph.process()[0], 2.0);
+
+    let mut empty = AudioGraph::new(48000.0);
+    assert_eq!(empty.render_frames(3), vec![vec![0.0; 3]]);
+    let _ = NodeId(0);
 }
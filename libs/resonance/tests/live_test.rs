@@ -0,0 +1,280 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use resonance::{
+    AudioGraph, AudioNode, BLOCK_SIZE, Gain, GraphContext, GraphError, Mixer, NodeId, Sample,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Counts allocations and frees made by the current thread while it is
+/// watching, so that tests running alongside do not interfere.
+struct CountingAllocator;
+
+thread_local! {
+    static WATCHING: Cell<bool> = const { Cell::new(false) };
+    static COUNT: Cell<usize> = const { Cell::new(0) };
+}
+
+fn note() {
+    if WATCHING.with(Cell::get) {
+        COUNT.with(|c| c.set(c.get() + 1));
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        note();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        note();
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        note();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Runs `f` and returns how many allocations and frees it made.
+fn count_allocations(f: impl FnOnce()) -> usize {
+    COUNT.with(|c| c.set(0));
+    WATCHING.with(|w| w.set(true));
+    f();
+    WATCHING.with(|w| w.set(false));
+    COUNT.with(Cell::get)
+}
+
+/// Outputs a constant value.
+struct Constant(Sample);
+
+impl AudioNode for Constant {
+    fn process(
+        &mut self,
+        _inputs: &[&[Sample; BLOCK_SIZE]],
+        outputs: &mut [&mut [Sample; BLOCK_SIZE]],
+        _context: &GraphContext,
+    ) {
+        outputs[0].fill(self.0);
+    }
+}
+
+/// A constant source feeding a gain, which is the output.
+fn constant_through_gain(value: Sample, gain: Sample) -> (AudioGraph, NodeId, NodeId) {
+    let mut graph = AudioGraph::new(48000.0);
+    let source = graph.add_node(Box::new(Constant(value)));
+    let gain = graph.add_node(Box::new(Gain::new(gain)));
+    graph.connect(source, gain, 0).unwrap();
+    graph.set_output(gain);
+    (graph, source, gain)
+}
+
+#[test]
+fn test_live_edits_apply_at_the_next_block() {
+    let (graph, source, gain) = constant_through_gain(1.0, 0.5);
+    let (mut controller, mut processor) = graph.into_live(16);
+    assert_eq!(processor.render()[0], [0.5; BLOCK_SIZE]);
+
+    controller.set_param(gain, 0, 2.0).unwrap();
+    assert_eq!(processor.render()[0], [2.0; BLOCK_SIZE]);
+
+    let louder = controller.add_node(Box::new(Constant(3.0))).unwrap();
+    controller.connect(louder, gain, 0).unwrap();
+    assert_eq!(processor.render()[0], [6.0; BLOCK_SIZE]);
+
+    controller.remove_node(louder).unwrap();
+    assert_eq!(processor.render()[0], [0.0; BLOCK_SIZE]);
+
+    controller.connect(source, gain, 0).unwrap();
+    assert_eq!(processor.render()[0], [2.0; BLOCK_SIZE]);
+}
+
+#[test]
+fn test_live_controller_rejects_cycles_without_sending() {
+    let (graph, source, gain) = constant_through_gain(1.0, 1.0);
+    let (mut controller, mut processor) = graph.into_live(16);
+    assert!(matches!(
+        controller.connect(gain, source, 0),
+        Err(GraphError::Cycle(_))
+    ));
+    assert_eq!(processor.render()[0], [1.0; BLOCK_SIZE]);
+}
+
+#[test]
+fn test_live_render_never_allocates() {
+    // The counter itself works.
+    assert!(count_allocations(|| drop(std::hint::black_box(vec![0u8; 8]))) > 0);
+
+    let (graph, source, gain) = constant_through_gain(1.0, 1.0);
+    let (mut controller, mut processor) = graph.into_live(64);
+    let mut render = || {
+        let processor = &mut processor;
+        count_allocations(move || {
+            processor.render();
+        })
+    };
+    assert_eq!(render(), 0);
+
+    // Enough nodes to outgrow the audio side's node table more than once.
+    let mut mixers = Vec::new();
+    for _ in 0..20 {
+        let mixer = controller.add_node(Box::new(Mixer::new())).unwrap();
+        controller.connect(source, mixer, 0).unwrap();
+        controller.connect(mixer, gain, 1).unwrap();
+        mixers.push(mixer);
+        assert_eq!(render(), 0);
+    }
+
+    controller.set_param(gain, 0, 0.5).unwrap();
+    controller.ramp_param(gain, 0, 0.0, 3 * BLOCK_SIZE).unwrap();
+    for _ in 0..4 {
+        assert_eq!(render(), 0);
+    }
+
+    for mixer in mixers {
+        controller.remove_node(mixer).unwrap();
+        assert_eq!(render(), 0);
+    }
+    controller.disconnect(gain, 1).unwrap();
+    controller.stop().unwrap();
+    assert_eq!(render(), 0);
+
+    // What the last blocks replaced came back to this thread.
+    assert!(controller.collect_garbage() > 0);
+    assert_eq!(controller.collect_garbage(), 0);
+}
+
+#[test]
+fn test_live_ramp_is_sample_accurate() {
+    let (graph, _, gain) = constant_through_gain(1.0, 0.0);
+    let (mut controller, mut processor) = graph.into_live(16);
+    let frames = 2 * BLOCK_SIZE;
+    controller.ramp_param(gain, 0, 1.0, frames).unwrap();
+
+    let mut rendered = Vec::new();
+    for _ in 0..3 {
+        rendered.extend_from_slice(&processor.render()[0]);
+    }
+    for (i, &sample) in rendered.iter().enumerate() {
+        let expected = ((i + 1) as Sample / frames as Sample).min(1.0);
+        assert!(
+            (sample - expected).abs() < 1e-12,
+            "sample {i}: {sample} != {expected}"
+        );
+    }
+    assert_eq!(rendered[frames - 1], 1.0);
+}
+
+#[test]
+fn test_live_set_param_cancels_a_ramp() {
+    let (graph, _, gain) = constant_through_gain(1.0, 0.0);
+    let (mut controller, mut processor) = graph.into_live(16);
+    controller
+        .ramp_param(gain, 0, 1.0, 10 * BLOCK_SIZE)
+        .unwrap();
+    processor.render();
+    controller.set_param(gain, 0, 0.25).unwrap();
+    assert_eq!(processor.render()[0], [0.25; BLOCK_SIZE]);
+    assert_eq!(processor.render()[0], [0.25; BLOCK_SIZE]);
+}
+
+#[test]
+fn test_live_queue_full_then_recovers() {
+    let (graph, _, gain) = constant_through_gain(1.0, 1.0);
+    let (mut controller, mut processor) = graph.into_live(4);
+
+    let mut sent = 0;
+    let error = loop {
+        match controller.set_param(gain, 0, sent as f64) {
+            Ok(()) => sent += 1,
+            Err(e) => break e,
+        }
+    };
+    assert_eq!(sent, 4);
+    assert_eq!(error, GraphError::QueueFull);
+
+    // A change that does not fit is not half-applied.
+    assert_eq!(
+        controller.add_node(Box::new(Constant(9.0))),
+        Err(GraphError::QueueFull)
+    );
+    assert_eq!(processor.render()[0], [3.0; BLOCK_SIZE]);
+
+    let added = controller.add_node(Box::new(Constant(9.0))).unwrap();
+    assert_eq!(added, NodeId(2));
+    controller.connect(added, gain, 0).unwrap();
+    assert_eq!(processor.render()[0], [27.0; BLOCK_SIZE]);
+}
+
+#[test]
+fn test_live_stop_silences_output() {
+    let (graph, _, gain) = constant_through_gain(1.0, 1.0);
+    let (mut controller, mut processor) = graph.into_live(16);
+    controller.stop().unwrap();
+    controller.set_param(gain, 0, 2.0).unwrap();
+    assert_eq!(processor.render(), &[[0.0; BLOCK_SIZE]]);
+}
+
+#[test]
+fn test_live_across_threads() {
+    let (graph, source, gain) = constant_through_gain(1.0, 1.0);
+    let (mut controller, mut processor) = graph.into_live(32);
+
+    let done = Arc::new(AtomicBool::new(false));
+    let audio = std::thread::spawn({
+        let done = done.clone();
+        move || {
+            let mut allocations = 0;
+            while !done.load(Ordering::Acquire) {
+                allocations += count_allocations(|| {
+                    processor.render();
+                });
+                std::thread::yield_now();
+            }
+            allocations
+        }
+    });
+
+    for round in 0..200 {
+        let node = loop {
+            match controller.add_node(Box::new(Constant(round as Sample))) {
+                Ok(node) => break node,
+                Err(GraphError::QueueFull) => std::thread::yield_now(),
+                Err(e) => panic!("{e}"),
+            }
+        };
+        while controller.connect(node, gain, 0) == Err(GraphError::QueueFull) {
+            std::thread::yield_now();
+        }
+        while controller.connect(source, gain, 0) == Err(GraphError::QueueFull) {
+            std::thread::yield_now();
+        }
+        while controller.remove_node(node) == Err(GraphError::QueueFull) {
+            std::thread::yield_now();
+        }
+    }
+    done.store(true, Ordering::Release);
+
+    assert_eq!(audio.join().unwrap(), 0);
+}
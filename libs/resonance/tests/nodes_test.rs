@@ -0,0 +1,434 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later
+// Copyright (C) 2026 The Architect & Una
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use resonance::dsp::{Complex, FftContext};
+use resonance::{
+    Adsr, AudioGraph, AudioNode, BLOCK_SIZE, Biquad, Compressor, Delay, Gain, GraphContext, Pan,
+    PolyBlepOscillator, Reverb, Sample, Waveform,
+};
+
+const RATE: Sample = 48000.0;
+/// FFT length for responses; at 48 kHz each bin is 11.71875 Hz wide.
+const N: usize = 4096;
+/// 1500 Hz falls exactly on bin 128.
+const CORNER: Sample = 1500.0;
+const CORNER_BIN: usize = 128;
+const NYQUIST_BIN: usize = N / 2;
+
+/// Runs `node` for `frames` samples, feeding it `input` on port 0 if given,
+/// and returns every output channel.
+fn run(node: &mut dyn AudioNode, input: Option<&[Sample]>, frames: usize) -> Vec<Vec<Sample>> {
+    let context = GraphContext::new(RATE);
+    let channels = node.output_channels(0);
+    let mut rendered = vec![Vec::with_capacity(frames); channels];
+    let mut blocks = vec![[0.0; BLOCK_SIZE]; channels];
+    for start in (0..frames).step_by(BLOCK_SIZE) {
+        let mut block = [0.0; BLOCK_SIZE];
+        if let Some(input) = input {
+            block.copy_from_slice(&input[start..start + BLOCK_SIZE]);
+        }
+        let inputs: Vec<&[Sample; BLOCK_SIZE]> = input.map(|_| &block).into_iter().collect();
+        let mut outputs: Vec<&mut [Sample; BLOCK_SIZE]> = blocks.iter_mut().collect();
+        node.process(&inputs, &mut outputs, &context);
+        for (channel, block) in rendered.iter_mut().zip(&blocks) {
+            channel.extend_from_slice(block);
+        }
+    }
+    rendered
+}
+
+fn impulse(frames: usize) -> Vec<Sample> {
+    let mut signal = vec![0.0; frames];
+    signal[0] = 1.0;
+    signal
+}
+
+/// Magnitudes of bins 0 to Nyquist.
+fn spectrum(signal: &[Sample]) -> Vec<Sample> {
+    let fft = FftContext::new(signal.len());
+    let mut buffer: Vec<Complex> = signal
+        .iter()
+        .map(|&s| Complex::new(s as f32, 0.0))
+        .collect();
+    fft.process(&mut buffer);
+    buffer[..=signal.len() / 2]
+        .iter()
+        .map(|c| (c.re as Sample).hypot(c.im as Sample))
+        .collect()
+}
+
+fn db(magnitude: Sample) -> Sample {
+    20.0 * magnitude.max(1e-12).log10()
+}
+
+/// The magnitude response in dB, from the first channel's impulse response.
+fn response_db(node: &mut dyn AudioNode) -> Vec<Sample> {
+    let response = run(node, Some(&impulse(N)), N);
+    spectrum(&response[0]).into_iter().map(db).collect()
+}
+
+fn assert_near(actual: Sample, expected: Sample, tolerance: Sample, what: &str) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{what}: {actual} is not within {tolerance} of {expected}"
+    );
+}
+
+#[test]
+fn test_biquad_low_and_high_pass() {
+    let low = response_db(&mut Biquad::low_pass(
+        CORNER,
+        std::f64::consts::FRAC_1_SQRT_2,
+    ));
+    assert_near(low[0], 0.0, 0.05, "low-pass at DC");
+    assert_near(low[CORNER_BIN], -3.01, 0.1, "low-pass at the corner");
+    assert!(low[CORNER_BIN * 8] < -30.0, "low-pass three octaves up");
+
+    let high = response_db(&mut Biquad::high_pass(
+        CORNER,
+        std::f64::consts::FRAC_1_SQRT_2,
+    ));
+    assert_near(high[NYQUIST_BIN], 0.0, 0.05, "high-pass at Nyquist");
+    assert_near(high[CORNER_BIN], -3.01, 0.1, "high-pass at the corner");
+    assert!(high[CORNER_BIN / 16] < -40.0, "high-pass four octaves down");
+}
+
+#[test]
+fn test_biquad_band_pass_and_notch() {
+    let band = response_db(&mut Biquad::band_pass(CORNER, 2.0));
+    assert_near(band[CORNER_BIN], 0.0, 0.05, "band-pass at the centre");
+    // An octave either side: 1 / sqrt(1 + Q^2 (2 - 1/2)^2), or -10 dB, give
+    // or take the bilinear transform's warping.
+    assert_near(band[CORNER_BIN / 2], -10.0, 0.2, "band-pass an octave down");
+    assert_near(band[CORNER_BIN * 2], -10.0, 0.2, "band-pass an octave up");
+
+    let notch = response_db(&mut Biquad::notch(CORNER, 1.0));
+    assert!(notch[CORNER_BIN] < -40.0, "notch at the centre");
+    assert_near(notch[0], 0.0, 0.05, "notch at DC");
+    assert_near(notch[NYQUIST_BIN], 0.0, 0.05, "notch at Nyquist");
+}
+
+#[test]
+fn test_biquad_peaking_and_shelves() {
+    let peak = response_db(&mut Biquad::peaking(CORNER, 1.0, 6.0));
+    assert_near(peak[CORNER_BIN], 6.0, 0.05, "peaking at the centre");
+    assert_near(peak[0], 0.0, 0.05, "peaking at DC");
+    assert_near(peak[NYQUIST_BIN], 0.0, 0.05, "peaking at Nyquist");
+
+    // Shelves pass half their gain (in dB) at the corner.
+    let low = response_db(&mut Biquad::low_shelf(CORNER, 6.0));
+    assert_near(low[0], 6.0, 0.05, "low shelf at DC");
+    assert_near(low[CORNER_BIN], 3.0, 0.05, "low shelf at the corner");
+    assert_near(low[NYQUIST_BIN], 0.0, 0.05, "low shelf at Nyquist");
+
+    let high = response_db(&mut Biquad::high_shelf(CORNER, -6.0));
+    assert_near(high[0], 0.0, 0.05, "high shelf at DC");
+    assert_near(high[CORNER_BIN], -3.0, 0.05, "high shelf at the corner");
+    assert_near(high[NYQUIST_BIN], -6.0, 0.05, "high shelf at Nyquist");
+}
+
+#[test]
+fn test_biquad_frequency_ramp_lands_on_target() {
+    let mut swept = Biquad::low_pass(500.0, 1.0);
+    swept.ramp_param(0, CORNER, 2 * BLOCK_SIZE);
+    run(&mut swept, Some(&[0.0; 2 * BLOCK_SIZE]), 2 * BLOCK_SIZE);
+    assert_eq!(swept.frequency(), CORNER);
+
+    // Having heard only silence, it now matches a filter built at the target.
+    let fresh = response_db(&mut Biquad::low_pass(CORNER, 1.0));
+    let ramped = response_db(&mut swept);
+    assert_near(
+        ramped[CORNER_BIN],
+        fresh[CORNER_BIN],
+        1e-6,
+        "after the sweep",
+    );
+}
+
+/// A frequency whose period fits `N` a whole number of times, so that every
+/// harmonic, and every alias, lands exactly on a bin.
+const TONE_BIN: usize = 200;
+
+fn tone_spectrum(waveform: Waveform) -> Vec<Sample> {
+    let frequency = TONE_BIN as Sample * RATE / N as Sample;
+    let mut osc = PolyBlepOscillator::new(waveform, frequency);
+    // Skip one frame so the triangle's integrator has settled.
+    let rendered = run(&mut osc, None, 2 * N);
+    spectrum(&rendered[0][N..])
+}
+
+/// Energy outside the harmonics, relative to the fundamental, in dB.
+fn alias_db(spectrum: &[Sample]) -> Sample {
+    let aliases: Sample = spectrum
+        .iter()
+        .enumerate()
+        .filter(|&(bin, _)| bin % TONE_BIN != 0)
+        .map(|(_, m)| m * m)
+        .sum();
+    10.0 * (aliases / spectrum[TONE_BIN].powi(2)).log10()
+}
+
+#[test]
+fn test_polyblep_saw_aliases_less_than_naive() {
+    let blep = tone_spectrum(Waveform::Saw);
+
+    let increment = TONE_BIN as Sample / N as Sample;
+    let naive: Vec<Sample> = (0..N)
+        .map(|i| 2.0 * (i as Sample * increment).fract() - 1.0)
+        .collect();
+    let naive = spectrum(&naive);
+
+    // A unit saw's fundamental has amplitude 2 / pi.
+    let fundamental = blep[TONE_BIN] / (N / 2) as Sample;
+    assert_near(
+        fundamental,
+        2.0 / std::f64::consts::PI,
+        0.02,
+        "saw fundamental",
+    );
+    assert!(
+        alias_db(&blep) < alias_db(&naive) - 10.0,
+        "PolyBLEP aliasing {} dB vs naive {} dB",
+        alias_db(&blep),
+        alias_db(&naive)
+    );
+}
+
+#[test]
+fn test_polyblep_square_and_triangle_have_odd_harmonics() {
+    let square = tone_spectrum(Waveform::Square);
+    assert!(square[2 * TONE_BIN] < square[TONE_BIN] * 1e-3);
+    // 1/3, less what the residual rounds off a harmonic this high.
+    assert_near(
+        square[3 * TONE_BIN] / square[TONE_BIN],
+        1.0 / 3.0,
+        0.03,
+        "square third harmonic",
+    );
+
+    let triangle = tone_spectrum(Waveform::Triangle);
+    assert!(
+        triangle[0] < triangle[TONE_BIN] * 1e-2,
+        "triangle is centred"
+    );
+    assert!(triangle[2 * TONE_BIN] < triangle[TONE_BIN] * 1e-3);
+    assert_near(
+        triangle[3 * TONE_BIN] / triangle[TONE_BIN],
+        1.0 / 9.0,
+        0.01,
+        "triangle third harmonic",
+    );
+    // 8 / pi^2 for a unit triangle, less a little to the integrator's leak.
+    let fundamental = triangle[TONE_BIN] / (N / 2) as Sample;
+    assert_near(fundamental, 0.8106, 0.03, "triangle fundamental");
+}
+
+#[test]
+fn test_adsr_stages() {
+    // 48 samples of attack, 96 of decay, 192 of release at 48 kHz.
+    let mut env = Adsr::new(0.001, 0.002, 0.5, 0.004);
+    env.set_gate(true);
+    let held = run(&mut env, None, 256).remove(0);
+    assert_near(held[0], 1.0 / 48.0, 1e-12, "first attack step");
+    assert_near(held[47], 1.0, 1e-12, "attack peak");
+    assert_near(held[48], 1.0 - 0.5 / 96.0, 1e-12, "first decay step");
+    assert_near(held[47 + 96], 0.5, 1e-12, "decay end");
+    assert!(held[144..].iter().all(|&level| level == 0.5), "sustain");
+
+    env.set_gate(false);
+    let released = run(&mut env, None, 256).remove(0);
+    assert_near(released[0], 0.5 - 0.5 / 192.0, 1e-12, "first release step");
+    assert_near(released[191], 0.0, 1e-9, "release end");
+    assert!(released[200..].iter().all(|&level| level == 0.0), "idle");
+}
+
+#[test]
+fn test_adsr_follows_gate_input() {
+    let mut env = Adsr::new(0.001, 0.001, 1.0, 0.001);
+    let mut gate = vec![0.0; 256];
+    gate[10..100].fill(1.0);
+    let out = run(&mut env, Some(&gate), 256).remove(0);
+    assert_eq!(out[9], 0.0);
+    assert_near(out[10], 1.0 / 48.0, 1e-12, "attack starts with the gate");
+    assert_near(out[99], 1.0, 1e-12, "sustaining at full level");
+    assert!(out[100] < 1.0, "released with the gate");
+    assert_eq!(out[255], 0.0);
+}
+
+#[test]
+fn test_delay_feedback_comb() {
+    let mut delay = Delay::new(0.01, RATE);
+    delay.time = 64.0 / RATE;
+    delay.feedback = 0.5;
+    let response = run(&mut delay, Some(&impulse(N)), N).remove(0);
+    assert_near(response[63], 0.0, 1e-9, "before the first echo");
+    assert_near(response[64], 1.0, 1e-9, "first echo");
+    assert_near(response[128], 0.5, 1e-9, "second echo");
+    assert_near(response[192], 0.25, 1e-9, "third echo");
+
+    // 1 / (1 - g z^-64): peaks of 1 / (1 - g) every N / 64 bins, troughs of
+    // 1 / (1 + g) halfway between.
+    let magnitude: Vec<Sample> = spectrum(&response).into_iter().map(db).collect();
+    let spacing = N / 64;
+    assert_near(magnitude[spacing * 5], db(2.0), 0.01, "comb peak");
+    assert_near(
+        magnitude[spacing * 5 + spacing / 2],
+        db(1.0 / 1.5),
+        0.01,
+        "comb trough",
+    );
+}
+
+#[test]
+fn test_delay_fractional_time_and_mix() {
+    let mut delay = Delay::new(0.01, RATE);
+    delay.time = 64.5 / RATE;
+    delay.mix = 0.5;
+    let response = run(&mut delay, Some(&impulse(256)), 256).remove(0);
+    assert_near(response[0], 0.5, 1e-9, "dry half");
+    assert_near(response[64], 0.25, 1e-9, "echo split between samples");
+    assert_near(response[65], 0.25, 1e-9, "echo split between samples");
+}
+
+#[test]
+fn test_reverb_decays_and_damping_darkens() {
+    const LONG: usize = 65536;
+    let render = |damping: Sample| {
+        let mut reverb = Reverb::new(RATE);
+        reverb.damping = damping;
+        run(&mut reverb, Some(&impulse(LONG)), LONG)
+    };
+    let rms = |s: &[Sample]| (s.iter().map(|x| x * x).sum::<Sample>() / s.len() as Sample).sqrt();
+
+    let bright = render(0.0);
+    let [left, right] = &bright[..] else {
+        panic!("reverb is stereo")
+    };
+    assert!(left.iter().all(|s| s.is_finite()));
+    assert_ne!(left, right, "the channels are decorrelated");
+    let early = rms(&left[2048..10240]);
+    assert!(early > 1e-3, "the tail is audible");
+    assert!(rms(&left[LONG - 8192..]) < early * 1e-2, "the tail decays");
+
+    // Energy above 8 kHz against energy below 2 kHz, a third of a second
+    // in, where the damping has been round the loops many times.
+    let tilt = |channels: &[Vec<Sample>]| {
+        const TAIL: usize = 16384;
+        let s = spectrum(&channels[0][TAIL..2 * TAIL]);
+        let bin = |hz: Sample| (hz * TAIL as Sample / RATE) as usize;
+        let energy = |bins: &[Sample]| bins.iter().map(|m| m * m).sum::<Sample>();
+        10.0 * (energy(&s[bin(8000.0)..]) / energy(&s[1..bin(2000.0)])).log10()
+    };
+    assert!(tilt(&render(1.0)) < tilt(&bright) - 20.0);
+}
+
+#[test]
+fn test_compressor_static_curve() {
+    // -6 dB into a -12 dB threshold at 2:1 comes out 3 dB down.
+    let mut compressor = Compressor::new(-12.0, 2.0);
+    let out = run(&mut compressor, Some(&[0.5; N]), N).remove(0);
+    let expected_db = db(0.5) - compressor.static_reduction_db(db(0.5));
+    assert_near(expected_db, -9.0103, 1e-3, "static curve");
+    assert_near(db(out[N - 1]), expected_db, 1e-3, "settled output");
+    assert!(out[0] > out[N - 1], "the attack takes time");
+
+    // Below the threshold nothing changes.
+    let mut compressor = Compressor::new(-12.0, 2.0);
+    let out = run(&mut compressor, Some(&[0.1; 256]), 256).remove(0);
+    assert!(out.iter().all(|&s| s == 0.1));
+
+    // A soft knee meets the hard curve at its edges.
+    let mut soft = Compressor::new(-12.0, 4.0);
+    soft.knee_db = 6.0;
+    let hard = Compressor::new(-12.0, 4.0);
+    assert_eq!(soft.static_reduction_db(-15.0), 0.0);
+    assert_near(
+        soft.static_reduction_db(-9.0),
+        hard.static_reduction_db(-9.0),
+        1e-12,
+        "knee top",
+    );
+    assert!(soft.static_reduction_db(-12.0) > 0.0);
+}
+
+#[test]
+fn test_limiter_ceiling_and_sidechain() {
+    let sine: Vec<Sample> = (0..N)
+        .map(|i| (std::f64::consts::TAU * 1000.0 * i as Sample / RATE).sin())
+        .collect();
+    let mut limiter = Compressor::limiter(-6.0);
+    let out = run(&mut limiter, Some(&sine), N).remove(0);
+    let ceiling = 10f64.powf(-6.0 / 20.0);
+    assert!(out.iter().all(|s| s.abs() <= ceiling + 1e-12));
+    assert!(out.iter().any(|s| s.abs() > ceiling * 0.99));
+
+    // Keyed from port 1, a loud sidechain ducks a quiet signal.
+    let mut ducker = Compressor::limiter(-20.0);
+    let context = GraphContext::new(RATE);
+    let mut out = [0.0; BLOCK_SIZE];
+    ducker.process(
+        &[&[0.1; BLOCK_SIZE], &[1.0; BLOCK_SIZE]],
+        &mut [&mut out],
+        &context,
+    );
+    assert!(out.iter().all(|&s| (s - 0.01).abs() < 1e-12));
+}
+
+#[test]
+fn test_pan_constant_power() {
+    for position in [-1.0, -0.5, 0.0, 0.3, 1.0] {
+        let (left, right) = Pan::gains(position);
+        assert_near(left * left + right * right, 1.0, 1e-12, "power");
+    }
+    let (left, right) = Pan::gains(0.0);
+    assert_near(db(left), -3.01, 0.01, "centre left");
+    assert_near(left, right, 1e-15, "centre");
+
+    let mut pan = Pan::new(-1.0);
+    let out = run(&mut pan, Some(&[1.0; BLOCK_SIZE]), BLOCK_SIZE);
+    assert!(out[0].iter().all(|&s| s == 1.0) && out[1].iter().all(|&s| s.abs() < 1e-15));
+
+    pan.ramp_param(0, 1.0, BLOCK_SIZE);
+    let out = run(&mut pan, Some(&[1.0; BLOCK_SIZE]), BLOCK_SIZE);
+    assert!(out[0][0] > out[1][0], "the sweep starts on the left");
+    assert_near(out[1][BLOCK_SIZE - 1], 1.0, 1e-12, "and ends on the right");
+}
+
+#[test]
+fn test_nodes_patch_in_a_graph() {
+    // Saw -> low-pass -> enveloped gain -> pan -> reverb.
+    let mut graph = AudioGraph::new(RATE);
+    let osc = graph.add_node(Box::new(PolyBlepOscillator::new(Waveform::Saw, 110.0)));
+    let filter = graph.add_node(Box::new(Biquad::low_pass(800.0, 2.0)));
+    let mut env = Adsr::new(0.005, 0.1, 0.7, 0.2);
+    env.set_gate(true);
+    let env = graph.add_node(Box::new(env));
+    let vca = graph.add_node(Box::new(Gain::new(0.0)));
+    let pan = graph.add_node(Box::new(Pan::new(0.25)));
+    let reverb = graph.add_node(Box::new(Reverb::new(RATE)));
+    graph.connect(osc, filter, 0).unwrap();
+    graph.connect(filter, vca, 0).unwrap();
+    graph.connect(env, vca, 1).unwrap();
+    graph.connect(vca, pan, 0).unwrap();
+    // The reverb is mono in: the panned pair is downmixed into it.
+    graph.connect(pan, reverb, 0).unwrap();
+    graph.set_output(reverb);
+
+    assert_eq!(graph.output_channels(), 2);
+    let rendered = graph.render_frames(N);
+    assert!(rendered.iter().flatten().all(|s| s.is_finite()));
+    assert!(rendered[1].iter().any(|s| s.abs() > 1e-4));
+}